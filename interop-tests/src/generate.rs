@@ -1,6 +1,7 @@
 use futures_executor::block_on;
-use interop_tests::generate_repos;
+use interop_tests::{generate_repos, JsonPretty};
 use std::path::Path;
+use tuf::pouf::Dsse;
 
 const KEYS_PATH: &str = "./keys.json";
 
@@ -8,11 +9,25 @@ fn main() {
     let keys_path = Path::new(KEYS_PATH);
 
     block_on(async {
-        generate_repos(keys_path, Path::new("consistent-snapshot-true"), true)
+        generate_repos::<JsonPretty>(keys_path, Path::new("consistent-snapshot-true"), true)
             .await
             .unwrap();
-        generate_repos(keys_path, Path::new("consistent-snapshot-false"), false)
+        generate_repos::<JsonPretty>(keys_path, Path::new("consistent-snapshot-false"), false)
             .await
             .unwrap();
+        generate_repos::<Dsse>(
+            keys_path,
+            &Path::new("dsse").join("consistent-snapshot-true"),
+            true,
+        )
+        .await
+        .unwrap();
+        generate_repos::<Dsse>(
+            keys_path,
+            &Path::new("dsse").join("consistent-snapshot-false"),
+            false,
+        )
+        .await
+        .unwrap();
     })
 }
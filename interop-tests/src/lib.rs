@@ -4,11 +4,12 @@ use serde_derive::Deserialize;
 use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
-use tuf::crypto::{Ed25519PrivateKey, HashAlgorithm, KeyType, PrivateKey, SignatureScheme};
+use tuf::crypto::{Ed25519PrivateKey, HashAlgorithm, KeyType, SignatureScheme, Signer};
 use tuf::metadata::{
     MetadataPath, MetadataVersion, Role, SnapshotMetadataBuilder, TargetPath,
     TargetsMetadataBuilder, TimestampMetadataBuilder,
 };
+use tuf::pouf::Pouf;
 use tuf::repo_builder::RepoBuilder;
 use tuf::repository::{FileSystemRepository, FileSystemRepositoryBuilder, RepositoryStorage};
 use walkdir::WalkDir;
@@ -104,10 +105,10 @@ pub fn read_dir_files(path: &Path) -> BTreeMap<PathBuf, String> {
 
 // updates the root metadata. If root_signer is Some, use that to sign the
 // metadata, otherwise use keys["root"].
-async fn update_root(
-    repo: &mut FileSystemRepository<JsonPretty>,
+async fn update_root<D: Pouf>(
+    repo: &mut FileSystemRepository<D>,
     keys: &RoleKeys,
-    root_signer: Option<&dyn PrivateKey>,
+    root_signer: Option<&dyn Signer>,
     version: u32,
     consistent_snapshot: bool,
 ) {
@@ -132,6 +133,7 @@ async fn update_root(
                 .version(version)
                 .consistent_snapshot(consistent_snapshot)
         })
+        .await
         .unwrap()
         .skip_targets()
         .skip_snapshot()
@@ -142,8 +144,8 @@ async fn update_root(
 }
 
 // adds a target and updates the non-root metadata files.
-async fn add_target(
-    repo: &mut FileSystemRepository<JsonPretty>,
+async fn add_target<D: Pouf>(
+    repo: &mut FileSystemRepository<D>,
     keys: &RoleKeys,
     step: u8,
     consistent_snapshot: bool,
@@ -172,7 +174,7 @@ async fn add_target(
     let target_data = step_str.as_bytes();
 
     let signed_targets = targets_builder
-        .signed::<JsonPretty>(keys.get("targets").unwrap())
+        .signed::<D>(keys.get("targets").unwrap())
         .unwrap();
     let targets = signed_targets.assume_valid().unwrap();
 
@@ -214,7 +216,7 @@ async fn add_target(
         .version(version)
         .insert_metadata(&signed_targets, &[HashAlgorithm::Sha256])
         .unwrap()
-        .signed::<JsonPretty>(keys.get("snapshot").unwrap())
+        .signed::<D>(keys.get("snapshot").unwrap())
         .unwrap();
 
     repo.store_metadata(
@@ -230,7 +232,7 @@ async fn add_target(
         .unwrap()
         .expires(expiration)
         .version(version)
-        .signed::<JsonPretty>(keys.get("timestamp").unwrap())
+        .signed::<D>(keys.get("timestamp").unwrap())
         .unwrap();
 
     // Timestamp doesn't require a version prefix even in consistent_snapshot.
@@ -245,8 +247,8 @@ async fn add_target(
 
 /// Generate a series of repositories in the `dir` path, using the keys in the `keys_path`. Each
 /// repository corresponds to a key rotation. This allows clients to test they can update through
-/// key transitions.
-pub async fn generate_repos(
+/// key transitions. The metadata is written in the `D` format.
+pub async fn generate_repos<D: Pouf>(
     keys_path: &Path,
     dir: &Path,
     consistent_snapshot: bool,
@@ -255,7 +257,7 @@ pub async fn generate_repos(
     let json_keys = init_json_keys(keys_path);
     let mut keys = init_role_keys(&json_keys);
     let dir0 = Path::new(dir).join("0");
-    let mut repo = FileSystemRepositoryBuilder::new(dir0)
+    let mut repo = FileSystemRepositoryBuilder::<D>::new(dir0)
         .metadata_prefix(Path::new("repository"))
         .targets_prefix(Path::new("repository").join("targets"))
         .build();
@@ -275,7 +277,7 @@ pub async fn generate_repos(
     for r in rotations.iter() {
         // Initialize new repo and copy the files from the previous step.
         let dir_i = Path::new(dir).join(i.to_string());
-        let mut repo = FileSystemRepositoryBuilder::new(dir_i)
+        let mut repo = FileSystemRepositoryBuilder::<D>::new(dir_i)
             .metadata_prefix(Path::new("repository"))
             .targets_prefix(Path::new("repository").join("targets"))
             .build();
@@ -300,7 +302,7 @@ pub async fn generate_repos(
         update_root(
             &mut repo,
             &keys,
-            root_signer.as_ref().map(|x| x as &dyn PrivateKey),
+            root_signer.as_ref().map(|x| x as &dyn Signer),
             (i + 1).into(),
             consistent_snapshot,
         )
@@ -4,7 +4,7 @@ set -eu
 
 cd `dirname $0`
 
-for d in consistent-snapshot-false consistent-snapshot-true; do
+for d in consistent-snapshot-false consistent-snapshot-true dsse; do
 	if [ -e $d ]; then
 		rm -r $d
 	fi
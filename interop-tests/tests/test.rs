@@ -33,6 +33,9 @@
 //! over time. Finally, each repository should also contain a single target that corresponds to the
 //! step name, that just contains the name of the step. This is used to verify that we can still
 //! download targets at each step of the test.
+//!
+//! The `metadata/dsse` directory follows the same scheme, but its metadata is wrapped in DSSE
+//! envelopes rather than written as POUF-1.
 
 use assert_matches::assert_matches;
 use futures_executor::block_on;
@@ -44,7 +47,7 @@ use std::path::{Path, PathBuf};
 use tuf::client::{Client, Config};
 use tuf::crypto::PublicKey;
 use tuf::metadata::{MetadataPath, MetadataVersion, RawSignedMetadata, RootMetadata, TargetPath};
-use tuf::pouf::{Pouf, Pouf1};
+use tuf::pouf::{Dsse, Pouf, Pouf1};
 use tuf::repository::{
     EphemeralRepository, FileSystemRepository, FileSystemRepositoryBuilder, RepositoryProvider,
 };
@@ -104,6 +107,26 @@ fn rust_tuf_identity_consistent_snapshot_true() {
     );
 }
 
+#[test]
+fn rust_tuf_dsse_identity_consistent_snapshot_false() {
+    test_key_rotation::<Dsse>(
+        Path::new("tests")
+            .join("metadata")
+            .join("dsse")
+            .join("consistent-snapshot-false"),
+    );
+}
+
+#[test]
+fn rust_tuf_dsse_identity_consistent_snapshot_true() {
+    test_key_rotation::<Dsse>(
+        Path::new("tests")
+            .join("metadata")
+            .join("dsse")
+            .join("consistent-snapshot-true"),
+    );
+}
+
 fn test_key_rotation<D>(dir: PathBuf)
 where
     D: Pouf,
@@ -243,7 +266,7 @@ where
 fn test_metadata_generation_does_not_change_consistent_snapshot_false() {
     block_on(async {
         let dir = tempfile::TempDir::new().unwrap();
-        interop_tests::generate_repos(
+        interop_tests::generate_repos::<JsonPretty>(
             &Path::new("tests").join("metadata").join("keys.json"),
             dir.path(),
             false,
@@ -264,7 +287,50 @@ fn test_metadata_generation_does_not_change_consistent_snapshot_false() {
 fn test_metadata_generation_does_not_change_consistent_snapshot_true() {
     block_on(async {
         let dir = tempfile::TempDir::new().unwrap();
-        interop_tests::generate_repos(
+        interop_tests::generate_repos::<JsonPretty>(
+            &Path::new("tests").join("metadata").join("keys.json"),
+            dir.path(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        compare_dirs(
+            &Path::new("tests")
+                .join("metadata")
+                .join("consistent-snapshot-true"),
+            dir.path(),
+        );
+    });
+}
+
+#[test]
+fn test_dsse_metadata_generation_does_not_change_consistent_snapshot_false() {
+    block_on(async {
+        let dir = tempfile::TempDir::new().unwrap();
+        interop_tests::generate_repos::<Dsse>(
+            &Path::new("tests").join("metadata").join("keys.json"),
+            dir.path(),
+            false,
+        )
+        .await
+        .unwrap();
+
+        compare_dirs(
+            &Path::new("tests")
+                .join("metadata")
+                .join("dsse")
+                .join("consistent-snapshot-false"),
+            dir.path(),
+        );
+    });
+}
+
+#[test]
+fn test_dsse_metadata_generation_does_not_change_consistent_snapshot_true() {
+    block_on(async {
+        let dir = tempfile::TempDir::new().unwrap();
+        interop_tests::generate_repos::<Dsse>(
             &Path::new("tests").join("metadata").join("keys.json"),
             dir.path(),
             true,
@@ -275,6 +341,7 @@ fn test_metadata_generation_does_not_change_consistent_snapshot_true() {
         compare_dirs(
             &Path::new("tests")
                 .join("metadata")
+                .join("dsse")
                 .join("consistent-snapshot-true"),
             dir.path(),
         );
@@ -0,0 +1,13 @@
+use {clap::Parser as _, std::process::ExitCode, tuf_cli::client::ClientCli};
+
+fn main() -> ExitCode {
+    let cli = ClientCli::parse();
+
+    match tuf_cli::block_on(tuf_cli::client::run_client(cli)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
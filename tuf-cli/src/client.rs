@@ -0,0 +1,214 @@
+//! The `tuf-client` command, which fetches verified targets from a repository.
+
+use {
+    clap::Parser,
+    std::{collections::BTreeMap, fs, path::PathBuf},
+    tuf::{
+        client::{Client, Config},
+        crypto::HashAlgorithm,
+        metadata::{Metadata, RawSignedMetadata, RootMetadata, TargetPath},
+        pouf::Pouf1,
+        repository::{FileSystemRepository, RepositoryProvider, SpecVersionRepository},
+        Database, Error, Result,
+    },
+};
+
+/// Fetch verified targets from a TUF repository.
+///
+/// Starting from a trusted root metadata, this updates all of the metadata from the remote
+/// repository and verifies it, then downloads each of the named targets into the cache directory.
+/// Remote repositories that keep their metadata in a directory per major version of the
+/// specification, as described in TAP 14, are supported.
+#[derive(Parser, Debug)]
+#[command(name = "tuf-client", version)]
+pub struct ClientCli {
+    /// The trusted root metadata file.
+    #[arg(long, value_name = "FILE")]
+    pub root: PathBuf,
+
+    /// The remote repository. Either a directory, or an `http://` URL.
+    #[arg(long, value_name = "DIR_OR_URL")]
+    pub remote: String,
+
+    /// The directory to store the verified metadata and targets in.
+    #[arg(long, value_name = "DIR")]
+    pub cache: PathBuf,
+
+    /// Where the metadata is stored in the remote repository.
+    #[arg(long, value_name = "PATH", default_value = "metadata")]
+    pub metadata_prefix: String,
+
+    /// Where the targets are stored in the remote repository.
+    #[arg(long, value_name = "PATH", default_value = "targets")]
+    pub targets_prefix: String,
+
+    /// The targets to fetch.
+    #[arg(value_name = "TARGET")]
+    pub target_paths: Vec<String>,
+}
+
+/// Run the `tuf-client` command.
+pub async fn run_client(cli: ClientCli) -> Result<()> {
+    if cli.remote.starts_with("http://") || cli.remote.starts_with("https://") {
+        return fetch_from_http(&cli).await;
+    }
+
+    let remote = FileSystemRepository::<Pouf1>::builder(&cli.remote)
+        .metadata_prefix(&cli.metadata_prefix)
+        .targets_prefix(&cli.targets_prefix)
+        .build();
+
+    fetch_targets(&cli, remote).await
+}
+
+#[cfg(feature = "hyper")]
+async fn fetch_from_http(cli: &ClientCli) -> Result<()> {
+    use tuf::repository::HttpRepositoryBuilder;
+
+    let uri = cli
+        .remote
+        .parse::<hyper::Uri>()
+        .map_err(|err| Error::IllegalArgument(format!("invalid URL {}: {}", cli.remote, err)))?;
+
+    let remote = HttpRepositoryBuilder::<_, Pouf1>::new_with_uri(uri, hyper::Client::new())
+        .user_agent(concat!("tuf-client/", env!("CARGO_PKG_VERSION")))
+        .metadata_prefix(url_path_segments(&cli.metadata_prefix))
+        .targets_prefix(url_path_segments(&cli.targets_prefix))
+        .build();
+
+    fetch_targets(cli, remote).await
+}
+
+#[cfg(not(feature = "hyper"))]
+async fn fetch_from_http(cli: &ClientCli) -> Result<()> {
+    Err(Error::IllegalArgument(format!(
+        "cannot fetch {}, since tuf-client was built without the `hyper` feature",
+        cli.remote
+    )))
+}
+
+#[cfg(feature = "hyper")]
+fn url_path_segments(prefix: &str) -> Vec<String> {
+    prefix
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn fetch_targets<R>(cli: &ClientCli, remote: R) -> Result<()>
+where
+    R: RepositoryProvider<Pouf1> + Sync,
+{
+    let raw_root =
+        RawSignedMetadata::<Pouf1, RootMetadata>::new(fs::read(&cli.root).map_err(|err| {
+            Error::IllegalArgument(format!("failed to read {}: {}", cli.root.display(), err))
+        })?);
+
+    let mut client = Client::with_trusted_root(
+        Config::default(),
+        &raw_root,
+        FileSystemRepository::<Pouf1>::new(&cli.cache),
+        SpecVersionRepository::new(remote),
+    )
+    .await?;
+
+    let initial_root_version = client.database().trusted_root().version();
+    client.update().await?;
+
+    print_trust_chain(initial_root_version, client.database());
+
+    for target_path in &cli.target_paths {
+        let target_path = TargetPath::new(target_path.clone())?;
+        let (target_description, delegation_path) = client
+            .fetch_target_description_and_delegation_path(&target_path)
+            .await?;
+        client.fetch_target_to_local(&target_path).await?;
+
+        let chain = delegation_path
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        let hashes = target_description
+            .hashes()
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(algorithm, value)| format!("{}:{}", hash_algorithm_name(algorithm), value))
+            .collect::<Vec<_>>();
+
+        println!(
+            "{}: {} bytes, {}, trusted by {}",
+            target_path,
+            target_description.length(),
+            hashes.join(", "),
+            chain
+        );
+    }
+
+    Ok(())
+}
+
+fn hash_algorithm_name(algorithm: &HashAlgorithm) -> String {
+    match serde_json::to_value(algorithm) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", algorithm),
+    }
+}
+
+/// Print the versions of the metadata we trusted, and the keys trusted to sign each top-level
+/// role.
+fn print_trust_chain(initial_root_version: u32, db: &Database<Pouf1>) {
+    let root = db.trusted_root();
+
+    if root.version() == initial_root_version {
+        println!("root: version {}", root.version());
+    } else {
+        println!(
+            "root: version {}, updated from version {}",
+            root.version(),
+            initial_root_version
+        );
+    }
+
+    for (role, threshold, key_ids) in [
+        ("root", root.root().threshold(), root.root().key_ids()),
+        (
+            "targets",
+            root.targets().threshold(),
+            root.targets().key_ids(),
+        ),
+        (
+            "snapshot",
+            root.snapshot().threshold(),
+            root.snapshot().key_ids(),
+        ),
+        (
+            "timestamp",
+            root.timestamp().threshold(),
+            root.timestamp().key_ids(),
+        ),
+    ] {
+        let mut key_ids = key_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        key_ids.sort();
+
+        println!(
+            "  {} keys (threshold {}): {}",
+            role,
+            threshold,
+            key_ids.join(", ")
+        );
+    }
+
+    if let Some(timestamp) = db.trusted_timestamp() {
+        println!("timestamp: version {}", timestamp.version());
+    }
+    if let Some(snapshot) = db.trusted_snapshot() {
+        println!("snapshot: version {}", snapshot.version());
+    }
+    if let Some(targets) = db.trusted_targets() {
+        println!("targets: version {}", targets.version());
+    }
+}
@@ -0,0 +1,282 @@
+//! Re-encoding a local repository's metadata in another format.
+
+use {
+    crate::repo,
+    chrono::{DateTime, Utc},
+    std::{
+        collections::{HashMap, HashSet},
+        fs,
+        path::Path,
+    },
+    tuf::{
+        crypto::{self, KeyId, Signer},
+        metadata::{
+            Metadata, MetadataDescription, MetadataPath, MetadataVersion, RawSignedMetadata,
+            SignedMetadataBuilder, SnapshotMetadataBuilder, TargetsMetadata, TimestampMetadata,
+        },
+        pouf::{Pouf, Pouf1},
+        repository::{FileSystemRepository, RepositoryStorage},
+        Error, Result,
+    },
+    walkdir::WalkDir,
+};
+
+/// Re-encode the POUF-1 repository at `src` as a repository of `D` metadata at `dst`.
+///
+/// The latest version of each metadata is converted, keeping its version, expiration and contents.
+/// Signatures can't be carried over since they are computed over the encoded metadata, so each
+/// metadata is signed again with whichever of `signers` its role trusts, which must reach the
+/// role's threshold. The snapshot and timestamp are updated to describe the re-encoded files, and
+/// the target files are copied as they are.
+///
+/// Only the latest root metadata is written, so clients of the converted repository must start
+/// from that version. `dst` must be empty or not exist yet, and the converted repository is
+/// verified before this returns.
+pub async fn convert_repository<D: Pouf>(
+    src: &Path,
+    dst: &Path,
+    signers: &[Box<dyn Signer>],
+) -> Result<()> {
+    if dst
+        .read_dir()
+        .map(|mut d| d.next().is_some())
+        .unwrap_or(false)
+    {
+        return Err(Error::IllegalArgument(format!(
+            "{} is not empty",
+            dst.display()
+        )));
+    }
+
+    let src_repo = FileSystemRepository::<Pouf1>::new(src);
+    let db = repo::load_database(&src_repo, &DateTime::<Utc>::MIN_UTC).await?;
+    let dst_repo = FileSystemRepository::<D>::new(dst);
+
+    let root = db.trusted_root();
+    let consistent_snapshot = root.consistent_snapshot();
+    let raw_root = sign::<D, _>(
+        &MetadataPath::root(),
+        &**root,
+        root.root().key_ids(),
+        root.root().threshold(),
+        signers,
+    )
+    .await?;
+
+    // Sign all of the targets metadata before writing anything, so a missing key doesn't leave a
+    // partially converted repository behind.
+    let mut raw_targets = HashMap::new();
+    if let Some(targets) = db.trusted_targets() {
+        let raw = sign::<D, _>(
+            &MetadataPath::targets(),
+            &**targets,
+            root.targets().key_ids(),
+            root.targets().threshold(),
+            signers,
+        )
+        .await?;
+        raw_targets.insert(MetadataPath::targets(), (targets.version(), raw));
+    }
+
+    for (role, targets) in db.trusted_delegations() {
+        let (key_ids, threshold) = db
+            .delegating_role(role)
+            .and_then(|parent| {
+                if parent == MetadataPath::targets() {
+                    db.trusted_targets().map(|t| &**t)
+                } else {
+                    db.trusted_delegations().get(&parent).map(|t| &**t)
+                }
+            })
+            .and_then(|parent| delegated_keys(parent, role))
+            .ok_or_else(|| Error::UnauthorizedDelegation {
+                parent_role: MetadataPath::targets(),
+                child_role: role.clone(),
+            })?;
+
+        let raw = sign::<D, _>(role, &**targets, key_ids, threshold, signers).await?;
+        raw_targets.insert(role.clone(), (targets.version(), raw));
+    }
+
+    let mut raw_snapshot = None;
+    if let Some(snapshot) = db.trusted_snapshot() {
+        let mut builder = SnapshotMetadataBuilder::from((**snapshot).clone());
+        for (path, description) in snapshot.meta() {
+            let (_, raw) = raw_targets
+                .get(path)
+                .ok_or_else(|| Error::MetadataNotFound {
+                    path: path.clone(),
+                    version: MetadataVersion::Number(description.version()),
+                })?;
+            builder = builder
+                .insert_metadata_description(path.clone(), describe(description, raw.as_bytes())?);
+        }
+
+        let raw = sign::<D, _>(
+            &MetadataPath::snapshot(),
+            &builder.build()?,
+            root.snapshot().key_ids(),
+            root.snapshot().threshold(),
+            signers,
+        )
+        .await?;
+        raw_snapshot = Some((snapshot.version(), raw));
+    }
+
+    let mut raw_timestamp = None;
+    if let (Some(timestamp), Some((_, snapshot))) = (db.trusted_timestamp(), &raw_snapshot) {
+        let timestamp = TimestampMetadata::new(
+            timestamp.version(),
+            *timestamp.expires(),
+            describe(timestamp.snapshot(), snapshot.as_bytes())?,
+        )?
+        .with_spec_version(timestamp.spec_version())?
+        .with_additional_fields(timestamp.additional_fields().clone())?;
+
+        raw_timestamp = Some(
+            sign::<D, _>(
+                &MetadataPath::timestamp(),
+                &timestamp,
+                root.timestamp().key_ids(),
+                root.timestamp().threshold(),
+                signers,
+            )
+            .await?,
+        );
+    }
+
+    store(
+        &dst_repo,
+        &MetadataPath::root(),
+        Some(root.version()),
+        &raw_root,
+    )
+    .await?;
+    for (path, (version, raw)) in &raw_targets {
+        let version = Some(*version).filter(|_| consistent_snapshot);
+        store(&dst_repo, path, version, raw).await?;
+    }
+    if let Some((version, raw)) = &raw_snapshot {
+        let version = Some(*version).filter(|_| consistent_snapshot);
+        store(&dst_repo, &MetadataPath::snapshot(), version, raw).await?;
+    }
+    if let Some(raw) = &raw_timestamp {
+        store(&dst_repo, &MetadataPath::timestamp(), None, raw).await?;
+    }
+
+    copy_targets(&src.join("targets"), &dst.join("targets"))?;
+
+    repo::load_database_from_root(&dst_repo, &raw_root, &DateTime::<Utc>::MIN_UTC).await?;
+
+    Ok(())
+}
+
+/// The keys and threshold `parent` trusts to sign the delegated role `role`.
+fn delegated_keys<'a>(
+    parent: &'a TargetsMetadata,
+    role: &MetadataPath,
+) -> Option<(&'a HashSet<KeyId>, u32)> {
+    let delegations = parent.delegations();
+
+    if let Some(delegation) = delegations.roles().iter().find(|d| d.name() == role) {
+        return Some((delegation.key_ids(), delegation.threshold()));
+    }
+
+    delegations
+        .succinct_roles()
+        .filter(|succinct_roles| succinct_roles.is_delegated_role(role))
+        .map(|succinct_roles| (succinct_roles.key_ids(), succinct_roles.threshold()))
+}
+
+/// Sign `metadata` with every one of `signers` whose key is in `key_ids`.
+async fn sign<D: Pouf, M: Metadata>(
+    role: &MetadataPath,
+    metadata: &M,
+    key_ids: &HashSet<KeyId>,
+    threshold: u32,
+    signers: &[Box<dyn Signer>],
+) -> Result<RawSignedMetadata<D, M>> {
+    let mut builder = SignedMetadataBuilder::<D, M>::from_metadata(metadata)?;
+    let mut signed_by = HashSet::new();
+
+    for signer in signers {
+        let key_id = signer.public_key().key_id();
+        if key_ids.contains(key_id) && signed_by.insert(key_id.clone()) {
+            builder = builder.sign(&**signer).await?;
+        }
+    }
+
+    if (signed_by.len() as u64) < u64::from(threshold) {
+        return Err(Error::MetadataMissingSignatures {
+            role: role.clone(),
+            number_of_valid_signatures: signed_by.len() as u32,
+            threshold,
+        });
+    }
+
+    builder.build().to_raw()
+}
+
+/// Describe the re-encoded `bytes` of the metadata described by `description`, with the same
+/// hash algorithms, and a length if it had one.
+fn describe<M: Metadata>(
+    description: &MetadataDescription<M>,
+    bytes: &[u8],
+) -> Result<MetadataDescription<M>> {
+    let hash_algs = description.hashes().keys().cloned().collect::<Vec<_>>();
+    let hashes = if hash_algs.is_empty() {
+        HashMap::new()
+    } else {
+        crypto::calculate_hashes_from_slice(bytes, &hash_algs)?
+    };
+
+    MetadataDescription::new(
+        description.version(),
+        description.length().map(|_| bytes.len()),
+        hashes,
+    )?
+    .with_additional_fields(description.additional_fields().clone())
+}
+
+/// Write `raw` to `path`, and also under `version` if there is one.
+async fn store<D: Pouf, M: Metadata>(
+    repo: &FileSystemRepository<D>,
+    path: &MetadataPath,
+    version: Option<u32>,
+    raw: &RawSignedMetadata<D, M>,
+) -> Result<()> {
+    repo.store_metadata(path, MetadataVersion::None, &mut raw.as_bytes())
+        .await?;
+
+    if let Some(version) = version {
+        repo.store_metadata(path, MetadataVersion::Number(version), &mut raw.as_bytes())
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn copy_targets(src: &Path, dst: &Path) -> Result<()> {
+    if !src.exists() {
+        return Ok(());
+    }
+
+    for entry in WalkDir::new(src) {
+        let entry = entry.map_err(|err| Error::Opaque(err.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(src)
+            .map_err(|err| Error::Opaque(err.to_string()))?;
+        let dst_path = dst.join(relative_path);
+        if let Some(parent) = dst_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(entry.path(), dst_path)?;
+    }
+
+    Ok(())
+}
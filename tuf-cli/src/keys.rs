@@ -0,0 +1,148 @@
+//! Reading and writing key files.
+//!
+//! Private keys are stored as unencrypted PKCS#8 DER files, the same format the `openssl`
+//! examples in [tuf::crypto] produce. Next to each generated private key we also write its public
+//! key as TUF JSON, with a `.pub` extension appended, so it can be handed out for delegations
+//! without exposing the private key.
+
+use {
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+    tuf::{
+        crypto::{
+            EcdsaPrivateKey, Ed25519PrivateKey, PublicKey, RsaPrivateKey, SignatureScheme, Signer,
+        },
+        Error, Result,
+    },
+};
+
+/// The path of the public key file that accompanies the private key at `path`.
+pub fn public_key_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".pub");
+    path.into()
+}
+
+/// Generate a new private key for `scheme`, and write it to `path`, along with its public key.
+pub fn generate_key(path: &Path, scheme: &SignatureScheme) -> Result<PublicKey> {
+    let der = match scheme {
+        SignatureScheme::Ed25519 => Ed25519PrivateKey::pkcs8()?,
+        SignatureScheme::EcdsaP256Sha256 | SignatureScheme::EcdsaP384Sha384 => {
+            EcdsaPrivateKey::pkcs8(scheme.clone())?
+        }
+        SignatureScheme::RsaSsaPssSha256
+        | SignatureScheme::RsaSsaPssSha512
+        | SignatureScheme::RsaPkcs1v15Sha256 => generate_rsa_key()?,
+        _ => {
+            return Err(Error::IllegalArgument(format!(
+                "unsupported signature scheme {}",
+                scheme
+            )))
+        }
+    };
+
+    let private_key = parse_private_key(&der, scheme)?;
+    let public_key = private_key.public_key().clone();
+
+    // Don't clobber an existing key, since it may be the only copy.
+    write_new_file(path, &der)?;
+    write_new_file(
+        &public_key_path(path),
+        &serde_json::to_vec_pretty(&public_key)?,
+    )?;
+
+    Ok(public_key)
+}
+
+#[cfg(feature = "rsa_keygen")]
+fn generate_rsa_key() -> Result<Vec<u8>> {
+    RsaPrivateKey::pkcs8()
+}
+
+#[cfg(not(feature = "rsa_keygen"))]
+fn generate_rsa_key() -> Result<Vec<u8>> {
+    Err(Error::IllegalArgument(
+        "cannot generate RSA keys, since tuf was built without the `rsa_keygen` feature".into(),
+    ))
+}
+
+/// Load the PKCS#8 private key stored at `path`.
+///
+/// The key type is detected from the key itself. RSA keys can be used with several signature
+/// schemes, so the scheme is taken from the accompanying public key file if there is one, and is
+/// otherwise `rsassa-pss-sha256`.
+pub fn load_private_key(path: &Path) -> Result<Box<dyn Signer>> {
+    let der = read_file(path)?;
+
+    let public_key_path = public_key_path(path);
+    if public_key_path.exists() {
+        let public_key = load_public_key(&public_key_path)?;
+        return parse_private_key(&der, public_key.scheme());
+    }
+
+    [
+        SignatureScheme::Ed25519,
+        SignatureScheme::EcdsaP256Sha256,
+        SignatureScheme::EcdsaP384Sha384,
+        SignatureScheme::RsaSsaPssSha256,
+    ]
+    .iter()
+    .find_map(|scheme| parse_private_key(&der, scheme).ok())
+    .ok_or_else(|| {
+        Error::Encoding(format!(
+            "{} is not a supported PKCS#8 private key",
+            path.display()
+        ))
+    })
+}
+
+/// Load each of the private keys in `paths`.
+pub fn load_private_keys(paths: &[PathBuf]) -> Result<Vec<Box<dyn Signer>>> {
+    paths.iter().map(|path| load_private_key(path)).collect()
+}
+
+/// Load a public key from `path`, which may either be a public key file written by
+/// [generate_key], or a private key file.
+pub fn load_public_key(path: &Path) -> Result<PublicKey> {
+    let bytes = read_file(path)?;
+
+    if let Ok(public_key) = serde_json::from_slice(&bytes) {
+        return Ok(public_key);
+    }
+
+    Ok(load_private_key(path)?.public_key().clone())
+}
+
+fn parse_private_key(der: &[u8], scheme: &SignatureScheme) -> Result<Box<dyn Signer>> {
+    Ok(match scheme {
+        SignatureScheme::Ed25519 => Box::new(Ed25519PrivateKey::from_pkcs8(der)?),
+        SignatureScheme::EcdsaP256Sha256 | SignatureScheme::EcdsaP384Sha384 => {
+            Box::new(EcdsaPrivateKey::from_pkcs8(der, scheme.clone())?)
+        }
+        _ => Box::new(RsaPrivateKey::from_pkcs8(der, scheme.clone())?),
+    })
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|err| {
+        Error::IllegalArgument(format!("failed to read {}: {}", path.display(), err))
+    })
+}
+
+fn write_new_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    use std::io::Write as _;
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|err| {
+            Error::IllegalArgument(format!("failed to create {}: {}", path.display(), err))
+        })?;
+
+    file.write_all(bytes)?;
+
+    Ok(())
+}
@@ -0,0 +1,638 @@
+//! Command-line tools for TUF repositories: `tuf`, for maintaining a repository stored in a local
+//! directory, and `tuf-client`, for fetching verified targets from a repository. See [client] for
+//! the latter.
+//!
+//! `tuf` repositories use the layout of [FileSystemRepository::new], with metadata stored under
+//! `metadata/` and targets stored under `targets/`. Everything is done offline, and all keys are
+//! read from files. See [keys] for the key file formats.
+
+use {
+    chrono::{DateTime, Utc},
+    clap::{Args, Parser, Subcommand, ValueEnum},
+    futures_util::io::AllowStdIo,
+    std::{
+        fs::File,
+        path::{Path, PathBuf},
+    },
+    tuf::{
+        audit::audit_repository,
+        crypto::{SignatureScheme, Signer},
+        metadata::{Delegation, MetadataPath, PathPattern, TargetPath},
+        pouf::{Cbor, Dsse, Pouf1},
+        repo_builder::{RepoBuilder, Targets},
+        repository::{FileSystemRepository, RepositoryStorage},
+        Database, Error, Result,
+    },
+    walkdir::WalkDir,
+};
+
+pub mod client;
+pub mod convert;
+pub mod keys;
+pub mod repo;
+
+/// Run `future` to completion. Fetching from an HTTP repository needs a tokio runtime.
+#[cfg(feature = "hyper")]
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the tokio runtime")
+        .block_on(future)
+}
+
+/// Run `future` to completion.
+#[cfg(not(feature = "hyper"))]
+pub use futures_executor::block_on;
+
+/// Maintain a TUF repository stored in a local directory.
+#[derive(Parser, Debug)]
+#[command(name = "tuf", version)]
+pub struct Cli {
+    /// The command to run.
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// The `tuf` subcommands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a new private key, and write its public key next to it with a `.pub` extension.
+    Keygen {
+        /// The signature scheme the key will be used with.
+        #[arg(long, default_value = "ed25519")]
+        scheme: String,
+
+        /// Where to write the private key.
+        path: PathBuf,
+    },
+
+    /// Create a new repository.
+    Init {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// Don't prefix metadata and targets with their version or hash.
+        #[arg(long)]
+        no_consistent_snapshot: bool,
+
+        /// The number of root keys that must sign the root metadata.
+        #[arg(long, default_value_t = 1)]
+        root_threshold: u32,
+
+        /// The number of targets keys that must sign the targets metadata.
+        #[arg(long, default_value_t = 1)]
+        targets_threshold: u32,
+
+        /// The number of snapshot keys that must sign the snapshot metadata.
+        #[arg(long, default_value_t = 1)]
+        snapshot_threshold: u32,
+
+        /// The number of timestamp keys that must sign the timestamp metadata.
+        #[arg(long, default_value_t = 1)]
+        timestamp_threshold: u32,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Add every file in a directory as a target, replacing any targets with the same path.
+    AddTargets {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// The directory of target files.
+        dir: PathBuf,
+
+        /// Prefix each target path with this, such as `packages/`.
+        #[arg(long, default_value = "")]
+        prefix: String,
+
+        #[command(flatten)]
+        delegated: DelegatedRole,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Remove targets. The target files are left in the repository.
+    RemoveTargets {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// The target paths to remove.
+        #[arg(value_name = "TARGET", required = true)]
+        target_paths: Vec<String>,
+
+        #[command(flatten)]
+        delegated: DelegatedRole,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Delegate targets to a new role, or replace an existing delegation with the same name.
+    Delegate {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// The name of the delegated role.
+        name: String,
+
+        /// A public key file for the delegated role. May be repeated.
+        #[arg(long = "key", value_name = "FILE", required = true)]
+        delegation_keys: Vec<PathBuf>,
+
+        /// A path pattern the delegated role is trusted for, such as `packages/*`. May be
+        /// repeated.
+        #[arg(long = "path", value_name = "PATTERN", required = true)]
+        paths: Vec<String>,
+
+        /// The number of keys that must sign the delegated role's metadata.
+        #[arg(long, default_value_t = 1)]
+        threshold: u32,
+
+        #[command(flatten)]
+        delegated: DelegatedRole,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Remove a delegation, along with the roles only delegated to through it.
+    Undelegate {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// The name of the delegated role.
+        name: String,
+
+        #[command(flatten)]
+        delegated: DelegatedRole,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Sign a new timestamp metadata, and a new snapshot metadata if needed.
+    Refresh {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// Sign a new snapshot metadata even if the current one hasn't expired.
+        #[arg(long)]
+        snapshot: bool,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Sign a new version of the root metadata, such as to extend its expiration or to rotate the
+    /// root keys. The `--root-key` keys become the new root keys.
+    SignRoot {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// A private key file of a current root key that is being rotated out. May be repeated.
+        #[arg(long = "previous-root-key", value_name = "FILE")]
+        previous_root_keys: Vec<PathBuf>,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+
+    /// Verify all of the metadata and targets in the repository.
+    Verify {
+        /// The repository directory.
+        repo: PathBuf,
+    },
+
+    /// Write a copy of the repository with its metadata in another format, such as compact CBOR
+    /// for constrained clients. Each metadata is signed again with whichever of the given keys its
+    /// role trusts, and only the latest root metadata is written.
+    Convert {
+        /// The repository directory.
+        repo: PathBuf,
+
+        /// Where to write the converted repository. It must be empty or not exist yet.
+        out: PathBuf,
+
+        /// The metadata format to convert to.
+        #[arg(long, value_enum, default_value_t = Format::Cbor)]
+        format: Format,
+
+        /// A private key file for a delegated targets role. May be repeated.
+        #[arg(long = "delegation-key", value_name = "FILE")]
+        delegation_keys: Vec<PathBuf>,
+
+        #[command(flatten)]
+        keys: RoleKeys,
+    },
+}
+
+/// The metadata formats a repository can be converted to.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Deterministic CBOR, with the `.cbor` extension.
+    Cbor,
+
+    /// POUF-1 metadata wrapped in DSSE envelopes.
+    Dsse,
+}
+
+/// The private key files used to sign each of the top-level roles. Each may be repeated.
+#[derive(Args, Debug, Default)]
+pub struct RoleKeys {
+    /// A root private key file.
+    #[arg(long = "root-key", value_name = "FILE")]
+    pub root_keys: Vec<PathBuf>,
+
+    /// A targets private key file.
+    #[arg(long = "targets-key", value_name = "FILE")]
+    pub targets_keys: Vec<PathBuf>,
+
+    /// A snapshot private key file.
+    #[arg(long = "snapshot-key", value_name = "FILE")]
+    pub snapshot_keys: Vec<PathBuf>,
+
+    /// A timestamp private key file.
+    #[arg(long = "timestamp-key", value_name = "FILE")]
+    pub timestamp_keys: Vec<PathBuf>,
+}
+
+/// Selects a delegated targets role to change, rather than the top-level targets.
+#[derive(Args, Debug, Default)]
+pub struct DelegatedRole {
+    /// Change this delegated targets role instead of the top-level targets.
+    #[arg(long, value_name = "ROLE")]
+    pub role: Option<String>,
+
+    /// A private key file for the delegated targets role. May be repeated.
+    #[arg(long = "role-key", value_name = "FILE", requires = "role")]
+    pub role_keys: Vec<PathBuf>,
+}
+
+/// The private keys loaded from [RoleKeys] and [DelegatedRole].
+struct Signers {
+    root: Vec<Box<dyn Signer>>,
+    targets: Vec<Box<dyn Signer>>,
+    snapshot: Vec<Box<dyn Signer>>,
+    timestamp: Vec<Box<dyn Signer>>,
+    delegated: Option<(MetadataPath, Vec<Box<dyn Signer>>)>,
+}
+
+impl Signers {
+    fn load(keys: &RoleKeys, delegated: Option<&DelegatedRole>) -> Result<Self> {
+        let delegated = match delegated.and_then(|d| d.role.as_ref().map(|role| (role, d))) {
+            Some((role, delegated)) => Some((
+                MetadataPath::new(role.clone())?,
+                keys::load_private_keys(&delegated.role_keys)?,
+            )),
+            None => None,
+        };
+
+        Ok(Self {
+            root: keys::load_private_keys(&keys.root_keys)?,
+            targets: keys::load_private_keys(&keys.targets_keys)?,
+            snapshot: keys::load_private_keys(&keys.snapshot_keys)?,
+            timestamp: keys::load_private_keys(&keys.timestamp_keys)?,
+            delegated,
+        })
+    }
+
+    fn delegated_role(&self) -> Option<&MetadataPath> {
+        self.delegated.as_ref().map(|(role, _)| role)
+    }
+
+    /// Configure `builder` to sign with these keys.
+    fn apply<'a, R>(&'a self, builder: RepoBuilder<'a, Pouf1, R>) -> RepoBuilder<'a, Pouf1, R>
+    where
+        R: RepositoryStorage<Pouf1>,
+    {
+        builder
+            .trusted_root_keys(&as_signers(&self.root))
+            .trusted_targets_keys(&as_signers(&self.targets))
+            .trusted_snapshot_keys(&as_signers(&self.snapshot))
+            .trusted_timestamp_keys(&as_signers(&self.timestamp))
+    }
+
+    /// Start a [RepoBuilder] that reuses the current root, and will sign any delegated role we're
+    /// changing.
+    fn targets_builder<'a>(
+        &'a self,
+        repo: &'a FileSystemRepository<Pouf1>,
+        db: &'a Database<Pouf1>,
+    ) -> RepoBuilder<'a, Pouf1, &'a FileSystemRepository<Pouf1>, Targets<Pouf1>> {
+        let builder = self.apply(RepoBuilder::from_database(repo, db)).skip_root();
+
+        match &self.delegated {
+            Some((role, keys)) => {
+                builder.signing_delegated_targets_keys(role.clone(), &as_signers(keys))
+            }
+            None => builder,
+        }
+    }
+}
+
+fn as_signers(keys: &[Box<dyn Signer>]) -> Vec<&dyn Signer> {
+    keys.iter().map(|key| &**key).collect()
+}
+
+/// Run a `tuf` command.
+pub async fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Keygen { scheme, path } => {
+            let public_key = keys::generate_key(&path, &SignatureScheme::new(&scheme))?;
+            println!("{}", public_key.key_id());
+            Ok(())
+        }
+        Command::Init {
+            repo,
+            no_consistent_snapshot,
+            root_threshold,
+            targets_threshold,
+            snapshot_threshold,
+            timestamp_threshold,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, None)?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+
+            signers
+                .apply(RepoBuilder::create(&repo))
+                .stage_root_with_builder(|builder| {
+                    builder
+                        .consistent_snapshot(!no_consistent_snapshot)
+                        .root_threshold(root_threshold)
+                        .targets_threshold(targets_threshold)
+                        .snapshot_threshold(snapshot_threshold)
+                        .timestamp_threshold(timestamp_threshold)
+                })
+                .await?
+                .commit()
+                .await?;
+
+            Ok(())
+        }
+        Command::AddTargets {
+            repo,
+            dir,
+            prefix,
+            delegated,
+            keys,
+        } => add_targets(&repo, &dir, &prefix, &delegated, &keys).await,
+        Command::RemoveTargets {
+            repo,
+            target_paths,
+            delegated,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, Some(&delegated))?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+            let db = load_for_update(&repo).await?;
+
+            let mut builder = signers.targets_builder(&repo, &db);
+            for target_path in target_paths {
+                let target_path = TargetPath::new(target_path)?;
+                builder = match signers.delegated_role() {
+                    Some(role) => builder.remove_delegated_target(role.clone(), target_path),
+                    None => builder.remove_target(target_path),
+                };
+            }
+            builder.commit().await?;
+
+            Ok(())
+        }
+        Command::Delegate {
+            repo,
+            name,
+            delegation_keys,
+            paths,
+            threshold,
+            delegated,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, Some(&delegated))?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+            let db = load_for_update(&repo).await?;
+
+            let delegation_keys = delegation_keys
+                .iter()
+                .map(|path| keys::load_public_key(path))
+                .collect::<Result<Vec<_>>>()?;
+
+            let mut delegation = Delegation::builder(MetadataPath::new(name)?).threshold(threshold);
+            for key in &delegation_keys {
+                delegation = delegation.key(key);
+            }
+            for path in paths {
+                delegation = delegation.delegate_path(PathPattern::new(path)?);
+            }
+            let delegation = delegation.build()?;
+
+            let mut builder = signers.targets_builder(&repo, &db);
+            match signers.delegated_role() {
+                Some(role) => {
+                    for key in delegation_keys {
+                        builder = builder.add_delegated_delegation_key(role.clone(), key);
+                    }
+                    builder = builder.add_delegated_delegation_role(role.clone(), delegation);
+                }
+                None => {
+                    for key in delegation_keys {
+                        builder = builder.add_delegation_key(key);
+                    }
+                    builder = builder.add_delegation_role(delegation);
+                }
+            }
+            builder.commit().await?;
+
+            Ok(())
+        }
+        Command::Undelegate {
+            repo,
+            name,
+            delegated,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, Some(&delegated))?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+            let db = load_for_update(&repo).await?;
+
+            let name = MetadataPath::new(name)?;
+            let builder = signers.targets_builder(&repo, &db);
+            let builder = match signers.delegated_role() {
+                Some(role) => builder.remove_delegated_delegation_role(role.clone(), name),
+                None => builder.remove_delegation_role(name),
+            };
+            builder.commit().await?;
+
+            Ok(())
+        }
+        Command::Refresh {
+            repo,
+            snapshot,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, None)?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+            let db = load_for_update(&repo).await?;
+
+            let builder = signers
+                .apply(RepoBuilder::from_database(&repo, &db))
+                .skip_root()
+                .skip_targets();
+
+            let builder = if snapshot {
+                builder.stage_snapshot().await?
+            } else {
+                builder.stage_snapshot_if_necessary().await?
+            };
+
+            builder.stage_timestamp().await?.commit().await?;
+
+            Ok(())
+        }
+        Command::SignRoot {
+            repo,
+            previous_root_keys,
+            keys,
+        } => {
+            let signers = Signers::load(&keys, None)?;
+            let previous_root_keys = keys::load_private_keys(&previous_root_keys)?;
+            let repo = FileSystemRepository::<Pouf1>::new(repo);
+            let db = load_for_update(&repo).await?;
+            let trusted_root = db.trusted_root();
+
+            // Only the root keys are changing, so carry over the other roles' keys from the
+            // current root rather than requiring their private keys.
+            signers
+                .apply(RepoBuilder::from_database(&repo, &db))
+                .signing_root_keys(&as_signers(&previous_root_keys))
+                .stage_root_with_builder(|builder| {
+                    let builder = trusted_root
+                        .targets_keys()
+                        .fold(builder, |builder, key| builder.targets_key(key.clone()));
+                    let builder = trusted_root
+                        .snapshot_keys()
+                        .fold(builder, |builder, key| builder.snapshot_key(key.clone()));
+                    trusted_root
+                        .timestamp_keys()
+                        .fold(builder, |builder, key| builder.timestamp_key(key.clone()))
+                })
+                .await?
+                .skip_targets()
+                .skip_snapshot()
+                .skip_timestamp()
+                .commit()
+                .await?;
+
+            Ok(())
+        }
+        Command::Verify { repo } => {
+            let count = verify(&repo).await?;
+            println!("verified {} targets", count);
+            Ok(())
+        }
+        Command::Convert {
+            repo,
+            out,
+            format,
+            delegation_keys,
+            keys,
+        } => {
+            let signers = [
+                &keys.root_keys,
+                &keys.targets_keys,
+                &keys.snapshot_keys,
+                &keys.timestamp_keys,
+                &delegation_keys,
+            ]
+            .into_iter()
+            .map(|paths| keys::load_private_keys(paths))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+            match format {
+                Format::Cbor => convert::convert_repository::<Cbor>(&repo, &out, &signers).await,
+                Format::Dsse => convert::convert_repository::<Dsse>(&repo, &out, &signers).await,
+            }
+        }
+    }
+}
+
+/// Load the repository's metadata so it can be updated. The metadata is not checked for
+/// expiration, since refreshing expired metadata is one of the reasons to update it.
+async fn load_for_update(repo: &FileSystemRepository<Pouf1>) -> Result<Database<Pouf1>> {
+    repo::load_database(repo, &DateTime::<Utc>::MIN_UTC).await
+}
+
+async fn add_targets(
+    repo: &Path,
+    dir: &Path,
+    prefix: &str,
+    delegated: &DelegatedRole,
+    keys: &RoleKeys,
+) -> Result<()> {
+    let signers = Signers::load(keys, Some(delegated))?;
+    let repo = FileSystemRepository::<Pouf1>::new(repo);
+    let db = load_for_update(&repo).await?;
+
+    let mut builder = signers.targets_builder(&repo, &db);
+
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry.map_err(|err| Error::Opaque(err.to_string()))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let relative_path = entry
+            .path()
+            .strip_prefix(dir)
+            .map_err(|err| Error::Opaque(err.to_string()))?;
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let target_path = TargetPath::new(format!("{}{}", prefix, relative_path))?;
+
+        let reader = AllowStdIo::new(File::open(entry.path())?);
+
+        builder = match signers.delegated_role() {
+            Some(role) => {
+                builder
+                    .add_delegated_target(role.clone(), target_path, reader)
+                    .await?
+            }
+            None => builder.add_target(target_path, reader).await?,
+        };
+    }
+
+    builder.commit().await?;
+
+    Ok(())
+}
+
+/// Verify the repository at `path`, and return how many targets it has.
+///
+/// See [audit_repository] for everything that is checked. If anything is wrong, the error lists
+/// all of the problems that were found.
+pub async fn verify(path: &Path) -> Result<usize> {
+    let repo = FileSystemRepository::<Pouf1>::new(path);
+    let report = audit_repository(&repo, &Utc::now()).await;
+
+    if report.is_ok() {
+        Ok(report.targets_checked())
+    } else {
+        let findings = report
+            .findings()
+            .iter()
+            .map(|finding| finding.to_string())
+            .collect::<Vec<_>>();
+
+        Err(Error::Opaque(findings.join("\n")))
+    }
+}
@@ -0,0 +1,13 @@
+use {clap::Parser as _, futures_executor::block_on, std::process::ExitCode, tuf_cli::Cli};
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match block_on(tuf_cli::run(cli)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Loading a local repository's metadata.
+
+use {
+    chrono::{DateTime, Utc},
+    futures_util::AsyncReadExt as _,
+    tuf::{
+        client::{Client, Config},
+        metadata::{
+            Metadata, MetadataPath, MetadataVersion, RawSignedMetadata, RootMetadata,
+            TargetsMetadata,
+        },
+        pouf::Pouf,
+        repository::{EphemeralRepository, FileSystemRepository, RepositoryProvider},
+        Database, Result,
+    },
+};
+
+/// Load and verify all of the metadata in `repo`, starting from its initial root metadata.
+///
+/// The initial root metadata is trusted on first use, since this is meant to be run by the
+/// repository's own maintainers against their local copy.
+///
+/// Metadata is checked for expiration against `start_time`. Any roles in the snapshot that can't
+/// be reached through the delegations are left out of the database.
+pub async fn load_database<D: Pouf>(
+    repo: &FileSystemRepository<D>,
+    start_time: &DateTime<Utc>,
+) -> Result<Database<D>> {
+    let raw_root: RawSignedMetadata<D, RootMetadata> =
+        fetch_raw_metadata(repo, &MetadataPath::root(), MetadataVersion::Number(1)).await?;
+
+    load_database_from_root(repo, &raw_root, start_time).await
+}
+
+/// Load and verify all of the metadata in `repo` like [load_database], but starting from
+/// `raw_root` rather than the repository's initial root metadata.
+pub async fn load_database_from_root<D: Pouf>(
+    repo: &FileSystemRepository<D>,
+    raw_root: &RawSignedMetadata<D, RootMetadata>,
+    start_time: &DateTime<Utc>,
+) -> Result<Database<D>> {
+    let mut client = Client::with_trusted_root(
+        Config::default(),
+        raw_root,
+        EphemeralRepository::new(),
+        repo,
+    )
+    .await?;
+    client.update_with_start_time(start_time).await?;
+
+    let mut db = client.into_parts().database;
+    load_delegations(repo, &mut db, start_time).await?;
+
+    Ok(db)
+}
+
+/// Load every delegated targets metadata described by the trusted snapshot, walking down from the
+/// top-level targets so each one is verified by the role that delegates to it.
+async fn load_delegations<D: Pouf>(
+    repo: &FileSystemRepository<D>,
+    db: &mut Database<D>,
+    start_time: &DateTime<Utc>,
+) -> Result<()> {
+    let consistent_snapshot = db.trusted_root().consistent_snapshot();
+
+    let mut pending = if let Some(snapshot) = db.trusted_snapshot() {
+        snapshot
+            .meta()
+            .iter()
+            .filter(|(role, _)| **role != MetadataPath::targets())
+            .map(|(role, description)| (role.clone(), description.version()))
+            .collect::<Vec<_>>()
+    } else {
+        return Ok(());
+    };
+    pending.sort();
+
+    loop {
+        let ready = pending
+            .iter()
+            .enumerate()
+            .find_map(|(i, (role, _))| db.delegating_role(role).map(|parent| (i, parent)));
+
+        let (i, parent) = if let Some(ready) = ready {
+            ready
+        } else {
+            return Ok(());
+        };
+
+        let (role, version) = pending.remove(i);
+        let version = if consistent_snapshot {
+            MetadataVersion::Number(version)
+        } else {
+            MetadataVersion::None
+        };
+
+        let raw_targets: RawSignedMetadata<D, TargetsMetadata> =
+            fetch_raw_metadata(repo, &role, version).await?;
+
+        db.update_delegated_targets(start_time, &parent, &role, &raw_targets)?;
+    }
+}
+
+async fn fetch_raw_metadata<D: Pouf, M: Metadata>(
+    repo: &FileSystemRepository<D>,
+    path: &MetadataPath,
+    version: MetadataVersion,
+) -> Result<RawSignedMetadata<D, M>> {
+    let mut reader = repo.fetch_metadata(path, version).await?;
+
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf).await?;
+
+    Ok(RawSignedMetadata::new(buf))
+}
@@ -0,0 +1,173 @@
+use {
+    assert_matches::assert_matches,
+    clap::CommandFactory as _,
+    common::{generate_keys, path_str, tuf, tuf_with_keys},
+    futures_executor::block_on,
+    std::fs,
+    tempfile::TempDir,
+    tuf::{metadata::MetadataPath, Error},
+    tuf_cli::Cli,
+};
+
+mod common;
+
+fn check_repository(consistent_snapshot: bool) {
+    let tmp = TempDir::new().unwrap();
+    let keys = tmp.path().join("keys");
+    let repo = tmp.path().join("repo");
+    let files = tmp.path().join("files");
+    fs::create_dir_all(&keys).unwrap();
+    fs::create_dir_all(files.join("nested")).unwrap();
+    fs::create_dir_all(files.join("a")).unwrap();
+
+    fs::write(files.join("foo"), b"foo").unwrap();
+    fs::write(files.join("nested").join("bar"), b"bar").unwrap();
+    fs::write(files.join("a").join("baz"), b"baz").unwrap();
+
+    generate_keys(&keys);
+    let repo_str = path_str(&repo);
+
+    let mut init = vec!["init", repo_str];
+    if !consistent_snapshot {
+        init.push("--no-consistent-snapshot");
+    }
+    tuf_with_keys(&init, &keys).unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 0);
+
+    // Add the top-level targets.
+    tuf_with_keys(
+        &["add-targets", repo_str, path_str(&files.join("nested"))],
+        &keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "add-targets",
+            repo_str,
+            path_str(&files),
+            "--prefix",
+            "top/",
+        ],
+        &keys,
+    )
+    .unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 4);
+
+    // Delegate `a/*` to `a`, and have `a` sign some targets.
+    tuf_with_keys(
+        &[
+            "delegate",
+            repo_str,
+            "a",
+            "--key",
+            path_str(&keys.join("a.pub")),
+            "--path",
+            "a/*",
+        ],
+        &keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "add-targets",
+            repo_str,
+            path_str(&files.join("a")),
+            "--prefix",
+            "a/",
+            "--role",
+            "a",
+            "--role-key",
+            path_str(&keys.join("a")),
+        ],
+        &keys,
+    )
+    .unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 5);
+
+    // Remove a target and the delegation.
+    tuf_with_keys(&["remove-targets", repo_str, "top/foo"], &keys).unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 4);
+    tuf_with_keys(&["undelegate", repo_str, "a"], &keys).unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 3);
+
+    // Refresh the timestamp and snapshot, and rotate the root key.
+    tuf_with_keys(&["refresh", repo_str, "--snapshot"], &keys).unwrap();
+    tuf(&[
+        "sign-root",
+        repo_str,
+        "--root-key",
+        path_str(&keys.join("root2")),
+        "--previous-root-key",
+        path_str(&keys.join("root")),
+    ])
+    .unwrap();
+    assert_eq!(block_on(tuf_cli::verify(&repo)).unwrap(), 3);
+
+    // The old root key can no longer sign the root.
+    assert_matches!(
+        tuf(&[
+            "sign-root",
+            repo_str,
+            "--root-key",
+            path_str(&keys.join("root")),
+        ]),
+        Err(Error::MetadataMissingSignatures { role, .. }) if role == MetadataPath::root()
+    );
+
+    // Tampering with a target is caught.
+    let targets_dir = repo.join("targets").join("top").join("nested");
+    for entry in fs::read_dir(&targets_dir).unwrap() {
+        fs::write(entry.unwrap().path(), b"tampered").unwrap();
+    }
+    assert!(block_on(tuf_cli::verify(&repo)).is_err());
+}
+
+#[test]
+fn test_cli() {
+    Cli::command().debug_assert();
+}
+
+#[test]
+fn test_maintain_consistent_snapshot_repository() {
+    check_repository(true);
+}
+
+#[test]
+fn test_maintain_repository() {
+    check_repository(false);
+}
+
+#[test]
+fn test_keygen_does_not_overwrite_keys() {
+    let tmp = TempDir::new().unwrap();
+    let key = tmp.path().join("key");
+
+    tuf(&["keygen", path_str(&key)]).unwrap();
+    let der = fs::read(&key).unwrap();
+
+    assert_matches!(
+        tuf(&["keygen", "--scheme", "ecdsa-sha2-nistp256", path_str(&key)]),
+        Err(Error::IllegalArgument(_))
+    );
+    assert_eq!(fs::read(&key).unwrap(), der);
+
+    let ecdsa_key = tmp.path().join("ecdsa");
+    tuf(&[
+        "keygen",
+        "--scheme",
+        "ecdsa-sha2-nistp256",
+        path_str(&ecdsa_key),
+    ])
+    .unwrap();
+    fs::remove_file(tuf_cli::keys::public_key_path(&ecdsa_key)).unwrap();
+
+    // Without the public key file, the scheme is detected from the private key.
+    assert_eq!(
+        tuf_cli::keys::load_private_key(&ecdsa_key)
+            .unwrap()
+            .public_key()
+            .scheme()
+            .as_str(),
+        "ecdsa-sha2-nistp256"
+    );
+}
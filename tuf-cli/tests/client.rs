@@ -0,0 +1,274 @@
+use {
+    assert_matches::assert_matches,
+    clap::{CommandFactory as _, Parser as _},
+    common::{generate_keys, path_str, tuf, tuf_with_keys},
+    futures_executor::block_on,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+    tempfile::TempDir,
+    tuf::{Error, Result},
+    tuf_cli::client::{run_client, ClientCli},
+};
+
+mod common;
+
+/// Create a repository in `dir/repo` with a top-level target `top/foo`, and a target `a/baz`
+/// signed by the delegated role `a`. The root is then rotated to a new key, so clients have to
+/// update the root they start from.
+fn create_repository(dir: &Path) -> PathBuf {
+    let keys = dir.join("keys");
+    let repo = dir.join("repo");
+    let files = dir.join("files");
+    fs::create_dir_all(&keys).unwrap();
+    fs::create_dir_all(files.join("top")).unwrap();
+    fs::create_dir_all(files.join("a")).unwrap();
+    fs::write(files.join("top").join("foo"), b"foo").unwrap();
+    fs::write(files.join("a").join("baz"), b"baz").unwrap();
+
+    generate_keys(&keys);
+    let repo_str = path_str(&repo);
+
+    tuf_with_keys(&["init", repo_str], &keys).unwrap();
+    tuf_with_keys(
+        &[
+            "add-targets",
+            repo_str,
+            path_str(&files.join("top")),
+            "--prefix",
+            "top/",
+        ],
+        &keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "delegate",
+            repo_str,
+            "a",
+            "--key",
+            path_str(&keys.join("a.pub")),
+            "--path",
+            "a/*",
+        ],
+        &keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "add-targets",
+            repo_str,
+            path_str(&files.join("a")),
+            "--prefix",
+            "a/",
+            "--role",
+            "a",
+            "--role-key",
+            path_str(&keys.join("a")),
+        ],
+        &keys,
+    )
+    .unwrap();
+    tuf(&[
+        "sign-root",
+        repo_str,
+        "--root-key",
+        path_str(&keys.join("root2")),
+        "--previous-root-key",
+        path_str(&keys.join("root")),
+    ])
+    .unwrap();
+
+    repo
+}
+
+fn client(root: &Path, remote: &str, cache: &Path, target_paths: &[&str]) -> ClientCli {
+    let args = [
+        "tuf-client",
+        "--root",
+        path_str(root),
+        "--remote",
+        remote,
+        "--cache",
+    ]
+    .into_iter()
+    .chain([path_str(cache)])
+    .chain(target_paths.iter().copied());
+
+    ClientCli::try_parse_from(args).unwrap()
+}
+
+fn fetch(repo: &Path, cache: &Path, target_paths: &[&str]) -> Result<()> {
+    let root = repo.join("metadata").join("1.root.json");
+    block_on(run_client(client(
+        &root,
+        path_str(repo),
+        cache,
+        target_paths,
+    )))
+}
+
+#[test]
+fn test_client_cli() {
+    ClientCli::command().debug_assert();
+}
+
+#[test]
+fn test_fetch_from_directory() {
+    let tmp = TempDir::new().unwrap();
+    let repo = create_repository(tmp.path());
+    let cache = tmp.path().join("cache");
+
+    fetch(&repo, &cache, &["top/foo", "a/baz"]).unwrap();
+
+    assert_eq!(fs::read(cache.join("targets/top/foo")).unwrap(), b"foo");
+    assert_eq!(fs::read(cache.join("targets/a/baz")).unwrap(), b"baz");
+    assert!(cache.join("metadata/root.json").exists());
+
+    // Targets the repository doesn't describe can't be fetched.
+    assert_matches!(
+        fetch(&repo, &cache, &["top/missing"]),
+        Err(Error::TargetNotFound(_))
+    );
+}
+
+#[test]
+fn test_fetch_from_spec_version_directory() {
+    let tmp = TempDir::new().unwrap();
+    let repo = create_repository(tmp.path());
+    let cache = tmp.path().join("cache");
+
+    // Move the metadata into the directory for major version 1, as described in TAP 14.
+    let metadata = repo.join("metadata");
+    fs::rename(&metadata, tmp.path().join("metadata-1")).unwrap();
+    fs::create_dir(&metadata).unwrap();
+    fs::rename(tmp.path().join("metadata-1"), metadata.join("1")).unwrap();
+
+    let root = metadata.join("1").join("1.root.json");
+    block_on(run_client(client(
+        &root,
+        path_str(&repo),
+        &cache,
+        &["top/foo", "a/baz"],
+    )))
+    .unwrap();
+
+    assert_eq!(fs::read(cache.join("targets/top/foo")).unwrap(), b"foo");
+    assert_eq!(fs::read(cache.join("targets/a/baz")).unwrap(), b"baz");
+}
+
+#[test]
+fn test_fetch_tampered_target_fails() {
+    let tmp = TempDir::new().unwrap();
+    let repo = create_repository(tmp.path());
+    let cache = tmp.path().join("cache");
+
+    for entry in fs::read_dir(repo.join("targets").join("a")).unwrap() {
+        fs::write(entry.unwrap().path(), b"bad").unwrap();
+    }
+
+    assert!(fetch(&repo, &cache, &["a/baz"]).is_err());
+    assert!(!cache.join("targets/a/baz").exists());
+
+    // The untouched targets can still be fetched.
+    fetch(&repo, &cache, &["top/foo"]).unwrap();
+}
+
+#[test]
+fn test_fetch_with_untrusted_root_fails() {
+    let tmp = TempDir::new().unwrap();
+    let repo = create_repository(tmp.path());
+    let other_repo = create_repository(&tmp.path().join("other"));
+    let cache = tmp.path().join("cache");
+
+    let root = other_repo.join("metadata").join("1.root.json");
+    assert!(block_on(run_client(client(
+        &root,
+        path_str(&repo),
+        &cache,
+        &["top/foo"]
+    )))
+    .is_err());
+    assert!(!cache.join("targets/top/foo").exists());
+}
+
+#[cfg(feature = "hyper")]
+#[test]
+fn test_fetch_from_http() {
+    let tmp = TempDir::new().unwrap();
+    let repo = create_repository(tmp.path());
+    let cache = tmp.path().join("cache");
+
+    let url = format!("http://{}/", http_server::serve(repo.clone()));
+    let root = repo.join("metadata").join("1.root.json");
+
+    tuf_cli::block_on(run_client(client(
+        &root,
+        &url,
+        &cache,
+        &["top/foo", "a/baz"],
+    )))
+    .unwrap();
+
+    assert_eq!(fs::read(cache.join("targets/top/foo")).unwrap(), b"foo");
+    assert_eq!(fs::read(cache.join("targets/a/baz")).unwrap(), b"baz");
+}
+
+#[cfg(feature = "hyper")]
+mod http_server {
+    use std::{
+        io::{BufRead as _, BufReader, Write as _},
+        net::{SocketAddr, TcpListener, TcpStream},
+        path::PathBuf,
+        thread,
+    };
+
+    /// Serve the files in `dir` over HTTP on a loopback port, for the rest of the test process.
+    pub fn serve(dir: PathBuf) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let _ = respond(&dir, stream.unwrap());
+            }
+        });
+
+        addr
+    }
+
+    fn respond(dir: &std::path::Path, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header == "\r\n" {
+                break;
+            }
+        }
+
+        let path = request_line.split(' ').nth(1).unwrap_or("/");
+        let file = path
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != "..")
+            .fold(dir.to_path_buf(), |file, segment| file.join(segment));
+
+        match std::fs::read(file) {
+            Ok(body) => {
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )?;
+                stream.write_all(&body)
+            }
+            Err(_) => write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            ),
+        }
+    }
+}
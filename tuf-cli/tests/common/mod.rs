@@ -0,0 +1,52 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use {
+    clap::Parser as _,
+    futures_executor::block_on,
+    std::path::Path,
+    tuf::{Error, Result},
+    tuf_cli::{run, Cli},
+};
+
+pub fn tuf(args: &[&str]) -> Result<()> {
+    let cli = Cli::try_parse_from(std::iter::once("tuf").chain(args.iter().copied()))
+        .map_err(|err| Error::IllegalArgument(err.to_string()))?;
+
+    block_on(run(cli))
+}
+
+pub fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+/// Generate a key for each top-level role, plus one for a delegation.
+pub fn generate_keys(dir: &Path) {
+    for name in ["root", "root2", "targets", "snapshot", "timestamp", "a"] {
+        tuf(&["keygen", path_str(&dir.join(name))]).unwrap();
+    }
+}
+
+fn top_level_keys(keys: &Path) -> Vec<String> {
+    ["root", "targets", "snapshot", "timestamp"]
+        .iter()
+        .flat_map(|role| {
+            vec![
+                format!("--{}-key", role),
+                path_str(&keys.join(role)).to_string(),
+            ]
+        })
+        .collect()
+}
+
+pub fn tuf_with_keys(args: &[&str], keys: &Path) -> Result<()> {
+    let key_args = top_level_keys(keys);
+    let args = args
+        .iter()
+        .copied()
+        .chain(key_args.iter().map(|s| s.as_str()))
+        .collect::<Vec<_>>();
+
+    tuf(&args)
+}
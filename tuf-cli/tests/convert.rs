@@ -0,0 +1,248 @@
+use {
+    assert_matches::assert_matches,
+    common::{generate_keys, path_str, tuf, tuf_with_keys},
+    futures_executor::block_on,
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+    tempfile::TempDir,
+    tuf::{
+        metadata::{Metadata, MetadataPath, RawSignedMetadata},
+        pouf::{Cbor, Dsse, Pouf, Pouf1},
+        repository::FileSystemRepository,
+        Database, Error,
+    },
+    tuf_cli::repo::{load_database, load_database_from_root},
+    walkdir::WalkDir,
+};
+
+mod common;
+
+/// Create a repository in `dir/repo` with a top-level target and a target signed by the delegated
+/// role `a`, whose root has been rotated to the `root2` key.
+fn create_repository(dir: &Path, keys: &Path) -> PathBuf {
+    let repo = dir.join("repo");
+    let files = dir.join("files");
+    fs::create_dir_all(keys).unwrap();
+    fs::create_dir_all(files.join("top")).unwrap();
+    fs::create_dir_all(files.join("a")).unwrap();
+    fs::write(files.join("top").join("foo"), b"foo").unwrap();
+    fs::write(files.join("a").join("baz"), b"baz").unwrap();
+
+    generate_keys(keys);
+    let repo_str = path_str(&repo);
+
+    tuf_with_keys(&["init", repo_str], keys).unwrap();
+    tuf_with_keys(
+        &["add-targets", repo_str, path_str(&files.join("top"))],
+        keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "delegate",
+            repo_str,
+            "a",
+            "--key",
+            path_str(&keys.join("a.pub")),
+            "--path",
+            "a/*",
+        ],
+        keys,
+    )
+    .unwrap();
+    tuf_with_keys(
+        &[
+            "add-targets",
+            repo_str,
+            path_str(&files.join("a")),
+            "--prefix",
+            "a/",
+            "--role",
+            "a",
+            "--role-key",
+            path_str(&keys.join("a")),
+        ],
+        keys,
+    )
+    .unwrap();
+    tuf(&[
+        "sign-root",
+        repo_str,
+        "--root-key",
+        path_str(&keys.join("root2")),
+        "--previous-root-key",
+        path_str(&keys.join("root")),
+    ])
+    .unwrap();
+
+    repo
+}
+
+fn convert(repo: &Path, out: &Path, keys: &Path, extra_args: &[&str]) -> tuf::Result<()> {
+    let root_key = keys.join("root2");
+    let targets_key = keys.join("targets");
+    let snapshot_key = keys.join("snapshot");
+    let timestamp_key = keys.join("timestamp");
+
+    let mut args = vec!["convert", path_str(repo), path_str(out)];
+    args.extend(extra_args);
+    args.extend([
+        "--root-key",
+        path_str(&root_key),
+        "--targets-key",
+        path_str(&targets_key),
+        "--snapshot-key",
+        path_str(&snapshot_key),
+        "--timestamp-key",
+        path_str(&timestamp_key),
+    ]);
+
+    tuf(&args)
+}
+
+fn load_converted<D: Pouf>(out: &Path) -> Database<D> {
+    let root = RawSignedMetadata::new(
+        fs::read(
+            out.join("metadata")
+                .join(format!("2.root.{}", D::extension())),
+        )
+        .unwrap(),
+    );
+
+    block_on(load_database_from_root(
+        &FileSystemRepository::<D>::new(out),
+        &root,
+        &chrono::Utc::now(),
+    ))
+    .unwrap()
+}
+
+fn check_same_metadata<D: Pouf>(original: &Database<Pouf1>, converted: &Database<D>) {
+    assert_eq!(**converted.trusted_root(), **original.trusted_root());
+    assert_eq!(
+        converted.trusted_targets().map(|t| &**t),
+        original.trusted_targets().map(|t| &**t)
+    );
+    assert_eq!(
+        converted.trusted_delegations()[&MetadataPath::new("a").unwrap()].targets(),
+        original.trusted_delegations()[&MetadataPath::new("a").unwrap()].targets()
+    );
+    assert_eq!(
+        converted.trusted_snapshot().unwrap().version(),
+        original.trusted_snapshot().unwrap().version()
+    );
+    assert_eq!(
+        converted.trusted_timestamp().unwrap().version(),
+        original.trusted_timestamp().unwrap().version()
+    );
+}
+
+#[test]
+fn test_convert_to_cbor() {
+    let tmp = TempDir::new().unwrap();
+    let keys = tmp.path().join("keys");
+    let repo = create_repository(tmp.path(), &keys);
+    let out = tmp.path().join("out");
+
+    convert(
+        &repo,
+        &out,
+        &keys,
+        &["--delegation-key", path_str(&keys.join("a"))],
+    )
+    .unwrap();
+
+    let original = block_on(load_database(
+        &FileSystemRepository::<Pouf1>::new(&repo),
+        &chrono::Utc::now(),
+    ))
+    .unwrap();
+    let converted = load_converted::<Cbor>(&out);
+    check_same_metadata(&original, &converted);
+
+    // Only the latest root is written, and the metadata is smaller than the JSON it came from.
+    let metadata = out.join("metadata");
+    assert!(!metadata.join("1.root.cbor").exists());
+    let json_len = fs::metadata(repo.join("metadata").join("2.root.json"))
+        .unwrap()
+        .len();
+    let cbor_len = fs::metadata(metadata.join("2.root.cbor")).unwrap().len();
+    assert!(cbor_len < json_len);
+
+    // The targets are copied over.
+    for entry in WalkDir::new(repo.join("targets")) {
+        let entry = entry.unwrap();
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative_path = entry.path().strip_prefix(repo.join("targets")).unwrap();
+        assert_eq!(
+            fs::read(out.join("targets").join(relative_path)).unwrap(),
+            fs::read(entry.path()).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_convert_to_dsse() {
+    let tmp = TempDir::new().unwrap();
+    let keys = tmp.path().join("keys");
+    let repo = create_repository(tmp.path(), &keys);
+    let out = tmp.path().join("out");
+
+    convert(
+        &repo,
+        &out,
+        &keys,
+        &[
+            "--format",
+            "dsse",
+            "--delegation-key",
+            path_str(&keys.join("a")),
+        ],
+    )
+    .unwrap();
+
+    let original = block_on(load_database(
+        &FileSystemRepository::<Pouf1>::new(&repo),
+        &chrono::Utc::now(),
+    ))
+    .unwrap();
+    let converted = load_converted::<Dsse>(&out);
+    check_same_metadata(&original, &converted);
+}
+
+#[test]
+fn test_convert_requires_every_role_key() {
+    let tmp = TempDir::new().unwrap();
+    let keys = tmp.path().join("keys");
+    let repo = create_repository(tmp.path(), &keys);
+    let out = tmp.path().join("out");
+
+    assert_matches!(
+        convert(&repo, &out, &keys, &[]),
+        Err(Error::MetadataMissingSignatures { role, .. }) if role == MetadataPath::new("a").unwrap()
+    );
+
+    // Nothing was written.
+    assert!(!out.join("metadata").exists());
+}
+
+#[test]
+fn test_convert_does_not_overwrite() {
+    let tmp = TempDir::new().unwrap();
+    let keys = tmp.path().join("keys");
+    let repo = create_repository(tmp.path(), &keys);
+
+    assert_matches!(
+        convert(
+            &repo,
+            &repo,
+            &keys,
+            &["--delegation-key", path_str(&keys.join("a"))]
+        ),
+        Err(Error::IllegalArgument(_))
+    );
+}
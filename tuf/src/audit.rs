@@ -0,0 +1,981 @@
+//! Offline auditing of a whole repository.
+//!
+//! Unlike [Client](crate::client::Client), which stops at the first problem it runs into, an audit
+//! keeps going for as long as it can, and collects everything it finds into an [AuditReport]. It
+//! is meant for repository maintainers who want to check a repository before publishing it.
+
+use {
+    crate::{
+        crypto::{self, HashAlgorithm, HashValue},
+        database::Database,
+        error::{Error, Result},
+        metadata::{
+            Metadata, MetadataDescription, MetadataPath, MetadataVersion, RawSignedMetadata,
+            RootMetadata, SnapshotMetadata, TargetDescription, TargetPath, TargetsMetadata,
+            TimestampMetadata,
+        },
+        pouf::Pouf,
+        repository::RepositoryProvider,
+    },
+    chrono::{DateTime, Utc},
+    futures_util::io::AsyncReadExt as _,
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt,
+        marker::PhantomData,
+    },
+};
+
+/// Something wrong with the repository that was found by an audit.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Finding {
+    /// The metadata could not be fetched, parsed, or verified.
+    InvalidMetadata {
+        /// The role of the metadata.
+        role: MetadataPath,
+        /// The version of the metadata that was fetched.
+        version: MetadataVersion,
+        /// Why the metadata is invalid.
+        error: Error,
+    },
+
+    /// The metadata expired before the time the repository was audited at.
+    ExpiredMetadata {
+        /// The role of the metadata.
+        role: MetadataPath,
+        /// The version of the metadata.
+        version: u32,
+        /// When the metadata expired.
+        expires: DateTime<Utc>,
+    },
+
+    /// The metadata does not match the length or hashes it is described with by its parent.
+    MetadataMismatch {
+        /// The role that describes the metadata.
+        parent_role: MetadataPath,
+        /// The role of the metadata.
+        role: MetadataPath,
+        /// The version of the metadata.
+        version: u32,
+        /// How the metadata differs from its description.
+        mismatch: Mismatch,
+    },
+
+    /// The snapshot describes a role that is not delegated to by any trusted targets metadata.
+    UnreachableRole {
+        /// The undelegated role.
+        role: MetadataPath,
+    },
+
+    /// A role is delegated to, but the snapshot does not describe it.
+    UndescribedRole {
+        /// The role that delegates to `role`.
+        parent_role: MetadataPath,
+        /// The role missing from the snapshot.
+        role: MetadataPath,
+    },
+
+    /// The target could not be fetched from the repository, or can't be verified.
+    InvalidTarget {
+        /// The role that describes the target.
+        role: MetadataPath,
+        /// The path the target was fetched from. Under consistent snapshots, this is prefixed with
+        /// the target's hash.
+        path: TargetPath,
+        /// Why the target is invalid.
+        error: Error,
+    },
+
+    /// The target does not match the length or hashes it is described with.
+    TargetMismatch {
+        /// The role that describes the target.
+        role: MetadataPath,
+        /// The path the target was fetched from. Under consistent snapshots, this is prefixed with
+        /// the target's hash.
+        path: TargetPath,
+        /// How the target differs from its description.
+        mismatch: Mismatch,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::InvalidMetadata {
+                role,
+                version,
+                error,
+            } => write!(
+                f,
+                "{} metadata version {} is invalid: {}",
+                role, version, error
+            ),
+            Finding::ExpiredMetadata {
+                role,
+                version,
+                expires,
+            } => write!(
+                f,
+                "{} metadata version {} expired at {}",
+                role, version, expires
+            ),
+            Finding::MetadataMismatch {
+                parent_role,
+                role,
+                version,
+                mismatch,
+            } => write!(
+                f,
+                "{} metadata version {} does not match its description in {}: {}",
+                role, version, parent_role, mismatch
+            ),
+            Finding::UnreachableRole { role } => write!(
+                f,
+                "{} is described by the snapshot, but is not delegated to",
+                role
+            ),
+            Finding::UndescribedRole { parent_role, role } => write!(
+                f,
+                "{} delegates to {}, but it is not described by the snapshot",
+                parent_role, role
+            ),
+            Finding::InvalidTarget { role, path, error } => {
+                write!(f, "target {} from {} is invalid: {}", path, role, error)
+            }
+            Finding::TargetMismatch {
+                role,
+                path,
+                mismatch,
+            } => write!(
+                f,
+                "target {} does not match its description in {}: {}",
+                path, role, mismatch
+            ),
+        }
+    }
+}
+
+/// How a file differs from its description.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The file has the wrong length.
+    Length {
+        /// The described length.
+        expected: u64,
+        /// The actual length.
+        actual: u64,
+    },
+
+    /// The file has the wrong hash.
+    Hash {
+        /// The algorithm of the mismatched hash.
+        algorithm: HashAlgorithm,
+        /// The described hash.
+        expected: HashValue,
+        /// The actual hash.
+        actual: HashValue,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::Length { expected, actual } => {
+                write!(f, "expected {} bytes, found {} bytes", expected, actual)
+            }
+            Mismatch::Hash {
+                algorithm,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "expected {:?} hash {}, found {}",
+                algorithm, expected, actual
+            ),
+        }
+    }
+}
+
+/// The results of auditing a repository with [audit_repository].
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    findings: Vec<Finding>,
+    targets_checked: usize,
+}
+
+impl AuditReport {
+    /// Everything that was found wrong with the repository, in the order it was found.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Consume the report, returning its findings.
+    pub fn into_findings(self) -> Vec<Finding> {
+        self.findings
+    }
+
+    /// Whether the audit found nothing wrong with the repository.
+    pub fn is_ok(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// The number of targets, across all trusted targets metadata, that were checked.
+    pub fn targets_checked(&self) -> usize {
+        self.targets_checked
+    }
+}
+
+/// Audit every piece of metadata and every target in `repo`.
+///
+/// The audit starts from version 1 of the root metadata, which is trusted on first use, and checks
+/// that:
+///
+/// * every version of the root metadata is signed by both the previous and its own root keys,
+/// * the timestamp, snapshot, top-level targets, and every reachable delegated targets metadata
+///   are signed by a threshold of the keys trusted by their parent,
+/// * each metadata matches the version, length, and hashes its parent describes it with,
+/// * the snapshot describes exactly the delegated roles that are reachable from the top-level
+///   targets,
+/// * every target described by the trusted targets metadata is in the repository, with the
+///   described length and hashes. Under consistent snapshots, the copy for each hash is checked,
+/// * none of the latest metadata expired before `start_time`.
+///
+/// Metadata that fails to verify is not trusted, so anything that can only be reached through it
+/// is not audited.
+pub async fn audit_repository<D, R>(repo: &R, start_time: &DateTime<Utc>) -> AuditReport
+where
+    D: Pouf,
+    R: RepositoryProvider<D>,
+{
+    let mut audit = Audit {
+        repo,
+        start_time,
+        report: AuditReport::default(),
+        _pouf: PhantomData,
+    };
+
+    if let Some(db) = audit.audit_metadata().await {
+        audit.audit_targets(&db).await;
+    }
+
+    audit.report
+}
+
+struct Audit<'a, D, R> {
+    repo: &'a R,
+    start_time: &'a DateTime<Utc>,
+    report: AuditReport,
+    _pouf: PhantomData<D>,
+}
+
+impl<'a, D, R> Audit<'a, D, R>
+where
+    D: Pouf,
+    R: RepositoryProvider<D>,
+{
+    /// Load and verify all of the metadata. Returns the database of metadata that could be
+    /// trusted, or `None` if not even the initial root metadata could be.
+    async fn audit_metadata(&mut self) -> Option<Database<D>> {
+        let mut db = self.audit_root().await?;
+
+        // The database checks expiration as it goes, which would stop us at the first expired
+        // metadata, so we report expired metadata ourselves.
+        let no_expiration = DateTime::<Utc>::MIN_UTC;
+
+        // Timestamp.
+        let timestamp_path = MetadataPath::timestamp();
+        let raw_timestamp = self
+            .fetch_metadata::<TimestampMetadata>(&timestamp_path, MetadataVersion::None)
+            .await;
+        let raw_timestamp = if let Some(raw_timestamp) = raw_timestamp {
+            raw_timestamp
+        } else {
+            return Some(db);
+        };
+        let timestamp = match db.update_timestamp(&no_expiration, &raw_timestamp) {
+            Ok(_) => db.trusted_timestamp()?.clone(),
+            Err(error) => {
+                self.invalid_metadata(&timestamp_path, MetadataVersion::None, error);
+                return Some(db);
+            }
+        };
+        self.check_expiration(&timestamp_path, &*timestamp);
+
+        let consistent_snapshot = db.trusted_root().consistent_snapshot();
+        let version = |description_version| {
+            if consistent_snapshot {
+                MetadataVersion::Number(description_version)
+            } else {
+                MetadataVersion::None
+            }
+        };
+
+        // Snapshot.
+        let snapshot_path = MetadataPath::snapshot();
+        let raw_snapshot = self
+            .fetch_described_metadata::<SnapshotMetadata>(
+                &timestamp_path,
+                &snapshot_path,
+                timestamp.snapshot(),
+                version(timestamp.snapshot().version()),
+            )
+            .await;
+        let raw_snapshot = if let Some(raw_snapshot) = raw_snapshot {
+            raw_snapshot
+        } else {
+            return Some(db);
+        };
+        if let Err(error) = db.update_snapshot(&no_expiration, &raw_snapshot) {
+            self.invalid_metadata(
+                &snapshot_path,
+                version(timestamp.snapshot().version()),
+                error,
+            );
+            return Some(db);
+        }
+        let snapshot = db.trusted_snapshot()?.clone();
+        self.check_expiration(&snapshot_path, &*snapshot);
+
+        // Top-level targets.
+        let targets_path = MetadataPath::targets();
+        let targets_description = if let Some(description) = snapshot.meta().get(&targets_path) {
+            description
+        } else {
+            self.report.findings.push(Finding::UndescribedRole {
+                parent_role: MetadataPath::root(),
+                role: targets_path,
+            });
+            return Some(db);
+        };
+        let raw_targets = self
+            .fetch_described_metadata::<TargetsMetadata>(
+                &snapshot_path,
+                &targets_path,
+                targets_description,
+                version(targets_description.version()),
+            )
+            .await;
+        let raw_targets = if let Some(raw_targets) = raw_targets {
+            raw_targets
+        } else {
+            return Some(db);
+        };
+        if let Err(error) = db.update_targets(&no_expiration, &raw_targets) {
+            self.invalid_metadata(&targets_path, version(targets_description.version()), error);
+            return Some(db);
+        }
+        if let Some(targets) = db.trusted_targets() {
+            self.check_expiration(&targets_path, &**targets);
+        }
+
+        // Delegated targets. Roles are loaded once one of their delegating roles has been, so each
+        // is verified with the keys of a role we already trust.
+        let mut pending = snapshot
+            .meta()
+            .iter()
+            .filter(|(role, _)| **role != targets_path)
+            .collect::<BTreeMap<_, _>>();
+        let mut complete = true;
+
+        while let Some((role, parent)) = pending
+            .keys()
+            .find_map(|role| db.delegating_role(role).map(|parent| (*role, parent)))
+        {
+            let description = pending.remove(role).unwrap();
+            let raw_delegated_targets = self
+                .fetch_described_metadata::<TargetsMetadata>(
+                    &snapshot_path,
+                    role,
+                    description,
+                    version(description.version()),
+                )
+                .await;
+            let raw_delegated_targets = if let Some(raw) = raw_delegated_targets {
+                raw
+            } else {
+                complete = false;
+                continue;
+            };
+
+            if let Err(error) =
+                db.update_delegated_targets(&no_expiration, &parent, role, &raw_delegated_targets)
+            {
+                self.invalid_metadata(role, version(description.version()), error);
+                complete = false;
+                continue;
+            }
+            if let Some(delegated_targets) = db.trusted_delegations().get(role) {
+                self.check_expiration(role, &**delegated_targets);
+            }
+        }
+
+        // If some metadata couldn't be trusted, the roles it delegates to were never loaded, so
+        // we can't tell if the remaining roles are actually unreachable.
+        if complete {
+            for role in pending.keys() {
+                self.report.findings.push(Finding::UnreachableRole {
+                    role: (*role).clone(),
+                });
+            }
+        }
+
+        for (parent_role, targets) in trusted_targets(&db) {
+            for delegation in targets.delegations().roles() {
+                if !snapshot.meta().contains_key(delegation.name()) {
+                    self.report.findings.push(Finding::UndescribedRole {
+                        parent_role: parent_role.clone(),
+                        role: delegation.name().clone(),
+                    });
+                }
+            }
+        }
+
+        Some(db)
+    }
+
+    /// Walk the chain of root metadata, starting from version 1.
+    async fn audit_root(&mut self) -> Option<Database<D>> {
+        let root_path = MetadataPath::root();
+
+        let raw_root = self
+            .fetch_metadata::<RootMetadata>(&root_path, MetadataVersion::Number(1))
+            .await?;
+        let mut db = match Database::from_trusted_root(&raw_root) {
+            Ok(db) => db,
+            Err(error) => {
+                self.invalid_metadata(&root_path, MetadataVersion::Number(1), error);
+                return None;
+            }
+        };
+
+        while let Some(next_version) = db.trusted_root().version().checked_add(1) {
+            let version = MetadataVersion::Number(next_version);
+
+            let raw_root = match self.read_metadata(&root_path, version).await {
+                Ok(buf) => RawSignedMetadata::<D, RootMetadata>::new(buf),
+                Err(Error::MetadataNotFound { .. }) => break,
+                Err(error) => {
+                    self.invalid_metadata(&root_path, version, error);
+                    break;
+                }
+            };
+
+            if let Err(error) = db.update_root(&raw_root) {
+                self.invalid_metadata(&root_path, version, error);
+                break;
+            }
+        }
+
+        self.check_expiration(&root_path, &**db.trusted_root());
+
+        Some(db)
+    }
+
+    /// Check every target described by the trusted targets metadata.
+    async fn audit_targets(&mut self, db: &Database<D>) {
+        let consistent_snapshot = db.trusted_root().consistent_snapshot();
+
+        for (role, targets) in trusted_targets(db) {
+            let targets = targets
+                .targets()
+                .iter()
+                .collect::<BTreeMap<&TargetPath, &TargetDescription>>();
+
+            for (target_path, target_description) in targets {
+                self.audit_target(&role, target_path, target_description, consistent_snapshot)
+                    .await;
+            }
+        }
+    }
+
+    async fn audit_target(
+        &mut self,
+        role: &MetadataPath,
+        target_path: &TargetPath,
+        target_description: &TargetDescription,
+        consistent_snapshot: bool,
+    ) {
+        self.report.targets_checked += 1;
+
+        let hashes = crypto::retain_supported_hashes(target_description.hashes());
+        if hashes.is_empty() {
+            self.report.findings.push(Finding::InvalidTarget {
+                role: role.clone(),
+                path: target_path.clone(),
+                error: Error::NoSupportedHashAlgorithm,
+            });
+            return;
+        }
+
+        // Under consistent snapshots, clients may fetch the target by any of its hashes, so there
+        // needs to be a copy for each of them.
+        let paths = if consistent_snapshot {
+            hashes
+                .iter()
+                .map(|(_, hash)| target_path.with_hash_prefix(hash))
+                .collect::<Result<Vec<_>>>()
+        } else {
+            Ok(vec![target_path.clone()])
+        };
+        let paths = match paths {
+            Ok(paths) => paths,
+            Err(error) => {
+                self.report.findings.push(Finding::InvalidTarget {
+                    role: role.clone(),
+                    path: target_path.clone(),
+                    error,
+                });
+                return;
+            }
+        };
+
+        let hash_algs = hashes
+            .iter()
+            .map(|(algorithm, _)| (*algorithm).clone())
+            .collect::<Vec<_>>();
+
+        for path in paths {
+            match self.hash_target(&path, &hash_algs).await {
+                Ok((actual_length, actual_hashes)) => {
+                    let mismatches = compare(
+                        actual_length,
+                        &actual_hashes,
+                        Some(target_description.length()),
+                        &hashes,
+                    );
+                    for mismatch in mismatches {
+                        self.report.findings.push(Finding::TargetMismatch {
+                            role: role.clone(),
+                            path: path.clone(),
+                            mismatch,
+                        });
+                    }
+                }
+                Err(error) => {
+                    self.report.findings.push(Finding::InvalidTarget {
+                        role: role.clone(),
+                        path,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Fetch metadata that is described by `parent_role`, and check it matches `description`.
+    async fn fetch_described_metadata<M: Metadata>(
+        &mut self,
+        parent_role: &MetadataPath,
+        role: &MetadataPath,
+        description: &MetadataDescription<M>,
+        version: MetadataVersion,
+    ) -> Option<RawSignedMetadata<D, M>> {
+        let raw_metadata = self.fetch_metadata::<M>(role, version).await?;
+
+        let hashes = crypto::retain_supported_hashes(description.hashes());
+        let hash_algs = hashes
+            .iter()
+            .map(|(algorithm, _)| (*algorithm).clone())
+            .collect::<Vec<_>>();
+        let actual_hashes = if hash_algs.is_empty() {
+            HashMap::new()
+        } else {
+            crypto::calculate_hashes_from_slice(raw_metadata.as_bytes(), &hash_algs)
+                .unwrap_or_default()
+        };
+
+        let mismatches = compare(
+            raw_metadata.as_bytes().len() as u64,
+            &actual_hashes,
+            description.length().map(|length| length as u64),
+            &hashes,
+        );
+        for mismatch in mismatches {
+            self.report.findings.push(Finding::MetadataMismatch {
+                parent_role: parent_role.clone(),
+                role: role.clone(),
+                version: description.version(),
+                mismatch,
+            });
+        }
+
+        Some(raw_metadata)
+    }
+
+    /// Fetch metadata, recording a finding if it can't be fetched.
+    async fn fetch_metadata<M: Metadata>(
+        &mut self,
+        role: &MetadataPath,
+        version: MetadataVersion,
+    ) -> Option<RawSignedMetadata<D, M>> {
+        match self.read_metadata(role, version).await {
+            Ok(buf) => Some(RawSignedMetadata::new(buf)),
+            Err(error) => {
+                self.invalid_metadata(role, version, error);
+                None
+            }
+        }
+    }
+
+    async fn read_metadata(
+        &self,
+        role: &MetadataPath,
+        version: MetadataVersion,
+    ) -> Result<Vec<u8>> {
+        let mut reader = self.repo.fetch_metadata(role, version).await?;
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).await?;
+        Ok(buf)
+    }
+
+    /// Stream the target from the repository, returning its length and hashes. Targets can be
+    /// large, so they are never held in memory.
+    async fn hash_target(
+        &self,
+        target_path: &TargetPath,
+        hash_algs: &[HashAlgorithm],
+    ) -> Result<(u64, HashMap<HashAlgorithm, HashValue>)> {
+        let reader = self.repo.fetch_target(target_path).await?;
+        crypto::calculate_hashes_from_reader(reader, hash_algs).await
+    }
+
+    fn invalid_metadata(&mut self, role: &MetadataPath, version: MetadataVersion, error: Error) {
+        self.report.findings.push(Finding::InvalidMetadata {
+            role: role.clone(),
+            version,
+            error,
+        });
+    }
+
+    fn check_expiration<M: Metadata>(&mut self, role: &MetadataPath, metadata: &M) {
+        if metadata.expires() <= self.start_time {
+            self.report.findings.push(Finding::ExpiredMetadata {
+                role: role.clone(),
+                version: metadata.version(),
+                expires: *metadata.expires(),
+            });
+        }
+    }
+}
+
+/// All of the trusted targets metadata, starting with the top-level targets, then the delegated
+/// targets in order of their role.
+fn trusted_targets<D: Pouf>(db: &Database<D>) -> Vec<(MetadataPath, &TargetsMetadata)> {
+    let top_level = db
+        .trusted_targets()
+        .map(|targets| (MetadataPath::targets(), &**targets));
+
+    let delegations = db
+        .trusted_delegations()
+        .iter()
+        .map(|(role, targets)| (role.clone(), &**targets))
+        .collect::<BTreeMap<_, _>>();
+
+    top_level.into_iter().chain(delegations).collect()
+}
+
+/// Compare the actual length and hashes of some bytes against a described length and hashes.
+fn compare(
+    actual_length: u64,
+    actual_hashes: &HashMap<HashAlgorithm, HashValue>,
+    length: Option<u64>,
+    hashes: &[(&'static HashAlgorithm, HashValue)],
+) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+
+    if let Some(length) = length {
+        if length != actual_length {
+            mismatches.push(Mismatch::Length {
+                expected: length,
+                actual: actual_length,
+            });
+        }
+    }
+
+    for (algorithm, expected) in hashes {
+        if let Some(actual) = actual_hashes.get(*algorithm).cloned() {
+            if &actual != expected {
+                mismatches.push(Mismatch::Hash {
+                    algorithm: (*algorithm).clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+    }
+
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            crypto::{Ed25519PrivateKey, PrivateKey},
+            metadata::{Delegation, PathPattern, RawSignedMetadataSet},
+            pouf::Pouf1,
+            repo_builder::RepoBuilder,
+            repository::{EphemeralRepository, RepositoryStorage as _},
+        },
+        assert_matches::assert_matches,
+        chrono::Duration,
+        futures_executor::block_on,
+        futures_util::io::Cursor,
+        lazy_static::lazy_static,
+        std::collections::HashSet,
+    };
+
+    lazy_static! {
+        static ref KEYS: Vec<Ed25519PrivateKey> = {
+            let keys: &[&[u8]] = &[
+                include_bytes!("../tests/ed25519/ed25519-1.pk8.der"),
+                include_bytes!("../tests/ed25519/ed25519-2.pk8.der"),
+            ];
+            keys.iter()
+                .map(|b| Ed25519PrivateKey::from_pkcs8(b).unwrap())
+                .collect()
+        };
+    }
+
+    /// Create a repository with two top-level targets, and a target signed by the delegated role
+    /// `a`. Targets are hashed with both SHA-256 and SHA-512, so consistent snapshot repositories
+    /// have two copies of each.
+    async fn create_repository(
+        consistent_snapshot: bool,
+    ) -> (EphemeralRepository<Pouf1>, RawSignedMetadataSet<Pouf1>) {
+        let mut repo = EphemeralRepository::<Pouf1>::new();
+        let role_a = MetadataPath::new("a").unwrap();
+
+        let metadata = RepoBuilder::create(&mut repo)
+            .trusted_root_keys(&[&KEYS[0]])
+            .trusted_targets_keys(&[&KEYS[0]])
+            .trusted_snapshot_keys(&[&KEYS[0]])
+            .trusted_timestamp_keys(&[&KEYS[0]])
+            .stage_root_with_builder(|builder| builder.consistent_snapshot(consistent_snapshot))
+            .await
+            .unwrap()
+            .target_hash_algorithms(&[HashAlgorithm::Sha256, HashAlgorithm::Sha512])
+            .add_target(TargetPath::new("foo").unwrap(), Cursor::new(b"foo"))
+            .await
+            .unwrap()
+            .add_target(TargetPath::new("bar/baz").unwrap(), Cursor::new(b"baz"))
+            .await
+            .unwrap()
+            .add_delegation_key(KEYS[1].public().clone())
+            .add_delegation_role(
+                Delegation::builder(role_a.clone())
+                    .key(KEYS[1].public())
+                    .delegate_path(PathPattern::new("a/*").unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .signing_delegated_targets_keys(role_a.clone(), &[&KEYS[1]])
+            .add_delegated_target(
+                role_a,
+                TargetPath::new("a/qux").unwrap(),
+                Cursor::new(b"qux"),
+            )
+            .await
+            .unwrap()
+            .stage_targets()
+            .await
+            .unwrap()
+            .snapshot_includes_length(true)
+            .snapshot_includes_hashes(&[HashAlgorithm::Sha256])
+            .commit()
+            .await
+            .unwrap();
+
+        (repo, metadata)
+    }
+
+    #[test]
+    fn test_audit_repository() {
+        block_on(async {
+            for consistent_snapshot in [true, false] {
+                let (repo, _) = create_repository(consistent_snapshot).await;
+
+                let report = audit_repository(&repo, &Utc::now()).await;
+                assert!(report.is_ok(), "{:?}", report.findings());
+                assert_eq!(report.targets_checked(), 3);
+            }
+        })
+    }
+
+    #[test]
+    fn test_audit_reports_expired_metadata() {
+        block_on(async {
+            let (repo, _) = create_repository(true).await;
+
+            let report = audit_repository(&repo, &(Utc::now() + Duration::days(400))).await;
+            assert_eq!(report.targets_checked(), 3);
+
+            let expired = report
+                .findings()
+                .iter()
+                .map(|finding| match finding {
+                    Finding::ExpiredMetadata { role, .. } => role.to_string(),
+                    finding => panic!("unexpected finding {:?}", finding),
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(
+                expired,
+                ["root", "timestamp", "snapshot", "targets", "a"]
+                    .iter()
+                    .map(|role| role.to_string())
+                    .collect()
+            );
+        })
+    }
+
+    #[test]
+    fn test_audit_reports_bad_targets() {
+        block_on(async {
+            let (mut repo, metadata) = create_repository(true).await;
+
+            // Describe a target that was never stored, and tamper with one copy of another.
+            let missing_path = TargetPath::new("missing").unwrap();
+            let missing_description =
+                TargetDescription::from_slice(b"missing", &[HashAlgorithm::Sha256]).unwrap();
+            let db = Database::from_trusted_metadata(&metadata).unwrap();
+
+            RepoBuilder::from_database(&mut repo, &db)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .skip_root()
+                .stage_targets_with_builder(|builder| {
+                    builder.insert_target_description(missing_path.clone(), missing_description)
+                })
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let foo = TargetPath::new("foo").unwrap();
+            let foo_description = db.trusted_targets().unwrap().targets()[&foo].clone();
+            let tampered_path = foo
+                .with_hash_prefix(&foo_description.hashes()[&HashAlgorithm::Sha512])
+                .unwrap();
+            repo.store_target(&tampered_path, &mut Cursor::new(b"bad foo"))
+                .await
+                .unwrap();
+
+            let report = audit_repository(&repo, &Utc::now()).await;
+            assert_eq!(report.targets_checked(), 4);
+
+            let findings = report.into_findings();
+            assert_eq!(findings.len(), 4, "{:?}", findings);
+            assert_matches!(
+                &findings[0],
+                Finding::TargetMismatch {
+                    role,
+                    path,
+                    mismatch: Mismatch::Length { expected: 3, actual: 7 },
+                } if role == &MetadataPath::targets() && path == &tampered_path
+            );
+            for (finding, expected_algorithm) in findings[1..3]
+                .iter()
+                .zip([HashAlgorithm::Sha512, HashAlgorithm::Sha256])
+            {
+                assert_matches!(
+                    finding,
+                    Finding::TargetMismatch {
+                        path,
+                        mismatch: Mismatch::Hash { algorithm, .. },
+                        ..
+                    } if path == &tampered_path && algorithm == &expected_algorithm
+                );
+            }
+            assert_matches!(
+                &findings[3],
+                Finding::InvalidTarget {
+                    path,
+                    error: Error::TargetNotFound(_),
+                    ..
+                } if path.as_str().ends_with(".missing")
+            );
+        })
+    }
+
+    #[test]
+    fn test_audit_reports_bad_metadata() {
+        block_on(async {
+            let (repo, _) = create_repository(false).await;
+
+            // Whitespace doesn't change the signature, but it does change the length and hashes the
+            // snapshot describes the targets metadata with.
+            let mut raw_targets = vec![];
+            repo.fetch_metadata(&MetadataPath::targets(), MetadataVersion::None)
+                .await
+                .unwrap()
+                .read_to_end(&mut raw_targets)
+                .await
+                .unwrap();
+            raw_targets.push(b'\n');
+            repo.store_metadata(
+                &MetadataPath::targets(),
+                MetadataVersion::None,
+                &mut Cursor::new(raw_targets),
+            )
+            .await
+            .unwrap();
+
+            // Break the root chain with a version 2 that is really version 1.
+            let mut raw_root = vec![];
+            repo.fetch_metadata(&MetadataPath::root(), MetadataVersion::Number(1))
+                .await
+                .unwrap()
+                .read_to_end(&mut raw_root)
+                .await
+                .unwrap();
+            repo.store_metadata(
+                &MetadataPath::root(),
+                MetadataVersion::Number(2),
+                &mut Cursor::new(raw_root),
+            )
+            .await
+            .unwrap();
+
+            let report = audit_repository(&repo, &Utc::now()).await;
+
+            // The rest of the repository is still audited.
+            assert_eq!(report.targets_checked(), 3);
+
+            let findings = report.into_findings();
+            assert_eq!(findings.len(), 3, "{:?}", findings);
+            assert_matches!(
+                &findings[0],
+                Finding::InvalidMetadata {
+                    role,
+                    version: MetadataVersion::Number(2),
+                    error: Error::AttemptedMetadataRollBack { .. },
+                } if role == &MetadataPath::root()
+            );
+            assert_matches!(
+                &findings[1],
+                Finding::MetadataMismatch {
+                    parent_role,
+                    role,
+                    mismatch: Mismatch::Length { .. },
+                    ..
+                } if parent_role == &MetadataPath::snapshot() && role == &MetadataPath::targets()
+            );
+            assert_matches!(
+                &findings[2],
+                Finding::MetadataMismatch {
+                    mismatch: Mismatch::Hash {
+                        algorithm: HashAlgorithm::Sha256,
+                        ..
+                    },
+                    ..
+                }
+            );
+        })
+    }
+}
@@ -49,19 +49,24 @@
 
 use chrono::{offset::Utc, DateTime};
 use futures_io::AsyncRead;
+use futures_util::io::{self, AsyncReadExt as _};
 use log::{error, warn};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::crypto::{self, HashAlgorithm, HashValue, PublicKey};
 use crate::database::Database;
 use crate::error::{Error, Result};
 use crate::metadata::{
     Metadata, MetadataPath, MetadataVersion, RawSignedMetadata, RootMetadata, SnapshotMetadata,
-    TargetDescription, TargetPath, TargetsMetadata,
+    TargetDescription, TargetPath, TargetsMetadata, TimestampMetadata,
 };
 use crate::pouf::Pouf;
 use crate::repository::{Repository, RepositoryProvider, RepositoryStorage};
+use crate::timer::{SharedTimer, Timer};
 use crate::verify::Verified;
 
 /// A client that interacts with TUF repositories.
@@ -76,6 +81,8 @@ where
     tuf: Database<D>,
     local: Repository<L, D>,
     remote: Repository<R, D>,
+    metadata_mirrors: HashMap<MetadataPath, String>,
+    target_mirrors: HashMap<TargetPath, String>,
 }
 
 impl<D, L, R> Client<D, L, R>
@@ -142,7 +149,10 @@ where
     /// # }
     /// ```
     pub async fn with_trusted_local(config: Config, local: L, remote: R) -> Result<Self> {
-        let (local, remote) = (Repository::new(local), Repository::new(remote));
+        let (local, remote) = (
+            Repository::new(local),
+            Repository::new(remote).with_timeouts(&config),
+        );
         let root_path = MetadataPath::root();
 
         // FIXME should this be MetadataVersion::None so we bootstrap with the latest version?
@@ -212,7 +222,10 @@ where
         local: L,
         remote: R,
     ) -> Result<Self> {
-        let (local, remote) = (Repository::new(local), Repository::new(remote));
+        let (local, remote) = (
+            Repository::new(local),
+            Repository::new(remote).with_timeouts(&config),
+        );
         let tuf = Database::from_trusted_root(trusted_root)?;
 
         Self::new(config, tuf, local, remote).await
@@ -288,7 +301,10 @@ where
     where
         I: IntoIterator<Item = &'a PublicKey>,
     {
-        let (mut local, remote) = (Repository::new(local), Repository::new(remote));
+        let (mut local, remote) = (
+            Repository::new(local),
+            Repository::new(remote).with_timeouts(&config),
+        );
 
         let root_path = MetadataPath::root();
         let (fetched, raw_root) = fetch_metadata_from_local_or_else_remote(
@@ -333,11 +349,14 @@ where
 
     /// Create a new TUF client. It will trust and update the TUF database.
     pub fn from_database(config: Config, tuf: Database<D>, local: L, remote: R) -> Self {
+        let remote = Repository::new(remote).with_timeouts(&config);
         Self {
             config,
             tuf,
             local: Repository::new(local),
-            remote: Repository::new(remote),
+            remote,
+            metadata_mirrors: HashMap::new(),
+            target_mirrors: HashMap::new(),
         }
     }
 
@@ -353,11 +372,14 @@ where
             local,
             remote,
         } = parts;
+        let remote = Repository::new(remote).with_timeouts(&config);
         Self {
             config,
             tuf: database,
             local: Repository::new(local),
-            remote: Repository::new(remote),
+            remote,
+            metadata_mirrors: HashMap::new(),
+            target_mirrors: HashMap::new(),
         }
     }
 
@@ -369,19 +391,34 @@ where
         remote: Repository<R, D>,
     ) -> Result<Self> {
         let start_time = Utc::now();
+        let mut metadata_mirrors = HashMap::new();
 
         let res = async {
-            let _r =
-                Self::update_root_with_repos(&start_time, &config, &mut tuf, None, &local).await?;
-            let _ts =
-                Self::update_timestamp_with_repos(&start_time, &config, &mut tuf, None, &local)
-                    .await?;
+            let _r = Self::update_root_with_repos(
+                &start_time,
+                &config,
+                &mut tuf,
+                None,
+                &local,
+                &mut metadata_mirrors,
+            )
+            .await?;
+            let _ts = Self::update_timestamp_with_repos(
+                &start_time,
+                &config,
+                &mut tuf,
+                None,
+                &local,
+                &mut metadata_mirrors,
+            )
+            .await?;
             let _sn = Self::update_snapshot_with_repos(
                 &start_time,
                 &config,
                 &mut tuf,
                 None,
                 &local,
+                &mut metadata_mirrors,
                 false,
             )
             .await?;
@@ -391,6 +428,7 @@ where
                 &mut tuf,
                 None,
                 &local,
+                &mut metadata_mirrors,
                 false,
             )
             .await?;
@@ -411,6 +449,8 @@ where
             config,
             local,
             remote,
+            metadata_mirrors,
+            target_mirrors: HashMap::new(),
         })
     }
 
@@ -443,6 +483,7 @@ where
             tuf,
             local,
             remote,
+            ..
         } = self;
         Parts {
             config,
@@ -482,6 +523,20 @@ where
         self.remote.as_inner_mut()
     }
 
+    /// The mirror that supplied the metadata for `path` that the client most recently updated,
+    /// if the remote repository fetches from several mirrors, such as a
+    /// [`MirrorsRepository`](crate::repository::MirrorsRepository).
+    pub fn metadata_mirror(&self, path: &MetadataPath) -> Option<&str> {
+        self.metadata_mirrors.get(path).map(String::as_str)
+    }
+
+    /// The mirror that supplied the copy of `target` that the client most recently fetched from
+    /// the remote repository, if it fetches from several mirrors, such as a
+    /// [`MirrorsRepository`](crate::repository::MirrorsRepository).
+    pub fn target_mirror(&self, target: &TargetPath) -> Option<&str> {
+        self.target_mirrors.get(target).map(String::as_str)
+    }
+
     /// Update TUF root metadata from the remote repository.
     ///
     /// Returns `true` if an update occurred and `false` otherwise.
@@ -492,6 +547,7 @@ where
             &mut self.tuf,
             Some(&mut self.local),
             &self.remote,
+            &mut self.metadata_mirrors,
         )
         .await
     }
@@ -502,6 +558,7 @@ where
         tuf: &mut Database<D>,
         mut local: Option<&mut Repository<L, D>>,
         remote: &Repository<Remote, D>,
+        mirrors: &mut HashMap<MetadataPath, String>,
     ) -> Result<bool>
     where
         Remote: RepositoryProvider<D>,
@@ -509,6 +566,7 @@ where
         let root_path = MetadataPath::root();
 
         let mut updated = false;
+        let mut rotations = 0;
 
         loop {
             /////////////////////////////////////////
@@ -523,18 +581,60 @@ where
             //     exact number is as yet unknown), then go to step 5.1.9. The value for Y is set
             //     by the authors of the application using TUF. For example, Y may be 2^10.
 
-            // FIXME(#306) We do not have an upper bound on the number of root metadata we'll
-            // fetch. This means that an attacker that's stolen the root keys could cause a client
-            // to fall into an infinite loop (but if an attacker has stolen the root keys, the
-            // client probably has worse problems to worry about).
-
             let next_version = MetadataVersion::Number(tuf.trusted_root().version() + 1);
-            let res = remote
-                .fetch_metadata(&root_path, next_version, config.max_root_length, vec![])
-                .await;
+
+            let trusted_timestamp = tuf.trusted_timestamp().is_some();
+            let trusted_snapshot = tuf.trusted_snapshot().is_some();
+            let trusted_targets = tuf.trusted_targets().is_some();
+
+            // The delegated targets roles we may have persisted, which are listed in the snapshot.
+            // Some repositories also list top-level roles in their snapshot.
+            let top_level_roles = [
+                MetadataPath::root(),
+                MetadataPath::snapshot(),
+                MetadataPath::targets(),
+                MetadataPath::timestamp(),
+            ];
+            let delegated_roles = tuf
+                .trusted_snapshot()
+                .map(|snapshot| {
+                    snapshot
+                        .meta()
+                        .keys()
+                        .filter(|role| !top_level_roles.contains(role))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            // An attacker that has stolen the root keys could otherwise keep us fetching new root
+            // metadata forever, so refuse to apply more than `max_root_rotations` of them.
+            let res = fetch_verified_metadata(
+                remote,
+                &root_path,
+                next_version,
+                config.max_root_length,
+                vec![],
+                |raw_signed_root| {
+                    if rotations >= config.max_root_rotations {
+                        return Ok(false);
+                    }
+                    tuf.update_root(raw_signed_root)?;
+                    Ok(true)
+                },
+            )
+            .await;
 
             let raw_signed_root = match res {
-                Ok(raw_signed_root) => raw_signed_root,
+                Ok((raw_signed_root, true, mirror)) => {
+                    record_mirror(mirrors, &root_path, mirror.as_deref());
+                    raw_signed_root
+                }
+                Ok((_, false, _)) => {
+                    return Err(Error::MaxRootRotationsExceeded {
+                        max_root_rotations: config.max_root_rotations,
+                    });
+                }
                 Err(Error::MetadataNotFound { .. }) => {
                     break;
                 }
@@ -542,11 +642,10 @@ where
                     return Err(err);
                 }
             };
+            rotations += 1;
 
             updated = true;
 
-            tuf.update_root(&raw_signed_root)?;
-
             /////////////////////////////////////////
             // TUF-1.0.9 §5.1.7:
             //
@@ -562,6 +661,43 @@ where
                 local
                     .store_metadata(&root_path, next_version, &raw_signed_root)
                     .await?;
+
+                // If the new root rotated keys, `tuf::Database` forgot the metadata signed with
+                // the old keys (see TUF-1.0.9 §5.1.9). Delete our persisted copies as well, so we
+                // don't load a fast-forwarded version of them the next time we're constructed.
+                if trusted_timestamp && tuf.trusted_timestamp().is_none() {
+                    local
+                        .remove_metadata::<TimestampMetadata>(
+                            &MetadataPath::timestamp(),
+                            MetadataVersion::None,
+                        )
+                        .await?;
+                }
+
+                if trusted_snapshot && tuf.trusted_snapshot().is_none() {
+                    local
+                        .remove_metadata::<SnapshotMetadata>(
+                            &MetadataPath::snapshot(),
+                            MetadataVersion::None,
+                        )
+                        .await?;
+                }
+
+                if trusted_targets && tuf.trusted_targets().is_none() {
+                    local
+                        .remove_metadata::<TargetsMetadata>(
+                            &MetadataPath::targets(),
+                            MetadataVersion::None,
+                        )
+                        .await?;
+
+                    // The delegated targets metadata is only trusted through the targets metadata.
+                    for role in &delegated_roles {
+                        local
+                            .remove_metadata::<TargetsMetadata>(role, MetadataVersion::None)
+                            .await?;
+                    }
+                }
             }
 
             /////////////////////////////////////////
@@ -603,6 +739,7 @@ where
             &mut self.tuf,
             Some(&mut self.local),
             &self.remote,
+            &mut self.metadata_mirrors,
         )
         .await
     }
@@ -613,6 +750,7 @@ where
         tuf: &mut Database<D>,
         local: Option<&mut Repository<L, D>>,
         remote: &Repository<Remote, D>,
+        mirrors: &mut HashMap<MetadataPath, String>,
     ) -> Result<bool>
     where
         Remote: RepositoryProvider<D>,
@@ -627,19 +765,23 @@ where
         //     example, X may be tens of kilobytes. The filename used to download the timestamp
         //     metadata file is of the fixed form FILENAME.EXT (e.g., timestamp.json).
 
-        let raw_signed_timestamp = remote
-            .fetch_metadata(
-                &timestamp_path,
-                MetadataVersion::None,
-                config.max_timestamp_length,
-                vec![],
-            )
-            .await?;
+        let (raw_signed_timestamp, updated, mirror) = fetch_verified_metadata(
+            remote,
+            &timestamp_path,
+            MetadataVersion::None,
+            config.max_timestamp_length,
+            vec![],
+            |raw_signed_timestamp| {
+                Ok(tuf
+                    .update_timestamp(start_time, raw_signed_timestamp)?
+                    .is_some())
+            },
+        )
+        .await?;
+
+        if updated {
+            record_mirror(mirrors, &timestamp_path, mirror.as_deref());
 
-        if tuf
-            .update_timestamp(start_time, &raw_signed_timestamp)?
-            .is_some()
-        {
             /////////////////////////////////////////
             // TUF-1.0.9 §5.2.4:
             //
@@ -671,6 +813,7 @@ where
             &mut self.tuf,
             Some(&mut self.local),
             &self.remote,
+            &mut self.metadata_mirrors,
             consistent_snapshot,
         )
         .await
@@ -682,6 +825,7 @@ where
         tuf: &mut Database<D>,
         local: Option<&mut Repository<L, D>>,
         remote: &Repository<Remote, D>,
+        mirrors: &mut HashMap<MetadataPath, String>,
         consistent_snapshots: bool,
     ) -> Result<bool>
     where
@@ -722,13 +866,21 @@ where
         // in the trusted timestamp metadata.
         let snapshot_hashes = crypto::retain_supported_hashes(snapshot_description.hashes());
 
-        let raw_signed_snapshot = remote
-            .fetch_metadata(&snapshot_path, version, snapshot_length, snapshot_hashes)
-            .await?;
-
         // https://theupdateframework.github.io/specification/v1.0.26/#update-snapshot 5.5.3 through
         // 5.5.6 are checked in [Database].
-        if tuf.update_snapshot(start_time, &raw_signed_snapshot)? {
+        let (raw_signed_snapshot, updated, mirror) = fetch_verified_metadata(
+            remote,
+            &snapshot_path,
+            version,
+            snapshot_length,
+            snapshot_hashes,
+            |raw_signed_snapshot| tuf.update_snapshot(start_time, raw_signed_snapshot),
+        )
+        .await?;
+
+        if updated {
+            record_mirror(mirrors, &snapshot_path, mirror.as_deref());
+
             // https://theupdateframework.github.io/specification/v1.0.26/#update-snapshot 5.5.7:
             //
             // Persist snapshot metadata. The client MUST write the file to non-volatile storage as
@@ -754,6 +906,7 @@ where
             &mut self.tuf,
             Some(&mut self.local),
             &self.remote,
+            &mut self.metadata_mirrors,
             consistent_snapshot,
         )
         .await
@@ -765,6 +918,7 @@ where
         tuf: &mut Database<D>,
         local: Option<&mut Repository<L, D>>,
         remote: &Repository<Remote, D>,
+        mirrors: &mut HashMap<MetadataPath, String>,
         consistent_snapshot: bool,
     ) -> Result<bool>
     where
@@ -810,11 +964,19 @@ where
         // MUST match the hashes, if any, listed in the trusted snapshot metadata. [...]
         let target_hashes = crypto::retain_supported_hashes(targets_description.hashes());
 
-        let raw_signed_targets = remote
-            .fetch_metadata(&targets_path, version, targets_length, target_hashes)
-            .await?;
+        let (raw_signed_targets, updated, mirror) = fetch_verified_metadata(
+            remote,
+            &targets_path,
+            version,
+            targets_length,
+            target_hashes,
+            |raw_signed_targets| tuf.update_targets(start_time, raw_signed_targets),
+        )
+        .await?;
+
+        if updated {
+            record_mirror(mirrors, &targets_path, mirror.as_deref());
 
-        if tuf.update_targets(start_time, &raw_signed_targets)? {
             /////////////////////////////////////////
             // TUF-1.0.9 §5.4.4:
             //
@@ -833,19 +995,20 @@ where
         }
     }
 
-    /// Fetch a target from the remote repo.
+    /// Fetch a target from the local repo if it has a copy that matches the trusted target
+    /// description, or otherwise from the remote repo. [`TargetReader::source`] reports which
+    /// repository the target is read from.
     ///
     /// It is **critical** that none of the bytes written to the `write` are used until this future
     /// returns `Ok`, as the hash of the target is not verified until all bytes are read from the
     /// repository.
-    pub async fn fetch_target(
-        &mut self,
-        target: &TargetPath,
-    ) -> Result<impl AsyncRead + Send + Unpin + '_> {
+    pub async fn fetch_target(&mut self, target: &TargetPath) -> Result<TargetReader<'_>> {
         self.fetch_target_with_start_time(target, &Utc::now()).await
     }
 
-    /// Fetch a target from the remote repo.
+    /// Fetch a target from the local repo if it has a copy that matches the trusted target
+    /// description, or otherwise from the remote repo. [`TargetReader::source`] reports which
+    /// repository the target is read from.
     ///
     /// It is **critical** that none of the bytes written to the `write` are used until this future
     /// returns `Ok`, as the hash of the target is not verified until all bytes are read from the
@@ -854,32 +1017,64 @@ where
         &mut self,
         target: &TargetPath,
         start_time: &DateTime<Utc>,
-    ) -> Result<impl AsyncRead + Send + Unpin + '_> {
+    ) -> Result<TargetReader<'_>> {
         let target_description = self
             .fetch_target_description_with_start_time(target, start_time)
             .await?;
 
-        // TODO: Check the local repository to see if it already has the target.
-        self.remote
+        if local_target_matches(&self.local, target, &target_description).await {
+            // The local copy could still change after we checked it, so it is verified again
+            // while it is being read.
+            let read = self
+                .local
+                .fetch_target(false, target, target_description)
+                .await?;
+
+            return Ok(TargetReader {
+                source: TargetSource::Local,
+                read: Box::new(read),
+            });
+        }
+
+        let read = self
+            .remote
             .fetch_target(
                 self.tuf.trusted_root().consistent_snapshot(),
                 target,
                 target_description,
             )
-            .await
+            .await?;
+        record_mirror(&mut self.target_mirrors, target, read.source_name());
+
+        Ok(TargetReader {
+            source: TargetSource::Remote,
+            read: Box::new(read),
+        })
     }
 
-    /// Fetch a target from the remote repo and write it to the local repo.
+    /// Fetch a target from the remote repo and write it to the local repo, unless the local repo
+    /// already has a copy that matches the trusted target description. Returns the repository the
+    /// target was read from.
+    ///
+    /// If the remote repository fetches from several mirrors, such as a
+    /// [`MirrorsRepository`](crate::repository::MirrorsRepository), a copy of the target that fails
+    /// verification is fetched again from another mirror.
     ///
     /// It is **critical** that none of the bytes written to the `write` are used until this future
     /// returns `Ok`, as the hash of the target is not verified until all bytes are read from the
     /// repository.
-    pub async fn fetch_target_to_local(&mut self, target: &TargetPath) -> Result<()> {
+    pub async fn fetch_target_to_local(&mut self, target: &TargetPath) -> Result<TargetSource> {
         self.fetch_target_to_local_with_start_time(target, &Utc::now())
             .await
     }
 
-    /// Fetch a target from the remote repo and write it to the local repo.
+    /// Fetch a target from the remote repo and write it to the local repo, unless the local repo
+    /// already has a copy that matches the trusted target description. Returns the repository the
+    /// target was read from.
+    ///
+    /// If the remote repository fetches from several mirrors, such as a
+    /// [`MirrorsRepository`](crate::repository::MirrorsRepository), a copy of the target that fails
+    /// verification is fetched again from another mirror.
     ///
     /// It is **critical** that none of the bytes written to the `write` are used until this future
     /// returns `Ok`, as the hash of the target is not verified until all bytes are read from the
@@ -888,20 +1083,27 @@ where
         &mut self,
         target: &TargetPath,
         start_time: &DateTime<Utc>,
-    ) -> Result<()> {
+    ) -> Result<TargetSource> {
         let target_description = self
             .fetch_target_description_with_start_time(target, start_time)
             .await?;
 
+        if local_target_matches(&self.local, target, &target_description).await {
+            return Ok(TargetSource::Local);
+        }
+
         // Since the async read we fetch from the remote repository has internal
         // lifetimes, we need to break up client into sub-objects so that rust
         // won't complain about trying to borrow `&self` for the fetch, and
         // `&mut self` for the store.
         let Client {
-            tuf, local, remote, ..
+            tuf,
+            local,
+            remote,
+            target_mirrors,
+            ..
         } = self;
 
-        // TODO: Check the local repository to see if it already has the target.
         let mut read = remote
             .fetch_target(
                 tuf.trusted_root().consistent_snapshot(),
@@ -910,7 +1112,35 @@ where
             )
             .await?;
 
-        local.store_target(target, &mut read).await
+        loop {
+            let mut tracked = TrackReadErrors::new(&mut read);
+            let result = local.store_target(target, &mut tracked).await;
+            let read_failed = tracked.failed();
+
+            let err = match result {
+                Ok(()) => {
+                    record_mirror(target_mirrors, target, read.source_name());
+                    return Ok(TargetSource::Remote);
+                }
+                // The target is only verified as it is read, so if reading it failed, the remote
+                // may be able to serve it from another source.
+                Err(err) if read_failed => err,
+                // Otherwise the local repository failed to store it, such as when the disk is
+                // full, which is not the fault of the source that served it.
+                Err(err) => return Err(err),
+            };
+
+            read = match read.reject(&err).await {
+                Some(result) => {
+                    warn!(
+                        "Failed to fetch target {:?}, trying another source: {}",
+                        target, err
+                    );
+                    result?
+                }
+                None => return Err(err),
+            };
+        }
     }
 
     /// Fetch a target description from the remote repo and return it.
@@ -928,6 +1158,32 @@ where
         target: &TargetPath,
         start_time: &DateTime<Utc>,
     ) -> Result<TargetDescription> {
+        let (target_description, _) = self
+            .fetch_target_description_and_delegation_path_with_start_time(target, start_time)
+            .await?;
+
+        Ok(target_description)
+    }
+
+    /// Fetch a target description from the remote repo and return it, along with the roles that
+    /// were followed to find it. The path starts with the top-level targets role, and ends with
+    /// the role that describes the target.
+    pub async fn fetch_target_description_and_delegation_path(
+        &mut self,
+        target: &TargetPath,
+    ) -> Result<(TargetDescription, Vec<MetadataPath>)> {
+        self.fetch_target_description_and_delegation_path_with_start_time(target, &Utc::now())
+            .await
+    }
+
+    /// Fetch a target description from the remote repo and return it, along with the roles that
+    /// were followed to find it. The path starts with the top-level targets role, and ends with
+    /// the role that describes the target.
+    pub async fn fetch_target_description_and_delegation_path_with_start_time(
+        &mut self,
+        target: &TargetPath,
+        start_time: &DateTime<Utc>,
+    ) -> Result<(TargetDescription, Vec<MetadataPath>)> {
         let snapshot = self
             .tuf
             .trusted_snapshot()
@@ -960,7 +1216,7 @@ where
         target: &TargetPath,
         snapshot: &SnapshotMetadata,
         targets: Option<(&Verified<TargetsMetadata>, MetadataPath)>,
-    ) -> (bool, Result<TargetDescription>) {
+    ) -> (bool, Result<(TargetDescription, Vec<MetadataPath>)>) {
         if current_depth > self.config.max_delegation_depth {
             warn!(
                 "Walking the delegation graph would have exceeded the configured max depth: {}",
@@ -991,21 +1247,39 @@ where
         };
 
         if let Some(t) = targets.targets().get(target) {
-            return (default_terminate, Ok(t.clone()));
+            return (default_terminate, Ok((t.clone(), vec![targets_role])));
         }
 
-        for delegation in targets.delegations().roles() {
-            if !delegation.paths().iter().any(|p| target.is_child(p)) {
-                if delegation.terminating() {
-                    return (true, Err(Error::TargetNotFound(target.clone())));
-                } else {
-                    continue;
-                }
+        // The delegated roles to search, along with whether each is terminating, and whether it
+        // is trusted with the target. Succinct roles resolve the target straight to its bin,
+        // which is never terminating.
+        let delegations = targets.delegations();
+        let roles = delegations
+            .roles()
+            .iter()
+            .map(|delegation| {
+                (
+                    delegation.name().clone(),
+                    delegation.terminating(),
+                    delegation.matches_target(target),
+                )
+            })
+            .chain(
+                delegations
+                    .succinct_roles()
+                    .map(|succinct_roles| (succinct_roles.role_for_target(target), false, true)),
+            )
+            .collect::<Vec<_>>();
+
+        for (role, terminating, matches_target) in roles {
+            // A delegation only terminates the search if it is trusted with the target.
+            if !matches_target {
+                continue;
             }
 
-            let role_meta = match snapshot.meta().get(delegation.name()) {
+            let role_meta = match snapshot.meta().get(&role) {
                 Some(m) => m,
-                None if delegation.terminating() => {
+                None if terminating => {
                     return (true, Err(Error::TargetNotFound(target.clone())));
                 }
                 None => {
@@ -1040,29 +1314,27 @@ where
             //      any, listed in the trusted snapshot metadata.
             let role_hashes = crypto::retain_supported_hashes(role_meta.hashes());
 
-            let raw_signed_meta = match self
-                .remote
-                .fetch_metadata(delegation.name(), version, role_length, role_hashes)
-                .await
-            {
-                Ok(m) => m,
-                Err(e) => {
-                    warn!("Failed to fetch metadata {:?}: {:?}", delegation.name(), e);
-                    if delegation.terminating() {
-                        return (true, Err(e));
-                    } else {
-                        continue;
-                    }
-                }
-            };
+            let res = fetch_verified_metadata(
+                &self.remote,
+                &role,
+                version,
+                role_length,
+                role_hashes,
+                |raw_signed_meta| {
+                    self.tuf.update_delegated_targets(
+                        start_time,
+                        &targets_role,
+                        &role,
+                        raw_signed_meta,
+                    )
+                },
+            )
+            .await;
+
+            match res {
+                Ok((raw_signed_meta, _, mirror)) => {
+                    record_mirror(&mut self.metadata_mirrors, &role, mirror.as_deref());
 
-            match self.tuf.update_delegated_targets(
-                start_time,
-                &targets_role,
-                delegation.name(),
-                &raw_signed_meta,
-            ) {
-                Ok(_) => {
                     /////////////////////////////////////////
                     // TUF-1.0.9 §5.4.4:
                     //
@@ -1071,44 +1343,43 @@ where
 
                     match self
                         .local
-                        .store_metadata(delegation.name(), MetadataVersion::None, &raw_signed_meta)
+                        .store_metadata(&role, MetadataVersion::None, &raw_signed_meta)
                         .await
                     {
                         Ok(_) => (),
                         Err(e) => {
-                            warn!(
-                                "Error storing metadata {:?} locally: {:?}",
-                                delegation.name(),
-                                e
-                            )
+                            warn!("Error storing metadata {:?} locally: {:?}", role, e)
                         }
                     }
 
-                    let meta = self
-                        .tuf
-                        .trusted_delegations()
-                        .get(delegation.name())
-                        .unwrap()
-                        .clone();
+                    let meta = self.tuf.trusted_delegations().get(&role).unwrap().clone();
                     let f: Pin<Box<dyn Future<Output = _>>> =
                         Box::pin(self.lookup_target_description(
                             start_time,
-                            delegation.terminating(),
+                            terminating,
                             current_depth + 1,
                             target,
                             snapshot,
-                            Some((&meta, delegation.name().clone())),
+                            Some((&meta, role.clone())),
                         ));
                     let (term, res) = f.await;
 
-                    if term && res.is_err() {
-                        return (true, res);
+                    if term || res.is_ok() {
+                        let res = res.map(|(description, mut path)| {
+                            path.insert(0, targets_role.clone());
+                            (description, path)
+                        });
+                        return (term, res);
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to fetch metadata {:?}: {:?}", role, e);
+                    if terminating {
+                        return (true, Err(e));
+                    } else {
+                        continue;
                     }
-
-                    // TODO end recursion early
                 }
-                Err(_) if !delegation.terminating() => continue,
-                Err(e) => return (true, Err(e)),
             };
         }
 
@@ -1145,6 +1416,208 @@ where
     pub remote: R,
 }
 
+/// The repository a [Client] read a target from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetSource {
+    /// The local repository already had a copy of the target that matched the trusted target
+    /// description.
+    Local,
+
+    /// The target was downloaded from the remote repository.
+    Remote,
+}
+
+/// An `AsyncRead` over the contents of a target, returned by [`Client::fetch_target`].
+///
+/// It is **critical** that none of the bytes from this reader are used until it has been fully
+/// consumed, as the length and hashes of the target are only verified at the end of the stream.
+pub struct TargetReader<'a> {
+    source: TargetSource,
+    read: Box<dyn AsyncRead + Send + Unpin + 'a>,
+}
+
+impl TargetReader<'_> {
+    /// The repository the target is being read from.
+    pub fn source(&self) -> TargetSource {
+        self.source
+    }
+}
+
+impl std::fmt::Debug for TargetReader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TargetReader")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsyncRead for TargetReader<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.read).poll_read(cx, buf)
+    }
+}
+
+/// Wraps a reader and remembers whether reading from it ever failed, so a failure of the reader can
+/// be told apart from a failure of whatever is consuming it.
+struct TrackReadErrors<R> {
+    read: R,
+    failed: bool,
+}
+
+impl<R> TrackReadErrors<R> {
+    fn new(read: R) -> Self {
+        Self {
+            read,
+            failed: false,
+        }
+    }
+
+    fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TrackReadErrors<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let poll = Pin::new(&mut self.read).poll_read(cx, buf);
+        if let Poll::Ready(Err(_)) = poll {
+            self.failed = true;
+        }
+        poll
+    }
+}
+
+/// Helper function that opens the local repository's copy of `target`, if it has one. The returned
+/// reader fails if the copy's length and hashes don't match `target_description`.
+async fn local_target<'a, D, L>(
+    local: &'a Repository<L, D>,
+    target: &TargetPath,
+    target_description: &TargetDescription,
+) -> Option<impl AsyncRead + Send + Unpin + 'a>
+where
+    D: Pouf,
+    L: RepositoryProvider<D>,
+{
+    match local
+        .fetch_target(false, target, target_description.clone())
+        .await
+    {
+        Ok(read) => Some(read),
+        Err(Error::TargetNotFound(_)) => None,
+        Err(err) => {
+            warn!(
+                "Failed to read target {:?} from local repo: {:?}",
+                target, err
+            );
+            None
+        }
+    }
+}
+
+/// Helper function that checks if the local repository has a copy of `target` whose length and
+/// hashes match `target_description`.
+async fn local_target_matches<D, L>(
+    local: &Repository<L, D>,
+    target: &TargetPath,
+    target_description: &TargetDescription,
+) -> bool
+where
+    D: Pouf,
+    L: RepositoryProvider<D>,
+{
+    let mut read = match local_target(local, target, target_description).await {
+        Some(read) => read,
+        None => return false,
+    };
+
+    match io::copy(&mut read, &mut io::sink()).await {
+        Ok(length) => length == target_description.length(),
+        Err(err) => {
+            warn!(
+                "Local copy of target {:?} does not match: {:?}",
+                target, err
+            );
+            false
+        }
+    }
+}
+
+/// Helper function that fetches metadata from `remote` and checks it with `verify`. If the metadata
+/// can't be read or fails verification, and `remote` can serve it from another source, such as
+/// another mirror, it is fetched again from there.
+///
+/// Returns the metadata, the result of `verify`, and the source the metadata came from, if
+/// `remote` reports one.
+async fn fetch_verified_metadata<D, R, M, T, F>(
+    remote: &Repository<R, D>,
+    path: &MetadataPath,
+    version: MetadataVersion,
+    max_length: Option<usize>,
+    hashes: Vec<(&'static HashAlgorithm, HashValue)>,
+    mut verify: F,
+) -> Result<(RawSignedMetadata<D, M>, T, Option<String>)>
+where
+    D: Pouf,
+    R: RepositoryProvider<D>,
+    M: Metadata,
+    F: FnMut(&RawSignedMetadata<D, M>) -> Result<T>,
+{
+    let mut read = remote
+        .fetch_metadata_with_source::<M>(path, version, max_length, hashes)
+        .await?;
+
+    loop {
+        let mut buf = Vec::new();
+        let err = match read.read_to_end(&mut buf).await {
+            Ok(_) => {
+                let raw_meta = RawSignedMetadata::new(buf);
+                match verify(&raw_meta) {
+                    Ok(verified) => {
+                        let source = read.source_name().map(str::to_owned);
+                        return Ok((raw_meta, verified, source));
+                    }
+                    Err(err) => err,
+                }
+            }
+            Err(err) => err.into(),
+        };
+
+        read = match read.reject(&err).await {
+            Some(result) => {
+                warn!(
+                    "Failed to fetch metadata {:?}, trying another source: {}",
+                    path, err
+                );
+                result?
+            }
+            None => return Err(err),
+        };
+    }
+}
+
+/// Remember which mirror `key` was fetched from, if the remote repository reported one.
+fn record_mirror<K>(mirrors: &mut HashMap<K, String>, key: &K, mirror: Option<&str>)
+where
+    K: Clone + Eq + std::hash::Hash,
+{
+    match mirror {
+        Some(mirror) => {
+            mirrors.insert(key.clone(), mirror.to_owned());
+        }
+        None => {
+            mirrors.remove(key);
+        }
+    }
+}
+
 /// Helper function that first tries to fetch the metadata from the local store, and if it doesn't
 /// exist or does and fails to parse, try fetching it from the remote store.
 async fn fetch_metadata_from_local_or_else_remote<'a, D, L, R, M>(
@@ -1192,6 +1665,9 @@ where
 /// assert_eq!(config.max_snapshot_length(), &Some(2000000));
 /// assert_eq!(config.max_targets_length(), &Some(5000000));
 /// assert_eq!(config.max_delegation_depth(), 8);
+/// assert_eq!(config.max_root_rotations(), 1024);
+/// assert_eq!(config.metadata_timeout(), None);
+/// assert_eq!(config.target_timeout(), None);
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Config {
@@ -1200,6 +1676,10 @@ pub struct Config {
     max_snapshot_length: Option<usize>,
     max_targets_length: Option<usize>,
     max_delegation_depth: u32,
+    max_root_rotations: u32,
+    metadata_timeout: Option<Duration>,
+    target_timeout: Option<Duration>,
+    pub(crate) timer: SharedTimer,
 }
 
 impl Config {
@@ -1232,6 +1712,26 @@ impl Config {
     pub fn max_delegation_depth(&self) -> u32 {
         self.max_delegation_depth
     }
+
+    /// The maximum number of new root metadata versions applied during a single update.
+    pub fn max_root_rotations(&self) -> u32 {
+        self.max_root_rotations
+    }
+
+    /// The optional maximum time to fetch a single metadata file from the remote repository.
+    pub fn metadata_timeout(&self) -> Option<Duration> {
+        self.metadata_timeout
+    }
+
+    /// The optional maximum time to fetch a single target from the remote repository.
+    pub fn target_timeout(&self) -> Option<Duration> {
+        self.target_timeout
+    }
+
+    /// The timer used to measure the timeouts.
+    pub fn timer(&self) -> &dyn Timer {
+        self.timer.get()
+    }
 }
 
 impl Default for Config {
@@ -1242,6 +1742,10 @@ impl Default for Config {
             max_snapshot_length: Some(2000000),
             max_targets_length: Some(5000000),
             max_delegation_depth: 8,
+            max_root_rotations: 1024,
+            metadata_timeout: None,
+            target_timeout: None,
+            timer: SharedTimer::default(),
         }
     }
 }
@@ -1287,6 +1791,35 @@ impl ConfigBuilder {
         self.cfg.max_delegation_depth = max;
         self
     }
+
+    /// Set the maximum number of new root metadata versions applied during a single update.
+    pub fn max_root_rotations(mut self, max: u32) -> Self {
+        self.cfg.max_root_rotations = max;
+        self
+    }
+
+    /// Set the optional maximum time to fetch a single metadata file from the remote repository,
+    /// from starting the fetch to reading the last byte. A fetch that takes longer fails with
+    /// [`std::io::ErrorKind::TimedOut`].
+    pub fn metadata_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.cfg.metadata_timeout = timeout;
+        self
+    }
+
+    /// Set the optional maximum time to fetch a single target from the remote repository, from
+    /// starting the fetch to reading the last byte. A fetch that takes longer fails with
+    /// [`std::io::ErrorKind::TimedOut`].
+    pub fn target_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.cfg.target_timeout = timeout;
+        self
+    }
+
+    /// Set the [`Timer`] used to measure the timeouts. Defaults to a
+    /// [`ThreadTimer`](crate::timer::ThreadTimer).
+    pub fn timer<T: Timer + 'static>(mut self, timer: T) -> Self {
+        self.cfg.timer = SharedTimer::new(timer);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -1294,23 +1827,28 @@ mod test {
     use super::*;
     use crate::crypto::{Ed25519PrivateKey, HashAlgorithm, PrivateKey};
     use crate::metadata::{
-        MetadataDescription, MetadataPath, MetadataVersion, RootMetadataBuilder,
-        SnapshotMetadataBuilder, TargetsMetadataBuilder, TimestampMetadataBuilder,
+        Delegation, DelegationBuilder, MetadataDescription, MetadataPath, MetadataVersion,
+        PathPattern, RootMetadataBuilder, SnapshotMetadataBuilder, SuccinctRoles,
+        TargetsMetadataBuilder, TimestampMetadataBuilder,
     };
     use crate::pouf::Pouf1;
     use crate::repo_builder::RepoBuilder;
     use crate::repository::{
-        fetch_metadata_to_string, EphemeralRepository, ErrorRepository, Track, TrackRepository,
+        fetch_metadata_to_string, fetch_target_to_string, EphemeralRepository, ErrorRepository,
+        MirrorsRepository, RetryPolicy, RetryRepository, Track, TrackRepository,
     };
     use assert_matches::assert_matches;
     use chrono::prelude::*;
     use futures_executor::block_on;
+    use futures_util::future::{BoxFuture, FutureExt as _};
+    use futures_util::io::AsyncReadExt;
     use lazy_static::lazy_static;
     use maplit::hashmap;
     use pretty_assertions::assert_eq;
     use serde_json::json;
     use std::collections::HashMap;
     use std::iter::once;
+    use std::sync::Arc;
 
     lazy_static! {
         static ref KEYS: Vec<Ed25519PrivateKey> = {
@@ -1439,6 +1977,7 @@ mod test {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root_with_builder(|bld| bld.consistent_snapshot(true))
+            .await
             .unwrap()
             .commit()
             .await
@@ -1452,12 +1991,16 @@ mod test {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root_with_builder(|bld| bld.version(2).consistent_snapshot(true))
+            .await
             .unwrap()
             .stage_targets_with_builder(|bld| bld.version(2))
+            .await
             .unwrap()
             .stage_snapshot_with_builder(|bld| bld.version(2))
+            .await
             .unwrap()
             .stage_timestamp_with_builder(|bld| bld.version(2))
+            .await
             .unwrap()
             .commit()
             .await
@@ -1601,6 +2144,7 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|bld| bld.consistent_snapshot(true))
+                .await
                 .unwrap()
                 .skip_targets()
                 .skip_snapshot()
@@ -1645,12 +2189,16 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|bld| bld.version(2).consistent_snapshot(true))
+                .await
                 .unwrap()
                 .stage_targets_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -1704,6 +2252,7 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|bld| bld.version(1).consistent_snapshot(true))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -1716,12 +2265,16 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|bld| bld.version(2).consistent_snapshot(true))
+                .await
                 .unwrap()
                 .stage_targets_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -1767,12 +2320,16 @@ mod test {
                         .consistent_snapshot(true)
                         .expires(Utc.ymd(2038, 1, 1).and_hms(0, 0, 0))
                 })
+                .await
                 .unwrap()
                 .stage_targets_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -1872,6 +2429,7 @@ mod test {
             .signing_timestamp_keys(&[&KEYS[1], &KEYS[2]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root_with_builder(|bld| bld.consistent_snapshot(consistent_snapshot))
+            .await
             .unwrap()
             .commit()
             .await
@@ -1975,6 +2533,7 @@ mod test {
             .trusted_snapshot_keys(&[&KEYS[1]])
             .trusted_timestamp_keys(&[&KEYS[1]])
             .stage_root_with_builder(|bld| bld.version(2).consistent_snapshot(consistent_snapshot))
+            .await
             .unwrap()
             .skip_targets()
             .skip_snapshot()
@@ -1991,6 +2550,7 @@ mod test {
             .trusted_snapshot_keys(&[&KEYS[2]])
             .trusted_timestamp_keys(&[&KEYS[2]])
             .stage_root_with_builder(|bld| bld.version(3).consistent_snapshot(consistent_snapshot))
+            .await
             .unwrap()
             .skip_targets()
             .skip_snapshot()
@@ -2007,7 +2567,7 @@ mod test {
 
         // Make sure we fetched and stored the metadata in the expected order. Note that we
         // re-fetch snapshot and targets because we rotated keys, which caused `tuf::Database` to delete
-        // the metadata.
+        // the metadata, and the client to delete its local copies.
         assert_eq!(
             client.remote_repo().take_tracks(),
             vec![
@@ -2024,6 +2584,9 @@ mod test {
             vec![
                 Track::store_meta(MetadataVersion::None, metadata2.root().unwrap()),
                 Track::store_meta(MetadataVersion::Number(2), metadata2.root().unwrap()),
+                Track::Remove(timestamp_path.clone(), MetadataVersion::None),
+                Track::Remove(MetadataPath::snapshot(), MetadataVersion::None),
+                Track::Remove(MetadataPath::targets(), MetadataVersion::None),
                 Track::store_meta(MetadataVersion::None, metadata3.root().unwrap()),
                 Track::store_meta(MetadataVersion::Number(3), metadata3.root().unwrap()),
                 Track::store_meta(MetadataVersion::None, metadata1.timestamp().unwrap()),
@@ -2033,8 +2596,332 @@ mod test {
         );
     }
 
-    #[test]
-    fn test_fetch_target_description_standard() {
+    async fn publish_root_versions(repo: &mut EphemeralRepository<Pouf1>, last_version: u32) {
+        RepoBuilder::create(&mut *repo)
+            .trusted_root_keys(&[&KEYS[0]])
+            .trusted_targets_keys(&[&KEYS[0]])
+            .trusted_snapshot_keys(&[&KEYS[0]])
+            .trusted_timestamp_keys(&[&KEYS[0]])
+            .commit()
+            .await
+            .unwrap();
+
+        for version in 2..=last_version {
+            RepoBuilder::create(&mut *repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root_with_builder(|bld| bld.version(version))
+                .await
+                .unwrap()
+                .skip_targets()
+                .skip_snapshot()
+                .skip_timestamp()
+                .commit()
+                .await
+                .unwrap();
+        }
+    }
+
+    /// A repository that signs every version of root metadata as it is requested, so it never
+    /// runs out of new root metadata.
+    struct EndlessRootRepository;
+
+    impl RepositoryProvider<Pouf1> for EndlessRootRepository {
+        fn fetch_metadata<'a>(
+            &'a self,
+            meta_path: &MetadataPath,
+            version: MetadataVersion,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            let meta_path = meta_path.clone();
+            async move {
+                match version {
+                    MetadataVersion::Number(version) if meta_path == MetadataPath::root() => {
+                        let root = RootMetadataBuilder::new()
+                            .version(version)
+                            .consistent_snapshot(true)
+                            .root_key(KEYS[0].public().clone())
+                            .snapshot_key(KEYS[0].public().clone())
+                            .targets_key(KEYS[0].public().clone())
+                            .timestamp_key(KEYS[0].public().clone())
+                            .signed::<Pouf1>(&KEYS[0])?
+                            .to_raw()?;
+                        Ok(
+                            Box::new(futures_util::io::Cursor::new(root.as_bytes().to_vec()))
+                                as Box<dyn AsyncRead + Send + Unpin>,
+                        )
+                    }
+                    _ => Err(Error::MetadataNotFound {
+                        path: meta_path,
+                        version,
+                    }),
+                }
+            }
+            .boxed()
+        }
+
+        fn fetch_target<'a>(
+            &'a self,
+            target_path: &TargetPath,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            let target_path = target_path.clone();
+            async move { Err(Error::TargetNotFound(target_path)) }.boxed()
+        }
+    }
+
+    #[test]
+    fn update_root_fails_after_max_root_rotations() {
+        block_on(async {
+            let repo = EndlessRootRepository;
+
+            let config = Config::build().max_root_rotations(3).finish().unwrap();
+            let mut client = Client::with_trusted_root_keys(
+                config,
+                MetadataVersion::Number(1),
+                1,
+                once(&KEYS[0].public().clone()),
+                EphemeralRepository::new(),
+                TrackRepository::new(repo),
+            )
+            .await
+            .unwrap();
+            client.remote_repo().take_tracks();
+
+            assert_matches!(
+                client.update_root(&Utc::now()).await,
+                Err(Error::MaxRootRotationsExceeded {
+                    max_root_rotations: 3
+                })
+            );
+
+            // We applied the allowed rotations, and stopped after seeing one more root.
+            assert_eq!(client.tuf.trusted_root().version(), 4);
+            assert_eq!(
+                client
+                    .remote_repo()
+                    .take_tracks()
+                    .into_iter()
+                    .filter(|track| matches!(track, Track::FetchFound { .. }))
+                    .count(),
+                4
+            );
+        })
+    }
+
+    #[test]
+    fn update_root_succeeds_with_exactly_max_root_rotations() {
+        block_on(async {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            publish_root_versions(&mut repo, 4).await;
+
+            let config = Config::build().max_root_rotations(3).finish().unwrap();
+            let mut client = Client::with_trusted_root_keys(
+                config,
+                MetadataVersion::Number(1),
+                1,
+                once(&KEYS[0].public().clone()),
+                EphemeralRepository::new(),
+                repo,
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update_root(&Utc::now()).await, Ok(true));
+            assert_eq!(client.tuf.trusted_root().version(), 4);
+        })
+    }
+
+    #[test]
+    fn update_recovers_from_fast_forward_attack_after_key_rotation() {
+        block_on(async {
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+
+            // An attacker that stole the timestamp, snapshot, and targets keys fast-forwards the
+            // version numbers of the metadata.
+            let _ = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[1]])
+                .trusted_snapshot_keys(&[&KEYS[1]])
+                .trusted_timestamp_keys(&[&KEYS[1]])
+                .stage_root()
+                .await
+                .unwrap()
+                .stage_targets_with_builder(|bld| bld.version(1000))
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| bld.version(1000))
+                .await
+                .unwrap()
+                .stage_timestamp_with_builder(|bld| bld.version(1000))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root_keys(
+                Config::default(),
+                MetadataVersion::Number(1),
+                1,
+                once(&KEYS[0].public().clone()),
+                TrackRepository::new(EphemeralRepository::new()),
+                remote,
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update().await, Ok(true));
+            assert_eq!(
+                client.database().trusted_timestamp().unwrap().version(),
+                1000
+            );
+            assert_eq!(
+                client.database().trusted_snapshot().unwrap().version(),
+                1000
+            );
+            assert_eq!(client.database().trusted_targets().unwrap().version(), 1000);
+
+            // The repository recovers by rotating the compromised keys, and publishing metadata
+            // with smaller version numbers.
+            let mut parts = client.into_parts();
+            parts.local.take_tracks();
+            let metadata2 = RepoBuilder::create(&mut parts.remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[2]])
+                .trusted_snapshot_keys(&[&KEYS[2]])
+                .trusted_timestamp_keys(&[&KEYS[2]])
+                .stage_root_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_targets_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::from_parts(parts);
+            assert_matches!(client.update().await, Ok(true));
+            assert_eq!(client.database().trusted_root().version(), 2);
+            assert_eq!(client.database().trusted_timestamp().unwrap().version(), 2);
+            assert_eq!(client.database().trusted_snapshot().unwrap().version(), 2);
+            assert_eq!(client.database().trusted_targets().unwrap().version(), 2);
+
+            // The fast-forwarded metadata was deleted from the local repository.
+            assert_eq!(
+                client.local_repo().take_tracks(),
+                vec![
+                    Track::store_meta(MetadataVersion::None, metadata2.root().unwrap()),
+                    Track::store_meta(MetadataVersion::Number(2), metadata2.root().unwrap()),
+                    Track::Remove(MetadataPath::timestamp(), MetadataVersion::None),
+                    Track::Remove(MetadataPath::snapshot(), MetadataVersion::None),
+                    Track::Remove(MetadataPath::targets(), MetadataVersion::None),
+                    Track::store_meta(MetadataVersion::None, metadata2.timestamp().unwrap()),
+                    Track::store_meta(MetadataVersion::None, metadata2.snapshot().unwrap()),
+                    Track::store_meta(MetadataVersion::None, metadata2.targets().unwrap()),
+                ],
+            );
+        })
+    }
+
+    #[test]
+    fn update_removes_delegated_metadata_after_targets_key_rotation() {
+        block_on(async {
+            let role = MetadataPath::new("a").unwrap();
+            let target_path = TargetPath::new("a/foo").unwrap();
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+            let _ = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[1]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[2].public().clone())
+                .add_delegation_role(
+                    Delegation::builder(role.clone())
+                        .key(KEYS[2].public())
+                        .delegate_path(PathPattern::new("a/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role.clone(), &[&KEYS[2]])
+                .add_delegated_target(
+                    role.clone(),
+                    target_path.clone(),
+                    futures_util::io::Cursor::new(b"foo"),
+                )
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root_keys(
+                Config::default(),
+                MetadataVersion::Number(1),
+                1,
+                once(&KEYS[0].public().clone()),
+                TrackRepository::new(EphemeralRepository::new()),
+                remote,
+            )
+            .await
+            .unwrap();
+            assert_matches!(client.update().await, Ok(true));
+            client.fetch_target_description(&target_path).await.unwrap();
+
+            // Rotate only the targets key.
+            let mut parts = client.into_parts();
+            parts.local.take_tracks();
+            let _ = RepoBuilder::create(&mut parts.remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[3]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_targets_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::from_parts(parts);
+            assert_matches!(client.update().await, Ok(true));
+
+            // The delegated metadata was signed by a delegation from the untrusted targets
+            // metadata, so it was deleted along with it.
+            let removed = client
+                .local_repo()
+                .take_tracks()
+                .into_iter()
+                .filter_map(|track| match track {
+                    Track::Remove(path, _) => Some(path),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(removed, vec![MetadataPath::targets(), role]);
+        })
+    }
+
+    #[test]
+    fn test_fetch_target_description_standard() {
         block_on(test_fetch_target_description(
             "standard/metadata".to_string(),
             TargetDescription::from_slice(
@@ -2091,6 +2978,7 @@ mod test {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root()
+            .await
             .unwrap()
             .stage_targets_with_builder(|bld| {
                 bld.insert_target_description(
@@ -2098,6 +2986,7 @@ mod test {
                     expected_description.clone(),
                 )
             })
+            .await
             .unwrap()
             .commit()
             .await
@@ -2121,7 +3010,300 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(description, expected_description);
+        assert_eq!(description, expected_description);
+    }
+
+    #[test]
+    fn test_fetch_target_description_from_hashed_bin() {
+        block_on(async {
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+
+            // sha256("foo") starts with "2", so it lands in the "bins/2" bin.
+            let target_path = TargetPath::new("foo").unwrap();
+            let target_description =
+                TargetDescription::from_slice(b"foo", &[HashAlgorithm::Sha256]).unwrap();
+            let bin_path = MetadataPath::new("bins/2").unwrap();
+
+            let bin = TargetsMetadataBuilder::new()
+                .insert_target_description(target_path.clone(), target_description.clone())
+                .signed::<Pouf1>(&KEYS[1])
+                .unwrap()
+                .to_raw()
+                .unwrap();
+
+            remote
+                .store_metadata(&bin_path, MetadataVersion::Number(1), &mut bin.as_bytes())
+                .await
+                .unwrap();
+
+            let mut builder = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone());
+
+            for bin in DelegationBuilder::hashed_bins("bins/", 16).unwrap() {
+                builder = builder.add_delegation_role(bin.key(KEYS[1].public()).build().unwrap());
+            }
+
+            let metadata = builder
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| {
+                    bld.insert_metadata_description(
+                        bin_path.clone(),
+                        MetadataDescription::from_slice(
+                            bin.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                })
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update().await, Ok(true));
+
+            assert_eq!(
+                client
+                    .fetch_target_description_and_delegation_path(&target_path)
+                    .await
+                    .unwrap(),
+                (
+                    target_description,
+                    vec![MetadataPath::targets(), bin_path.clone()]
+                )
+            );
+
+            // A target in a bin we have no metadata for is not found.
+            assert_matches!(
+                client
+                    .fetch_target_description(&TargetPath::new("bar").unwrap())
+                    .await,
+                Err(Error::TargetNotFound(_))
+            );
+        })
+    }
+
+    #[test]
+    fn test_fetch_target_description_skips_non_matching_terminating_delegation() {
+        block_on(async {
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+
+            let target_path = TargetPath::new("foo").unwrap();
+            let target_description =
+                TargetDescription::from_slice(b"foo", &[HashAlgorithm::Sha256]).unwrap();
+            let delegation_path = MetadataPath::new("delegation").unwrap();
+
+            let delegation = TargetsMetadataBuilder::new()
+                .insert_target_description(target_path.clone(), target_description.clone())
+                .signed::<Pouf1>(&KEYS[1])
+                .unwrap()
+                .to_raw()
+                .unwrap();
+
+            remote
+                .store_metadata(
+                    &delegation_path,
+                    MetadataVersion::Number(1),
+                    &mut delegation.as_bytes(),
+                )
+                .await
+                .unwrap();
+
+            // The terminating delegation is listed first, but isn't trusted with `foo`, so it
+            // shouldn't stop the search from reaching the delegation that is.
+            let metadata = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .add_delegation_role(
+                    Delegation::new(
+                        MetadataPath::new("baz").unwrap(),
+                        true,
+                        1,
+                        once(KEYS[1].public().key_id().clone()).collect(),
+                        once(PathPattern::new("baz/*").unwrap()).collect(),
+                    )
+                    .unwrap(),
+                )
+                .add_delegation_role(
+                    Delegation::builder(delegation_path.clone())
+                        .key(KEYS[1].public())
+                        .delegate_path(PathPattern::new("foo").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| {
+                    bld.insert_metadata_description(
+                        delegation_path.clone(),
+                        MetadataDescription::from_slice(
+                            delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                })
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update().await, Ok(true));
+
+            assert_eq!(
+                client
+                    .fetch_target_description_and_delegation_path(&target_path)
+                    .await
+                    .unwrap(),
+                (
+                    target_description,
+                    vec![MetadataPath::targets(), delegation_path]
+                )
+            );
+        })
+    }
+
+    #[test]
+    fn test_fetch_target_description_from_succinct_roles() {
+        block_on(async {
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+
+            // sha256("foo") starts with 0x2c, so it lands in the "bin-2c" bin.
+            let target_path = TargetPath::new("foo").unwrap();
+            let target_description =
+                TargetDescription::from_slice(b"foo", &[HashAlgorithm::Sha256]).unwrap();
+            let bin_path = MetadataPath::new("bin-2c").unwrap();
+
+            let bin = TargetsMetadataBuilder::new()
+                .insert_target_description(target_path.clone(), target_description.clone())
+                .signed::<Pouf1>(&KEYS[1])
+                .unwrap()
+                .to_raw()
+                .unwrap();
+
+            remote
+                .store_metadata(&bin_path, MetadataVersion::Number(1), &mut bin.as_bytes())
+                .await
+                .unwrap();
+
+            let succinct_roles = SuccinctRoles::new(
+                once(KEYS[1].public().key_id().clone()).collect(),
+                1,
+                8,
+                "bin".into(),
+            )
+            .unwrap();
+
+            let metadata = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .delegation_succinct_roles(succinct_roles)
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|bld| {
+                    bld.insert_metadata_description(
+                        bin_path.clone(),
+                        MetadataDescription::from_slice(
+                            bin.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                })
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let track_remote = TrackRepository::new(remote);
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                track_remote,
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update().await, Ok(true));
+            let _ = client.remote_repo().take_tracks();
+
+            assert_eq!(
+                client
+                    .fetch_target_description_and_delegation_path(&target_path)
+                    .await
+                    .unwrap(),
+                (
+                    target_description,
+                    vec![MetadataPath::targets(), bin_path.clone()]
+                )
+            );
+
+            // We should only have fetched the one bin that holds the target.
+            assert_eq!(
+                client.remote_repo().take_tracks(),
+                vec![Track::fetch_found(
+                    &bin_path,
+                    MetadataVersion::Number(1),
+                    bin.as_bytes()
+                )],
+            );
+
+            // A target in a bin we have no metadata for is not found.
+            assert_matches!(
+                client
+                    .fetch_target_description(&TargetPath::new("bar").unwrap())
+                    .await,
+                Err(Error::TargetNotFound(_))
+            );
+        })
     }
 
     #[test]
@@ -2169,12 +3351,16 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_targets_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|bld| bld.version(2))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2187,12 +3373,11 @@ mod test {
             let mut client = Client::from_parts(parts);
             assert_matches!(client.update().await, Err(Error::Encoding(_)));
 
-            // FIXME(#297): rust-tuf diverges from the spec by throwing away the
-            // metadata if the root is updated.
+            // The new root didn't rotate any keys, so we still trust the old metadata.
             assert_eq!(client.database().trusted_root().version(), 2);
-            assert_eq!(client.database().trusted_timestamp(), None);
-            assert_eq!(client.database().trusted_snapshot(), None);
-            assert_eq!(client.database().trusted_targets(), None);
+            assert_eq!(client.database().trusted_timestamp().unwrap().version(), 1);
+            assert_eq!(client.database().trusted_snapshot().unwrap().version(), 1);
+            assert_eq!(client.database().trusted_targets().unwrap().version(), 1);
 
             // However, due to https://github.com/theupdateframework/specification/issues/131, if
             // the update is retried a few times it will still succeed.
@@ -2200,7 +3385,7 @@ mod test {
             assert_eq!(client.database().trusted_root().version(), 2);
             assert_eq!(client.database().trusted_timestamp().unwrap().version(), 2);
             assert_eq!(client.database().trusted_snapshot(), None);
-            assert_eq!(client.database().trusted_targets(), None);
+            assert_eq!(client.database().trusted_targets().unwrap().version(), 1);
 
             assert_matches!(client.update().await, Err(Error::Encoding(_)));
             assert_eq!(client.database().trusted_root().version(), 2);
@@ -2222,6 +3407,110 @@ mod test {
         });
     }
 
+    #[test]
+    fn fetch_target_prefers_matching_local_copy() {
+        block_on(async {
+            let mut remote = EphemeralRepository::<Pouf1>::new();
+
+            let target_path = TargetPath::new("foo").unwrap();
+            let target_file: &[u8] = b"things fade, alternatives exclude";
+
+            let _ = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .add_target(
+                    target_path.clone(),
+                    futures_util::io::Cursor::new(target_file),
+                )
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root_keys(
+                Config::default(),
+                MetadataVersion::Number(1),
+                1,
+                once(&KEYS[0].public().clone()),
+                EphemeralRepository::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+            client.update().await.unwrap();
+
+            async fn read_target<D, L, R>(
+                client: &mut Client<D, L, R>,
+                target_path: &TargetPath,
+            ) -> (TargetSource, Vec<u8>)
+            where
+                D: Pouf,
+                L: RepositoryProvider<D> + RepositoryStorage<D>,
+                R: RepositoryProvider<D>,
+            {
+                let mut rdr = client.fetch_target(target_path).await.unwrap();
+                let mut buf = vec![];
+                rdr.read_to_end(&mut buf).await.unwrap();
+                (rdr.source(), buf)
+            }
+
+            // Nothing is cached yet, so the target comes from the remote repository.
+            assert_eq!(
+                read_target(&mut client, &target_path).await,
+                (TargetSource::Remote, target_file.to_vec())
+            );
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Ok(TargetSource::Remote)
+            );
+
+            // Now that the local repository has the target, we don't need to download it again.
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Ok(TargetSource::Local)
+            );
+            assert_eq!(
+                read_target(&mut client, &target_path).await,
+                (TargetSource::Local, target_file.to_vec())
+            );
+
+            // A corrupted local copy is ignored, and replaced with the remote copy.
+            client
+                .local_repo()
+                .store_target(&target_path, &mut &b"you're in a desert"[..])
+                .await
+                .unwrap();
+            assert_eq!(
+                read_target(&mut client, &target_path).await,
+                (TargetSource::Remote, target_file.to_vec())
+            );
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Ok(TargetSource::Remote)
+            );
+            assert_eq!(
+                read_target(&mut client, &target_path).await,
+                (TargetSource::Local, target_file.to_vec())
+            );
+
+            // So is a corrupted local copy that has the right length.
+            let mut corrupted = target_file.to_vec();
+            corrupted[0] ^= 1;
+            client
+                .local_repo()
+                .store_target(&target_path, &mut &corrupted[..])
+                .await
+                .unwrap();
+            assert_eq!(
+                read_target(&mut client, &target_path).await,
+                (TargetSource::Remote, target_file.to_vec())
+            );
+        })
+    }
+
     #[test]
     fn test_local_and_remote_repo_methods() {
         block_on(async {
@@ -2234,12 +3523,16 @@ mod test {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .stage_targets()
+                .await
                 .unwrap()
                 .stage_snapshot()
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|bld| bld.version(1))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2269,6 +3562,7 @@ mod test {
             .skip_targets()
             .skip_snapshot()
             .stage_timestamp_with_builder(|bld| bld.version(2))
+            .await
             .unwrap()
             .commit()
             .await
@@ -2417,4 +3711,339 @@ mod test {
             assert_matches!(client.update().await, Ok(true));
         })
     }
+
+    /// A mirror that serves `repo`, except that its snapshot metadata and targets are tampered.
+    struct TamperedMirror<R>(R);
+
+    impl<D, R> RepositoryProvider<D> for TamperedMirror<R>
+    where
+        D: Pouf,
+        R: RepositoryProvider<D> + Sync,
+    {
+        fn fetch_metadata<'a>(
+            &'a self,
+            meta_path: &MetadataPath,
+            version: MetadataVersion,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            if meta_path == &MetadataPath::snapshot() {
+                async { Ok(Box::new(&b"tampered"[..]) as Box<dyn AsyncRead + Send + Unpin>) }
+                    .boxed()
+            } else {
+                self.0.fetch_metadata(meta_path, version)
+            }
+        }
+
+        fn fetch_target<'a>(
+            &'a self,
+            _target_path: &TargetPath,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            async { Ok(Box::new(&b"bad"[..]) as Box<dyn AsyncRead + Send + Unpin>) }.boxed()
+        }
+    }
+
+    type Mirrors = MirrorsRepository<Box<dyn RepositoryProvider<Pouf1> + Send + Sync>>;
+
+    fn mirrors(repo: &Arc<EphemeralRepository<Pouf1>>) -> Mirrors {
+        MirrorsRepository::new([
+            (
+                "tampered",
+                Box::new(TamperedMirror(repo.clone()))
+                    as Box<dyn RepositoryProvider<Pouf1> + Send + Sync>,
+            ),
+            ("good", Box::new(repo.clone())),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn client_fails_over_to_mirror_with_valid_data() {
+        block_on(async {
+            let target_path = TargetPath::new("foo").unwrap();
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .add_target(target_path.clone(), futures_util::io::Cursor::new(b"foo"))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+            let repo = Arc::new(repo);
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                mirrors(&repo),
+            )
+            .await
+            .unwrap();
+
+            // The tampered snapshot is rejected, and fetched from the good mirror instead.
+            assert_matches!(client.update().await, Ok(true));
+            assert_eq!(
+                client.metadata_mirror(&MetadataPath::timestamp()),
+                Some("tampered")
+            );
+            assert_eq!(
+                client.metadata_mirror(&MetadataPath::snapshot()),
+                Some("good")
+            );
+            assert_eq!(
+                client.metadata_mirror(&MetadataPath::targets()),
+                Some("good")
+            );
+            assert_eq!(client.remote_repo().penalty("tampered"), Some(1));
+            assert_eq!(client.remote_repo().penalty("good"), Some(0));
+
+            // Likewise for a tampered target, when starting over with fresh mirrors.
+            let mut parts = client.into_parts();
+            parts.remote = mirrors(&repo);
+            let mut client = Client::from_parts(parts);
+
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Ok(TargetSource::Remote)
+            );
+            assert_eq!(client.target_mirror(&target_path), Some("good"));
+            assert_eq!(client.remote_repo().penalty("tampered"), Some(1));
+            assert_eq!(
+                fetch_target_to_string(client.local_repo(), &target_path)
+                    .await
+                    .unwrap(),
+                "foo"
+            );
+        })
+    }
+
+    #[test]
+    fn client_does_not_penalize_mirrors_for_local_store_errors() {
+        block_on(async {
+            let target_path = TargetPath::new("foo").unwrap();
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .add_target(target_path.clone(), futures_util::io::Cursor::new(b"foo"))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let remote = MirrorsRepository::new([("a", &repo), ("b", &repo)]).unwrap();
+            let mut client = Client::from_database(
+                Config::default(),
+                Database::from_trusted_metadata(&metadata).unwrap(),
+                ErrorRepository::new(EphemeralRepository::new()),
+                remote,
+            );
+
+            // The target is served correctly, but the local repository fails to store it.
+            client.local_repo().fail_target_stores(true);
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::Other
+            );
+            assert_eq!(client.target_mirror(&target_path), None);
+            assert_eq!(client.remote_repo().penalty("a"), Some(0));
+            assert_eq!(client.remote_repo().penalty("b"), Some(0));
+
+            client.local_repo().fail_target_stores(false);
+            assert_matches!(
+                client.fetch_target_to_local(&target_path).await,
+                Ok(TargetSource::Remote)
+            );
+            assert_eq!(client.target_mirror(&target_path), Some("a"));
+        })
+    }
+
+    #[test]
+    fn client_fails_when_every_mirror_serves_bad_data() {
+        block_on(async {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .commit()
+                .await
+                .unwrap();
+
+            let remote = MirrorsRepository::new([
+                ("a", TamperedMirror(&repo)),
+                ("b", TamperedMirror(&repo)),
+            ])
+            .unwrap();
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+
+            assert!(client.update().await.is_err());
+            assert_eq!(client.remote_repo().penalty("a"), Some(1));
+            assert_eq!(client.remote_repo().penalty("b"), Some(1));
+            assert_eq!(client.database().trusted_snapshot(), None);
+        })
+    }
+
+    /// A repository that stops sending the timestamp metadata and targets partway through.
+    struct StalledRepository<R>(R);
+
+    struct Stalled;
+
+    impl AsyncRead for Stalled {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            _buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            Poll::Pending
+        }
+    }
+
+    impl<D, R> RepositoryProvider<D> for StalledRepository<R>
+    where
+        D: Pouf,
+        R: RepositoryProvider<D> + Sync,
+    {
+        fn fetch_metadata<'a>(
+            &'a self,
+            meta_path: &MetadataPath,
+            version: MetadataVersion,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            if meta_path == &MetadataPath::timestamp() {
+                async { Ok(Box::new(Stalled) as Box<dyn AsyncRead + Send + Unpin>) }.boxed()
+            } else {
+                self.0.fetch_metadata(meta_path, version)
+            }
+        }
+
+        fn fetch_target<'a>(
+            &'a self,
+            target_path: &TargetPath,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            let target_path = target_path.clone();
+            async move {
+                let read = self.0.fetch_target(&target_path).await?;
+                Ok(Box::new(read.take(1).chain(Stalled)) as Box<dyn AsyncRead + Send + Unpin>)
+            }
+            .boxed()
+        }
+    }
+
+    #[test]
+    fn client_fails_fetches_that_exceed_the_timeouts() {
+        block_on(async {
+            let target_path = TargetPath::new("foo").unwrap();
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .add_target(target_path.clone(), futures_util::io::Cursor::new(b"foo"))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let config = Config::build()
+                .metadata_timeout(Some(Duration::from_millis(50)))
+                .target_timeout(Some(Duration::from_millis(50)))
+                .finish()
+                .unwrap();
+            let mut client = Client::with_trusted_root(
+                config,
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                StalledRepository(&repo),
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(
+                client.update().await,
+                Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::TimedOut
+            );
+
+            // Fetch the target with the metadata from a repository that doesn't stall.
+            let mut client = Client::from_database(
+                client.config.clone(),
+                Database::from_trusted_metadata(&metadata).unwrap(),
+                EphemeralRepository::new(),
+                StalledRepository(&repo),
+            );
+            let mut read = client.fetch_target(&target_path).await.unwrap();
+            let err = read.read_to_end(&mut vec![]).await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        })
+    }
+
+    #[test]
+    fn client_update_retries_transient_errors() {
+        block_on(async {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .commit()
+                .await
+                .unwrap();
+
+            let remote = ErrorRepository::new(&repo);
+            let unavailable = || Error::BadHttpStatus {
+                uri: "http://example.com/timestamp.json".into(),
+                code: http::StatusCode::SERVICE_UNAVAILABLE,
+            };
+            remote.fail_next_fetches([unavailable(), unavailable()]);
+            // The connection is also reset partway through reading the timestamp.
+            remote.fail_next_reads([(8, std::io::ErrorKind::ConnectionReset)]);
+
+            let policy = RetryPolicy::build()
+                .initial_backoff(Duration::from_millis(1))
+                .finish()
+                .unwrap();
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                RetryRepository::new(remote, policy),
+            )
+            .await
+            .unwrap();
+
+            assert_matches!(client.update().await, Ok(true));
+            assert!(client.database().trusted_snapshot().is_some());
+            // The next root, timestamp, snapshot and targets, and three retries.
+            assert_eq!(client.remote_repo().as_inner().fetches(), 7);
+
+            // Without retries, the first error aborts the update.
+            let mut parts = client.into_parts();
+            let remote = parts.remote.into_inner();
+            remote.fail_next_fetches([unavailable()]);
+            parts.remote = RetryRepository::new(
+                remote,
+                RetryPolicy::build().max_attempts(1).finish().unwrap(),
+            );
+            let mut client = Client::from_parts(parts);
+            assert_matches!(
+                client.update().await,
+                Err(Error::BadHttpStatus { code, .. }) if code == http::StatusCode::SERVICE_UNAVAILABLE
+            );
+        })
+    }
 }
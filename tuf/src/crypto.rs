@@ -1,17 +1,25 @@
 //! Cryptographic structures and functions.
 
 use {
-    data_encoding::HEXLOWER,
+    data_encoding::{BASE64, HEXLOWER},
     derp::{self, Der, Tag},
     futures_io::AsyncRead,
-    futures_util::AsyncReadExt as _,
+    futures_util::{
+        future::{self, BoxFuture, FutureExt as _},
+        AsyncReadExt as _,
+    },
     ring::{
         digest::{self, SHA256, SHA512},
         rand::SystemRandom,
-        signature::{Ed25519KeyPair, KeyPair, ED25519},
+        signature::{
+            EcdsaKeyPair, EcdsaSigningAlgorithm, Ed25519KeyPair, KeyPair, RsaKeyPair,
+            ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING, ECDSA_P384_SHA384_ASN1,
+            ECDSA_P384_SHA384_ASN1_SIGNING, ED25519, RSA_PKCS1_2048_8192_SHA256, RSA_PKCS1_SHA256,
+            RSA_PSS_2048_8192_SHA256, RSA_PSS_2048_8192_SHA512, RSA_PSS_SHA256, RSA_PSS_SHA512,
+        },
     },
     serde::{
-        de::{Deserialize, Deserializer, Error as DeserializeError},
+        de::{Deserialize, Deserializer, Error as DeserializeError, Visitor},
         ser::{Error as SerializeError, Serialize, Serializer},
     },
     serde_derive::{Deserialize, Serialize},
@@ -21,23 +29,13 @@ use {
         fmt::{self, Debug, Display},
         hash,
         str::FromStr,
+        sync::Arc,
     },
     untrusted::Input,
 };
 
-#[cfg(feature = "unstable_rsa")]
-use {
-    data_encoding::BASE64URL,
-    ring::signature::{
-        RsaKeyPair, RSA_PSS_2048_8192_SHA256, RSA_PSS_2048_8192_SHA512, RSA_PSS_SHA256,
-        RSA_PSS_SHA512,
-    },
-    std::{
-        io::Write,
-        process::{Command, Stdio},
-        sync::Arc,
-    },
-};
+#[cfg(feature = "rsa_keygen")]
+use {rand_core::OsRng, rsa::pkcs8::EncodePrivateKey};
 
 use crate::error::{derp_error_to_error, Error, Result};
 use crate::metadata::MetadataPath;
@@ -46,12 +44,23 @@ use crate::pouf::pouf1::shims;
 const HASH_ALG_PREFS: &[HashAlgorithm] = &[HashAlgorithm::Sha512, HashAlgorithm::Sha256];
 
 /// 1.2.840.113549.1.1.1 rsaEncryption(PKCS #1)
-#[cfg(feature = "unstable_rsa")]
 const RSA_SPKI_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
 
 /// 1.3.101.112 curveEd25519(EdDSA 25519 signature algorithm)
 const ED25519_SPKI_OID: &[u8] = &[0x2b, 0x65, 0x70];
 
+/// 1.2.840.10045.2.1 id-ecPublicKey(Elliptic curve public key cryptography)
+const ECDSA_SPKI_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+/// 1.2.840.10045.3.1.7 prime256v1(NIST P-256 curve)
+const ECDSA_P256_CURVE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// 1.3.132.0.34 secp384r1(NIST P-384 curve)
+const ECDSA_P384_CURVE_OID: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+
+/// The PEM label of a `SubjectPublicKeyInfo` public key.
+const PEM_PUBLIC_KEY_LABEL: &str = "PUBLIC KEY";
+
 /// The length of an ed25519 private key in bytes
 const ED25519_PRIVATE_KEY_LENGTH: usize = 32;
 
@@ -199,14 +208,20 @@ fn shim_public_key(
     signature_scheme: &SignatureScheme,
     keyid_hash_algorithms: &Option<Vec<String>>,
     public_key: &[u8],
+    additional_fields: &HashMap<String, serde_json::Value>,
 ) -> Result<shims::PublicKey> {
     let key = match (key_type, signature_scheme) {
         (KeyType::Ed25519, SignatureScheme::Ed25519) => HEXLOWER.encode(public_key),
-        #[cfg(feature = "unstable_rsa")]
-        (KeyType::Rsa, SignatureScheme::RsaSsaPssSha256)
-        | (KeyType::Rsa, SignatureScheme::RsaSsaPssSha512) => {
-            let bytes = write_spki(public_key, key_type).map_err(derp_error_to_error)?;
-            BASE64URL.encode(&bytes)
+        (KeyType::Ecdsa, SignatureScheme::EcdsaP256Sha256)
+        | (KeyType::Ecdsa, SignatureScheme::EcdsaP384Sha384)
+        | (KeyType::EcdsaSha2Nistp256, SignatureScheme::EcdsaP256Sha256)
+        | (KeyType::EcdsaSha2Nistp384, SignatureScheme::EcdsaP384Sha384)
+        | (KeyType::Rsa, SignatureScheme::RsaSsaPssSha256)
+        | (KeyType::Rsa, SignatureScheme::RsaSsaPssSha512)
+        | (KeyType::Rsa, SignatureScheme::RsaPkcs1v15Sha256) => {
+            let bytes =
+                write_spki(public_key, key_type, signature_scheme).map_err(derp_error_to_error)?;
+            write_pem(PEM_PUBLIC_KEY_LABEL, &bytes)
         }
         (_, _) => {
             // We don't understand this key type and/or signature scheme, so we left it as a UTF-8 string.
@@ -227,6 +242,10 @@ fn shim_public_key(
         signature_scheme.clone(),
         keyid_hash_algorithms.clone(),
         key,
+        additional_fields
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
     ))
 }
 
@@ -235,6 +254,7 @@ fn calculate_key_id(
     signature_scheme: &SignatureScheme,
     keyid_hash_algorithms: &Option<Vec<String>>,
     public_key: &[u8],
+    additional_fields: &HashMap<String, serde_json::Value>,
 ) -> Result<KeyId> {
     use crate::pouf::{Pouf, Pouf1};
 
@@ -243,6 +263,7 @@ fn calculate_key_id(
         signature_scheme,
         keyid_hash_algorithms,
         public_key,
+        additional_fields,
     )?;
     let public_key = Pouf1::canonicalize(&Pouf1::serialize(&public_key)?)?;
     let mut context = digest::Context::new(&SHA256);
@@ -287,14 +308,57 @@ impl Serialize for KeyId {
     where
         S: Serializer,
     {
-        self.0.serialize(ser)
+        if ser.is_human_readable() {
+            self.0.serialize(ser)
+        } else {
+            // Binary formats carry the digest itself rather than its hex encoding.
+            let bytes = HEXLOWER.decode(self.0.as_bytes()).map_err(|_| {
+                SerializeError::custom(format!("key ID {:?} is not lowercase hex", self.0))
+            })?;
+            ser.serialize_bytes(&bytes)
+        }
     }
 }
 
 impl<'de> Deserialize<'de> for KeyId {
     fn deserialize<D: Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
-        let string: String = Deserialize::deserialize(de)?;
-        KeyId::from_str(&string).map_err(|e| DeserializeError::custom(format!("{:?}", e)))
+        struct KeyIdVisitor {
+            allow_hex: bool,
+        }
+
+        // Human readable formats write key IDs as hex, and binary formats as bytes.
+        impl<'de> Visitor<'de> for KeyIdVisitor {
+            type Value = KeyId;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a key ID")
+            }
+
+            fn visit_str<E: DeserializeError>(self, s: &str) -> ::std::result::Result<KeyId, E> {
+                if !self.allow_hex {
+                    return Err(E::invalid_type(serde::de::Unexpected::Str(s), &self));
+                }
+
+                KeyId::from_str(s).map_err(|e| E::custom(format!("{:?}", e)))
+            }
+
+            fn visit_bytes<E: DeserializeError>(
+                self,
+                bytes: &[u8],
+            ) -> ::std::result::Result<KeyId, E> {
+                if self.allow_hex {
+                    return Err(E::invalid_type(serde::de::Unexpected::Bytes(bytes), &self));
+                }
+
+                KeyId::from_str(&HEXLOWER.encode(bytes)).map_err(|e| E::custom(format!("{:?}", e)))
+            }
+        }
+
+        if de.is_human_readable() {
+            de.deserialize_str(KeyIdVisitor { allow_hex: true })
+        } else {
+            de.deserialize_bytes(KeyIdVisitor { allow_hex: false })
+        }
     }
 }
 
@@ -305,14 +369,24 @@ pub enum SignatureScheme {
     /// [Ed25519](https://ed25519.cr.yp.to/)
     Ed25519,
 
+    /// [ECDSA](https://tools.ietf.org/html/rfc5656) on the NIST P-256 curve calculated over
+    /// SHA256
+    EcdsaP256Sha256,
+
+    /// [ECDSA](https://tools.ietf.org/html/rfc5656) on the NIST P-384 curve calculated over
+    /// SHA384
+    EcdsaP384Sha384,
+
     /// [RSASSA-PSS](https://tools.ietf.org/html/rfc5756) calculated over SHA256
-    #[cfg(feature = "unstable_rsa")]
     RsaSsaPssSha256,
 
     /// [RSASSA-PSS](https://tools.ietf.org/html/rfc5756) calculated over SHA512
-    #[cfg(feature = "unstable_rsa")]
     RsaSsaPssSha512,
 
+    /// [RSASSA-PKCS1-v1_5](https://tools.ietf.org/html/rfc8017#section-8.2) calculated over
+    /// SHA256. This is only intended for verifying legacy repositories.
+    RsaPkcs1v15Sha256,
+
     /// Placeholder for an unknown scheme.
     Unknown(String),
 }
@@ -322,10 +396,11 @@ impl SignatureScheme {
     pub fn new(name: &str) -> Self {
         match name {
             "ed25519" => SignatureScheme::Ed25519,
-            #[cfg(feature = "unstable_rsa")]
+            "ecdsa-sha2-nistp256" => SignatureScheme::EcdsaP256Sha256,
+            "ecdsa-sha2-nistp384" => SignatureScheme::EcdsaP384Sha384,
             "rsassa-pss-sha256" => SignatureScheme::RsaSsaPssSha256,
-            #[cfg(feature = "unstable_rsa")]
             "rsassa-pss-sha512" => SignatureScheme::RsaSsaPssSha512,
+            "rsa-pkcs1v15-sha256" => SignatureScheme::RsaPkcs1v15Sha256,
             scheme => SignatureScheme::Unknown(scheme.to_string()),
         }
     }
@@ -334,13 +409,32 @@ impl SignatureScheme {
     pub fn as_str(&self) -> &str {
         match *self {
             SignatureScheme::Ed25519 => "ed25519",
-            #[cfg(feature = "unstable_rsa")]
+            SignatureScheme::EcdsaP256Sha256 => "ecdsa-sha2-nistp256",
+            SignatureScheme::EcdsaP384Sha384 => "ecdsa-sha2-nistp384",
             SignatureScheme::RsaSsaPssSha256 => "rsassa-pss-sha256",
-            #[cfg(feature = "unstable_rsa")]
             SignatureScheme::RsaSsaPssSha512 => "rsassa-pss-sha512",
+            SignatureScheme::RsaPkcs1v15Sha256 => "rsa-pkcs1v15-sha256",
             SignatureScheme::Unknown(ref s) => s,
         }
     }
+
+    /// The OID of the elliptic curve used by an ECDSA signature scheme.
+    fn ecdsa_curve_oid(&self) -> Option<&'static [u8]> {
+        match *self {
+            SignatureScheme::EcdsaP256Sha256 => Some(ECDSA_P256_CURVE_OID),
+            SignatureScheme::EcdsaP384Sha384 => Some(ECDSA_P384_CURVE_OID),
+            _ => None,
+        }
+    }
+
+    /// The length of an uncompressed public key point for an ECDSA signature scheme.
+    fn ecdsa_public_key_length(&self) -> Option<usize> {
+        match *self {
+            SignatureScheme::EcdsaP256Sha256 => Some(65),
+            SignatureScheme::EcdsaP384Sha384 => Some(97),
+            _ => None,
+        }
+    }
 }
 
 impl Display for SignatureScheme {
@@ -398,10 +492,19 @@ pub enum KeyType {
     /// [Ed25519](https://ed25519.cr.yp.to/)
     Ed25519,
 
+    /// [ECDSA](https://en.wikipedia.org/wiki/Elliptic_Curve_Digital_Signature_Algorithm)
+    Ecdsa,
+
     /// [RSA](https://en.wikipedia.org/wiki/RSA_%28cryptosystem%29)
-    #[cfg(feature = "unstable_rsa")]
     Rsa,
 
+    /// ECDSA on the P-256 curve, under the legacy name older python-tuf and sigstore metadata
+    /// use. It is kept apart from [KeyType::Ecdsa] because the key ID covers the name.
+    EcdsaSha2Nistp256,
+
+    /// ECDSA on the P-384 curve, under the legacy name older python-tuf metadata uses.
+    EcdsaSha2Nistp384,
+
     /// Placeholder for an unknown key type.
     Unknown(String),
 }
@@ -411,8 +514,10 @@ impl KeyType {
     pub fn new(name: &str) -> Self {
         match name {
             "ed25519" => KeyType::Ed25519,
-            #[cfg(feature = "unstable_rsa")]
+            "ecdsa" => KeyType::Ecdsa,
             "rsa" => KeyType::Rsa,
+            "ecdsa-sha2-nistp256" => KeyType::EcdsaSha2Nistp256,
+            "ecdsa-sha2-nistp384" => KeyType::EcdsaSha2Nistp384,
             keytype => KeyType::Unknown(keytype.to_string()),
         }
     }
@@ -421,17 +526,35 @@ impl KeyType {
     pub fn as_str(&self) -> &str {
         match *self {
             KeyType::Ed25519 => "ed25519",
-            #[cfg(feature = "unstable_rsa")]
+            KeyType::Ecdsa => "ecdsa",
             KeyType::Rsa => "rsa",
+            KeyType::EcdsaSha2Nistp256 => "ecdsa-sha2-nistp256",
+            KeyType::EcdsaSha2Nistp384 => "ecdsa-sha2-nistp384",
             KeyType::Unknown(ref s) => s,
         }
     }
 
+    fn is_ecdsa(&self) -> bool {
+        matches!(
+            self,
+            KeyType::Ecdsa | KeyType::EcdsaSha2Nistp256 | KeyType::EcdsaSha2Nistp384
+        )
+    }
+
+    /// The signature scheme a key type named after its curve must be used with.
+    fn legacy_ecdsa_scheme(&self) -> Option<SignatureScheme> {
+        match *self {
+            KeyType::EcdsaSha2Nistp256 => Some(SignatureScheme::EcdsaP256Sha256),
+            KeyType::EcdsaSha2Nistp384 => Some(SignatureScheme::EcdsaP384Sha384),
+            _ => None,
+        }
+    }
+
     fn from_oid(oid: &[u8]) -> Result<Self> {
         match oid {
-            #[cfg(feature = "unstable_rsa")]
             x if x == RSA_SPKI_OID => Ok(KeyType::Rsa),
             x if x == ED25519_SPKI_OID => Ok(KeyType::Ed25519),
+            x if x == ECDSA_SPKI_OID => Ok(KeyType::Ecdsa),
             x => Err(Error::Encoding(format!(
                 "Unknown OID: {}",
                 x.iter().map(|b| format!("{:x}", b)).collect::<String>()
@@ -442,7 +565,9 @@ impl KeyType {
     fn as_oid(&self) -> Result<&'static [u8]> {
         match *self {
             KeyType::Ed25519 => Ok(ED25519_SPKI_OID),
-            #[cfg(feature = "unstable_rsa")]
+            KeyType::Ecdsa | KeyType::EcdsaSha2Nistp256 | KeyType::EcdsaSha2Nistp384 => {
+                Ok(ECDSA_SPKI_OID)
+            }
             KeyType::Rsa => Ok(RSA_SPKI_OID),
             KeyType::Unknown(ref s) => Err(Error::UnknownKeyType(s.clone())),
         }
@@ -480,6 +605,29 @@ pub trait PrivateKey {
     fn public(&self) -> &PublicKey;
 }
 
+/// A key that signs messages asynchronously.
+///
+/// This is meant for keys whose material is not held in this process, such as keys stored in a
+/// hardware security module or behind a remote signing service, where signing may require a round
+/// trip or a human to touch a token. Every [PrivateKey] is also a `Signer`.
+pub trait Signer {
+    /// Return the public component of the key.
+    fn public_key(&self) -> &PublicKey;
+
+    /// Sign a message.
+    fn sign_async<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<Signature>>;
+}
+
+impl<K: PrivateKey + ?Sized> Signer for K {
+    fn public_key(&self) -> &PublicKey {
+        self.public()
+    }
+
+    fn sign_async<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<Signature>> {
+        future::ready(self.sign(msg)).boxed()
+    }
+}
+
 /// A structure containing information about an Ed25519 private key.
 pub struct Ed25519PrivateKey {
     private: Ed25519KeyPair,
@@ -589,47 +737,113 @@ impl PrivateKey for Ed25519PrivateKey {
     }
 }
 
+/// A structure containing information about an ECDSA private key.
+pub struct EcdsaPrivateKey {
+    private: EcdsaKeyPair,
+    public: PublicKey,
+}
+
+impl EcdsaPrivateKey {
+    /// Generate ECDSA key bytes in pkcs8 format for the curve used by `scheme`.
+    pub fn pkcs8(scheme: SignatureScheme) -> Result<Vec<u8>> {
+        EcdsaKeyPair::generate_pkcs8(ecdsa_signing_algorithm(&scheme)?, &SystemRandom::new())
+            .map(|bytes| bytes.as_ref().to_vec())
+            .map_err(|_| Error::Opaque("Failed to generate ECDSA key".into()))
+    }
+
+    /// Create a private key from PKCS#8v2 DER bytes.
+    ///
+    /// # Generating Keys
+    ///
+    /// ```bash
+    /// $ umask 077
+    /// $ openssl genpkey -algorithm EC \
+    ///     -pkeyopt ec_paramgen_curve:P-256 \
+    ///     -pkeyopt ec_param_enc:named_curve | \
+    ///     openssl pkcs8 -topk8 -nocrypt -outform der > ecdsa-p256-private-key.pk8
+    /// ```
+    pub fn from_pkcs8(der_key: &[u8], scheme: SignatureScheme) -> Result<Self> {
+        let private = EcdsaKeyPair::from_pkcs8(ecdsa_signing_algorithm(&scheme)?, der_key)
+            .map_err(|_| Error::Encoding("Could not parse key as PKCS#8v2".into()))?;
+
+        let public = PublicKey::new(
+            KeyType::Ecdsa,
+            scheme,
+            python_tuf_compatibility_keyid_hash_algorithms(),
+            private.public_key().as_ref().to_vec(),
+        )?;
+
+        Ok(EcdsaPrivateKey { private, public })
+    }
+}
+
+impl PrivateKey for EcdsaPrivateKey {
+    fn sign(&self, msg: &[u8]) -> Result<Signature> {
+        let value = self
+            .private
+            .sign(&SystemRandom::new(), msg)
+            .map_err(|_| Error::Opaque("Failed to sign message.".into()))?;
+
+        Ok(Signature {
+            key_id: self.public.key_id().clone(),
+            value: SignatureValue(value.as_ref().to_vec()),
+        })
+    }
+
+    fn public(&self) -> &PublicKey {
+        &self.public
+    }
+}
+
+fn ecdsa_signing_algorithm(scheme: &SignatureScheme) -> Result<&'static EcdsaSigningAlgorithm> {
+    match scheme {
+        SignatureScheme::EcdsaP256Sha256 => Ok(&ECDSA_P256_SHA256_ASN1_SIGNING),
+        SignatureScheme::EcdsaP384Sha384 => Ok(&ECDSA_P384_SHA384_ASN1_SIGNING),
+        _ => Err(Error::IllegalArgument(format!(
+            "ECDSA keys do not support the signing scheme {:?}",
+            scheme
+        ))),
+    }
+}
+
 /// A structure containing information about an Rsa private key.
-#[cfg(feature = "unstable_rsa")]
 pub struct RsaPrivateKey {
     private: Arc<RsaKeyPair>,
     public: PublicKey,
 }
 
-#[cfg(feature = "unstable_rsa")]
 impl RsaPrivateKey {
-    /// Generate RSA key bytes in pkcs8 format.
+    /// Generate 4096 bit RSA key bytes in pkcs8 format.
     ///
-    /// Note: `openssl` needs to the on the `$PATH`.
+    /// This requires the `rsa_keygen` feature.
+    #[cfg(feature = "rsa_keygen")]
     pub fn pkcs8() -> Result<Vec<u8>> {
-        let gen = Command::new("openssl")
-            .args(&[
-                "genpkey",
-                "-algorithm",
-                "RSA",
-                "-pkeyopt",
-                "rsa_keygen_bits:4096",
-                "-pkeyopt",
-                "rsa_keygen_pubexp:65537",
-                "-outform",
-                "der",
-            ])
-            .output()?;
-
-        let mut pk8 = Command::new("openssl")
-            .args(&[
-                "pkcs8", "-inform", "der", "-topk8", "-nocrypt", "-outform", "der",
-            ])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()?;
-
-        match pk8.stdin {
-            Some(ref mut stdin) => stdin.write_all(&gen.stdout)?,
-            None => return Err(Error::Opaque("openssl has no stdin".into())),
-        };
+        Self::pkcs8_with_bits(4096)
+    }
 
-        Ok(pk8.wait_with_output()?.stdout)
+    /// Generate RSA key bytes in pkcs8 format with a modulus of `bits` bits and a public exponent
+    /// of 65537. Only 2048, 3072, and 4096 bit keys are supported.
+    ///
+    /// This requires the `rsa_keygen` feature.
+    #[cfg(feature = "rsa_keygen")]
+    pub fn pkcs8_with_bits(bits: usize) -> Result<Vec<u8>> {
+        match bits {
+            2048 | 3072 | 4096 => (),
+            _ => {
+                return Err(Error::IllegalArgument(format!(
+                    "RSA keys must be 2048, 3072, or 4096 bits. Found {}",
+                    bits
+                )))
+            }
+        }
+
+        let key = ::rsa::RsaPrivateKey::new(&mut OsRng, bits)
+            .map_err(|e| Error::Opaque(format!("Failed to generate RSA key: {}", e)))?;
+        let der = key
+            .to_pkcs8_der()
+            .map_err(|e| Error::Opaque(format!("Failed to encode RSA key as PKCS#8: {}", e)))?;
+
+        Ok(der.as_bytes().to_vec())
     }
 
     /// Create a private key from PKCS#8v2 DER bytes.
@@ -645,7 +859,9 @@ impl RsaPrivateKey {
     /// ```
     pub fn from_pkcs8(der_key: &[u8], scheme: SignatureScheme) -> Result<Self> {
         match scheme {
-            SignatureScheme::RsaSsaPssSha256 | SignatureScheme::RsaSsaPssSha512 => (),
+            SignatureScheme::RsaSsaPssSha256
+            | SignatureScheme::RsaSsaPssSha512
+            | SignatureScheme::RsaPkcs1v15Sha256 => (),
             _ => {
                 return Err(Error::IllegalArgument(format!(
                     "RSA keys do not support the signing scheme {:?}",
@@ -678,14 +894,14 @@ impl RsaPrivateKey {
     }
 }
 
-#[cfg(feature = "unstable_rsa")]
 impl PrivateKey for RsaPrivateKey {
     fn sign(&self, msg: &[u8]) -> Result<Signature> {
         let rng = SystemRandom::new();
         let mut buf = vec![0; self.private.public_modulus_len()];
-        let scheme = match &self.public.scheme {
+        let scheme: &dyn ring::signature::RsaEncoding = match &self.public.scheme {
             SignatureScheme::RsaSsaPssSha256 => &RSA_PSS_SHA256,
             SignatureScheme::RsaSsaPssSha512 => &RSA_PSS_SHA512,
+            SignatureScheme::RsaPkcs1v15Sha256 => &RSA_PKCS1_SHA256,
             s => unreachable!("Key {:?} can't be used with scheme {:?}", self.private, s),
         };
 
@@ -713,6 +929,7 @@ pub struct PublicKey {
     scheme: SignatureScheme,
     keyid_hash_algorithms: Option<Vec<String>>,
     value: PublicKeyValue,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl PublicKey {
@@ -722,7 +939,14 @@ impl PublicKey {
         keyid_hash_algorithms: Option<Vec<String>>,
         value: Vec<u8>,
     ) -> Result<Self> {
-        let key_id = calculate_key_id(&typ, &scheme, &keyid_hash_algorithms, &value)?;
+        let additional_fields = HashMap::new();
+        let key_id = calculate_key_id(
+            &typ,
+            &scheme,
+            &keyid_hash_algorithms,
+            &value,
+            &additional_fields,
+        )?;
         let value = PublicKeyValue(value);
         Ok(PublicKey {
             typ,
@@ -730,6 +954,7 @@ impl PublicKey {
             scheme,
             keyid_hash_algorithms,
             value,
+            additional_fields,
         })
     }
 
@@ -763,8 +988,17 @@ impl PublicKey {
                         let typ = KeyType::from_oid(typ.as_slice_less_safe())
                             .map_err(|_| derp::Error::WrongValue)?;
 
-                        // for RSA / ed25519 this is null, so don't both parsing it
-                        derp::read_null(input)?;
+                        if typ == KeyType::Ecdsa {
+                            // ECDSA keys name their curve, which must be the one used by the
+                            // signature scheme.
+                            let curve = derp::expect_tag_and_get_value(input, Tag::Oid)?;
+                            if Some(curve.as_slice_less_safe()) != scheme.ecdsa_curve_oid() {
+                                return Err(derp::Error::WrongValue);
+                            }
+                        } else {
+                            // for RSA / ed25519 this is null, so don't both parsing it
+                            derp::read_null(input)?;
+                        }
                         Ok(typ)
                     })?;
                     let value = derp::bit_string_with_no_unused_bits(input)?;
@@ -773,6 +1007,15 @@ impl PublicKey {
             })
             .map_err(derp_error_to_error)?;
 
+        // Only uncompressed points are supported, which is also what python-tuf writes.
+        if typ == KeyType::Ecdsa
+            && (scheme.ecdsa_public_key_length() != Some(value.len()) || value[0] != 0x04)
+        {
+            return Err(Error::Encoding(
+                "ECDSA public keys must be uncompressed points".into(),
+            ));
+        }
+
         Self::new(typ, scheme, keyid_hash_algorithms, value)
     }
 
@@ -805,7 +1048,7 @@ impl PublicKey {
     ///
     /// See the documentation on `KeyValue` for more information on SPKI.
     pub fn as_spki(&self) -> Result<Vec<u8>> {
-        write_spki(&self.value.0, &self.typ).map_err(derp_error_to_error)
+        write_spki(&self.value.0, &self.typ, &self.scheme).map_err(derp_error_to_error)
     }
 
     /// An immutable reference to the key's type.
@@ -828,14 +1071,44 @@ impl PublicKey {
         &self.value.0
     }
 
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are serialized with the key, and so are covered by
+    /// its ID.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does, and recalculate the key ID to cover them. It is an error for one of them to share
+    /// a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        crate::metadata::check_additional_fields(
+            &additional_fields,
+            &["keytype", "scheme", "keyid_hash_algorithms", "keyval"],
+        )?;
+        self.key_id = calculate_key_id(
+            &self.typ,
+            &self.scheme,
+            &self.keyid_hash_algorithms,
+            &self.value.0,
+            &additional_fields,
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
+
     /// Use this key to verify a message with a signature.
     pub fn verify(&self, role: &MetadataPath, msg: &[u8], sig: &Signature) -> Result<()> {
         let alg: &dyn ring::signature::VerificationAlgorithm = match self.scheme {
             SignatureScheme::Ed25519 => &ED25519,
-            #[cfg(feature = "unstable_rsa")]
+            SignatureScheme::EcdsaP256Sha256 => &ECDSA_P256_SHA256_ASN1,
+            SignatureScheme::EcdsaP384Sha384 => &ECDSA_P384_SHA384_ASN1,
             SignatureScheme::RsaSsaPssSha256 => &RSA_PSS_2048_8192_SHA256,
-            #[cfg(feature = "unstable_rsa")]
             SignatureScheme::RsaSsaPssSha512 => &RSA_PSS_2048_8192_SHA512,
+            SignatureScheme::RsaPkcs1v15Sha256 => &RSA_PKCS1_2048_8192_SHA256,
             SignatureScheme::Unknown(ref s) => {
                 return Err(Error::UnknownSignatureScheme(s.to_string()));
             }
@@ -854,6 +1127,7 @@ impl PartialEq for PublicKey {
             && self.scheme == other.scheme
             && self.keyid_hash_algorithms == other.keyid_hash_algorithms
             && self.value == other.value
+            && self.additional_fields == other.additional_fields
     }
 }
 
@@ -873,7 +1147,8 @@ impl PartialOrd for PublicKey {
 
 impl hash::Hash for PublicKey {
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
-        // key_id is derived from these fields, so we ignore it.
+        // key_id is derived from these fields, so we ignore it. The additional fields can't be
+        // hashed, but leaving them out still keeps equal keys hashing the same.
         self.typ.hash(state);
         self.scheme.hash(state);
         self.keyid_hash_algorithms.hash(state);
@@ -891,6 +1166,7 @@ impl Serialize for PublicKey {
             &self.scheme,
             &self.keyid_hash_algorithms,
             &self.value.0,
+            &self.additional_fields,
         )
         .map_err(|e| SerializeError::custom(format!("Couldn't write key as SPKI: {:?}", e)))?;
         key.serialize(ser)
@@ -924,20 +1200,47 @@ impl<'de> Deserialize<'de> for PublicKey {
                     DeserializeError::custom(format!("Couldn't parse key as ed25519: {:?}", e))
                 })?
             }
-            #[cfg(feature = "unstable_rsa")]
-            KeyType::Rsa => {
-                let bytes = BASE64URL
-                    .decode(intermediate.public_key().as_bytes())
-                    .map_err(|e| DeserializeError::custom(format!("{:?}", e)))?;
+            KeyType::Ecdsa
+            | KeyType::EcdsaSha2Nistp256
+            | KeyType::EcdsaSha2Nistp384
+            | KeyType::Rsa => {
+                if let Some(scheme) = intermediate.keytype().legacy_ecdsa_scheme() {
+                    if intermediate.scheme() != &scheme {
+                        return Err(DeserializeError::custom(format!(
+                            "{} key type must be used with the {} signature scheme, not {:?}",
+                            intermediate.keytype(),
+                            scheme,
+                            intermediate.scheme()
+                        )));
+                    }
+                }
+
+                let bytes =
+                    read_pem(PEM_PUBLIC_KEY_LABEL, intermediate.public_key()).map_err(|e| {
+                        DeserializeError::custom(format!("Couldn't parse key as PEM: {:?}", e))
+                    })?;
 
-                PublicKey::from_spki_with_keyid_hash_algorithms(
+                let key = PublicKey::from_spki_with_keyid_hash_algorithms(
                     &bytes,
                     intermediate.scheme().clone(),
                     intermediate.keyid_hash_algorithms().clone(),
                 )
                 .map_err(|e| {
                     DeserializeError::custom(format!("Couldn't parse key as SPKI: {:?}", e))
-                })?
+                })?;
+
+                // SPKI only says the key is ECDSA, but the key ID covers the legacy name.
+                if key.typ.is_ecdsa() && intermediate.keytype().legacy_ecdsa_scheme().is_some() {
+                    PublicKey::new(
+                        intermediate.keytype().clone(),
+                        key.scheme,
+                        key.keyid_hash_algorithms,
+                        key.value.0,
+                    )
+                    .map_err(|e| DeserializeError::custom(format!("Couldn't parse key: {:?}", e)))?
+                } else {
+                    key
+                }
             }
             KeyType::Unknown(_) => {
                 // We don't know this key type, so just leave it as a UTF-8 string.
@@ -960,7 +1263,18 @@ impl<'de> Deserialize<'de> for PublicKey {
             )));
         }
 
-        Ok(key)
+        if intermediate.additional_fields().is_empty() {
+            Ok(key)
+        } else {
+            key.with_additional_fields(
+                intermediate
+                    .additional_fields()
+                    .iter()
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            )
+            .map_err(|e| DeserializeError::custom(format!("Couldn't parse key: {:?}", e)))
+        }
     }
 }
 
@@ -985,6 +1299,11 @@ pub struct Signature {
 }
 
 impl Signature {
+    /// Create a new `Signature` from the `KeyId` of the key that produced it and its value.
+    pub fn new(key_id: KeyId, value: SignatureValue) -> Self {
+        Signature { key_id, value }
+    }
+
     /// An immutable reference to the `KeyId` of the key that produced the signature.
     pub fn key_id(&self) -> &KeyId {
         &self.key_id
@@ -1067,7 +1386,11 @@ impl Display for HashValue {
     }
 }
 
-fn write_spki(public: &[u8], key_type: &KeyType) -> ::std::result::Result<Vec<u8>, derp::Error> {
+fn write_spki(
+    public: &[u8],
+    key_type: &KeyType,
+    scheme: &SignatureScheme,
+) -> ::std::result::Result<Vec<u8>, derp::Error> {
     let mut output = Vec::new();
     {
         let mut der = Der::new(&mut output);
@@ -1075,7 +1398,14 @@ fn write_spki(public: &[u8], key_type: &KeyType) -> ::std::result::Result<Vec<u8
             der.sequence(|der| match key_type.as_oid().ok() {
                 Some(tag) => {
                     der.element(Tag::Oid, tag)?;
-                    der.null()
+                    if key_type.is_ecdsa() {
+                        match scheme.ecdsa_curve_oid() {
+                            Some(curve) => der.element(Tag::Oid, curve),
+                            None => Err(derp::Error::WrongValue),
+                        }
+                    } else {
+                        der.null()
+                    }
                 }
                 None => Err(derp::Error::WrongValue),
             })?;
@@ -1086,7 +1416,39 @@ fn write_spki(public: &[u8], key_type: &KeyType) -> ::std::result::Result<Vec<u8
     Ok(output)
 }
 
-#[cfg(feature = "unstable_rsa")]
+/// Encode `der` as a PEM document with the given label, wrapping the base64 body at 64 columns.
+fn write_pem(label: &str, der: &[u8]) -> String {
+    let body = BASE64.encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", label);
+    for line in body.as_bytes().chunks(64) {
+        // The base64 alphabet is ASCII, so every chunk is valid UTF-8.
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", label));
+    pem
+}
+
+/// Decode the DER bytes from a PEM document with the given label.
+fn read_pem(label: &str, pem: &str) -> Result<Vec<u8>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+
+    let body = pem
+        .trim()
+        .strip_prefix(&begin)
+        .and_then(|pem| pem.strip_suffix(&end))
+        .ok_or_else(|| Error::Encoding(format!("PEM document is not a {}", label)))?;
+    let body = body
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+
+    BASE64
+        .decode(body.as_bytes())
+        .map_err(|e| Error::Encoding(format!("Couldn't parse PEM body as base64: {:?}", e)))
+}
+
 fn extract_rsa_pub_from_pkcs8(der_key: &[u8]) -> ::std::result::Result<Vec<u8>, derp::Error> {
     let input = Input::from(der_key);
     input.read_all(derp::Error::Read, |input| {
@@ -1120,7 +1482,6 @@ fn extract_rsa_pub_from_pkcs8(der_key: &[u8]) -> ::std::result::Result<Vec<u8>,
     })
 }
 
-#[cfg(feature = "unstable_rsa")]
 fn write_pkcs1(n: &[u8], e: &[u8]) -> ::std::result::Result<Vec<u8>, derp::Error> {
     let mut output = Vec::new();
     {
@@ -1138,13 +1499,14 @@ fn write_pkcs1(n: &[u8], e: &[u8]) -> ::std::result::Result<Vec<u8>, derp::Error
 mod test {
     use super::*;
     use assert_matches::assert_matches;
+    use maplit::hashmap;
     use pretty_assertions::assert_eq;
     use serde_json::{self, json};
 
-    #[cfg(feature = "unstable_rsa")]
     mod rsa {
         pub(super) const PK8_2048: &[u8] = include_bytes!("../tests/rsa/rsa-2048.pk8.der");
         pub(super) const SPKI_2048: &[u8] = include_bytes!("../tests/rsa/rsa-2048.spki.der");
+        pub(super) const PEM_2048: &str = include_str!("../tests/rsa/rsa-2048.spki.pem");
         pub(super) const PCKS1_2048: &[u8] = include_bytes!("../tests/rsa/rsa-2048.pkcs1.der");
 
         pub(super) const PK8_4096: &[u8] = include_bytes!("../tests/rsa/rsa-4096.pk8.der");
@@ -1160,7 +1522,18 @@ mod test {
         pub(super) const PK8_2: &[u8] = include_bytes!("../tests/ed25519/ed25519-2.pk8.der");
     }
 
-    #[cfg(feature = "unstable_rsa")]
+    mod ecdsa {
+        pub(super) const PK8_P256: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p256.pk8.der");
+        pub(super) const SPKI_P256: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p256.spki.der");
+        pub(super) const PEM_P256: &str = include_str!("../tests/ecdsa/ecdsa-p256.pub");
+        pub(super) const SIG_P256: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p256.sig");
+
+        pub(super) const PK8_P384: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p384.pk8.der");
+        pub(super) const SPKI_P384: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p384.spki.der");
+        pub(super) const PEM_P384: &str = include_str!("../tests/ecdsa/ecdsa-p384.pub");
+        pub(super) const SIG_P384: &[u8] = include_bytes!("../tests/ecdsa/ecdsa-p384.sig");
+    }
+
     #[test]
     fn parse_public_rsa_2048_spki() {
         let key = PublicKey::from_spki(rsa::SPKI_2048, SignatureScheme::RsaSsaPssSha256).unwrap();
@@ -1168,7 +1541,6 @@ mod test {
         assert_eq!(key.scheme, SignatureScheme::RsaSsaPssSha256);
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn parse_public_rsa_4096_spki() {
         let key = PublicKey::from_spki(rsa::SPKI_4096, SignatureScheme::RsaSsaPssSha256).unwrap();
@@ -1183,6 +1555,31 @@ mod test {
         assert_eq!(key.scheme, SignatureScheme::Ed25519);
     }
 
+    #[test]
+    fn parse_public_ecdsa_spki() {
+        let key = PublicKey::from_spki(ecdsa::SPKI_P256, SignatureScheme::EcdsaP256Sha256).unwrap();
+        assert_eq!(key.typ, KeyType::Ecdsa);
+        assert_eq!(key.scheme, SignatureScheme::EcdsaP256Sha256);
+        assert_eq!(key.as_spki().unwrap(), ecdsa::SPKI_P256);
+
+        let key = PublicKey::from_spki(ecdsa::SPKI_P384, SignatureScheme::EcdsaP384Sha384).unwrap();
+        assert_eq!(key.typ, KeyType::Ecdsa);
+        assert_eq!(key.scheme, SignatureScheme::EcdsaP384Sha384);
+        assert_eq!(key.as_spki().unwrap(), ecdsa::SPKI_P384);
+    }
+
+    #[test]
+    fn parse_public_ecdsa_spki_rejects_wrong_curve() {
+        assert_matches!(
+            PublicKey::from_spki(ecdsa::SPKI_P256, SignatureScheme::EcdsaP384Sha384),
+            Err(Error::Encoding(_))
+        );
+        assert_matches!(
+            PublicKey::from_spki(ecdsa::SPKI_P384, SignatureScheme::Ed25519),
+            Err(Error::Encoding(_))
+        );
+    }
+
     #[test]
     fn parse_public_ed25519() {
         let key = PublicKey::from_ed25519(ed25519::PUBLIC_KEY).unwrap();
@@ -1224,7 +1621,6 @@ mod test {
         assert_eq!(key.scheme, SignatureScheme::Ed25519);
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn rsa_2048_read_pkcs8_and_sign() {
         let msg = b"test";
@@ -1238,9 +1634,13 @@ mod test {
             RsaPrivateKey::from_pkcs8(rsa::PK8_2048, SignatureScheme::RsaSsaPssSha512).unwrap();
         let sig = key.sign(msg).unwrap();
         key.public.verify(&MetadataPath::root(), msg, &sig).unwrap();
+
+        let key =
+            RsaPrivateKey::from_pkcs8(rsa::PK8_2048, SignatureScheme::RsaPkcs1v15Sha256).unwrap();
+        let sig = key.sign(msg).unwrap();
+        key.public.verify(&MetadataPath::root(), msg, &sig).unwrap();
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn rsa_4096_read_pkcs8_and_sign() {
         let msg = b"test";
@@ -1256,20 +1656,74 @@ mod test {
         key.public.verify(&MetadataPath::root(), msg, &sig).unwrap();
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn extract_pkcs1_from_rsa_2048_pkcs8() {
         let res = extract_rsa_pub_from_pkcs8(rsa::PK8_2048).unwrap();
         assert_eq!(res.as_slice(), rsa::PCKS1_2048);
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn extract_pkcs1_from_rsa_4096_pkcs8() {
         let res = extract_rsa_pub_from_pkcs8(rsa::PK8_4096).unwrap();
         assert_eq!(res.as_slice(), rsa::PCKS1_4096);
     }
 
+    #[test]
+    fn ecdsa_read_pkcs8_and_sign() {
+        let msg = b"test";
+
+        for (pk8, scheme) in [
+            (ecdsa::PK8_P256, SignatureScheme::EcdsaP256Sha256),
+            (ecdsa::PK8_P384, SignatureScheme::EcdsaP384Sha384),
+        ] {
+            let key = EcdsaPrivateKey::from_pkcs8(pk8, scheme).unwrap();
+            let sig = key.sign(msg).unwrap();
+            key.public.verify(&MetadataPath::root(), msg, &sig).unwrap();
+
+            assert_matches!(
+                key.public.verify(&MetadataPath::root(), b"not test", &sig),
+                Err(Error::BadSignature(_))
+            );
+        }
+    }
+
+    #[test]
+    fn ecdsa_from_pkcs8_rejects_mismatched_scheme() {
+        assert_matches!(
+            EcdsaPrivateKey::from_pkcs8(ecdsa::PK8_P256, SignatureScheme::EcdsaP384Sha384).err(),
+            Some(Error::Encoding(_))
+        );
+        assert_matches!(
+            EcdsaPrivateKey::from_pkcs8(ecdsa::PK8_P256, SignatureScheme::Ed25519).err(),
+            Some(Error::IllegalArgument(_))
+        );
+    }
+
+    #[test]
+    fn ecdsa_verify_openssl_signatures() {
+        let msg = b"hello world";
+
+        for (spki, scheme, sig) in [
+            (
+                ecdsa::SPKI_P256,
+                SignatureScheme::EcdsaP256Sha256,
+                ecdsa::SIG_P256,
+            ),
+            (
+                ecdsa::SPKI_P384,
+                SignatureScheme::EcdsaP384Sha384,
+                ecdsa::SIG_P384,
+            ),
+        ] {
+            let key = PublicKey::from_spki(spki, scheme).unwrap();
+            let sig = Signature {
+                key_id: key.key_id().clone(),
+                value: SignatureValue::new(sig.to_vec()),
+            };
+            key.verify(&MetadataPath::root(), msg, &sig).unwrap();
+        }
+    }
+
     #[test]
     fn ed25519_read_pkcs8_and_sign() {
         let key = Ed25519PrivateKey::from_pkcs8(ed25519::PK8_1).unwrap();
@@ -1409,6 +1863,25 @@ mod test {
         let encoded = serde_json::to_value(&parsed).unwrap();
         assert_eq!(encoded, jsn);
 
+        let jsn = json!("ecdsa");
+        let parsed: KeyType = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(parsed, KeyType::Ecdsa);
+
+        let encoded = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(encoded, jsn);
+
+        for (name, typ) in [
+            ("ecdsa-sha2-nistp256", KeyType::EcdsaSha2Nistp256),
+            ("ecdsa-sha2-nistp384", KeyType::EcdsaSha2Nistp384),
+        ] {
+            let jsn = json!(name);
+            let parsed: KeyType = serde_json::from_value(jsn.clone()).unwrap();
+            assert_eq!(parsed, typ);
+
+            let encoded = serde_json::to_value(&parsed).unwrap();
+            assert_eq!(encoded, jsn);
+        }
+
         let jsn = json!("unknown");
         let parsed: KeyType = serde_json::from_value(jsn).unwrap();
         assert_eq!(parsed, KeyType::Unknown("unknown".into()));
@@ -1423,6 +1896,27 @@ mod test {
         let encoded = serde_json::to_value(&parsed).unwrap();
         assert_eq!(encoded, jsn);
 
+        let jsn = json!("ecdsa-sha2-nistp256");
+        let parsed: SignatureScheme = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(parsed, SignatureScheme::EcdsaP256Sha256);
+
+        let encoded = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(encoded, jsn);
+
+        let jsn = json!("ecdsa-sha2-nistp384");
+        let parsed: SignatureScheme = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(parsed, SignatureScheme::EcdsaP384Sha384);
+
+        let encoded = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(encoded, jsn);
+
+        let jsn = json!("rsa-pkcs1v15-sha256");
+        let parsed: SignatureScheme = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(parsed, SignatureScheme::RsaPkcs1v15Sha256);
+
+        let encoded = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(encoded, jsn);
+
         let jsn = json!("unknown");
         let parsed: SignatureScheme = serde_json::from_value(jsn).unwrap();
         assert_eq!(parsed, SignatureScheme::Unknown("unknown".into()));
@@ -1463,7 +1957,6 @@ mod test {
         assert_eq!(decoded, pub_key);
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn serde_rsa_public_key() {
         let der = rsa::SPKI_2048;
@@ -1474,7 +1967,7 @@ mod test {
             "scheme": "rsassa-pss-sha256",
             "keyid_hash_algorithms": ["sha256", "sha512"],
             "keyval": {
-                "public": BASE64URL.encode(der),
+                "public": rsa::PEM_2048,
             }
         });
         assert_eq!(encoded, jsn);
@@ -1482,7 +1975,18 @@ mod test {
         assert_eq!(decoded, pub_key);
     }
 
-    #[cfg(feature = "unstable_rsa")]
+    #[test]
+    fn de_rsa_public_key_rejects_base64url_spki() {
+        let jsn = json!({
+            "keytype": "rsa",
+            "scheme": "rsassa-pss-sha256",
+            "keyval": {
+                "public": data_encoding::BASE64URL.encode(rsa::SPKI_2048),
+            }
+        });
+        assert!(serde_json::from_value::<PublicKey>(jsn).is_err());
+    }
+
     #[test]
     fn de_ser_rsa_public_key_with_keyid_hash_algo() {
         let original = json!({
@@ -1490,7 +1994,7 @@ mod test {
             "scheme": "rsassa-pss-sha256",
             "keyid_hash_algorithms": ["sha256", "sha512"],
             "keyval": {
-                "public": BASE64URL.encode(rsa::SPKI_2048),
+                "public": rsa::PEM_2048,
             }
         });
 
@@ -1500,14 +2004,13 @@ mod test {
         assert_eq!(original, encoded);
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn de_ser_rsa_public_key_without_keyid_hash_algo() {
         let original = json!({
             "keytype": "rsa",
             "scheme": "rsassa-pss-sha256",
             "keyval": {
-                "public": BASE64URL.encode(rsa::SPKI_2048),
+                "public": rsa::PEM_2048,
             }
         });
 
@@ -1517,6 +2020,134 @@ mod test {
         assert_eq!(original, encoded);
     }
 
+    #[test]
+    fn serde_ecdsa_public_key() {
+        for (spki, pem, scheme, scheme_name) in [
+            (
+                ecdsa::SPKI_P256,
+                ecdsa::PEM_P256,
+                SignatureScheme::EcdsaP256Sha256,
+                "ecdsa-sha2-nistp256",
+            ),
+            (
+                ecdsa::SPKI_P384,
+                ecdsa::PEM_P384,
+                SignatureScheme::EcdsaP384Sha384,
+                "ecdsa-sha2-nistp384",
+            ),
+        ] {
+            let pub_key = PublicKey::from_spki(spki, scheme).unwrap();
+            let encoded = serde_json::to_value(&pub_key).unwrap();
+            let jsn = json!({
+                "keytype": "ecdsa",
+                "scheme": scheme_name,
+                "keyid_hash_algorithms": ["sha256", "sha512"],
+                "keyval": {
+                    "public": pem,
+                }
+            });
+            assert_eq!(encoded, jsn);
+            let decoded: PublicKey = serde_json::from_value(encoded).unwrap();
+            assert_eq!(decoded, pub_key);
+        }
+    }
+
+    #[test]
+    fn ecdsa_key_id_covers_unescaped_pem() {
+        // python-tuf calculates key IDs over OLPC canonical JSON, which doesn't escape the
+        // newlines in the PEM encoded key.
+        for (spki, pem, scheme, scheme_name) in [
+            (
+                ecdsa::SPKI_P256,
+                ecdsa::PEM_P256,
+                SignatureScheme::EcdsaP256Sha256,
+                "ecdsa-sha2-nistp256",
+            ),
+            (
+                ecdsa::SPKI_P384,
+                ecdsa::PEM_P384,
+                SignatureScheme::EcdsaP384Sha384,
+                "ecdsa-sha2-nistp384",
+            ),
+        ] {
+            let pub_key = PublicKey::from_spki(spki, scheme).unwrap();
+            let canonical = format!(
+                "{{\"keyid_hash_algorithms\":[\"sha256\",\"sha512\"],\"keytype\":\"ecdsa\",\
+                 \"keyval\":{{\"public\":\"{}\"}},\"scheme\":\"{}\"}}",
+                pem, scheme_name
+            );
+            let key_id = HEXLOWER.encode(digest::digest(&SHA256, canonical.as_bytes()).as_ref());
+            assert_eq!(pub_key.key_id(), &KeyId::from_str(&key_id).unwrap());
+        }
+    }
+
+    #[test]
+    fn serde_legacy_ecdsa_public_key() {
+        // Older python-tuf and sigstore metadata name ECDSA keys after their scheme.
+        for (spki, pem, scheme, name, typ) in [
+            (
+                ecdsa::SPKI_P256,
+                ecdsa::PEM_P256,
+                SignatureScheme::EcdsaP256Sha256,
+                "ecdsa-sha2-nistp256",
+                KeyType::EcdsaSha2Nistp256,
+            ),
+            (
+                ecdsa::SPKI_P384,
+                ecdsa::PEM_P384,
+                SignatureScheme::EcdsaP384Sha384,
+                "ecdsa-sha2-nistp384",
+                KeyType::EcdsaSha2Nistp384,
+            ),
+        ] {
+            let jsn = json!({
+                "keytype": name,
+                "scheme": name,
+                "keyval": {
+                    "public": pem,
+                }
+            });
+            let decoded: PublicKey = serde_json::from_value(jsn.clone()).unwrap();
+            assert_eq!(decoded.typ(), &typ);
+            assert_eq!(decoded.as_spki().unwrap(), spki);
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), jsn);
+
+            // The key ID is calculated over the name the metadata used.
+            let canonical = format!(
+                "{{\"keytype\":\"{}\",\"keyval\":{{\"public\":\"{}\"}},\"scheme\":\"{}\"}}",
+                name, pem, name
+            );
+            let key_id = HEXLOWER.encode(digest::digest(&SHA256, canonical.as_bytes()).as_ref());
+            assert_eq!(decoded.key_id(), &KeyId::from_str(&key_id).unwrap());
+            assert_ne!(
+                decoded.key_id(),
+                PublicKey::from_spki(spki, scheme).unwrap().key_id()
+            );
+        }
+
+        // The legacy name fixes the curve.
+        let jsn = json!({
+            "keytype": "ecdsa-sha2-nistp256",
+            "scheme": "ecdsa-sha2-nistp384",
+            "keyval": {
+                "public": ecdsa::PEM_P384,
+            }
+        });
+        assert!(serde_json::from_value::<PublicKey>(jsn).is_err());
+    }
+
+    #[test]
+    fn de_ecdsa_public_key_rejects_bad_pem() {
+        let jsn = json!({
+            "keytype": "ecdsa",
+            "scheme": "ecdsa-sha2-nistp256",
+            "keyval": {
+                "public": BASE64.encode(ecdsa::SPKI_P256),
+            }
+        });
+        assert!(serde_json::from_value::<PublicKey>(jsn).is_err());
+    }
+
     #[test]
     fn serde_ed25519_public_key() {
         let pub_key = Ed25519PrivateKey::from_pkcs8(ed25519::PK8_1)
@@ -1592,6 +2223,45 @@ mod test {
         assert_eq!(original, decoded);
     }
 
+    #[test]
+    fn de_ser_ed25519_public_key_preserves_unknown_fields() {
+        let pub_key = Ed25519PrivateKey::from_pkcs8(ed25519::PK8_1)
+            .unwrap()
+            .public()
+            .clone();
+        let original = json!({
+            "keytype": "ed25519",
+            "scheme": "ed25519",
+            "keyval": {
+                "public": HEXLOWER.encode(pub_key.as_bytes()),
+            },
+            "x-rust-tuf-extension": {"purpose": "testing"},
+        });
+
+        let decoded: PublicKey = serde_json::from_value(original.clone()).unwrap();
+        assert_eq!(
+            decoded.additional_fields(),
+            &hashmap! {
+                "x-rust-tuf-extension".into() => json!({"purpose": "testing"}),
+            }
+        );
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), original);
+
+        // The key ID covers the unknown fields, like it does in other implementations.
+        let canonical = format!(
+            "{{\"keytype\":\"ed25519\",\"keyval\":{{\"public\":\"{}\"}},\"scheme\":\"ed25519\",\
+             \"x-rust-tuf-extension\":{{\"purpose\":\"testing\"}}}}",
+            HEXLOWER.encode(pub_key.as_bytes())
+        );
+        let key_id = HEXLOWER.encode(digest::digest(&SHA256, canonical.as_bytes()).as_ref());
+        assert_eq!(decoded.key_id(), &KeyId::from_str(&key_id).unwrap());
+        assert_ne!(decoded.key_id(), pub_key.key_id());
+
+        assert!(pub_key
+            .with_additional_fields(hashmap! { "keyval".into() => json!({}) })
+            .is_err());
+    }
+
     #[test]
     fn serde_signature() {
         let key = Ed25519PrivateKey::from_pkcs8(ed25519::PK8_1).unwrap();
@@ -1629,21 +2299,50 @@ mod test {
         assert_eq!(decoded, sig);
     }
 
-    #[cfg(feature = "unstable_rsa")]
-    #[cfg(not(any(target_os = "fuchsia", windows)))]
+    #[cfg(feature = "rsa_keygen")]
     #[test]
     fn new_rsa_key() {
         let bytes = RsaPrivateKey::pkcs8().unwrap();
         let _ = RsaPrivateKey::from_pkcs8(&bytes, SignatureScheme::RsaSsaPssSha256).unwrap();
     }
 
+    #[cfg(feature = "rsa_keygen")]
+    #[test]
+    fn new_rsa_key_with_bits() {
+        for bits in [2048, 3072] {
+            let bytes = RsaPrivateKey::pkcs8_with_bits(bits).unwrap();
+            let key = RsaPrivateKey::from_pkcs8(&bytes, SignatureScheme::RsaSsaPssSha512).unwrap();
+            assert_eq!(key.private.public_modulus_len() * 8, bits);
+
+            let msg = b"test";
+            let sig = key.sign(msg).unwrap();
+            key.public.verify(&MetadataPath::root(), msg, &sig).unwrap();
+        }
+
+        assert_matches!(
+            RsaPrivateKey::pkcs8_with_bits(1024),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+
+    #[test]
+    fn new_ecdsa_key() {
+        for scheme in [
+            SignatureScheme::EcdsaP256Sha256,
+            SignatureScheme::EcdsaP384Sha384,
+        ] {
+            let bytes = EcdsaPrivateKey::pkcs8(scheme.clone()).unwrap();
+            let key = EcdsaPrivateKey::from_pkcs8(&bytes, scheme.clone()).unwrap();
+            assert_eq!(key.public().scheme(), &scheme);
+        }
+    }
+
     #[test]
     fn new_ed25519_key() {
         let bytes = Ed25519PrivateKey::pkcs8().unwrap();
         let _ = Ed25519PrivateKey::from_pkcs8(&bytes).unwrap();
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn test_rsa_public_key_eq() {
         let key256_1 =
@@ -1679,7 +2378,6 @@ mod test {
         assert_ne!(hasher1.finish(), hasher2.finish());
     }
 
-    #[cfg(feature = "unstable_rsa")]
     #[test]
     fn test_rsa_public_key_hash() {
         let key256 =
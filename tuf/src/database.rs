@@ -5,7 +5,7 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 
-use crate::crypto::PublicKey;
+use crate::crypto::{KeyId, PublicKey};
 use crate::error::Error;
 use crate::metadata::{
     Delegations, Metadata, MetadataPath, MetadataVersion, RawSignedMetadata, RawSignedMetadataSet,
@@ -212,6 +212,23 @@ impl<D: Pouf> Database<D> {
         &self.trusted_delegations
     }
 
+    /// Find a trusted targets role that delegates to `role`. The top-level targets is preferred,
+    /// then the delegated targets in order of their role.
+    pub fn delegating_role(&self, role: &MetadataPath) -> Option<MetadataPath> {
+        if let Some(targets) = &self.trusted_targets {
+            if targets.delegations().delegates_to(role) {
+                return Some(MetadataPath::targets());
+            }
+        }
+
+        self.trusted_delegations
+            .iter()
+            .filter(|(_, targets)| targets.delegations().delegates_to(role))
+            .map(|(path, _)| path)
+            .min()
+            .cloned()
+    }
+
     /// Verify and update metadata. Returns true if any of the metadata was updated.
     pub fn update_metadata(&mut self, metadata: &RawSignedMetadataSet<D>) -> Result<bool> {
         self.update_metadata_with_start_time(metadata, &Utc::now())
@@ -265,6 +282,10 @@ impl<D: Pouf> Database<D> {
     }
 
     /// Verify and update the root metadata.
+    ///
+    /// If the new root rotates the timestamp or snapshot keys, the trusted timestamp, snapshot,
+    /// and targets metadata are forgotten. If it only rotates the targets keys, only the trusted
+    /// targets metadata is forgotten.
     pub fn update_root(&mut self, raw_root: &RawSignedMetadata<D, RootMetadata>) -> Result<()> {
         let verified = {
             let trusted_root = &self.trusted_root;
@@ -348,7 +369,17 @@ impl<D: Pouf> Database<D> {
         //     or (3) the targets, or a delegated targets, metadata file in the snapshot
         //     metadata. Please see the Mercury paper for more details.
 
-        self.purge_metadata();
+        if verified.timestamp() != self.trusted_root.timestamp()
+            || verified.snapshot() != self.trusted_root.snapshot()
+        {
+            // The snapshot pins the versions of the targets and delegated targets metadata, so we
+            // need to forget those too in order to recover from them being fast-forwarded.
+            self.purge_metadata();
+        } else if verified.targets() != self.trusted_root.targets() {
+            // The targets metadata was verified with keys that are no longer trusted.
+            self.trusted_targets = None;
+            self.trusted_delegations.clear();
+        }
 
         /////////////////////////////////////////
         // TUF-1.0.5 §5.1.6:
@@ -846,25 +877,35 @@ impl<D: Pouf> Database<D> {
         // Only consider targets metadata that define delegations.
         let trusted_delegations = trusted_parent.delegations();
 
+        // Filter the delegations keys to just the ones for this delegation.
+        let authorized_keys = |key_ids: &HashSet<KeyId>| {
+            trusted_delegations
+                .keys()
+                .iter()
+                .filter_map(|(k, v)| if key_ids.contains(k) { Some(v) } else { None })
+                .collect()
+        };
+
         for trusted_delegation in trusted_delegations.roles() {
             if trusted_delegation.name() != role {
                 continue;
             }
 
-            // Filter the delegations keys to just the ones for this delegation.
-            let authorized_keys = trusted_delegations
-                .keys()
-                .iter()
-                .filter_map(|(k, v)| {
-                    if trusted_delegation.key_ids().contains(k) {
-                        Some(v)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
+            return Ok(Some((
+                trusted_delegation.threshold(),
+                authorized_keys(trusted_delegation.key_ids()),
+            )));
+        }
 
-            return Ok(Some((trusted_delegation.threshold(), authorized_keys)));
+        // Succinct roles name their bins, so we can check the role directly rather than
+        // searching through every bin.
+        if let Some(succinct_roles) = trusted_delegations.succinct_roles() {
+            if succinct_roles.is_delegated_role(role) {
+                return Ok(Some((
+                    succinct_roles.threshold(),
+                    authorized_keys(succinct_roles.key_ids()),
+                )));
+            }
         }
 
         Ok(None)
@@ -895,56 +936,68 @@ impl<D: Pouf> Database<D> {
             return Ok(d.clone());
         }
 
-        fn lookup<'a, D: Pouf>(
+        fn lookup<D: Pouf>(
             start_time: &DateTime<Utc>,
-            tuf: &'a Database<D>,
+            tuf: &Database<D>,
             default_terminate: bool,
-            current_depth: u32,
             target_path: &TargetPath,
-            delegations: &'a Delegations,
-            parents: &[HashSet<TargetPath>],
-            visited: &mut HashSet<&'a MetadataPath>,
+            delegations: &Delegations,
+            visited: &mut HashSet<MetadataPath>,
         ) -> (bool, Option<TargetDescription>) {
-            for delegation in delegations.roles() {
-                if visited.contains(delegation.name()) {
-                    return (delegation.terminating(), None);
+            // The delegated roles to search, along with whether each is terminating, and
+            // whether it is trusted with the target. Succinct roles resolve the target straight
+            // to its bin, which is never terminating.
+            let roles = delegations
+                .roles()
+                .iter()
+                .map(|delegation| {
+                    (
+                        delegation.name().clone(),
+                        delegation.terminating(),
+                        delegation.matches_target(target_path),
+                    )
+                })
+                .chain(delegations.succinct_roles().map(|succinct_roles| {
+                    (succinct_roles.role_for_target(target_path), false, true)
+                }));
+
+            for (role, terminating, matches_target) in roles {
+                // Every delegation along the chain must be trusted with the target, but we
+                // only descend into a delegation once it's been checked, so we only need to
+                // check the current one. A delegation only terminates the search if it is
+                // trusted with the target.
+                if !matches_target {
+                    continue;
                 }
-                let _ = visited.insert(delegation.name());
-
-                let mut new_parents = parents.to_owned();
-                new_parents.push(delegation.paths().clone());
 
-                if current_depth > 0 && !target_path.matches_chain(parents) {
-                    return (delegation.terminating(), None);
+                if visited.contains(&role) {
+                    return (terminating, None);
                 }
+                let _ = visited.insert(role.clone());
 
-                let trusted_delegation = match tuf.trusted_delegations.get(delegation.name()) {
+                let trusted_delegation = match tuf.trusted_delegations.get(&role) {
                     Some(trusted_delegation) => trusted_delegation,
-                    None => return (delegation.terminating(), None),
+                    None => return (terminating, None),
                 };
 
                 if trusted_delegation.expires() <= start_time {
-                    return (delegation.terminating(), None);
+                    return (terminating, None);
                 }
 
                 if let Some(target) = trusted_delegation.targets().get(target_path) {
-                    return (delegation.terminating(), Some(target.clone()));
+                    return (terminating, Some(target.clone()));
                 }
 
                 let trusted_child_delegations = trusted_delegation.delegations();
 
                 // We only need to check the child delegations if it delegates to any child roles.
-                if !trusted_child_delegations.roles().is_empty() {
-                    let mut new_parents = parents.to_vec();
-                    new_parents.push(delegation.paths().clone());
+                if !trusted_child_delegations.is_empty() {
                     let (term, res) = lookup(
                         start_time,
                         tuf,
-                        delegation.terminating(),
-                        current_depth + 1,
+                        terminating,
                         target_path,
                         trusted_child_delegations,
-                        &new_parents,
                         visited,
                     );
                     if term {
@@ -958,7 +1011,7 @@ impl<D: Pouf> Database<D> {
         }
 
         let delegations = targets.delegations();
-        if delegations.roles().is_empty() {
+        if delegations.is_empty() {
             Err(Error::TargetNotFound(target_path.clone()))
         } else {
             let mut visited = HashSet::new();
@@ -966,10 +1019,8 @@ impl<D: Pouf> Database<D> {
                 start_time,
                 self,
                 false,
-                0,
                 target_path,
                 delegations,
-                &[],
                 &mut visited,
             )
             .1
@@ -1064,6 +1115,7 @@ mod test {
     };
     use crate::pouf::Pouf1;
     use assert_matches::assert_matches;
+    use futures_executor::block_on;
     use lazy_static::lazy_static;
     use std::iter::once;
 
@@ -1233,7 +1285,7 @@ mod test {
             .unwrap();
 
         // add the original key's signature to make it cross signed
-        root.add_signature(&KEYS[0]).unwrap();
+        block_on(root.add_signature(&KEYS[0])).unwrap();
         let raw_root = root.to_raw().unwrap();
 
         assert_matches!(tuf.update_root(&raw_root), Ok(()));
@@ -1273,6 +1325,137 @@ mod test {
         assert!(tuf.update_root(&raw_root).is_err());
     }
 
+    fn rotation_root(
+        version: u32,
+        snapshot_key: &Ed25519PrivateKey,
+        targets_key: &Ed25519PrivateKey,
+        timestamp_key: &Ed25519PrivateKey,
+    ) -> RawSignedMetadata<Pouf1, RootMetadata> {
+        RootMetadataBuilder::new()
+            .version(version)
+            .root_key(KEYS[0].public().clone())
+            .snapshot_key(snapshot_key.public().clone())
+            .targets_key(targets_key.public().clone())
+            .timestamp_key(timestamp_key.public().clone())
+            .signed::<Pouf1>(&KEYS[0])
+            .unwrap()
+            .to_raw()
+            .unwrap()
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn rotation_metadata(
+        version: u32,
+        snapshot_key: &Ed25519PrivateKey,
+        targets_key: &Ed25519PrivateKey,
+        timestamp_key: &Ed25519PrivateKey,
+    ) -> (
+        RawSignedMetadata<Pouf1, TimestampMetadata>,
+        RawSignedMetadata<Pouf1, SnapshotMetadata>,
+        RawSignedMetadata<Pouf1, TargetsMetadata>,
+    ) {
+        let targets = TargetsMetadataBuilder::new()
+            .version(version)
+            .signed::<Pouf1>(targets_key)
+            .unwrap();
+
+        let snapshot = SnapshotMetadataBuilder::new()
+            .version(version)
+            .insert_metadata(&targets, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .signed::<Pouf1>(snapshot_key)
+            .unwrap();
+
+        let timestamp =
+            TimestampMetadataBuilder::from_snapshot(&snapshot, &[HashAlgorithm::Sha256])
+                .unwrap()
+                .version(version)
+                .signed::<Pouf1>(timestamp_key)
+                .unwrap();
+
+        (
+            timestamp.to_raw().unwrap(),
+            snapshot.to_raw().unwrap(),
+            targets.to_raw().unwrap(),
+        )
+    }
+
+    #[test]
+    fn root_update_without_key_rotation_keeps_metadata() {
+        let now = Utc::now();
+
+        let mut tuf =
+            Database::from_trusted_root(&rotation_root(1, &KEYS[1], &KEYS[1], &KEYS[1])).unwrap();
+
+        let (timestamp, snapshot, targets) = rotation_metadata(1, &KEYS[1], &KEYS[1], &KEYS[1]);
+        tuf.update_timestamp(&now, &timestamp).unwrap();
+        tuf.update_snapshot(&now, &snapshot).unwrap();
+        tuf.update_targets(&now, &targets).unwrap();
+
+        tuf.update_root(&rotation_root(2, &KEYS[1], &KEYS[1], &KEYS[1]))
+            .unwrap();
+
+        assert_eq!(tuf.trusted_timestamp().unwrap().version(), 1);
+        assert_eq!(tuf.trusted_snapshot().unwrap().version(), 1);
+        assert_eq!(tuf.trusted_targets().unwrap().version(), 1);
+    }
+
+    #[test]
+    fn root_update_with_targets_key_rotation_forgets_targets() {
+        let now = Utc::now();
+
+        let mut tuf =
+            Database::from_trusted_root(&rotation_root(1, &KEYS[1], &KEYS[1], &KEYS[1])).unwrap();
+
+        let (timestamp, snapshot, targets) = rotation_metadata(1, &KEYS[1], &KEYS[1], &KEYS[1]);
+        tuf.update_timestamp(&now, &timestamp).unwrap();
+        tuf.update_snapshot(&now, &snapshot).unwrap();
+        tuf.update_targets(&now, &targets).unwrap();
+
+        tuf.update_root(&rotation_root(2, &KEYS[1], &KEYS[2], &KEYS[1]))
+            .unwrap();
+
+        assert_eq!(tuf.trusted_timestamp().unwrap().version(), 1);
+        assert_eq!(tuf.trusted_snapshot().unwrap().version(), 1);
+        assert_eq!(tuf.trusted_targets(), None);
+    }
+
+    #[test]
+    fn root_update_with_timestamp_key_rotation_recovers_from_fast_forward() {
+        let now = Utc::now();
+
+        let mut tuf =
+            Database::from_trusted_root(&rotation_root(1, &KEYS[1], &KEYS[1], &KEYS[1])).unwrap();
+
+        // An attacker with the timestamp, snapshot and targets keys fast-forwards the metadata.
+        let (timestamp, snapshot, targets) = rotation_metadata(1000, &KEYS[1], &KEYS[1], &KEYS[1]);
+        tuf.update_timestamp(&now, &timestamp).unwrap();
+        tuf.update_snapshot(&now, &snapshot).unwrap();
+        tuf.update_targets(&now, &targets).unwrap();
+
+        // Without a key rotation, the client rejects the repository's real metadata.
+        let (timestamp, _, _) = rotation_metadata(2, &KEYS[1], &KEYS[1], &KEYS[1]);
+        assert_matches!(
+            tuf.update_timestamp(&now, &timestamp),
+            Err(Error::AttemptedMetadataRollBack { role, trusted_version: 1000, new_version: 2 })
+            if role == MetadataPath::timestamp()
+        );
+
+        // Rotating the keys lets the client recover.
+        let (timestamp, snapshot, targets) = rotation_metadata(2, &KEYS[2], &KEYS[2], &KEYS[2]);
+        tuf.update_root(&rotation_root(2, &KEYS[2], &KEYS[2], &KEYS[2]))
+            .unwrap();
+
+        assert_eq!(tuf.trusted_timestamp(), None);
+        assert_eq!(tuf.trusted_snapshot(), None);
+        assert_eq!(tuf.trusted_targets(), None);
+
+        assert_matches!(tuf.update_timestamp(&now, &timestamp), Ok(Some(_)));
+        assert_matches!(tuf.update_snapshot(&now, &snapshot), Ok(true));
+        assert_matches!(tuf.update_targets(&now, &targets), Ok(true));
+        assert_eq!(tuf.trusted_timestamp().unwrap().version(), 2);
+    }
+
     #[test]
     fn good_timestamp_update() {
         let now = Utc::now();
@@ -205,12 +205,34 @@ pub enum Error {
         child_role: MetadataPath,
     },
 
+    /// The remote repository offered more root metadata versions than the client is configured to
+    /// walk through in a single update.
+    #[error("exceeded the maximum number of root rotations: {max_root_rotations}")]
+    MaxRootRotationsExceeded {
+        /// The configured maximum number of root rotations.
+        max_root_rotations: u32,
+    },
+
     /// The metadata must be signed with at least one private key.
     #[error("{role} must be signed with at least one private key")]
     MissingPrivateKey {
         /// The metadata to be signed.
         role: MetadataPath,
     },
+
+    /// A repository knew, before serving any of it, that metadata or a target is longer than the
+    /// client will accept.
+    #[error("{location} is {length} bytes, which exceeds the maximum of {max_length} bytes")]
+    ExceededMaxLength {
+        /// The URI or path of the metadata or target.
+        location: String,
+
+        /// The length reported by the repository.
+        length: u64,
+
+        /// The maximum length the client will accept.
+        max_length: u64,
+    },
 }
 
 pub(crate) fn derp_error_to_error(err: derp::Error) -> Error {
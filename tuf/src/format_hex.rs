@@ -1,20 +1,60 @@
 use data_encoding::HEXLOWER;
-use serde::{self, Deserialize, Deserializer, Serializer};
+use serde::{self, de::Visitor, Deserializer, Serializer};
+use std::fmt;
 use std::result::Result;
 
+/// Serialize `value` as lowercase hex, or as raw bytes for binary formats.
 pub fn serialize<S>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    serializer.serialize_str(&HEXLOWER.encode(value))
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&HEXLOWER.encode(value))
+    } else {
+        serializer.serialize_bytes(value)
+    }
 }
 
 pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    HEXLOWER
-        .decode(s.as_bytes())
-        .map_err(serde::de::Error::custom)
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(HexVisitor { allow_hex: true })
+    } else {
+        deserializer.deserialize_bytes(HexVisitor { allow_hex: false })
+    }
+}
+
+/// Accepts hex, or raw bytes if the format is not human readable.
+struct HexVisitor {
+    allow_hex: bool,
+}
+
+impl<'de> Visitor<'de> for HexVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.allow_hex {
+            f.write_str("a lowercase hex string")
+        } else {
+            f.write_str("bytes")
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Vec<u8>, E> {
+        if !self.allow_hex {
+            return Err(E::invalid_type(serde::de::Unexpected::Str(s), &self));
+        }
+
+        HEXLOWER.decode(s.as_bytes()).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Vec<u8>, E> {
+        if self.allow_hex {
+            return Err(E::invalid_type(serde::de::Unexpected::Bytes(bytes), &self));
+        }
+
+        Ok(bytes.to_vec())
+    }
 }
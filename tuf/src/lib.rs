@@ -25,8 +25,10 @@
 //! interoperate. The TUF spec has moved to describing *how a framework should behave* leaving many
 //! of the detais up to the implementor. Therefore, there are **zero** guarantees that this library
 //! will work with any other TUF implementation. Should you want to access a TUF repository that
-//! uses `rust-tuf` as its backend from another language, ASN.1 modules and metadata schemas are
-//! provided that will allow you to interoperate with this library.
+//! uses `rust-tuf` as its backend from another language, the metadata schemas documented in the
+//! [pouf] module will allow you to interoperate with this library. Metadata can be written as
+//! canonical JSON ([Pouf1](pouf::Pouf1)), as DSSE envelopes ([Dsse](pouf::Dsse)), or as compact
+//! deterministic CBOR ([Cbor](pouf::Cbor)) for clients where parsing JSON is too costly.
 //!
 //! # Implementation Considerations
 //!
@@ -111,6 +113,7 @@
     clippy::too_many_arguments
 )]
 
+pub mod audit;
 pub mod client;
 pub mod crypto;
 pub mod database;
@@ -119,6 +122,7 @@ pub mod metadata;
 pub mod pouf;
 pub mod repo_builder;
 pub mod repository;
+pub mod timer;
 pub mod verify;
 
 mod format_hex;
@@ -2,17 +2,21 @@
 
 use chrono::offset::Utc;
 use chrono::{DateTime, Duration};
+use data_encoding::HEXLOWER;
 use futures_io::AsyncRead;
+use ring::digest;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, Error as DeserializeError};
 use serde::ser::{Error as SerializeError, Serialize, Serializer};
 use serde_derive::{Deserialize, Serialize};
 use std::borrow::{Borrow, Cow};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self, Debug, Display};
 use std::marker::PhantomData;
 use std::str;
 
-use crate::crypto::{self, HashAlgorithm, HashValue, KeyId, PrivateKey, PublicKey, Signature};
+use crate::crypto::{
+    self, HashAlgorithm, HashValue, KeyId, PrivateKey, PublicKey, Signature, Signer,
+};
 use crate::error::Error;
 use crate::pouf::pouf1::shims;
 use crate::pouf::Pouf;
@@ -65,7 +69,6 @@ static PATH_ILLEGAL_STRINGS: &[&str] = &[
     ">",
     "\"",
     "|",
-    "?",
     // control characters, all illegal in FAT
     "\u{000}",
     "\u{001}",
@@ -103,6 +106,15 @@ static PATH_ILLEGAL_STRINGS: &[&str] = &[
 ];
 
 fn safe_path(path: &str) -> Result<()> {
+    // `?` is only meaningful as a wildcard in a `PathPattern`.
+    if path.contains('?') {
+        return Err(Error::IllegalArgument("Path cannot contain \"?\"".into()));
+    }
+
+    safe_path_pattern(path)
+}
+
+fn safe_path_pattern(path: &str) -> Result<()> {
     if path.is_empty() {
         return Err(Error::IllegalArgument("Path cannot be empty".into()));
     }
@@ -144,8 +156,26 @@ fn safe_path(path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Make sure none of the `additional_fields` have the same name as one of the `defined_fields`,
+/// which would otherwise be serialized twice.
+pub(crate) fn check_additional_fields(
+    additional_fields: &HashMap<String, serde_json::Value>,
+    defined_fields: &[&str],
+) -> Result<()> {
+    match additional_fields
+        .keys()
+        .find(|name| defined_fields.contains(&name.as_str()))
+    {
+        Some(name) => Err(Error::IllegalArgument(format!(
+            "Additional field {:?} is already defined",
+            name
+        ))),
+        None => Ok(()),
+    }
+}
+
 /// The TUF role.
-#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Role {
     /// The root role.
     #[serde(rename = "root")]
@@ -234,6 +264,152 @@ impl MetadataVersion {
     }
 }
 
+/// The version of the TUF specification a piece of metadata was written for.
+///
+/// Versions follow [SemVer], and metadata written for any minor or patch release of the major
+/// version this library implements is accepted. Some older metadata uses the version `1.0`, which
+/// omits the patch number, so that form is accepted and preserved as well.
+///
+/// ```
+/// # use tuf::metadata::SpecVersion;
+/// let version: SpecVersion = "1.0.31".parse().unwrap();
+/// assert_eq!((version.major(), version.minor(), version.patch()), (1, 0, 31));
+/// assert!(version.is_supported());
+///
+/// let legacy: SpecVersion = "1.0".parse().unwrap();
+/// assert_eq!(legacy, SpecVersion::new(1, 0, 0));
+/// assert_eq!(legacy.to_string(), "1.0");
+///
+/// assert!(!"2.0.0".parse::<SpecVersion>().unwrap().is_supported());
+/// assert!("1.0.0-rc1".parse::<SpecVersion>().is_err());
+/// ```
+///
+/// [SemVer]: https://semver.org/
+#[derive(Debug, Clone, Copy)]
+pub struct SpecVersion {
+    major: u32,
+    minor: u32,
+    patch: Option<u32>,
+}
+
+impl SpecVersion {
+    /// The major version of the specification this library implements.
+    pub const SUPPORTED_MAJOR: u32 = 1;
+
+    /// Create a new `SpecVersion`.
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        SpecVersion {
+            major,
+            minor,
+            patch: Some(patch),
+        }
+    }
+
+    /// The major version.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// The minor version.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// The patch version, which is 0 if it was omitted.
+    pub fn patch(&self) -> u32 {
+        self.patch.unwrap_or(0)
+    }
+
+    /// Whether or not this library can process metadata written for this version.
+    pub fn is_supported(&self) -> bool {
+        self.major == Self::SUPPORTED_MAJOR
+    }
+
+    fn check_supported(self) -> Result<Self> {
+        if self.is_supported() {
+            Ok(self)
+        } else {
+            Err(Error::IllegalArgument(format!(
+                "Unsupported spec version {}",
+                self
+            )))
+        }
+    }
+}
+
+impl Default for SpecVersion {
+    /// The version written into new metadata, `1.0`.
+    fn default() -> Self {
+        SpecVersion {
+            major: Self::SUPPORTED_MAJOR,
+            minor: 0,
+            patch: None,
+        }
+    }
+}
+
+impl PartialEq for SpecVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for SpecVersion {}
+
+impl PartialOrd for SpecVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SpecVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.major, self.minor, self.patch()).cmp(&(other.major, other.minor, other.patch()))
+    }
+}
+
+impl str::FromStr for SpecVersion {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self> {
+        // Only accept plain decimal numbers without leading zeros, as SemVer requires.
+        fn number(component: &str) -> Option<u32> {
+            if component.is_empty()
+                || (component.len() > 1 && component.starts_with('0'))
+                || !component.bytes().all(|b| b.is_ascii_digit())
+            {
+                return None;
+            }
+
+            component.parse().ok()
+        }
+
+        let components = string.split('.').map(number).collect::<Vec<_>>();
+
+        match components[..] {
+            [Some(major), Some(minor), Some(patch)] => Ok(SpecVersion::new(major, minor, patch)),
+            [Some(major), Some(minor)] => Ok(SpecVersion {
+                major,
+                minor,
+                patch: None,
+            }),
+            _ => Err(Error::Encoding(format!(
+                "Invalid spec version {:?}",
+                string
+            ))),
+        }
+    }
+}
+
+impl Display for SpecVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.patch {
+            Some(patch) => write!(f, "{}.{}.{}", self.major, self.minor, patch),
+            None => write!(f, "{}.{}", self.major, self.minor),
+        }
+    }
+}
+
 /// Top level trait used for role metadata.
 pub trait Metadata: Debug + PartialEq + Serialize + DeserializeOwned {
     /// The role associated with the metadata.
@@ -277,7 +453,12 @@ where
     /// **WARNING**: This does not verify signatures, so it exposes users to potential parser
     /// exploits.
     pub fn parse_untrusted(&self) -> Result<SignedMetadata<D, M>> {
-        D::from_slice(&self.bytes)
+        let (signatures, metadata) = D::decode_signed(&self.bytes)?;
+        Ok(SignedMetadata {
+            signatures,
+            metadata,
+            _marker: PhantomData,
+        })
     }
 }
 
@@ -287,6 +468,7 @@ where
 pub struct RawSignedMetadataSet<D> {
     root: Option<RawSignedMetadata<D, RootMetadata>>,
     targets: Option<RawSignedMetadata<D, TargetsMetadata>>,
+    delegated_targets: BTreeMap<MetadataPath, RawSignedMetadata<D, TargetsMetadata>>,
     snapshot: Option<RawSignedMetadata<D, SnapshotMetadata>>,
     timestamp: Option<RawSignedMetadata<D, TimestampMetadata>>,
 }
@@ -302,6 +484,13 @@ impl<D> RawSignedMetadataSet<D> {
         self.targets.as_ref()
     }
 
+    /// Returns the built delegated targets metadata, keyed by role.
+    pub fn delegated_targets(
+        &self,
+    ) -> &BTreeMap<MetadataPath, RawSignedMetadata<D, TargetsMetadata>> {
+        &self.delegated_targets
+    }
+
     /// Returns a reference to the built snapshot metadata, if any.
     pub fn snapshot(&self) -> Option<&RawSignedMetadata<D, SnapshotMetadata>> {
         self.snapshot.as_ref()
@@ -332,6 +521,7 @@ where
             metadata: RawSignedMetadataSet {
                 root: None,
                 targets: None,
+                delegated_targets: BTreeMap::new(),
                 snapshot: None,
                 timestamp: None,
             },
@@ -350,6 +540,19 @@ where
         self
     }
 
+    /// Set or replace the delegated targets metadata for `role`.
+    pub fn delegated_targets(
+        mut self,
+        role: MetadataPath,
+        delegated_targets: RawSignedMetadata<D, TargetsMetadata>,
+    ) -> Self {
+        let _ = self
+            .metadata
+            .delegated_targets
+            .insert(role, delegated_targets);
+        self
+    }
+
     /// Set or replace the snapshot metadata.
     pub fn snapshot(mut self, snapshot: RawSignedMetadata<D, SnapshotMetadata>) -> Self {
         self.metadata.snapshot = Some(snapshot);
@@ -404,15 +607,18 @@ where
         })
     }
 
-    /// Sign the metadata using the given `private_key`, replacing any existing signatures with the
+    /// Sign the metadata using the given `signer`, replacing any existing signatures with the
     /// same `KeyId`.
     ///
     /// **WARNING**: You should never have multiple TUF private keys on the same machine, so if
     /// you're using this to append several signatures at once, you are doing something wrong. The
     /// preferred method is to generate your copy of the metadata locally and use
     /// `SignedMetadata::merge_signatures` to perform the "append" operations.
-    pub fn sign(mut self, private_key: &dyn PrivateKey) -> Result<Self> {
-        let sig = private_key.sign(&self.metadata_bytes)?;
+    ///
+    /// Returns an error if the `signer` produces a signature for a different key than its public
+    /// key.
+    pub async fn sign(mut self, signer: &dyn Signer) -> Result<Self> {
+        let sig = sign_with(signer, &self.metadata_bytes).await?;
         let _ = self.signatures.insert(sig.key_id().clone(), sig);
         Ok(self)
     }
@@ -435,6 +641,21 @@ where
     }
 }
 
+/// Sign `msg` with `signer`, checking that the signature claims to be from the signer's key, since
+/// an external signer could otherwise attach a signature under any key ID.
+async fn sign_with(signer: &dyn Signer, msg: &[u8]) -> Result<Signature> {
+    let sig = signer.sign_async(msg).await?;
+    let key_id = signer.public_key().key_id();
+    if sig.key_id() != key_id {
+        return Err(Error::IllegalArgument(format!(
+            "Signer for key {:?} produced a signature for key {:?}",
+            key_id,
+            sig.key_id()
+        )));
+    }
+    Ok(sig)
+}
+
 /// Serialized metadata with attached unverified signatures.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SignedMetadata<D, M>
@@ -454,7 +675,8 @@ where
     M: Metadata,
 {
     /// Create a new `SignedMetadata`. The supplied private key is used to sign the canonicalized
-    /// bytes of the provided metadata with the provided scheme.
+    /// bytes of the provided metadata with the provided scheme. Use [SignedMetadataBuilder] to
+    /// sign with a [Signer].
     ///
     /// ```
     /// # use chrono::prelude::*;
@@ -486,18 +708,17 @@ where
     /// TUF metadata hashes are on the raw bytes of the metadata, so it is not guaranteed that the
     /// hash of the returned bytes will match a hash included in, for example, a snapshot metadata
     /// file, as:
-    /// * Parsing metadata removes unknown fields, which would not be included in the returned
-    /// bytes,
     /// * [Pouf] implementations only guarantee the bytes are canonical for the purpose of a
     /// signature. Metadata obtained from a remote source may have included different whitespace
     /// or ordered fields in a way that is not preserved when parsing that metadata.
     pub fn to_raw(&self) -> Result<RawSignedMetadata<D, M>> {
-        let bytes = D::canonicalize(&D::serialize(self)?)?;
+        let bytes = D::encode_signed(&self.signatures, &self.metadata)?;
         Ok(RawSignedMetadata::new(bytes))
     }
 
     /// Append a signature to this signed metadata. Will overwrite signature by keys with the same
-    /// ID.
+    /// ID. Returns an error if the `signer` produces a signature for a different key than its
+    /// public key.
     ///
     /// **WARNING**: You should never have multiple TUF private keys on the same machine, so if
     /// you're using this to append several signatures at once, you are doing something wrong. The
@@ -506,6 +727,7 @@ where
     ///
     /// ```
     /// # use chrono::prelude::*;
+    /// # use futures_executor::block_on;
     /// # use tuf::crypto::{Ed25519PrivateKey, PrivateKey, SignatureScheme, HashAlgorithm};
     /// # use tuf::pouf::Pouf1;
     /// # use tuf::metadata::{SignedMetadata, SnapshotMetadataBuilder};
@@ -521,22 +743,58 @@ where
     /// let snapshot = SnapshotMetadataBuilder::new().build().unwrap();
     /// let mut snapshot = SignedMetadata::<Pouf1, _>::new(&snapshot, &key_1).unwrap();
     ///
-    /// snapshot.add_signature(&key_2).unwrap();
+    /// block_on(snapshot.add_signature(&key_2)).unwrap();
     /// assert_eq!(snapshot.signatures().len(), 2);
     ///
-    /// snapshot.add_signature(&key_2).unwrap();
+    /// block_on(snapshot.add_signature(&key_2)).unwrap();
     /// assert_eq!(snapshot.signatures().len(), 2);
     /// ```
-    pub fn add_signature(&mut self, private_key: &dyn PrivateKey) -> Result<()> {
-        let bytes = D::canonicalize(&self.metadata)?;
-        let sig = private_key.sign(&bytes)?;
-        self.signatures
-            .retain(|s| s.key_id() != private_key.public().key_id());
-        self.signatures.push(sig);
-        self.signatures.sort();
+    pub async fn add_signature(&mut self, signer: &dyn Signer) -> Result<()> {
+        let sig = sign_with(signer, &self.payload()?).await?;
+        self.add_detached_signature(sig);
         Ok(())
     }
 
+    /// The canonical bytes of the metadata, which is what signatures are computed over. This can
+    /// be handed to a keyholder so they can produce a detached signature with
+    /// [PrivateKey::sign](crate::crypto::PrivateKey::sign) without needing the rest of the
+    /// repository.
+    pub fn payload(&self) -> Result<Vec<u8>> {
+        D::canonicalize(&self.metadata)
+    }
+
+    /// Append a detached signature over the [payload](Self::payload) of this metadata. Will
+    /// overwrite the signature by a key with the same ID.
+    ///
+    /// The signature is not checked here, since the key that produced it may not be known yet.
+    /// Invalid signatures do not count towards the threshold when the metadata is verified.
+    ///
+    /// ```
+    /// # use tuf::crypto::{Ed25519PrivateKey, PrivateKey};
+    /// # use tuf::pouf::Pouf1;
+    /// # use tuf::metadata::{SignedMetadataBuilder, SnapshotMetadataBuilder};
+    /// #
+    /// # let key: &[u8] = include_bytes!("../tests/ed25519/ed25519-1.pk8.der");
+    /// let key = Ed25519PrivateKey::from_pkcs8(&key).unwrap();
+    ///
+    /// let snapshot = SnapshotMetadataBuilder::new().build().unwrap();
+    /// let mut snapshot = SignedMetadataBuilder::<Pouf1, _>::from_metadata(&snapshot)
+    ///     .unwrap()
+    ///     .build();
+    /// assert!(snapshot.signatures().is_empty());
+    ///
+    /// // This would usually happen on the keyholder's machine.
+    /// let sig = key.sign(&snapshot.payload().unwrap()).unwrap();
+    ///
+    /// snapshot.add_detached_signature(sig);
+    /// assert_eq!(snapshot.signatures().len(), 1);
+    /// ```
+    pub fn add_detached_signature(&mut self, signature: Signature) {
+        self.signatures.retain(|s| s.key_id() != signature.key_id());
+        self.signatures.push(signature);
+        self.signatures.sort();
+    }
+
     /// Merge the singatures from `other` into `self` if and only if
     /// `self.as_ref() == other.as_ref()`. If `self` and `other` contain signatures from the same
     /// key ID, then the signatures from `self` will replace the signatures from `other`.
@@ -594,7 +852,9 @@ where
 }
 
 /// Helper to construct `RootMetadata`.
+#[derive(Clone)]
 pub struct RootMetadataBuilder {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     consistent_snapshot: bool,
@@ -607,6 +867,8 @@ pub struct RootMetadataBuilder {
     targets_key_ids: HashSet<KeyId>,
     timestamp_threshold: u32,
     timestamp_key_ids: HashSet<KeyId>,
+    role_additional_fields: HashMap<Role, HashMap<String, serde_json::Value>>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl RootMetadataBuilder {
@@ -618,6 +880,7 @@ impl RootMetadataBuilder {
     /// * role thresholds: 1
     pub fn new() -> Self {
         RootMetadataBuilder {
+            spec_version: SpecVersion::default(),
             version: 1,
             expires: Utc::now() + Duration::days(365),
             consistent_snapshot: true,
@@ -630,9 +893,17 @@ impl RootMetadataBuilder {
             targets_key_ids: HashSet::new(),
             timestamp_threshold: 1,
             timestamp_key_ids: HashSet::new(),
+            role_additional_fields: HashMap::new(),
+            additional_fields: HashMap::new(),
         }
     }
 
+    /// Set the version of the specification this metadata is written for.
+    pub fn spec_version(mut self, spec_version: SpecVersion) -> Self {
+        self.spec_version = spec_version;
+        self
+    }
+
     /// Set the version number for this metadata.
     pub fn version(mut self, version: u32) -> Self {
         self.version = version;
@@ -707,18 +978,52 @@ impl RootMetadataBuilder {
         self
     }
 
+    /// Add a field this library does not understand to the definition of `role`.
+    pub fn role_additional_field<N>(mut self, role: Role, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.role_additional_fields
+            .entry(role)
+            .or_default()
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct a new `RootMetadata`.
-    pub fn build(self) -> Result<RootMetadata> {
+    pub fn build(mut self) -> Result<RootMetadata> {
+        let mut role_additional_fields = |role| {
+            self.role_additional_fields
+                .remove(&role)
+                .unwrap_or_default()
+        };
+
         RootMetadata::new(
             self.version,
             self.expires,
             self.consistent_snapshot,
             self.keys,
-            RoleDefinition::new(self.root_threshold, self.root_key_ids)?,
-            RoleDefinition::new(self.snapshot_threshold, self.snapshot_key_ids)?,
-            RoleDefinition::new(self.targets_threshold, self.targets_key_ids)?,
-            RoleDefinition::new(self.timestamp_threshold, self.timestamp_key_ids)?,
-        )
+            RoleDefinition::new(self.root_threshold, self.root_key_ids)?
+                .with_additional_fields(role_additional_fields(Role::Root))?,
+            RoleDefinition::new(self.snapshot_threshold, self.snapshot_key_ids)?
+                .with_additional_fields(role_additional_fields(Role::Snapshot))?,
+            RoleDefinition::new(self.targets_threshold, self.targets_key_ids)?
+                .with_additional_fields(role_additional_fields(Role::Targets))?,
+            RoleDefinition::new(self.timestamp_threshold, self.timestamp_key_ids)?
+                .with_additional_fields(role_additional_fields(Role::Timestamp))?,
+        )?
+        .with_spec_version(self.spec_version)?
+        .with_additional_fields(self.additional_fields)
     }
 
     /// Construct a new `SignedMetadata<D, RootMetadata>`.
@@ -738,7 +1043,18 @@ impl Default for RootMetadataBuilder {
 
 impl From<RootMetadata> for RootMetadataBuilder {
     fn from(metadata: RootMetadata) -> Self {
+        let role_additional_fields = [
+            (Role::Root, metadata.root.additional_fields),
+            (Role::Snapshot, metadata.snapshot.additional_fields),
+            (Role::Targets, metadata.targets.additional_fields),
+            (Role::Timestamp, metadata.timestamp.additional_fields),
+        ]
+        .into_iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .collect();
+
         RootMetadataBuilder {
+            spec_version: metadata.spec_version,
             version: metadata.version,
             expires: metadata.expires,
             consistent_snapshot: metadata.consistent_snapshot,
@@ -751,6 +1067,8 @@ impl From<RootMetadata> for RootMetadataBuilder {
             targets_key_ids: metadata.targets.key_ids,
             timestamp_threshold: metadata.timestamp.threshold,
             timestamp_key_ids: metadata.timestamp.key_ids,
+            role_additional_fields,
+            additional_fields: metadata.additional_fields,
         }
     }
 }
@@ -758,6 +1076,7 @@ impl From<RootMetadata> for RootMetadataBuilder {
 /// Metadata for the root role.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RootMetadata {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     consistent_snapshot: bool,
@@ -766,6 +1085,7 @@ pub struct RootMetadata {
     snapshot: RoleDefinition<SnapshotMetadata>,
     targets: RoleDefinition<TargetsMetadata>,
     timestamp: RoleDefinition<TimestampMetadata>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl RootMetadata {
@@ -787,6 +1107,7 @@ impl RootMetadata {
         }
 
         Ok(RootMetadata {
+            spec_version: SpecVersion::default(),
             version,
             expires,
             consistent_snapshot,
@@ -795,6 +1116,7 @@ impl RootMetadata {
             snapshot,
             targets,
             timestamp,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -860,6 +1182,46 @@ impl RootMetadata {
     pub fn timestamp(&self) -> &RoleDefinition<TimestampMetadata> {
         &self.timestamp
     }
+
+    /// The version of the specification this metadata was written for.
+    pub fn spec_version(&self) -> SpecVersion {
+        self.spec_version
+    }
+
+    /// Set the version of the specification this metadata was written for. It is an error for
+    /// the version to have a major version this library doesn't support.
+    pub fn with_spec_version(mut self, spec_version: SpecVersion) -> Result<Self> {
+        self.spec_version = spec_version.check_supported()?;
+        Ok(self)
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &[
+                "_type",
+                "spec_version",
+                "version",
+                "expires",
+                "consistent_snapshot",
+                "keys",
+                "roles",
+            ],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Metadata for RootMetadata {
@@ -899,6 +1261,7 @@ impl<'de> Deserialize<'de> for RootMetadata {
 pub struct RoleDefinition<M: Metadata> {
     threshold: u32,
     key_ids: HashSet<KeyId>,
+    additional_fields: HashMap<String, serde_json::Value>,
     _metadata: PhantomData<M>,
 }
 
@@ -922,6 +1285,7 @@ impl<M: Metadata> RoleDefinition<M> {
         Ok(RoleDefinition {
             threshold,
             key_ids,
+            additional_fields: HashMap::new(),
             _metadata: PhantomData,
         })
     }
@@ -935,6 +1299,23 @@ impl<M: Metadata> RoleDefinition<M> {
     pub fn key_ids(&self) -> &HashSet<KeyId> {
         &self.key_ids
     }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(&additional_fields, &["threshold", "keyids"])?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl<M: Metadata> Serialize for RoleDefinition<M> {
@@ -1071,9 +1452,11 @@ impl<'de> Deserialize<'de> for MetadataPath {
 
 /// Helper to construct `TimestampMetadata`.
 pub struct TimestampMetadataBuilder {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     snapshot: MetadataDescription<SnapshotMetadata>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl TimestampMetadataBuilder {
@@ -1105,12 +1488,20 @@ impl TimestampMetadataBuilder {
     /// * expires: 1 day from the current time.
     pub fn from_metadata_description(description: MetadataDescription<SnapshotMetadata>) -> Self {
         TimestampMetadataBuilder {
+            spec_version: SpecVersion::default(),
             version: 1,
             expires: Utc::now() + Duration::days(1),
             snapshot: description,
+            additional_fields: HashMap::new(),
         }
     }
 
+    /// Set the version of the specification this metadata is written for.
+    pub fn spec_version(mut self, spec_version: SpecVersion) -> Self {
+        self.spec_version = spec_version;
+        self
+    }
+
     /// Set the version number for this metadata.
     pub fn version(mut self, version: u32) -> Self {
         self.version = version;
@@ -1123,9 +1514,21 @@ impl TimestampMetadataBuilder {
         self
     }
 
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct a new `TimestampMetadata`.
     pub fn build(self) -> Result<TimestampMetadata> {
-        TimestampMetadata::new(self.version, self.expires, self.snapshot)
+        TimestampMetadata::new(self.version, self.expires, self.snapshot)?
+            .with_spec_version(self.spec_version)?
+            .with_additional_fields(self.additional_fields)
     }
 
     /// Construct a new `SignedMetadata<D, TimestampMetadata>`.
@@ -1143,9 +1546,11 @@ impl TimestampMetadataBuilder {
 /// Metadata for the timestamp role.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TimestampMetadata {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     snapshot: MetadataDescription<SnapshotMetadata>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl TimestampMetadata {
@@ -1162,9 +1567,11 @@ impl TimestampMetadata {
         }
 
         Ok(TimestampMetadata {
+            spec_version: SpecVersion::default(),
             version,
             expires,
             snapshot,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -1172,6 +1579,38 @@ impl TimestampMetadata {
     pub fn snapshot(&self) -> &MetadataDescription<SnapshotMetadata> {
         &self.snapshot
     }
+
+    /// The version of the specification this metadata was written for.
+    pub fn spec_version(&self) -> SpecVersion {
+        self.spec_version
+    }
+
+    /// Set the version of the specification this metadata was written for. It is an error for
+    /// the version to have a major version this library doesn't support.
+    pub fn with_spec_version(mut self, spec_version: SpecVersion) -> Result<Self> {
+        self.spec_version = spec_version.check_supported()?;
+        Ok(self)
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &["_type", "spec_version", "version", "expires", "meta"],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Metadata for TimestampMetadata {
@@ -1212,6 +1651,7 @@ pub struct MetadataDescription<M: Metadata> {
     version: u32,
     length: Option<usize>,
     hashes: HashMap<HashAlgorithm, HashValue>,
+    additional_fields: HashMap<String, serde_json::Value>,
     _metadata: PhantomData<M>,
 }
 
@@ -1234,6 +1674,7 @@ impl<M: Metadata> MetadataDescription<M> {
             version,
             length: Some(buf.len()),
             hashes,
+            additional_fields: HashMap::new(),
             _metadata: PhantomData,
         })
     }
@@ -1252,6 +1693,7 @@ impl<M: Metadata> MetadataDescription<M> {
             version,
             length,
             hashes,
+            additional_fields: HashMap::new(),
             _metadata: PhantomData,
         })
     }
@@ -1270,6 +1712,23 @@ impl<M: Metadata> MetadataDescription<M> {
     pub fn hashes(&self) -> &HashMap<HashAlgorithm, HashValue> {
         &self.hashes
     }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(&additional_fields, &["version", "length", "hashes"])?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl<M: Metadata> Serialize for MetadataDescription<M> {
@@ -1292,9 +1751,11 @@ impl<'de, M: Metadata> Deserialize<'de> for MetadataDescription<M> {
 
 /// Helper to construct `SnapshotMetadata`.
 pub struct SnapshotMetadataBuilder {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     meta: HashMap<MetadataPath, MetadataDescription<TargetsMetadata>>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl SnapshotMetadataBuilder {
@@ -1304,9 +1765,11 @@ impl SnapshotMetadataBuilder {
     /// * expires: 7 days from the current time.
     pub fn new() -> Self {
         SnapshotMetadataBuilder {
+            spec_version: SpecVersion::default(),
             version: 1,
             expires: Utc::now() + Duration::days(7),
             meta: HashMap::new(),
+            additional_fields: HashMap::new(),
         }
     }
 
@@ -1324,6 +1787,12 @@ impl SnapshotMetadataBuilder {
         SnapshotMetadataBuilder::new().insert_metadata(targets, hash_algs)
     }
 
+    /// Set the version of the specification this metadata is written for.
+    pub fn spec_version(mut self, spec_version: SpecVersion) -> Self {
+        self.spec_version = spec_version;
+        self
+    }
+
     /// Set the version number for this metadata.
     pub fn version(mut self, version: u32) -> Self {
         self.version = version;
@@ -1381,9 +1850,21 @@ impl SnapshotMetadataBuilder {
         self
     }
 
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct a new `SnapshotMetadata`.
     pub fn build(self) -> Result<SnapshotMetadata> {
-        SnapshotMetadata::new(self.version, self.expires, self.meta)
+        SnapshotMetadata::new(self.version, self.expires, self.meta)?
+            .with_spec_version(self.spec_version)?
+            .with_additional_fields(self.additional_fields)
     }
 
     /// Construct a new `SignedMetadata<D, SnapshotMetadata>`.
@@ -1407,9 +1888,11 @@ impl Default for SnapshotMetadataBuilder {
 impl From<SnapshotMetadata> for SnapshotMetadataBuilder {
     fn from(meta: SnapshotMetadata) -> Self {
         SnapshotMetadataBuilder {
+            spec_version: meta.spec_version,
             version: meta.version,
             expires: meta.expires,
             meta: meta.meta,
+            additional_fields: meta.additional_fields,
         }
     }
 }
@@ -1417,9 +1900,11 @@ impl From<SnapshotMetadata> for SnapshotMetadataBuilder {
 /// Metadata for the snapshot role.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SnapshotMetadata {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     meta: HashMap<MetadataPath, MetadataDescription<TargetsMetadata>>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl SnapshotMetadata {
@@ -1436,9 +1921,11 @@ impl SnapshotMetadata {
         }
 
         Ok(SnapshotMetadata {
+            spec_version: SpecVersion::default(),
             version,
             expires,
             meta,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -1446,6 +1933,38 @@ impl SnapshotMetadata {
     pub fn meta(&self) -> &HashMap<MetadataPath, MetadataDescription<TargetsMetadata>> {
         &self.meta
     }
+
+    /// The version of the specification this metadata was written for.
+    pub fn spec_version(&self) -> SpecVersion {
+        self.spec_version
+    }
+
+    /// Set the version of the specification this metadata was written for. It is an error for
+    /// the version to have a major version this library doesn't support.
+    pub fn with_spec_version(mut self, spec_version: SpecVersion) -> Result<Self> {
+        self.spec_version = spec_version.check_supported()?;
+        Ok(self)
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &["_type", "spec_version", "version", "expires", "meta"],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Metadata for SnapshotMetadata {
@@ -1542,34 +2061,31 @@ impl TargetPath {
         self.0.starts_with(&parent.0)
     }
 
-    /// Whether or not the current target is available at the end of the given chain of target
-    /// paths. For the chain to be valid, each target path in a group must be a child of of all
-    /// previous groups.
-    // TODO this is hideous and uses way too much clone/heap but I think recursively,
-    // so here we are
-    pub fn matches_chain(&self, parents: &[HashSet<TargetPath>]) -> bool {
+    /// Whether or not the current target is available at the end of the given chain of path
+    /// patterns. For the chain to be valid, the target must match at least one pattern in every
+    /// group, since each delegation along the way must have been trusted with the target.
+    ///
+    /// ```
+    /// # use std::collections::HashSet;
+    /// # use tuf::metadata::{PathPattern, TargetPath};
+    /// let target = TargetPath::new("releases/foo.tgz").unwrap();
+    /// let chain = vec![
+    ///     vec![PathPattern::new("releases/").unwrap()].into_iter().collect::<HashSet<_>>(),
+    ///     vec![PathPattern::new("releases/*.tgz").unwrap()].into_iter().collect(),
+    /// ];
+    /// assert!(target.matches_chain(&chain));
+    ///
+    /// let target = TargetPath::new("releases/foo.zip").unwrap();
+    /// assert!(!target.matches_chain(&chain));
+    /// ```
+    pub fn matches_chain(&self, parents: &[HashSet<PathPattern>]) -> bool {
         if parents.is_empty() {
             return false;
         }
-        if parents.len() == 1 {
-            return parents[0].iter().any(|p| p == self || self.is_child(p));
-        }
 
-        let new = parents[1..]
+        parents
             .iter()
-            .map(|group| {
-                group
-                    .iter()
-                    .filter(|parent| {
-                        parents[0]
-                            .iter()
-                            .any(|p| parent.is_child(p) || parent == &p)
-                    })
-                    .cloned()
-                    .collect::<HashSet<_>>()
-            })
-            .collect::<Vec<_>>();
-        self.matches_chain(&new)
+            .all(|group| group.iter().any(|pattern| pattern.matches(self)))
     }
 
     /// Prefix the target path with a hash value to support TUF spec 5.5.2.
@@ -1589,6 +2105,16 @@ impl TargetPath {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// The SHA-256 digest of the path, which hashed bin delegations are keyed on.
+    fn sha256(&self) -> digest::Digest {
+        digest::digest(&digest::SHA256, self.0.as_bytes())
+    }
+
+    /// The lowercase hex SHA-256 digest of the path.
+    pub(crate) fn hex_sha256(&self) -> String {
+        HEXLOWER.encode(self.sha256().as_ref())
+    }
 }
 
 impl Display for TargetPath {
@@ -1610,68 +2136,337 @@ impl Borrow<str> for TargetPath {
     }
 }
 
-/// Description of a target, used in verification.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TargetDescription {
-    length: u64,
-    hashes: HashMap<HashAlgorithm, HashValue>,
-    custom: HashMap<String, serde_json::Value>,
-}
+/// A shell-style wildcard pattern that delegations use to describe the targets they are trusted
+/// with.
+///
+/// Per the TUF specification, a pattern is split into `/`-separated components and matches a
+/// target only if the target has the same number of components and each pair of components
+/// match. Within a component:
+///
+/// * `*` matches any sequence of characters, and so never crosses a `/`.
+/// * `?` matches any single character.
+/// * `[seq]` matches any character in `seq`, which may contain ranges such as `a-z`.
+/// * `[!seq]` matches any character not in `seq`.
+///
+/// For compatibility with metadata that delegates whole directories, a pattern ending in `/`
+/// matches every target beneath that directory.
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize)]
+pub struct PathPattern(String);
 
-impl TargetDescription {
-    /// Create a new `TargetDescription`.
+impl PathPattern {
+    /// Create a new `PathPattern` from a `String`.
     ///
-    /// Note: Creating this manually could lead to errors, and the `from_reader` method is
-    /// preferred.
-    pub fn new(
-        length: u64,
-        hashes: HashMap<HashAlgorithm, HashValue>,
-        custom: HashMap<String, serde_json::Value>,
-    ) -> Result<Self> {
-        if hashes.is_empty() {
-            return Err(Error::IllegalArgument(
-                "Cannot have empty set of hashes".into(),
-            ));
-        }
-
-        Ok(TargetDescription {
-            length,
-            hashes,
-            custom,
-        })
+    /// ```
+    /// # use tuf::metadata::PathPattern;
+    /// assert!(PathPattern::new("foo").is_ok());
+    /// assert!(PathPattern::new("foo/*.tgz").is_ok());
+    /// assert!(PathPattern::new("foo-?.tgz").is_ok());
+    /// assert!(PathPattern::new("/foo").is_err());
+    /// assert!(PathPattern::new("foo/../bar").is_err());
+    /// ```
+    pub fn new<P: Into<String>>(pattern: P) -> Result<Self> {
+        let pattern = pattern.into();
+        safe_path_pattern(&pattern)?;
+        Ok(PathPattern(pattern))
     }
 
-    /// Read the from the given slice and calculate the length and hash values.
+    /// Whether or not `target` matches this pattern.
     ///
     /// ```
-    /// # use data_encoding::BASE64URL;
-    /// # use tuf::crypto::{HashAlgorithm,HashValue};
-    /// # use tuf::metadata::TargetDescription;
-    /// #
-    /// let bytes: &[u8] = b"it was a pleasure to burn";
-    ///
-    /// let target_description = TargetDescription::from_slice(
-    ///     bytes,
-    ///     &[HashAlgorithm::Sha256, HashAlgorithm::Sha512],
-    /// ).unwrap();
-    ///
-    /// let s = "Rd9zlbzrdWfeL7gnIEi05X-Yv2TCpy4qqZM1N72ZWQs=";
-    /// let sha256 = HashValue::new(BASE64URL.decode(s.as_bytes()).unwrap());
+    /// # use tuf::metadata::{PathPattern, TargetPath};
+    /// let pattern = PathPattern::new("targets/*.tgz").unwrap();
+    /// assert!(pattern.matches(&TargetPath::new("targets/foo.tgz").unwrap()));
+    /// assert!(!pattern.matches(&TargetPath::new("targets/foo.txt").unwrap()));
+    /// assert!(!pattern.matches(&TargetPath::new("targets/foo/bar.tgz").unwrap()));
     ///
-    /// let s ="tuIxwKybYdvJpWuUj6dubvpwhkAozWB6hMJIRzqn2jOUdtDTBg381brV4K\
-    ///     BU1zKP8GShoJuXEtCf5NkDTCEJgQ==";
-    /// let sha512 = HashValue::new(BASE64URL.decode(s.as_bytes()).unwrap());
+    /// let pattern = PathPattern::new("foo-version-?.tgz").unwrap();
+    /// assert!(pattern.matches(&TargetPath::new("foo-version-2.tgz").unwrap()));
+    /// assert!(!pattern.matches(&TargetPath::new("foo-version-alpha.tgz").unwrap()));
     ///
-    /// assert_eq!(target_description.length(), bytes.len() as u64);
-    /// assert_eq!(target_description.hashes().get(&HashAlgorithm::Sha256), Some(&sha256));
-    /// assert_eq!(target_description.hashes().get(&HashAlgorithm::Sha512), Some(&sha512));
+    /// let pattern = PathPattern::new("foo/").unwrap();
+    /// assert!(pattern.matches(&TargetPath::new("foo/bar/baz").unwrap()));
+    /// assert!(!pattern.matches(&TargetPath::new("foo").unwrap()));
     /// ```
-    pub fn from_slice(buf: &[u8], hash_algs: &[HashAlgorithm]) -> Result<Self> {
-        Self::from_slice_with_custom(buf, hash_algs, HashMap::new())
-    }
+    pub fn matches(&self, target: &TargetPath) -> bool {
+        let target_components = target.as_str().split('/').collect::<Vec<_>>();
 
-    /// Read the from the given reader and custom metadata and calculate the length and hash
-    /// values.
+        let (pattern, is_dir) = match self.0.strip_suffix('/') {
+            Some(pattern) => (pattern, true),
+            None => (self.0.as_str(), false),
+        };
+        let pattern_components = pattern.split('/').collect::<Vec<_>>();
+
+        if is_dir {
+            if target_components.len() <= pattern_components.len() {
+                return false;
+            }
+        } else if target_components.len() != pattern_components.len() {
+            return false;
+        }
+
+        pattern_components
+            .iter()
+            .zip(target_components.iter())
+            .all(|(pattern, component)| {
+                let pattern = pattern.chars().collect::<Vec<_>>();
+                let component = component.chars().collect::<Vec<_>>();
+                fnmatch(&pattern, &component)
+            })
+    }
+
+    /// The string value of the pattern.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathPattern {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+        let s: String = Deserialize::deserialize(de)?;
+        PathPattern::new(s).map_err(|e| DeserializeError::custom(format!("{:?}", e)))
+    }
+}
+
+/// Match a single path component against a single pattern component, following the rules of
+/// python's `fnmatch.fnmatchcase`.
+fn fnmatch(pattern: &[char], name: &[char]) -> bool {
+    let mut p = 0;
+    let mut n = 0;
+
+    // The pattern and name positions just after the last `*` we saw, so we can retry with the
+    // `*` consuming one more character if the rest of the pattern fails to match.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    p += 1;
+                    backtrack = Some((p, n));
+                    continue;
+                }
+                '?' => {
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+                '[' => match fnmatch_class(&pattern[p..], name[n]) {
+                    Some((true, len)) => {
+                        p += len;
+                        n += 1;
+                        continue;
+                    }
+                    Some((false, _)) => {}
+                    // An unterminated `[` is treated as a literal.
+                    None if name[n] == '[' => {
+                        p += 1;
+                        n += 1;
+                        continue;
+                    }
+                    None => {}
+                },
+                c if c == name[n] => {
+                    p += 1;
+                    n += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        match backtrack {
+            Some((star_p, star_n)) => {
+                p = star_p;
+                n = star_n + 1;
+                backtrack = Some((star_p, n));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Match `c` against the `[seq]` class at the start of `class`. Returns whether or not the
+/// character matched along with the length of the class, or `None` if the class is unterminated.
+fn fnmatch_class(class: &[char], c: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+
+    let negate = class.get(i) == Some(&'!');
+    if negate {
+        i += 1;
+    }
+
+    // A `]` immediately after the opening `[` or `[!` is part of the sequence.
+    let start = i;
+    let mut matched = false;
+
+    while i < class.len() && (class[i] != ']' || i == start) {
+        match (class.get(i + 1), class.get(i + 2)) {
+            (Some('-'), Some(hi)) if *hi != ']' => {
+                if class[i] <= c && c <= *hi {
+                    matched = true;
+                }
+                i += 3;
+            }
+            _ => {
+                if class[i] == c {
+                    matched = true;
+                }
+                i += 1;
+            }
+        }
+    }
+
+    if i >= class.len() {
+        return None;
+    }
+
+    Some((matched != negate, i + 1))
+}
+
+/// A lowercase hex prefix of the SHA-256 digest of a target path, used by delegations that
+/// distribute targets across hashed bins rather than by path.
+///
+/// ```
+/// # use tuf::metadata::{PathHashPrefix, TargetPath};
+/// // sha256("foo") = 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+/// let target = TargetPath::new("foo").unwrap();
+/// assert!(PathHashPrefix::new("2c").unwrap().matches(&target));
+/// assert!(PathHashPrefix::new("2c26b").unwrap().matches(&target));
+/// assert!(!PathHashPrefix::new("2d").unwrap().matches(&target));
+///
+/// assert!(PathHashPrefix::new("").is_err());
+/// assert!(PathHashPrefix::new("2C").is_err());
+/// assert!(PathHashPrefix::new("xyz").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Hash, Eq, PartialOrd, Ord, Serialize)]
+pub struct PathHashPrefix(String);
+
+impl PathHashPrefix {
+    /// Create a new `PathHashPrefix` from a lowercase hex `String`.
+    pub fn new<P: Into<String>>(prefix: P) -> Result<Self> {
+        let prefix = prefix.into();
+
+        if prefix.is_empty() {
+            return Err(Error::IllegalArgument(
+                "Path hash prefix cannot be empty".into(),
+            ));
+        }
+
+        if prefix.len() > 64 {
+            return Err(Error::IllegalArgument(
+                "Path hash prefix cannot be longer than a SHA-256 digest".into(),
+            ));
+        }
+
+        if !prefix
+            .bytes()
+            .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        {
+            return Err(Error::IllegalArgument(format!(
+                "Path hash prefix must be lowercase hex: {:?}",
+                prefix
+            )));
+        }
+
+        Ok(PathHashPrefix(prefix))
+    }
+
+    /// Whether or not the hex SHA-256 digest of `target` starts with this prefix.
+    pub fn matches(&self, target: &TargetPath) -> bool {
+        target.hex_sha256().starts_with(&self.0)
+    }
+
+    /// The string value of the prefix.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for PathHashPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PathHashPrefix {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+        let s: String = Deserialize::deserialize(de)?;
+        PathHashPrefix::new(s).map_err(|e| DeserializeError::custom(format!("{:?}", e)))
+    }
+}
+
+/// Description of a target, used in verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetDescription {
+    length: u64,
+    hashes: HashMap<HashAlgorithm, HashValue>,
+    custom: HashMap<String, serde_json::Value>,
+    additional_fields: HashMap<String, serde_json::Value>,
+}
+
+impl TargetDescription {
+    /// Create a new `TargetDescription`.
+    ///
+    /// Note: Creating this manually could lead to errors, and the `from_reader` method is
+    /// preferred.
+    pub fn new(
+        length: u64,
+        hashes: HashMap<HashAlgorithm, HashValue>,
+        custom: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        if hashes.is_empty() {
+            return Err(Error::IllegalArgument(
+                "Cannot have empty set of hashes".into(),
+            ));
+        }
+
+        Ok(TargetDescription {
+            length,
+            hashes,
+            custom,
+            additional_fields: HashMap::new(),
+        })
+    }
+
+    /// Read the from the given slice and calculate the length and hash values.
+    ///
+    /// ```
+    /// # use data_encoding::BASE64URL;
+    /// # use tuf::crypto::{HashAlgorithm,HashValue};
+    /// # use tuf::metadata::TargetDescription;
+    /// #
+    /// let bytes: &[u8] = b"it was a pleasure to burn";
+    ///
+    /// let target_description = TargetDescription::from_slice(
+    ///     bytes,
+    ///     &[HashAlgorithm::Sha256, HashAlgorithm::Sha512],
+    /// ).unwrap();
+    ///
+    /// let s = "Rd9zlbzrdWfeL7gnIEi05X-Yv2TCpy4qqZM1N72ZWQs=";
+    /// let sha256 = HashValue::new(BASE64URL.decode(s.as_bytes()).unwrap());
+    ///
+    /// let s ="tuIxwKybYdvJpWuUj6dubvpwhkAozWB6hMJIRzqn2jOUdtDTBg381brV4K\
+    ///     BU1zKP8GShoJuXEtCf5NkDTCEJgQ==";
+    /// let sha512 = HashValue::new(BASE64URL.decode(s.as_bytes()).unwrap());
+    ///
+    /// assert_eq!(target_description.length(), bytes.len() as u64);
+    /// assert_eq!(target_description.hashes().get(&HashAlgorithm::Sha256), Some(&sha256));
+    /// assert_eq!(target_description.hashes().get(&HashAlgorithm::Sha512), Some(&sha512));
+    /// ```
+    pub fn from_slice(buf: &[u8], hash_algs: &[HashAlgorithm]) -> Result<Self> {
+        Self::from_slice_with_custom(buf, hash_algs, HashMap::new())
+    }
+
+    /// Read the from the given reader and custom metadata and calculate the length and hash
+    /// values.
     ///
     /// ```
     /// # use data_encoding::BASE64URL;
@@ -1713,6 +2508,7 @@ impl TargetDescription {
             length: buf.len() as u64,
             hashes,
             custom,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -1800,6 +2596,7 @@ impl TargetDescription {
             length,
             hashes,
             custom,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -1817,6 +2614,23 @@ impl TargetDescription {
     pub fn custom(&self) -> &HashMap<String, serde_json::Value> {
         &self.custom
     }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(&additional_fields, &["length", "hashes", "custom"])?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Serialize for TargetDescription {
@@ -1840,10 +2654,12 @@ impl<'de> Deserialize<'de> for TargetDescription {
 /// Metadata for the targets role.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TargetsMetadata {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     targets: HashMap<TargetPath, TargetDescription>,
     delegations: Delegations,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl TargetsMetadata {
@@ -1861,10 +2677,12 @@ impl TargetsMetadata {
         }
 
         Ok(TargetsMetadata {
+            spec_version: SpecVersion::default(),
             version,
             expires,
             targets,
             delegations,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -1877,6 +2695,45 @@ impl TargetsMetadata {
     pub fn delegations(&self) -> &Delegations {
         &self.delegations
     }
+
+    /// The version of the specification this metadata was written for.
+    pub fn spec_version(&self) -> SpecVersion {
+        self.spec_version
+    }
+
+    /// Set the version of the specification this metadata was written for. It is an error for
+    /// the version to have a major version this library doesn't support.
+    pub fn with_spec_version(mut self, spec_version: SpecVersion) -> Result<Self> {
+        self.spec_version = spec_version.check_supported()?;
+        Ok(self)
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &[
+                "_type",
+                "spec_version",
+                "version",
+                "expires",
+                "targets",
+                "delegations",
+            ],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Metadata for TargetsMetadata {
@@ -1913,10 +2770,12 @@ impl<'de> Deserialize<'de> for TargetsMetadata {
 
 /// Helper to construct `TargetsMetadata`.
 pub struct TargetsMetadataBuilder {
+    spec_version: SpecVersion,
     version: u32,
     expires: DateTime<Utc>,
     targets: HashMap<TargetPath, TargetDescription>,
     delegations: Option<Delegations>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl TargetsMetadataBuilder {
@@ -1926,13 +2785,21 @@ impl TargetsMetadataBuilder {
     /// * expires: 90 days from the current time.
     pub fn new() -> Self {
         TargetsMetadataBuilder {
+            spec_version: SpecVersion::default(),
             version: 1,
             expires: Utc::now() + Duration::days(90),
             targets: HashMap::new(),
             delegations: None,
+            additional_fields: HashMap::new(),
         }
     }
 
+    /// Set the version of the specification this metadata is written for.
+    pub fn spec_version(mut self, spec_version: SpecVersion) -> Self {
+        self.spec_version = spec_version;
+        self
+    }
+
     /// Set the version number for this metadata.
     pub fn version(mut self, version: u32) -> Self {
         self.version = version;
@@ -1980,12 +2847,28 @@ impl TargetsMetadataBuilder {
         self
     }
 
+    /// Remove the target at `path` from this target metadata, if it exists.
+    pub fn remove_target(mut self, path: &TargetPath) -> Self {
+        self.targets.remove(path);
+        self
+    }
+
     /// Add `Delegations` to this target metadata.
     pub fn delegations(mut self, delegations: Delegations) -> Self {
         self.delegations = Some(delegations);
         self
     }
 
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct a new `TargetsMetadata`.
     pub fn build(self) -> Result<TargetsMetadata> {
         TargetsMetadata::new(
@@ -1993,7 +2876,9 @@ impl TargetsMetadataBuilder {
             self.expires,
             self.targets,
             self.delegations.unwrap_or_default(),
-        )
+        )?
+        .with_spec_version(self.spec_version)?
+        .with_additional_fields(self.additional_fields)
     }
 
     /// Construct a new `SignedMetadata<D, TargetsMetadata>`.
@@ -2015,10 +2900,15 @@ impl Default for TargetsMetadataBuilder {
 }
 
 /// Wrapper to described a collections of delegations.
+///
+/// Delegations either list their roles explicitly, or describe a set of hashed bins with
+/// [SuccinctRoles], but not both.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Delegations {
     keys: HashMap<KeyId, PublicKey>,
     roles: Vec<Delegation>,
+    succinct_roles: Option<SuccinctRoles>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl Delegations {
@@ -2043,12 +2933,51 @@ impl Delegations {
             ));
         }
 
-        Ok(Delegations { keys, roles })
+        Ok(Delegations {
+            keys,
+            roles,
+            succinct_roles: None,
+            additional_fields: HashMap::new(),
+        })
+    }
+
+    /// Create a new `Delegations` wrapper from the given set of trusted keys and the hashed bins
+    /// described by `succinct_roles`. Every key ID the bins trust must be one of `keys`.
+    pub fn new_with_succinct_roles(
+        keys: HashMap<KeyId, PublicKey>,
+        succinct_roles: SuccinctRoles,
+    ) -> Result<Self> {
+        if let Some(key_id) = succinct_roles
+            .key_ids()
+            .iter()
+            .find(|key_id| !keys.contains_key(key_id))
+        {
+            return Err(Error::IllegalArgument(format!(
+                "Succinct roles key ID {:?} is not one of the delegated keys",
+                key_id
+            )));
+        }
+
+        let threshold = succinct_roles.threshold();
+        if threshold < 1 || (succinct_roles.key_ids().len() as u64) < u64::from(threshold) {
+            return Err(Error::IllegalArgument(format!(
+                "Succinct roles threshold must be between 1 and {}, not {}",
+                succinct_roles.key_ids().len(),
+                threshold
+            )));
+        }
+
+        Ok(Delegations {
+            keys,
+            roles: vec![],
+            succinct_roles: Some(succinct_roles),
+            additional_fields: HashMap::new(),
+        })
     }
 
     /// Return if this delegation is empty.
     pub fn is_empty(&self) -> bool {
-        self.keys.is_empty() && self.roles.is_empty()
+        self.keys.is_empty() && self.roles.is_empty() && self.succinct_roles.is_none()
     }
 
     /// An immutable reference to the keys used for this set of delegations.
@@ -2060,6 +2989,39 @@ impl Delegations {
     pub fn roles(&self) -> &Vec<Delegation> {
         &self.roles
     }
+
+    /// An immutable reference to the succinct hashed bin roles, if any.
+    pub fn succinct_roles(&self) -> Option<&SuccinctRoles> {
+        self.succinct_roles.as_ref()
+    }
+
+    /// Whether these delegations delegate to `role`, either by name or as one of the succinct
+    /// hashed bins.
+    pub fn delegates_to(&self, role: &MetadataPath) -> bool {
+        self.roles.iter().any(|d| d.name() == role)
+            || self
+                .succinct_roles
+                .as_ref()
+                .map(|succinct_roles| succinct_roles.is_delegated_role(role))
+                .unwrap_or(false)
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(&additional_fields, &["keys", "roles", "succinct_roles"])?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
 }
 
 impl Serialize for Delegations {
@@ -2080,12 +3042,206 @@ impl<'de> Deserialize<'de> for Delegations {
     }
 }
 
+/// A set of hashed bin delegations that share the same keys and threshold, as described in
+/// [TAP 15].
+///
+/// Targets are distributed across `2^bit_length` bins by the leading `bit_length` bits of the
+/// SHA-256 digest of their path. The bins are named `{name_prefix}-{bin}`, where `bin` is the
+/// zero-padded lowercase hex bin number. Succinct roles are never terminating.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// # use tuf::crypto::{Ed25519PrivateKey, PrivateKey};
+/// # use tuf::metadata::{MetadataPath, SuccinctRoles, TargetPath};
+/// # let key = Ed25519PrivateKey::from_pkcs8(&Ed25519PrivateKey::pkcs8().unwrap()).unwrap();
+/// let key_ids = vec![key.public().key_id().clone()].into_iter().collect::<HashSet<_>>();
+/// let succinct_roles = SuccinctRoles::new(key_ids, 1, 8, "bin".into()).unwrap();
+/// assert_eq!(succinct_roles.number_of_bins(), 256);
+///
+/// // sha256("foo") = 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+/// let role = succinct_roles.role_for_target(&TargetPath::new("foo").unwrap());
+/// assert_eq!(role, MetadataPath::new("bin-2c").unwrap());
+/// assert!(succinct_roles.is_delegated_role(&role));
+/// assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin-2c0").unwrap()));
+/// ```
+///
+/// [TAP 15]: https://github.com/theupdateframework/taps/blob/master/tap15.md
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuccinctRoles {
+    key_ids: HashSet<KeyId>,
+    threshold: u32,
+    bit_length: u8,
+    name_prefix: String,
+    additional_fields: HashMap<String, serde_json::Value>,
+}
+
+impl SuccinctRoles {
+    /// Create a new set of succinct hashed bin roles. `bit_length` must be between 1 and 32.
+    pub fn new(
+        key_ids: HashSet<KeyId>,
+        threshold: u32,
+        bit_length: u8,
+        name_prefix: String,
+    ) -> Result<Self> {
+        if key_ids.is_empty() {
+            return Err(Error::IllegalArgument("Cannot have empty key IDs".into()));
+        }
+
+        if threshold < 1 {
+            return Err(Error::IllegalArgument("Cannot have threshold < 1".into()));
+        }
+
+        if (key_ids.len() as u64) < u64::from(threshold) {
+            return Err(Error::IllegalArgument(
+                "Cannot have threshold less than number of keys".into(),
+            ));
+        }
+
+        if !(1..=32).contains(&bit_length) {
+            return Err(Error::IllegalArgument(format!(
+                "Succinct roles bit length must be between 1 and 32, not {}",
+                bit_length
+            )));
+        }
+
+        // Every role name shares the prefix, so checking one of them checks them all.
+        safe_path(&format!("{}-0", name_prefix))?;
+
+        Ok(SuccinctRoles {
+            key_ids,
+            threshold,
+            bit_length,
+            name_prefix,
+            additional_fields: HashMap::new(),
+        })
+    }
+
+    /// An immutable reference to the key IDs trusted to sign every bin.
+    pub fn key_ids(&self) -> &HashSet<KeyId> {
+        &self.key_ids
+    }
+
+    /// The threshold of signatures every bin requires.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// The number of leading bits of the target path hash used to pick a bin.
+    pub fn bit_length(&self) -> u8 {
+        self.bit_length
+    }
+
+    /// The prefix of every bin's role name.
+    pub fn name_prefix(&self) -> &str {
+        &self.name_prefix
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &["keyids", "threshold", "bit_length", "name_prefix"],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
+
+    /// The number of bins, `2^bit_length`.
+    pub fn number_of_bins(&self) -> u64 {
+        1 << self.bit_length
+    }
+
+    /// The number of hex digits in a bin's role name suffix.
+    fn suffix_len(&self) -> usize {
+        (usize::from(self.bit_length) + 3) / 4
+    }
+
+    fn role_name(&self, bin: u64) -> MetadataPath {
+        MetadataPath(Cow::Owned(format!(
+            "{}-{:0width$x}",
+            self.name_prefix,
+            bin,
+            width = self.suffix_len()
+        )))
+    }
+
+    /// The role names of every bin, in order.
+    pub fn role_names(&self) -> impl Iterator<Item = MetadataPath> + '_ {
+        (0..self.number_of_bins()).map(move |bin| self.role_name(bin))
+    }
+
+    /// The role name of the bin that is trusted with `target`.
+    pub fn role_for_target(&self, target: &TargetPath) -> MetadataPath {
+        let digest = target.sha256();
+        let hash = digest.as_ref();
+        let prefix = u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]);
+        let bin = u64::from(prefix) >> (32 - self.bit_length);
+
+        self.role_name(bin)
+    }
+
+    /// Whether or not `role` names one of these bins.
+    pub fn is_delegated_role(&self, role: &MetadataPath) -> bool {
+        let suffix = match role
+            .0
+            .strip_prefix(self.name_prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('-'))
+        {
+            Some(suffix) => suffix,
+            None => return false,
+        };
+
+        if suffix.len() != self.suffix_len()
+            || !suffix
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        {
+            return false;
+        }
+
+        match u64::from_str_radix(suffix, 16) {
+            Ok(bin) => bin < self.number_of_bins(),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Serialize for SuccinctRoles {
+    fn serialize<S>(&self, ser: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        shims::SuccinctRoles::from(self).serialize(ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for SuccinctRoles {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> ::std::result::Result<Self, D::Error> {
+        let intermediate: shims::SuccinctRoles = Deserialize::deserialize(de)?;
+        intermediate
+            .try_into()
+            .map_err(|e| DeserializeError::custom(format!("{:?}", e)))
+    }
+}
+
 /// A builder for [Delegations].
 #[derive(Default)]
 pub struct DelegationsBuilder {
     keys: HashMap<KeyId, PublicKey>,
     roles: Vec<Delegation>,
     role_index: HashMap<MetadataPath, usize>,
+    succinct_roles: Option<SuccinctRoles>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl DelegationsBuilder {
@@ -2095,6 +3251,8 @@ impl DelegationsBuilder {
             keys: HashMap::new(),
             roles: vec![],
             role_index: HashMap::new(),
+            succinct_roles: None,
+            additional_fields: HashMap::new(),
         }
     }
 
@@ -2104,6 +3262,12 @@ impl DelegationsBuilder {
         self
     }
 
+    /// Remove the key with this [KeyId] from the delegation [PublicKey] set, if it exists.
+    pub fn remove_key(mut self, key_id: &KeyId) -> Self {
+        self.keys.remove(key_id);
+        self
+    }
+
     /// Add a [Delegation].
     pub fn role(mut self, delegation: Delegation) -> Self {
         // The delegation list is ordered and unique by role name, so check if we should overwrite
@@ -2120,20 +3284,67 @@ impl DelegationsBuilder {
         self
     }
 
+    /// Remove the [Delegation] to `role`, if it exists.
+    pub fn remove_role(mut self, role: &MetadataPath) -> Self {
+        if self.role_index.remove(role).is_some() {
+            self.roles.retain(|delegation| &delegation.name != role);
+
+            for (idx, delegation) in self.roles.iter().enumerate() {
+                self.role_index.insert(delegation.name.clone(), idx);
+            }
+        }
+
+        self
+    }
+
+    /// Delegate to the hashed bins described by `succinct_roles`, replacing any previously set.
+    /// This cannot be combined with [DelegationsBuilder::role].
+    pub fn succinct_roles(mut self, succinct_roles: SuccinctRoles) -> Self {
+        self.succinct_roles = Some(succinct_roles);
+        self
+    }
+
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct a new [Delegations].
     pub fn build(self) -> Result<Delegations> {
-        Delegations::new(self.keys, self.roles)
+        let delegations = match self.succinct_roles {
+            None => Delegations::new(self.keys, self.roles)?,
+            Some(succinct_roles) if self.roles.is_empty() => {
+                Delegations::new_with_succinct_roles(self.keys, succinct_roles)?
+            }
+            Some(_) => {
+                return Err(Error::IllegalArgument(
+                    "Cannot have both roles and succinct roles in delegations.".into(),
+                ))
+            }
+        };
+
+        delegations.with_additional_fields(self.additional_fields)
     }
 }
 
 /// A delegated targets role.
+///
+/// A delegation is trusted with the targets matching either a set of [PathPattern]s, or a set of
+/// [PathHashPrefix]es of the SHA-256 digest of the target path. Exactly one of these is non-empty.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Delegation {
     name: MetadataPath,
     terminating: bool,
     threshold: u32,
     key_ids: HashSet<KeyId>,
-    paths: HashSet<TargetPath>,
+    paths: HashSet<PathPattern>,
+    path_hash_prefixes: HashSet<PathHashPrefix>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl Delegation {
@@ -2148,16 +3359,52 @@ impl Delegation {
         terminating: bool,
         threshold: u32,
         key_ids: HashSet<KeyId>,
-        paths: HashSet<TargetPath>,
+        paths: HashSet<PathPattern>,
     ) -> Result<Self> {
-        if key_ids.is_empty() {
-            return Err(Error::IllegalArgument("Cannot have empty key IDs".into()));
-        }
-
         if paths.is_empty() {
             return Err(Error::IllegalArgument("Cannot have empty paths".into()));
         }
 
+        Self::new_inner(name, terminating, threshold, key_ids, paths, HashSet::new())
+    }
+
+    /// Create a new delegation that is trusted with the targets whose path hashes start with
+    /// one of `path_hash_prefixes`.
+    pub fn new_with_path_hash_prefixes(
+        name: MetadataPath,
+        terminating: bool,
+        threshold: u32,
+        key_ids: HashSet<KeyId>,
+        path_hash_prefixes: HashSet<PathHashPrefix>,
+    ) -> Result<Self> {
+        if path_hash_prefixes.is_empty() {
+            return Err(Error::IllegalArgument(
+                "Cannot have empty path hash prefixes".into(),
+            ));
+        }
+
+        Self::new_inner(
+            name,
+            terminating,
+            threshold,
+            key_ids,
+            HashSet::new(),
+            path_hash_prefixes,
+        )
+    }
+
+    fn new_inner(
+        name: MetadataPath,
+        terminating: bool,
+        threshold: u32,
+        key_ids: HashSet<KeyId>,
+        paths: HashSet<PathPattern>,
+        path_hash_prefixes: HashSet<PathHashPrefix>,
+    ) -> Result<Self> {
+        if key_ids.is_empty() {
+            return Err(Error::IllegalArgument("Cannot have empty key IDs".into()));
+        }
+
         if threshold < 1 {
             return Err(Error::IllegalArgument("Cannot have threshold < 1".into()));
         }
@@ -2174,6 +3421,8 @@ impl Delegation {
             threshold,
             key_ids,
             paths,
+            path_hash_prefixes,
+            additional_fields: HashMap::new(),
         })
     }
 
@@ -2197,10 +3446,78 @@ impl Delegation {
         self.threshold
     }
 
-    /// An immutable reference to the delegation's authorized paths.
-    pub fn paths(&self) -> &HashSet<TargetPath> {
+    /// An immutable reference to the delegation's authorized path patterns. This is empty if
+    /// the delegation is scoped by [Delegation::path_hash_prefixes].
+    pub fn paths(&self) -> &HashSet<PathPattern> {
         &self.paths
     }
+
+    /// An immutable reference to the delegation's authorized path hash prefixes. This is empty
+    /// if the delegation is scoped by [Delegation::paths].
+    pub fn path_hash_prefixes(&self) -> &HashSet<PathHashPrefix> {
+        &self.path_hash_prefixes
+    }
+
+    /// An immutable reference to the fields this library does not understand, such as extensions
+    /// added by other implementations. These are preserved when the metadata is re-serialized.
+    pub fn additional_fields(&self) -> &HashMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
+
+    /// Set the fields this library does not understand, which are serialized alongside the fields
+    /// it does. It is an error for one of them to share a name with a field that is understood.
+    pub fn with_additional_fields(
+        mut self,
+        additional_fields: HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        check_additional_fields(
+            &additional_fields,
+            &[
+                "name",
+                "terminating",
+                "threshold",
+                "keyids",
+                "paths",
+                "path_hash_prefixes",
+            ],
+        )?;
+        self.additional_fields = additional_fields;
+        Ok(self)
+    }
+
+    /// Whether or not this delegation is trusted with `target`.
+    ///
+    /// ```
+    /// # use tuf::crypto::{Ed25519PrivateKey, PrivateKey};
+    /// # use tuf::metadata::{Delegation, MetadataPath, PathHashPrefix, PathPattern, TargetPath};
+    /// # let key = Ed25519PrivateKey::from_pkcs8(&Ed25519PrivateKey::pkcs8().unwrap()).unwrap();
+    /// let delegation = Delegation::builder(MetadataPath::new("releases").unwrap())
+    ///     .key(key.public())
+    ///     .delegate_path(PathPattern::new("releases/*.tgz").unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(delegation.matches_target(&TargetPath::new("releases/foo.tgz").unwrap()));
+    /// assert!(!delegation.matches_target(&TargetPath::new("foo.tgz").unwrap()));
+    ///
+    /// // sha256("foo") = 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+    /// let delegation = Delegation::builder(MetadataPath::new("bin-2").unwrap())
+    ///     .key(key.public())
+    ///     .delegate_path_hash_prefix(PathHashPrefix::new("2").unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(delegation.matches_target(&TargetPath::new("foo").unwrap()));
+    /// assert!(!delegation.matches_target(&TargetPath::new("bar").unwrap()));
+    /// ```
+    pub fn matches_target(&self, target: &TargetPath) -> bool {
+        if !self.path_hash_prefixes.is_empty() {
+            return self
+                .path_hash_prefixes
+                .iter()
+                .any(|prefix| prefix.matches(target));
+        }
+
+        self.paths.iter().any(|pattern| pattern.matches(target))
+    }
 }
 
 impl Serialize for Delegation {
@@ -2227,7 +3544,9 @@ pub struct DelegationBuilder {
     terminating: bool,
     threshold: u32,
     key_ids: HashSet<KeyId>,
-    paths: HashSet<TargetPath>,
+    paths: HashSet<PathPattern>,
+    path_hash_prefixes: HashSet<PathHashPrefix>,
+    additional_fields: HashMap<String, serde_json::Value>,
 }
 
 impl DelegationBuilder {
@@ -2239,9 +3558,72 @@ impl DelegationBuilder {
             threshold: 1,
             key_ids: HashSet::new(),
             paths: HashSet::new(),
+            path_hash_prefixes: HashSet::new(),
+            additional_fields: HashMap::new(),
         }
     }
 
+    /// Create builders for `number_of_bins` delegations that together cover every target by
+    /// path hash prefix, named `{name_prefix}{first}-{last}` after the range of prefixes each
+    /// bin is trusted with (or `{name_prefix}{prefix}` if the bin has a single prefix).
+    /// `number_of_bins` must be a power of two between 2 and 2^32.
+    ///
+    /// The returned builders still need keys before they can be built.
+    ///
+    /// ```
+    /// # use tuf::metadata::DelegationBuilder;
+    /// let bins = DelegationBuilder::hashed_bins("bins/", 8).unwrap();
+    /// assert_eq!(bins.len(), 8);
+    ///
+    /// assert!(DelegationBuilder::hashed_bins("bins/", 0).is_err());
+    /// assert!(DelegationBuilder::hashed_bins("bins/", 3).is_err());
+    /// ```
+    pub fn hashed_bins(name_prefix: &str, number_of_bins: u64) -> Result<Vec<Self>> {
+        if !(2..=(1 << 32)).contains(&number_of_bins) || !number_of_bins.is_power_of_two() {
+            return Err(Error::IllegalArgument(format!(
+                "Number of hashed bins must be a power of two between 2 and 2^32, not {}",
+                number_of_bins
+            )));
+        }
+
+        // Each hex digit covers 4 bits, so use the fewest digits that can distinguish every bin.
+        let bits = number_of_bins.trailing_zeros();
+        let prefix_len = ((bits + 3) / 4) as usize;
+        let prefix_count = 1u64 << (4 * prefix_len);
+        let bin_size = prefix_count / number_of_bins;
+
+        let mut bins = Vec::with_capacity(number_of_bins as usize);
+        for bin in 0..number_of_bins {
+            let first = bin * bin_size;
+            let last = first + bin_size - 1;
+
+            let name = if bin_size == 1 {
+                format!("{}{:0width$x}", name_prefix, first, width = prefix_len)
+            } else {
+                format!(
+                    "{}{:0width$x}-{:0width$x}",
+                    name_prefix,
+                    first,
+                    last,
+                    width = prefix_len
+                )
+            };
+
+            let mut builder = DelegationBuilder::new(MetadataPath::new(name)?);
+            for prefix in first..=last {
+                builder = builder.delegate_path_hash_prefix(PathHashPrefix(format!(
+                    "{:0width$x}",
+                    prefix,
+                    width = prefix_len
+                )));
+            }
+
+            bins.push(builder);
+        }
+
+        Ok(bins)
+    }
+
     /// The threshold number of signatures required for the delegation to be trusted.
     pub fn threshold(mut self, threshold: u32) -> Self {
         self.threshold = threshold;
@@ -2260,21 +3642,53 @@ impl DelegationBuilder {
         self
     }
 
-    /// Delegate `path` to this delegation.
-    pub fn delegate_path(mut self, path: TargetPath) -> Self {
+    /// Delegate the targets matching `path` to this delegation.
+    pub fn delegate_path(mut self, path: PathPattern) -> Self {
         self.paths.insert(path);
         self
     }
 
+    /// Delegate the targets whose path hashes start with `prefix` to this delegation.
+    pub fn delegate_path_hash_prefix(mut self, prefix: PathHashPrefix) -> Self {
+        self.path_hash_prefixes.insert(prefix);
+        self
+    }
+
+    /// Add a field this library does not understand, which is serialized alongside the fields it
+    /// does.
+    pub fn additional_field<N>(mut self, name: N, value: serde_json::Value) -> Self
+    where
+        N: Into<String>,
+    {
+        self.additional_fields.insert(name.into(), value);
+        self
+    }
+
     /// Construct the [Delegation].
     pub fn build(self) -> Result<Delegation> {
-        Delegation::new(
-            self.role,
-            self.terminating,
-            self.threshold,
-            self.key_ids,
-            self.paths,
-        )
+        let delegation = match (self.paths.is_empty(), self.path_hash_prefixes.is_empty()) {
+            (_, true) => Delegation::new(
+                self.role,
+                self.terminating,
+                self.threshold,
+                self.key_ids,
+                self.paths,
+            )?,
+            (true, false) => Delegation::new_with_path_hash_prefixes(
+                self.role,
+                self.terminating,
+                self.threshold,
+                self.key_ids,
+                self.path_hash_prefixes,
+            )?,
+            (false, false) => {
+                return Err(Error::IllegalArgument(
+                    "Cannot delegate both paths and path hash prefixes".into(),
+                ))
+            }
+        };
+
+        delegation.with_additional_fields(self.additional_fields)
     }
 }
 
@@ -2287,6 +3701,7 @@ mod test {
     use assert_matches::assert_matches;
     use chrono::prelude::*;
     use futures_executor::block_on;
+    use futures_util::future::{self, BoxFuture, FutureExt as _};
     use maplit::{hashmap, hashset};
     use pretty_assertions::assert_eq;
     use serde_json::json;
@@ -2359,6 +3774,10 @@ mod test {
             (false, "foo", &[&[], &["foo"]]),
             // empty 3
             (false, "foo", &[&["foo"], &[]]),
+            // wildcard nested
+            (true, "foo/bar.tgz", &[&["foo/*"], &["foo/*.tgz"]]),
+            // wildcard illegally nested
+            (false, "foo/bar.tgz", &[&["foo/*.zip"], &["foo/*.tgz"]]),
         ];
 
         for case in test_cases {
@@ -2370,7 +3789,7 @@ mod test {
                 .map(|group| {
                     group
                         .iter()
-                        .map(|p| TargetPath::new(p.to_string()).unwrap())
+                        .map(|p| PathPattern::new(p.to_string()).unwrap())
                         .collect::<HashSet<_>>()
                 })
                 .collect::<Vec<_>>();
@@ -2382,6 +3801,81 @@ mod test {
         }
     }
 
+    #[test]
+    fn path_pattern_matches() {
+        let test_cases: &[(bool, &str, &str)] = &[
+            // exact match
+            (true, "foo", "foo"),
+            (false, "foo", "bar"),
+            (false, "foo", "foo/bar"),
+            // `*` matches within a single component
+            (true, "*", "foo"),
+            (true, "*", ""),
+            (false, "*", "foo/bar"),
+            (true, "foo/*", "foo/bar"),
+            (false, "foo/*", "foo/bar/baz"),
+            (true, "*/bar", "foo/bar"),
+            (true, "foo/*/baz", "foo/bar/baz"),
+            (true, "releases/*.tar.gz", "releases/foo-1.0.tar.gz"),
+            (false, "releases/*.tar.gz", "releases/foo-1.0.zip"),
+            (false, "releases/*.tar.gz", "releases/1.0/foo.tar.gz"),
+            (true, "*.tgz*", "foo.tgz"),
+            (true, "a*b*c", "aXbYbZc"),
+            (false, "a*b*c", "aXbYbZ"),
+            // `?` matches a single character
+            (true, "foo-?.tgz", "foo-1.tgz"),
+            (false, "foo-?.tgz", "foo-.tgz"),
+            (false, "foo-?.tgz", "foo-12.tgz"),
+            (false, "foo?bar", "foo/bar"),
+            // `[seq]` and `[!seq]`
+            (true, "foo-[0-9].tgz", "foo-7.tgz"),
+            (false, "foo-[0-9].tgz", "foo-a.tgz"),
+            (true, "foo-[!0-9].tgz", "foo-a.tgz"),
+            (false, "foo-[!0-9].tgz", "foo-7.tgz"),
+            (true, "foo-[abc].tgz", "foo-b.tgz"),
+            (true, "foo-[]].tgz", "foo-].tgz"),
+            (true, "foo-[a-].tgz", "foo--.tgz"),
+            // an unterminated `[` is a literal
+            (true, "foo[", "foo["),
+            (false, "foo[", "foob"),
+            // directory patterns
+            (true, "foo/", "foo/bar"),
+            (true, "foo/", "foo/bar/baz"),
+            (false, "foo/", "foo"),
+            (false, "foo/", "foobar"),
+            (true, "foo/*/", "foo/bar/baz"),
+            (false, "foo/*/", "foo/bar"),
+        ];
+
+        for (expected, pattern, target) in test_cases {
+            let pattern = PathPattern::new(*pattern).unwrap();
+            let target = TargetPath(target.to_string());
+            assert_eq!(
+                pattern.matches(&target),
+                *expected,
+                "pattern: {:?} target: {:?}",
+                pattern,
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn path_pattern_allows_question_mark() {
+        assert!(TargetPath::new("foo-?.tgz").is_err());
+        assert!(MetadataPath::new("foo-?").is_err());
+        assert!(PathPattern::new("foo-?.tgz").is_ok());
+        assert!(PathPattern::new("../foo-?.tgz").is_err());
+    }
+
+    #[test]
+    fn serde_path_pattern() {
+        let p = serde_json::from_str::<PathPattern>("\"foo/*.tgz\"").unwrap();
+        assert_eq!(p.as_str(), "foo/*.tgz");
+        assert_eq!(serde_json::to_value(p).unwrap(), json!("foo/*.tgz"));
+        assert!(serde_json::from_str::<PathPattern>("\"/foo\"").is_err());
+    }
+
     #[test]
     fn serde_target_path() {
         let s = "foo/bar";
@@ -2632,19 +4126,62 @@ mod test {
         let root_key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
         let decoded: RootMetadata = serde_json::from_value(jsn).unwrap();
 
-        let signed: SignedMetadata<crate::pouf::pouf1::Pouf1, _> =
-            SignedMetadata::new(&decoded, &root_key).unwrap();
-        let raw_root = signed.to_raw().unwrap();
+        let signed: SignedMetadata<crate::pouf::pouf1::Pouf1, _> =
+            SignedMetadata::new(&decoded, &root_key).unwrap();
+        let raw_root = signed.to_raw().unwrap();
+
+        assert_matches!(
+            verify_signatures(
+                &MetadataPath::root(),
+                &raw_root,
+                1,
+                &[root_key.public().clone()]
+            ),
+            Ok(_)
+        );
+    }
+
+    /// Claims to hold one key, but signs with another.
+    struct MismatchedSigner {
+        public: PublicKey,
+        private: Ed25519PrivateKey,
+    }
+
+    impl Signer for MismatchedSigner {
+        fn public_key(&self) -> &PublicKey {
+            &self.public
+        }
+
+        fn sign_async<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<Signature>> {
+            future::ready(self.private.sign(msg)).boxed()
+        }
+    }
+
+    #[test]
+    fn sign_rejects_mismatched_signer() {
+        let signer = MismatchedSigner {
+            public: Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8)
+                .unwrap()
+                .public()
+                .clone(),
+            private: Ed25519PrivateKey::from_pkcs8(ED25519_2_PK8).unwrap(),
+        };
+        let snapshot = SnapshotMetadataBuilder::new().build().unwrap();
 
+        let builder = SignedMetadataBuilder::<Pouf1, _>::from_metadata(&snapshot).unwrap();
         assert_matches!(
-            verify_signatures(
-                &MetadataPath::root(),
-                &raw_root,
-                1,
-                &[root_key.public().clone()]
-            ),
-            Ok(_)
+            block_on(builder.sign(&signer)),
+            Err(Error::IllegalArgument(_))
+        );
+
+        let mut signed = SignedMetadataBuilder::<Pouf1, _>::from_metadata(&snapshot)
+            .unwrap()
+            .build();
+        assert_matches!(
+            block_on(signed.add_signature(&signer)),
+            Err(Error::IllegalArgument(_))
         );
+        assert!(signed.signatures().is_empty());
     }
 
     #[test]
@@ -2709,6 +4246,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn verify_rsa_root_metadata_from_other_implementations() {
+        for bytes in [
+            &include_bytes!("../tests/rsa/root-rsassa-pss-sha256.json")[..],
+            &include_bytes!("../tests/rsa/root-rsa-pkcs1v15-sha256.json")[..],
+        ] {
+            let raw_root = RawSignedMetadata::<Pouf1, RootMetadata>::new(bytes.to_vec());
+            let root = raw_root.parse_untrusted().unwrap().assume_valid().unwrap();
+
+            // Keys whose ID doesn't match our calculation are dropped, so this checks that we
+            // agree on the key ID.
+            assert_eq!(root.keys().len(), 1);
+
+            assert_matches!(
+                verify_signatures(&MetadataPath::root(), &raw_root, 1, root.root_keys()),
+                Ok(_)
+            );
+        }
+    }
+
     fn verify_signature_with_unknown_fields<M>(mut metadata: serde_json::Value)
     where
         M: Metadata,
@@ -2716,15 +4273,17 @@ mod test {
         let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
         let public_keys = vec![key.public().clone()];
 
-        let mut standard = SignedMetadataBuilder::<Pouf1, M>::from_raw_metadata(metadata.clone())
-            .unwrap()
-            .sign(&key)
-            .unwrap()
-            .build()
-            .to_raw()
-            .unwrap()
-            .parse_untrusted()
-            .unwrap();
+        let mut standard = block_on(
+            SignedMetadataBuilder::<Pouf1, M>::from_raw_metadata(metadata.clone())
+                .unwrap()
+                .sign(&key),
+        )
+        .unwrap()
+        .build()
+        .to_raw()
+        .unwrap()
+        .parse_untrusted()
+        .unwrap();
 
         metadata.as_object_mut().unwrap().insert(
             "custom".into(),
@@ -2733,15 +4292,17 @@ mod test {
                 "this-too": 42,
             }),
         );
-        let mut custom = SignedMetadataBuilder::<Pouf1, M>::from_raw_metadata(metadata)
-            .unwrap()
-            .sign(&key)
-            .unwrap()
-            .build()
-            .to_raw()
-            .unwrap()
-            .parse_untrusted()
-            .unwrap();
+        let mut custom = block_on(
+            SignedMetadataBuilder::<Pouf1, M>::from_raw_metadata(metadata)
+                .unwrap()
+                .sign(&key),
+        )
+        .unwrap()
+        .build()
+        .to_raw()
+        .unwrap()
+        .parse_untrusted()
+        .unwrap();
 
         // Ensure the signatures are valid as-is.
         assert_matches!(
@@ -3091,7 +4652,7 @@ mod test {
                 false,
                 1,
                 hashset!(key.public().key_id().clone()),
-                hashset!(TargetPath::new("baz/quux").unwrap()),
+                hashset!(PathPattern::new("baz/quux").unwrap()),
             )
             .unwrap()],
         )
@@ -3278,7 +4839,7 @@ mod test {
                 false,
                 1,
                 hashset!(key.key_id().clone()),
-                hashset!(TargetPath::new("bar").unwrap()),
+                hashset!(PathPattern::new("bar").unwrap()),
             )
             .unwrap()],
         )
@@ -3297,7 +4858,7 @@ mod test {
             false,
             1,
             hashset!(key.key_id().clone()),
-            hashset!(TargetPath::new("bar").unwrap()),
+            hashset!(PathPattern::new("bar").unwrap()),
         )
         .unwrap();
 
@@ -3455,6 +5016,35 @@ mod test {
         assert!(serde_json::from_value::<RootMetadata>(root).is_err());
     }
 
+    #[test]
+    fn serde_metadata_with_newer_spec_version() {
+        let mut root = make_root();
+        let _ = root
+            .as_object_mut()
+            .unwrap()
+            .insert("spec_version".into(), json!("1.0.31"));
+
+        let decoded = serde_json::from_value::<RootMetadata>(root.clone()).unwrap();
+        assert_eq!(decoded.spec_version(), SpecVersion::new(1, 0, 31));
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), root);
+
+        let targets = TargetsMetadataBuilder::new()
+            .spec_version(SpecVersion::new(1, 2, 0))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&targets).unwrap()["spec_version"],
+            json!("1.2.0")
+        );
+
+        assert_matches!(
+            TargetsMetadataBuilder::new()
+                .spec_version(SpecVersion::new(2, 0, 0))
+                .build(),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+
     // Refuse to deserialize role definitions with duplicated key ids
     #[test]
     fn deserialize_json_role_definition_duplicate_key_ids() {
@@ -3722,6 +5312,347 @@ mod test {
         assert!(serde_json::from_value::<Delegation>(delegation).is_err());
     }
 
+    #[test]
+    fn serde_delegation_with_path_hash_prefixes() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let delegation = Delegation::builder(MetadataPath::new("bins/0").unwrap())
+            .key(key.public())
+            .delegate_path_hash_prefix(PathHashPrefix::new("01").unwrap())
+            .delegate_path_hash_prefix(PathHashPrefix::new("00").unwrap())
+            .build()
+            .unwrap();
+
+        let jsn = json!({
+            "name": "bins/0",
+            "terminating": false,
+            "threshold": 1,
+            "keyids": ["a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a"],
+            "path_hash_prefixes": ["00", "01"],
+        });
+
+        let encoded = serde_json::to_value(&delegation).unwrap();
+        assert_eq!(encoded, jsn);
+        let decoded: Delegation = serde_json::from_value(encoded).unwrap();
+        assert_eq!(decoded, delegation);
+        assert!(decoded.paths().is_empty());
+    }
+
+    // Refuse to deserialize a delegation with both paths and path hash prefixes, or neither
+    #[test]
+    fn deserialize_json_delegation_paths_and_path_hash_prefixes() {
+        let mut delegation = make_delegation();
+        let _ = delegation
+            .as_object_mut()
+            .unwrap()
+            .insert("path_hash_prefixes".into(), json!(["ab"]));
+        assert!(serde_json::from_value::<Delegation>(delegation).is_err());
+
+        let mut delegation = make_delegation();
+        let _ = delegation.as_object_mut().unwrap().remove("paths");
+        assert!(serde_json::from_value::<Delegation>(delegation).is_err());
+    }
+
+    // Refuse to deserialize a delegation with duplicate or malformed path hash prefixes
+    #[test]
+    fn deserialize_json_delegation_bad_path_hash_prefixes() {
+        for prefixes in &[json!(["ab", "ab"]), json!(["AB"]), json!([""]), json!([])] {
+            let mut delegation = make_delegation();
+            let obj = delegation.as_object_mut().unwrap();
+            let _ = obj.remove("paths");
+            let _ = obj.insert("path_hash_prefixes".into(), prefixes.clone());
+            assert!(serde_json::from_value::<Delegation>(delegation).is_err());
+        }
+    }
+
+    #[test]
+    fn delegation_builder_rejects_paths_and_path_hash_prefixes() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        assert_matches!(
+            Delegation::builder(MetadataPath::new("foo").unwrap())
+                .key(key.public())
+                .delegate_path(PathPattern::new("foo").unwrap())
+                .delegate_path_hash_prefix(PathHashPrefix::new("00").unwrap())
+                .build(),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+
+    #[test]
+    fn hashed_bins_cover_all_prefixes() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+
+        for (number_of_bins, prefix_len, first_name, last_name) in &[
+            (2, 1, "bins/0-7", "bins/8-f"),
+            (16, 1, "bins/0", "bins/f"),
+            (32, 2, "bins/00-07", "bins/f8-ff"),
+            (256, 2, "bins/00", "bins/ff"),
+            (512, 3, "bins/000-007", "bins/ff8-fff"),
+        ] {
+            let bins = DelegationBuilder::hashed_bins("bins/", *number_of_bins)
+                .unwrap()
+                .into_iter()
+                .map(|bin| bin.key(key.public()).build().unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(bins.len() as u64, *number_of_bins);
+            assert_eq!(bins[0].name().to_string(), *first_name);
+            assert_eq!(bins[bins.len() - 1].name().to_string(), *last_name);
+
+            // Every prefix is assigned to exactly one bin.
+            let mut prefixes = HashSet::new();
+            for bin in &bins {
+                for prefix in bin.path_hash_prefixes() {
+                    assert_eq!(prefix.as_str().len(), *prefix_len);
+                    assert!(prefixes.insert(prefix.clone()));
+                }
+            }
+            assert_eq!(prefixes.len(), 1 << (4 * prefix_len));
+
+            // So every target matches exactly one bin.
+            for target in &["foo", "bar/baz", "releases/foo-1.0.tgz"] {
+                let target = TargetPath::new(*target).unwrap();
+                assert_eq!(
+                    bins.iter()
+                        .filter(|bin| bin.matches_target(&target))
+                        .count(),
+                    1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn serde_delegations_with_succinct_roles() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let delegations = Delegations::builder()
+            .key(key.public().clone())
+            .succinct_roles(
+                SuccinctRoles::new(hashset!(key.public().key_id().clone()), 1, 16, "bin".into())
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        let jsn = json!({
+            "keys": {
+                "a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a": {
+                    "keytype": "ed25519",
+                    "scheme": "ed25519",
+                    "keyid_hash_algorithms": ["sha256", "sha512"],
+                    "keyval": {
+                        "public": "eb8ac26b5c9ef0279e3be3e82262a93bce16fe58\
+                            ee422500d38caf461c65a3b6",
+                    }
+                },
+            },
+            "succinct_roles": {
+                "keyids": ["a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a"],
+                "threshold": 1,
+                "bit_length": 16,
+                "name_prefix": "bin",
+            },
+        });
+
+        let encoded = serde_json::to_value(&delegations).unwrap();
+        assert_eq!(encoded, jsn);
+        let decoded: Delegations = serde_json::from_value(encoded).unwrap();
+        assert_eq!(decoded, delegations);
+        assert!(decoded.roles().is_empty());
+    }
+
+    // Refuse to deserialize delegations with both roles and succinct roles, or neither
+    #[test]
+    fn deserialize_json_delegations_roles_and_succinct_roles() {
+        let succinct_roles = json!({
+            "keyids": ["a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a"],
+            "threshold": 1,
+            "bit_length": 16,
+            "name_prefix": "bin",
+        });
+
+        let mut delegations = make_delegations();
+        let _ = delegations
+            .as_object_mut()
+            .unwrap()
+            .insert("succinct_roles".into(), succinct_roles);
+        assert!(serde_json::from_value::<Delegations>(delegations).is_err());
+
+        let mut delegations = make_delegations();
+        let _ = delegations.as_object_mut().unwrap().remove("roles");
+        assert!(serde_json::from_value::<Delegations>(delegations).is_err());
+    }
+
+    #[test]
+    fn delegations_builder_rejects_roles_and_succinct_roles() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        assert_matches!(
+            Delegations::builder()
+                .key(key.public().clone())
+                .role(
+                    Delegation::builder(MetadataPath::new("foo").unwrap())
+                        .key(key.public())
+                        .delegate_path(PathPattern::new("foo").unwrap())
+                        .build()
+                        .unwrap()
+                )
+                .succinct_roles(
+                    SuccinctRoles::new(hashset!(key.public().key_id().clone()), 1, 4, "bin".into())
+                        .unwrap()
+                )
+                .build(),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+
+    #[test]
+    fn delegations_reject_succinct_roles_with_unknown_keys() {
+        let key1 = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let key2 = Ed25519PrivateKey::from_pkcs8(ED25519_2_PK8).unwrap();
+        let succinct_roles = SuccinctRoles::new(
+            hashset!(
+                key1.public().key_id().clone(),
+                key2.public().key_id().clone()
+            ),
+            1,
+            8,
+            "bin".into(),
+        )
+        .unwrap();
+
+        assert_matches!(
+            Delegations::new_with_succinct_roles(
+                hashmap!(key1.public().key_id().clone() => key1.public().clone()),
+                succinct_roles.clone(),
+            ),
+            Err(Error::IllegalArgument(_))
+        );
+
+        assert!(Delegations::new_with_succinct_roles(
+            hashmap!(
+                key1.public().key_id().clone() => key1.public().clone(),
+                key2.public().key_id().clone() => key2.public().clone(),
+            ),
+            succinct_roles,
+        )
+        .is_ok());
+
+        // The same applies when decoding.
+        let jsn = json!({
+            "keys": {},
+            "succinct_roles": {
+                "keyids": ["a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a"],
+                "threshold": 1,
+                "bit_length": 8,
+                "name_prefix": "bin",
+            },
+        });
+        assert!(serde_json::from_value::<Delegations>(jsn).is_err());
+    }
+
+    #[test]
+    fn succinct_roles_new_rejects_bad_arguments() {
+        let key_id = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8)
+            .unwrap()
+            .public()
+            .key_id()
+            .clone();
+
+        assert!(SuccinctRoles::new(hashset!(), 1, 8, "bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 0, 8, "bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 2, 8, "bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 1, 0, "bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 1, 33, "bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 1, 8, "../bin".into()).is_err());
+        assert!(SuccinctRoles::new(hashset!(key_id.clone()), 1, 1, "bin".into()).is_ok());
+        assert!(SuccinctRoles::new(hashset!(key_id), 1, 32, "bins/bin".into()).is_ok());
+    }
+
+    #[test]
+    fn succinct_roles_resolve_targets_to_bins() {
+        let key_id = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8)
+            .unwrap()
+            .public()
+            .key_id()
+            .clone();
+
+        // sha256("foo") = 2c26b46b..., sha256("bar") = fcde2b2e...
+        let foo = TargetPath::new("foo").unwrap();
+        let bar = TargetPath::new("bar").unwrap();
+
+        for (bit_length, foo_role, bar_role, last_role) in &[
+            (1, "bin-0", "bin-1", "bin-1"),
+            (4, "bin-2", "bin-f", "bin-f"),
+            (5, "bin-05", "bin-1f", "bin-1f"),
+            (8, "bin-2c", "bin-fc", "bin-ff"),
+            (10, "bin-0b0", "bin-3f3", "bin-3ff"),
+            (32, "bin-2c26b46b", "bin-fcde2b2e", "bin-ffffffff"),
+        ] {
+            let succinct_roles =
+                SuccinctRoles::new(hashset!(key_id.clone()), 1, *bit_length, "bin".into()).unwrap();
+
+            assert_eq!(succinct_roles.role_for_target(&foo).to_string(), *foo_role);
+            assert_eq!(succinct_roles.role_for_target(&bar).to_string(), *bar_role);
+
+            let last_role = MetadataPath::new(*last_role).unwrap();
+            assert!(succinct_roles.is_delegated_role(&last_role));
+
+            if *bit_length < 32 {
+                assert_eq!(
+                    succinct_roles.role_names().last(),
+                    Some(last_role),
+                    "bit length {}",
+                    bit_length
+                );
+            }
+        }
+
+        let succinct_roles = SuccinctRoles::new(hashset!(key_id), 1, 5, "bin".into()).unwrap();
+        assert_eq!(succinct_roles.number_of_bins(), 32);
+        assert_eq!(succinct_roles.role_names().count(), 32);
+        assert!(succinct_roles.is_delegated_role(&MetadataPath::new("bin-00").unwrap()));
+        assert!(succinct_roles.is_delegated_role(&MetadataPath::new("bin-1f").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin-20").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin-0").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin-1F").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin-+1").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bins-00").unwrap()));
+        assert!(!succinct_roles.is_delegated_role(&MetadataPath::new("bin00").unwrap()));
+    }
+
+    #[test]
+    fn delegations_delegates_to_roles_and_succinct_bins() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8)
+            .unwrap()
+            .public()
+            .clone();
+
+        let delegations = DelegationsBuilder::new()
+            .key(key.clone())
+            .role(
+                DelegationBuilder::new(MetadataPath::new("a").unwrap())
+                    .key(&key)
+                    .delegate_path(PathPattern::new("a/*").unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert!(delegations.delegates_to(&MetadataPath::new("a").unwrap()));
+        assert!(!delegations.delegates_to(&MetadataPath::new("b").unwrap()));
+        assert!(!delegations.delegates_to(&MetadataPath::new("bin-00").unwrap()));
+
+        let delegations = DelegationsBuilder::new()
+            .key(key.clone())
+            .succinct_roles(
+                SuccinctRoles::new(hashset!(key.key_id().clone()), 1, 5, "bin".into()).unwrap(),
+            )
+            .build()
+            .unwrap();
+        assert!(delegations.delegates_to(&MetadataPath::new("bin-1f").unwrap()));
+        assert!(!delegations.delegates_to(&MetadataPath::new("bin-20").unwrap()));
+        assert!(!delegations.delegates_to(&MetadataPath::new("a").unwrap()));
+    }
+
     // Refuse to deserialize a Delegations struct with duplicate keys
     #[test]
     fn deserialize_json_delegations_duplicate_keys() {
@@ -3757,4 +5688,146 @@ mod test {
             result => panic!("unexpected result: {:?}", result),
         }
     }
+
+    #[test]
+    fn serde_root_metadata_preserves_additional_fields() {
+        let mut jsn = make_root();
+        let obj = jsn.as_object_mut().unwrap();
+        let _ = obj.insert("x-extension".into(), json!({"nested": [1, 2, 3]}));
+        let _ = obj["roles"]["targets"]
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("targets role"));
+
+        let root: RootMetadata = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(
+            root.additional_fields(),
+            &hashmap! { "x-extension".into() => json!({"nested": [1, 2, 3]}) }
+        );
+        assert_eq!(
+            root.targets().additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("targets role") }
+        );
+        assert!(root.root().additional_fields().is_empty());
+        assert_eq!(serde_json::to_value(&root).unwrap(), jsn);
+
+        // The builder carries the fields over too.
+        let rebuilt = RootMetadataBuilder::from(root.clone()).build().unwrap();
+        assert_eq!(rebuilt, root);
+    }
+
+    #[test]
+    fn serde_targets_metadata_preserves_additional_fields() {
+        let mut jsn = make_targets();
+        let obj = jsn.as_object_mut().unwrap();
+        let _ = obj.insert("unrecognized_field".into(), json!(true));
+        let _ = obj.insert(
+            "targets".into(),
+            json!({
+                "foo": {
+                    "length": 3,
+                    "hashes": {
+                        "sha256": "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae",
+                    },
+                    "unrecognized_field": "foo",
+                },
+            }),
+        );
+
+        let mut delegations = make_delegations();
+        let _ = delegations
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("delegations"));
+        let _ = delegations["roles"][0]
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("delegation"));
+        let _ = obj.insert("delegations".into(), delegations);
+
+        let targets: TargetsMetadata = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(
+            targets.additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!(true) }
+        );
+        assert_eq!(
+            targets.targets()[&TargetPath::new("foo").unwrap()].additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("foo") }
+        );
+        assert_eq!(
+            targets.delegations().additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("delegations") }
+        );
+        assert_eq!(
+            targets.delegations().roles()[0].additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("delegation") }
+        );
+        assert_eq!(serde_json::to_value(&targets).unwrap(), jsn);
+    }
+
+    #[test]
+    fn serde_timestamp_and_snapshot_metadata_preserve_additional_fields() {
+        let mut jsn = make_timestamp();
+        let _ = jsn
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("timestamp"));
+        let _ = jsn["meta"]["snapshot.json"]
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("snapshot.json"));
+
+        let timestamp: TimestampMetadata = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(
+            timestamp.additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("timestamp") }
+        );
+        assert_eq!(
+            timestamp.snapshot().additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("snapshot.json") }
+        );
+        assert_eq!(serde_json::to_value(&timestamp).unwrap(), jsn);
+
+        let mut jsn = make_snapshot();
+        let _ = jsn
+            .as_object_mut()
+            .unwrap()
+            .insert("unrecognized_field".into(), json!("snapshot"));
+
+        let snapshot: SnapshotMetadata = serde_json::from_value(jsn.clone()).unwrap();
+        assert_eq!(
+            snapshot.additional_fields(),
+            &hashmap! { "unrecognized_field".into() => json!("snapshot") }
+        );
+        assert_eq!(serde_json::to_value(&snapshot).unwrap(), jsn);
+    }
+
+    #[test]
+    fn additional_fields_cannot_shadow_defined_fields() {
+        assert_matches!(
+            SnapshotMetadataBuilder::new()
+                .additional_field("version", json!(2))
+                .build(),
+            Err(Error::IllegalArgument(_))
+        );
+
+        assert_matches!(
+            RootMetadataBuilder::new()
+                .root_key(
+                    Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8)
+                        .unwrap()
+                        .public()
+                        .clone()
+                )
+                .role_additional_field(Role::Root, "keyids", json!([]))
+                .build(),
+            Err(Error::IllegalArgument(_))
+        );
+
+        let snapshot = SnapshotMetadataBuilder::new()
+            .additional_field("x-extension", json!(2))
+            .build()
+            .unwrap();
+        assert_eq!(snapshot.additional_fields()["x-extension"], json!(2));
+    }
 }
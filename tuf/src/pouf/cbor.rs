@@ -0,0 +1,1366 @@
+use serde::de::{self, DeserializeOwned, IgnoredAny, IntoDeserializer as _, Visitor};
+use serde::ser::{self, Serialize};
+use serde::Deserialize as _;
+use std::fmt;
+
+use crate::crypto::Signature;
+use crate::error::Error;
+use crate::pouf::Pouf;
+use crate::Result;
+
+/// The deepest nesting of arrays and maps that will be decoded, to bound the recursion on
+/// untrusted input.
+const MAX_DEPTH: usize = 128;
+
+/// A compact binary POUF that encodes metadata as deterministic [CBOR].
+///
+/// The metadata uses the same data model and field names as [Pouf1](super::Pouf1), so a POUF-1
+/// document can be re-encoded without losing any information, although the signatures have to be
+/// computed again. Only the encoding of the files changes: paths inside the metadata, such as the
+/// `targets.json` entry in the snapshot, keep their POUF-1 names, while the files themselves use
+/// the `cbor` extension.
+///
+/// Metadata is decoded directly from the CBOR bytes, and signatures are computed over the exact
+/// bytes of the `signed` item as they appear in the file, so verifying metadata never needs to
+/// re-encode it.
+///
+/// # Canonicalization
+///
+/// Metadata is written using the core deterministic encoding of [RFC 8949 section 4.2.1], with
+/// the JSON data model mapped onto CBOR as follows:
+///
+/// * `null`, `false` and `true` are the simple values 22, 20 and 21.
+/// * Non-negative integers are unsigned integers (major type 0), and negative integers are
+///   negative integers (major type 1). Only integers that fit in an `i64` or a `u64` are allowed.
+///   Floating point numbers are not allowed.
+/// * Hashes, key IDs and signatures, which POUF-1 writes as hex strings, are byte strings
+///   (major type 2) of the raw digest or signature.
+/// * Other strings are UTF-8 text strings (major type 3).
+/// * Arrays are arrays (major type 4).
+/// * Objects are maps (major type 5). Their keys are text strings, except that key IDs are byte
+///   strings, such as in the `keys` of the root metadata.
+///
+/// In addition:
+///
+/// * Integers, and the lengths of strings, arrays and maps, use the shortest possible encoding.
+/// * Strings, arrays and maps always have a definite length.
+/// * Map keys are sorted by the bytewise lexicographic order of their encoding, and are unique.
+/// * No tags or other simple values are used.
+///
+/// The decoder rejects any input that does not follow the encoding rules, so every value has
+/// exactly one encoding. Hashes, key IDs and signatures must be byte strings, not hex text.
+/// Unknown fields are preserved, but may not contain byte strings.
+///
+/// The signed metadata is a map of the `signed` item and the `signatures`, in that order since
+/// `signed` is the shorter key.
+///
+/// [CBOR]: https://www.rfc-editor.org/rfc/rfc8949.html
+/// [RFC 8949 section 4.2.1]: https://www.rfc-editor.org/rfc/rfc8949.html#section-4.2.1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cbor;
+
+impl Pouf for Cbor {
+    /// The CBOR encoding of the `signed` portion of the metadata, which is exactly what was signed.
+    type RawData = Vec<u8>;
+
+    /// ```
+    /// # use tuf::pouf::{Cbor, Pouf};
+    /// assert_eq!(Cbor::extension(), "cbor");
+    /// ```
+    fn extension() -> &'static str {
+        "cbor"
+    }
+
+    /// ```
+    /// # use tuf::pouf::{Cbor, Pouf};
+    /// let raw = vec![0xa2, 0x61, b'b', 0x82, 0xf5, 0xf6, 0x62, b'a', b'a', 0x01];
+    /// assert_eq!(Cbor::canonicalize(&raw).unwrap(), raw);
+    ///
+    /// // The same map with its keys out of order.
+    /// let raw = vec![0xa2, 0x62, b'a', b'a', 0x01, 0x61, b'b', 0x82, 0xf5, 0xf6];
+    /// assert!(Cbor::canonicalize(&raw).is_err());
+    /// ```
+    fn canonicalize(raw_data: &Self::RawData) -> Result<Vec<u8>> {
+        // The raw data already is the canonical encoding, as long as it follows the rules.
+        from_bytes::<IgnoredAny>(raw_data)?;
+        Ok(raw_data.clone())
+    }
+
+    /// ```
+    /// # use serde_derive::Deserialize;
+    /// # use tuf::pouf::{Cbor, Pouf};
+    /// #
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct Thing {
+    ///    foo: String,
+    ///    bar: String,
+    /// }
+    ///
+    /// let raw = vec![
+    ///     0xa2, 0x63, b'b', b'a', b'r', 0x63, b'l', b'o', b'l', 0x63, b'f', b'o', b'o', 0x63,
+    ///     b'w', b'a', b't',
+    /// ];
+    /// let thing = Thing { foo: "wat".into(), bar: "lol".into() };
+    /// let de: Thing = Cbor::deserialize(&raw).unwrap();
+    /// assert_eq!(de, thing);
+    /// ```
+    fn deserialize<T>(raw_data: &Self::RawData) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Self::from_slice(raw_data)
+    }
+
+    /// ```
+    /// # use serde_derive::Serialize;
+    /// # use tuf::pouf::{Cbor, Pouf};
+    /// #
+    /// #[derive(Serialize)]
+    /// struct Thing {
+    ///    foo: String,
+    ///    bar: String,
+    /// }
+    ///
+    /// let thing = Thing { foo: "wat".into(), bar: "lol".into() };
+    /// let se = Cbor::serialize(&thing).unwrap();
+    /// assert_eq!(
+    ///     se,
+    ///     [
+    ///         0xa2, 0x63, b'b', b'a', b'r', 0x63, b'l', b'o', b'l', 0x63, b'f', b'o', b'o', 0x63,
+    ///         b'w', b'a', b't',
+    ///     ]
+    /// );
+    /// ```
+    fn serialize<T>(data: &T) -> Result<Self::RawData>
+    where
+        T: Serialize,
+    {
+        Ok(to_vec(data)?)
+    }
+
+    /// ```
+    /// # use tuf::pouf::{Cbor, Pouf};
+    /// # use std::collections::HashMap;
+    /// let cbor: &[u8] = &[0xa1, 0x63, b'f', b'o', b'o', 0x63, b'b', b'a', b'r'];
+    /// let map: HashMap<String, String> = Cbor::from_slice(cbor).unwrap();
+    /// assert_eq!(map["foo"], "bar");
+    /// ```
+    fn from_slice<T>(slice: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(from_bytes(slice)?)
+    }
+
+    fn encode_signed(signatures: &[Signature], signed: &Self::RawData) -> Result<Vec<u8>> {
+        from_bytes::<IgnoredAny>(signed)?;
+
+        let mut buf = Vec::with_capacity(signed.len() + 128 * signatures.len() + 32);
+        write_header(MAJOR_MAP, 2, &mut buf);
+        write_text("signed", &mut buf);
+        buf.extend(signed);
+        write_text("signatures", &mut buf);
+        signatures.serialize(&mut Serializer { buf: &mut buf })?;
+
+        Ok(buf)
+    }
+
+    fn decode_signed(slice: &[u8]) -> Result<(Vec<Signature>, Self::RawData)> {
+        let mut de = Deserializer::new(slice);
+
+        if de.header()? != (MAJOR_MAP, 2) {
+            return Err(cbor_error(
+                "signed metadata must be a map of `signed` and `signatures`",
+            ));
+        }
+
+        de.expect_text("signed")?;
+        let signed = de.skip_value()?;
+        de.expect_text("signatures")?;
+        let signatures = Vec::<Signature>::deserialize(&mut de)?;
+        de.end()?;
+
+        Ok((signatures, signed.to_vec()))
+    }
+}
+
+fn cbor_error(msg: &str) -> Error {
+    CborError(msg.into()).into()
+}
+
+/// An error encoding or decoding CBOR, which serde needs its own type for.
+#[derive(Debug)]
+struct CborError(String);
+
+impl CborError {
+    fn new(msg: &str) -> Self {
+        CborError(msg.into())
+    }
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CborError {}
+
+impl ser::Error for CborError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CborError(msg.to_string())
+    }
+}
+
+impl de::Error for CborError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CborError(msg.to_string())
+    }
+}
+
+impl From<CborError> for Error {
+    fn from(err: CborError) -> Error {
+        Error::Encoding(format!("CBOR: {}", err.0))
+    }
+}
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+
+/// Write the shortest header for a data item of major type `major` with argument `arg`.
+fn write_header(major: u8, arg: u64, buf: &mut Vec<u8>) {
+    let major = major << 5;
+    if arg < 24 {
+        buf.push(major | arg as u8);
+    } else if arg <= u8::MAX.into() {
+        buf.push(major | 24);
+        buf.push(arg as u8);
+    } else if arg <= u16::MAX.into() {
+        buf.push(major | 25);
+        buf.extend((arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX.into() {
+        buf.push(major | 26);
+        buf.extend((arg as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend(arg.to_be_bytes());
+    }
+}
+
+fn write_text(s: &str, buf: &mut Vec<u8>) {
+    write_header(MAJOR_TEXT, s.len() as u64, buf);
+    buf.extend(s.as_bytes());
+}
+
+fn to_vec<T>(value: &T) -> std::result::Result<Vec<u8>, CborError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut buf = Vec::new();
+    value.serialize(&mut Serializer { buf: &mut buf })?;
+    Ok(buf)
+}
+
+fn from_bytes<'de, T>(slice: &'de [u8]) -> std::result::Result<T, CborError>
+where
+    T: de::Deserialize<'de>,
+{
+    let mut de = Deserializer::new(slice);
+    let value = T::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+/// Writes the canonical encoding of a value.
+struct Serializer<'a> {
+    buf: &'a mut Vec<u8>,
+}
+
+impl<'a, 'b> ser::Serializer for &'a mut Serializer<'b> {
+    type Ok = ();
+    type Error = CborError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = SeqSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = MapSerializer<'a>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> std::result::Result<(), CborError> {
+        self.buf.push(if v { TRUE } else { FALSE });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> std::result::Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> std::result::Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> std::result::Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> std::result::Result<(), CborError> {
+        if v >= 0 {
+            write_header(MAJOR_UNSIGNED, v as u64, self.buf);
+        } else {
+            // `v` is negative, and CBOR encodes it as `-1 - v`.
+            write_header(MAJOR_NEGATIVE, !v as u64, self.buf);
+        }
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> std::result::Result<(), CborError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> std::result::Result<(), CborError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> std::result::Result<(), CborError> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> std::result::Result<(), CborError> {
+        write_header(MAJOR_UNSIGNED, v, self.buf);
+        Ok(())
+    }
+
+    fn serialize_f32(self, _v: f32) -> std::result::Result<(), CborError> {
+        Err(CborError::new("floating point numbers are not allowed"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> std::result::Result<(), CborError> {
+        Err(CborError::new("floating point numbers are not allowed"))
+    }
+
+    fn serialize_char(self, v: char) -> std::result::Result<(), CborError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> std::result::Result<(), CborError> {
+        write_text(v, self.buf);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> std::result::Result<(), CborError> {
+        write_header(MAJOR_BYTES, v.len() as u64, self.buf);
+        self.buf.extend(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> std::result::Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> std::result::Result<(), CborError> {
+        self.buf.push(NULL);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> std::result::Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> std::result::Result<(), CborError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        write_header(MAJOR_MAP, 1, self.buf);
+        write_text(variant, self.buf);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> std::result::Result<SeqSerializer<'a>, CborError> {
+        Ok(SeqSerializer::new(self.buf, None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> std::result::Result<SeqSerializer<'a>, CborError> {
+        Ok(SeqSerializer::new(self.buf, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> std::result::Result<SeqSerializer<'a>, CborError> {
+        Ok(SeqSerializer::new(self.buf, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> std::result::Result<SeqSerializer<'a>, CborError> {
+        Ok(SeqSerializer::new(self.buf, Some(variant)))
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> std::result::Result<MapSerializer<'a>, CborError> {
+        Ok(MapSerializer::new(self.buf, None))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> std::result::Result<MapSerializer<'a>, CborError> {
+        Ok(MapSerializer::new(self.buf, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> std::result::Result<MapSerializer<'a>, CborError> {
+        Ok(MapSerializer::new(self.buf, Some(variant)))
+    }
+}
+
+/// Writes the header of an enum variant that carries data, which is encoded as a map with a single
+/// entry from the variant name to its data.
+fn write_variant(variant: Option<&str>, buf: &mut Vec<u8>) {
+    if let Some(variant) = variant {
+        write_header(MAJOR_MAP, 1, buf);
+        write_text(variant, buf);
+    }
+}
+
+/// Writes an array, once the number of items is known.
+struct SeqSerializer<'a> {
+    buf: &'a mut Vec<u8>,
+    variant: Option<&'static str>,
+    items: Vec<u8>,
+    len: u64,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn new(buf: &'a mut Vec<u8>, variant: Option<&'static str>) -> Self {
+        Self {
+            buf,
+            variant,
+            items: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn item<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(&mut Serializer {
+            buf: &mut self.items,
+        })?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> std::result::Result<(), CborError> {
+        write_variant(self.variant, self.buf);
+        write_header(MAJOR_ARRAY, self.len, self.buf);
+        self.buf.extend(self.items);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.item(value)
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.item(value)
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.item(value)
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.item(value)
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+/// Writes a map, once all of its entries are known and can be sorted.
+struct MapSerializer<'a> {
+    buf: &'a mut Vec<u8>,
+    variant: Option<&'static str>,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    key: Option<Vec<u8>>,
+}
+
+impl<'a> MapSerializer<'a> {
+    fn new(buf: &'a mut Vec<u8>, variant: Option<&'static str>) -> Self {
+        Self {
+            buf,
+            variant,
+            entries: Vec::new(),
+            key: None,
+        }
+    }
+
+    fn finish(mut self) -> std::result::Result<(), CborError> {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if self.entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(CborError::new("map keys must be unique"));
+        }
+
+        write_variant(self.variant, self.buf);
+        write_header(MAJOR_MAP, self.entries.len() as u64, self.buf);
+        for (key, value) in self.entries {
+            self.buf.extend(key);
+            self.buf.extend(value);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for MapSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = to_vec(key)?;
+        match key[0] >> 5 {
+            MAJOR_BYTES | MAJOR_TEXT => {}
+            _ => return Err(CborError::new("map keys must be text or byte strings")),
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| CborError::new("map value without a key"))?;
+        self.entries.push((key, to_vec(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut encoded_key = Vec::new();
+        write_text(key, &mut encoded_key);
+        self.entries.push((encoded_key, to_vec(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> std::result::Result<(), CborError>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> std::result::Result<(), CborError> {
+        self.finish()
+    }
+}
+
+/// Reads values directly from their encoding, rejecting anything that isn't canonical.
+struct Deserializer<'de> {
+    buf: &'de [u8],
+    depth: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    fn new(buf: &'de [u8]) -> Self {
+        Self { buf, depth: 0 }
+    }
+
+    fn take(&mut self, len: u64) -> std::result::Result<&'de [u8], CborError> {
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= self.buf.len())
+            .ok_or_else(|| CborError::new("unexpected end of input"))?;
+        let (taken, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(taken)
+    }
+
+    fn peek(&self) -> std::result::Result<u8, CborError> {
+        self.buf
+            .first()
+            .copied()
+            .ok_or_else(|| CborError::new("unexpected end of input"))
+    }
+
+    /// Read a header, returning its major type and argument.
+    fn header(&mut self) -> std::result::Result<(u8, u64), CborError> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+
+        if major == MAJOR_SIMPLE {
+            return Ok((major, (initial & 0x1f).into()));
+        }
+
+        let (arg, min) = match initial & 0x1f {
+            info @ 0..=23 => return Ok((major, info.into())),
+            24 => (self.take(1)?[0].into(), 24),
+            25 => (
+                u16::from_be_bytes(self.take(2)?.try_into().unwrap()).into(),
+                u64::from(u8::MAX) + 1,
+            ),
+            26 => (
+                u32::from_be_bytes(self.take(4)?.try_into().unwrap()).into(),
+                u64::from(u16::MAX) + 1,
+            ),
+            27 => (
+                u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+                u64::from(u32::MAX) + 1,
+            ),
+            31 => return Err(CborError::new("indefinite lengths are not allowed")),
+            _ => return Err(CborError::new("reserved additional information")),
+        };
+
+        if arg < min {
+            return Err(CborError::new(
+                "integers and lengths must use the shortest encoding",
+            ));
+        }
+
+        Ok((major, arg))
+    }
+
+    fn text(&mut self, len: u64) -> std::result::Result<&'de str, CborError> {
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes).map_err(|_| CborError::new("text is not valid UTF-8"))
+    }
+
+    /// Read a text string, which must be `expected`.
+    fn expect_text(&mut self, expected: &str) -> std::result::Result<(), CborError> {
+        match self.header()? {
+            (MAJOR_TEXT, len) if self.text(len)? == expected => Ok(()),
+            _ => Err(CborError(format!("expected {:?}", expected))),
+        }
+    }
+
+    /// Check the next value, and return its encoding.
+    fn skip_value(&mut self) -> std::result::Result<&'de [u8], CborError> {
+        let start = self.buf;
+        IgnoredAny::deserialize(&mut *self)?;
+        Ok(&start[..start.len() - self.buf.len()])
+    }
+
+    fn end(&self) -> std::result::Result<(), CborError> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(CborError::new("trailing bytes after the top-level value"))
+        }
+    }
+
+    /// Enter an array or a map.
+    fn enter(&mut self) -> std::result::Result<(), CborError> {
+        if self.depth > MAX_DEPTH {
+            return Err(CborError::new("nested too deeply"));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = CborError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        let initial = self.peek()?;
+        let (major, arg) = self.header()?;
+        match major {
+            MAJOR_UNSIGNED => visitor.visit_u64(arg),
+            MAJOR_NEGATIVE => {
+                let n = i64::try_from(arg).map_err(|_| CborError::new("integer out of range"))?;
+                visitor.visit_i64(-1 - n)
+            }
+            MAJOR_BYTES => visitor.visit_borrowed_bytes(self.take(arg)?),
+            MAJOR_TEXT => visitor.visit_borrowed_str(self.text(arg)?),
+            MAJOR_ARRAY => {
+                self.enter()?;
+                let mut seq = SeqAccess {
+                    de: &mut *self,
+                    remaining: arg,
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                if seq.remaining != 0 {
+                    return Err(CborError::new("array has more items than expected"));
+                }
+                self.depth -= 1;
+                Ok(value)
+            }
+            MAJOR_MAP => {
+                self.enter()?;
+                let mut map = MapAccess {
+                    de: &mut *self,
+                    remaining: arg,
+                    previous_key: None,
+                };
+                let value = visitor.visit_map(&mut map)?;
+                if map.remaining != 0 {
+                    return Err(CborError::new("map has more entries than expected"));
+                }
+                self.depth -= 1;
+                Ok(value)
+            }
+            MAJOR_SIMPLE => match initial {
+                FALSE => visitor.visit_bool(false),
+                TRUE => visitor.visit_bool(true),
+                NULL => visitor.visit_unit(),
+                _ => Err(CborError::new(
+                    "only false, true and null simple values are allowed",
+                )),
+            },
+            MAJOR_TAG => Err(CborError::new("tags are not allowed")),
+            _ => unreachable!("the major type has 3 bits"),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        if self.peek()? == NULL {
+            self.buf = &self.buf[1..];
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.header()? {
+            (MAJOR_TEXT, len) => visitor.visit_enum(self.text(len)?.into_deserializer()),
+            (MAJOR_MAP, 1) => {
+                self.enter()?;
+                let value = visitor.visit_enum(VariantAccess { de: &mut *self })?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => Err(CborError::new(
+                "enums must be a variant name, or a map from the variant name to its data",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: u64,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = CborError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> std::result::Result<Option<T::Value>, CborError>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Every item takes at least a byte, so don't trust a length that's longer.
+        Some(self.remaining.min(self.de.buf.len() as u64) as usize)
+    }
+}
+
+struct MapAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: u64,
+    previous_key: Option<&'de [u8]>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = CborError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> std::result::Result<Option<K::Value>, CborError>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+
+        match self.de.peek()? >> 5 {
+            MAJOR_BYTES | MAJOR_TEXT => {}
+            _ => return Err(CborError::new("map keys must be text or byte strings")),
+        }
+
+        let key_start = self.de.buf;
+        let key = seed.deserialize(&mut *self.de)?;
+        let encoded_key = &key_start[..key_start.len() - self.de.buf.len()];
+
+        if let Some(previous_key) = self.previous_key {
+            if previous_key >= encoded_key {
+                return Err(CborError::new("map keys must be sorted and unique"));
+            }
+        }
+        self.previous_key = Some(encoded_key);
+
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> std::result::Result<V::Value, CborError>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Every entry takes at least two bytes, so don't trust a length that's longer.
+        Some(self.remaining.min(self.de.buf.len() as u64 / 2) as usize)
+    }
+}
+
+/// Reads an enum variant that carries data, from a map with a single entry.
+struct VariantAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantAccess<'_, 'de> {
+    type Error = CborError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> std::result::Result<(V::Value, Self), CborError>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if self.de.peek()? >> 5 != MAJOR_TEXT {
+            return Err(CborError::new("enum variant names must be text"));
+        }
+        let variant = seed.deserialize(&mut *self.de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'_, 'de> {
+    type Error = CborError;
+
+    fn unit_variant(self) -> std::result::Result<(), CborError> {
+        de::Deserialize::deserialize(self.de)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> std::result::Result<T::Value, CborError>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_seq(self.de, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(self.de, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::crypto::{Ed25519PrivateKey, HashAlgorithm, HashValue, KeyId, PrivateKey};
+    use crate::metadata::{
+        MetadataDescription, MetadataPath, RawSignedMetadata, RootMetadata, RootMetadataBuilder,
+        SignedMetadata, SnapshotMetadata, SnapshotMetadataBuilder,
+    };
+    use crate::pouf::Pouf1;
+    use crate::verify::verify_signatures;
+    use assert_matches::assert_matches;
+    use chrono::prelude::*;
+    use data_encoding::HEXLOWER;
+    use maplit::hashmap;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn encode(jsn: &serde_json::Value) -> Result<Vec<u8>> {
+        Cbor::serialize(jsn)
+    }
+
+    fn decode(slice: &[u8]) -> Result<serde_json::Value> {
+        Cbor::from_slice(slice)
+    }
+
+    fn root_key() -> Ed25519PrivateKey {
+        Ed25519PrivateKey::from_pkcs8(&Ed25519PrivateKey::pkcs8().unwrap()).unwrap()
+    }
+
+    fn root(key: &Ed25519PrivateKey) -> RootMetadata {
+        RootMetadataBuilder::new()
+            .expires(Utc.with_ymd_and_hms(2038, 1, 1, 0, 0, 0).unwrap())
+            .root_key(key.public().clone())
+            .snapshot_key(key.public().clone())
+            .targets_key(key.public().clone())
+            .timestamp_key(key.public().clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn encode_integers() {
+        // Examples from RFC 8949 appendix A.
+        for (n, expected) in [
+            (json!(0), &[0x00][..]),
+            (json!(23), &[0x17]),
+            (json!(24), &[0x18, 0x18]),
+            (json!(255), &[0x18, 0xff]),
+            (json!(256), &[0x19, 0x01, 0x00]),
+            (json!(1000000), &[0x1a, 0x00, 0x0f, 0x42, 0x40]),
+            (
+                json!(u64::MAX),
+                &[0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+            (json!(-1), &[0x20]),
+            (json!(-1000), &[0x39, 0x03, 0xe7]),
+            (
+                json!(i64::MIN),
+                &[0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            ),
+        ] {
+            assert_eq!(encode(&n).unwrap(), expected, "{}", n);
+            assert_eq!(decode(expected).unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn encode_rejects_floats() {
+        assert_matches!(encode(&json!(1.5)), Err(Error::Encoding(_)));
+    }
+
+    #[test]
+    fn encode_sorts_map_keys_by_encoding() {
+        // Shorter keys sort first, since their length is part of the encoding.
+        let jsn = json!({"b": 1, "aa": 2, "a": 3, "\u{e9}": 4});
+        let encoded = encode(&jsn).unwrap();
+        assert_eq!(
+            encoded,
+            [
+                0xa4, 0x61, b'a', 0x03, 0x61, b'b', 0x01, 0x62, b'a', b'a', 0x02, 0x62, 0xc3, 0xa9,
+                0x04
+            ]
+        );
+        assert_eq!(decode(&encoded).unwrap(), jsn);
+    }
+
+    #[test]
+    fn round_trip() {
+        let jsn = json!({
+            "null": null,
+            "bools": [true, false],
+            "text": "new\nline \u{1f600}",
+            "nested": {"empty": {}, "list": [[], [1, -2, {"x": "y"}]]},
+            "long": "x".repeat(300),
+        });
+        let encoded = encode(&jsn).unwrap();
+        assert_eq!(decode(&encoded).unwrap(), jsn);
+        assert_eq!(Cbor::canonicalize(&encoded).unwrap(), encoded);
+    }
+
+    #[test]
+    fn hashes_are_byte_strings() {
+        let hash = HashValue::new(vec![0xde, 0xad, 0xbe, 0xef]);
+        let hashes = hashmap! { HashAlgorithm::Sha256 => hash.clone() };
+        let encoded = Cbor::serialize(&hashes).unwrap();
+        assert_eq!(
+            encoded,
+            [0xa1, 0x66, b's', b'h', b'a', 0x32, b'5', b'6', 0x44, 0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            Cbor::deserialize::<HashMap<HashAlgorithm, HashValue>>(&encoded).unwrap(),
+            hashes
+        );
+    }
+
+    #[test]
+    fn decode_rejects_hex_text() {
+        let hashes = encode(&json!({"sha256": "deadbeef"})).unwrap();
+        assert_matches!(
+            Cbor::deserialize::<HashMap<HashAlgorithm, HashValue>>(&hashes),
+            Err(Error::Encoding(msg)) if msg.contains("expected bytes")
+        );
+
+        let key_id = encode(&json!(
+            "a9f3ebc9b138762563a9c27b6edd439959e559709babd123e8d449ba2c18c61a"
+        ))
+        .unwrap();
+        assert_matches!(
+            Cbor::deserialize::<KeyId>(&key_id),
+            Err(Error::Encoding(msg)) if msg.contains("invalid type")
+        );
+
+        // Nor inside metadata.
+        let key = root_key();
+        let root = root(&key);
+        let pouf1 = Pouf1::serialize(&root).unwrap();
+        assert_matches!(
+            Cbor::deserialize::<RootMetadata>(&encode(&pouf1).unwrap()),
+            Err(Error::Encoding(msg)) if msg.contains("invalid type")
+        );
+        assert_eq!(
+            Cbor::deserialize::<RootMetadata>(&Cbor::serialize(&root).unwrap()).unwrap(),
+            root
+        );
+    }
+
+    #[test]
+    fn decode_rejects_non_canonical_encodings() {
+        for (bad, reason) in [
+            (&[0x18, 0x17][..], "non-shortest integer"),
+            (&[0x19, 0x00, 0xff], "non-shortest integer"),
+            (&[0x78, 0x01, b'a'], "non-shortest length"),
+            (&[0x58, 0x01, 0x00], "non-shortest byte string length"),
+            (&[0x9f, 0x01, 0xff], "indefinite array"),
+            (&[0x7f, 0x61, b'a', 0xff], "indefinite text"),
+            (&[0x5f, 0x41, 0x00, 0xff], "indefinite bytes"),
+            (&[0xa2, 0x61, b'b', 0x01, 0x61, b'a', 0x02], "unsorted keys"),
+            (
+                &[0xa2, 0x61, b'a', 0x01, 0x61, b'a', 0x02],
+                "duplicate keys",
+            ),
+            (
+                &[0xa2, 0x61, b'a', 0x01, 0x41, b'a', 0x02],
+                "byte string key after text key",
+            ),
+            (&[0xa1, 0x01, 0x02], "integer key"),
+            (&[0xc1, 0x00], "tag"),
+            (&[0xf9, 0x3c, 0x00], "float"),
+            (&[0xf7], "undefined"),
+            (&[0x1c], "reserved"),
+            (&[0x61, 0xff], "invalid UTF-8"),
+            (
+                &[0x3b, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+                "too negative",
+            ),
+            (&[0x01, 0x02], "trailing bytes"),
+            (&[0x62, b'a'], "truncated"),
+            (&[0x42, 0x00], "truncated bytes"),
+            (
+                &[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+                "huge length",
+            ),
+            (&[], "empty"),
+        ] {
+            assert_matches!(
+                Cbor::canonicalize(&bad.to_vec()),
+                Err(Error::Encoding(_)),
+                "{}",
+                reason
+            );
+        }
+
+        // Byte strings are only used where the data model expects bytes.
+        assert_matches!(decode(&[0x41, 0x00]), Err(Error::Encoding(_)));
+        assert!(Cbor::canonicalize(&vec![0x41, 0x00]).is_ok());
+    }
+
+    #[test]
+    fn decode_limits_nesting() {
+        let mut deep = vec![0x81; MAX_DEPTH];
+        deep.push(0x80);
+        assert!(decode(&deep).is_ok());
+
+        let mut deeper = vec![0x81; MAX_DEPTH + 1];
+        deeper.push(0x80);
+        assert_matches!(decode(&deeper), Err(Error::Encoding(_)));
+    }
+
+    #[test]
+    fn signed_metadata_round_trips() {
+        let key = root_key();
+        let root = root(&key);
+
+        let signed = SignedMetadata::<Cbor, _>::new(&root, &key).unwrap();
+        let raw = signed.to_raw().unwrap();
+        assert_eq!(raw.parse_untrusted().unwrap(), signed);
+        assert_eq!(
+            *verify_signatures(&MetadataPath::root(), &raw, 1, [key.public()]).unwrap(),
+            root
+        );
+
+        // The encoding is the same data as POUF-1, just smaller.
+        let pouf1 = SignedMetadata::<Pouf1, _>::new(&root, &key)
+            .unwrap()
+            .to_raw()
+            .unwrap();
+        assert!(raw.as_bytes().len() < pouf1.as_bytes().len());
+        assert_eq!(signed.assume_valid().unwrap(), root);
+
+        // Key IDs and signatures are byte strings rather than hex.
+        let hex_key_id = key.public().key_id().to_string();
+        let mut key_id_bytes = vec![0x58, 0x20];
+        key_id_bytes.extend(HEXLOWER.decode(hex_key_id.as_bytes()).unwrap());
+        assert!(raw
+            .as_bytes()
+            .windows(key_id_bytes.len())
+            .any(|window| window == key_id_bytes));
+        assert!(!raw
+            .as_bytes()
+            .windows(hex_key_id.len())
+            .any(|window| window == hex_key_id.as_bytes()));
+        let mut sig_bytes = vec![0x58, 0x40];
+        sig_bytes.extend(signed.signatures()[0].value().as_bytes());
+        assert!(raw
+            .as_bytes()
+            .windows(sig_bytes.len())
+            .any(|window| window == sig_bytes));
+
+        // Signatures computed over the POUF-1 encoding don't carry over.
+        let pouf1_signed = serde_json::from_slice::<serde_json::Value>(pouf1.as_bytes()).unwrap()
+            ["signed"]
+            .clone();
+        let mismatched = RawSignedMetadata::<Cbor, RootMetadata>::new(
+            Cbor::encode_signed(
+                SignedMetadata::<Pouf1, RootMetadata>::new(&root, &key)
+                    .unwrap()
+                    .signatures(),
+                &encode(&pouf1_signed).unwrap(),
+            )
+            .unwrap(),
+        );
+        assert_matches!(
+            verify_signatures(&MetadataPath::root(), &mismatched, 1, [key.public()]),
+            Err(Error::MetadataMissingSignatures { .. })
+        );
+    }
+
+    #[test]
+    fn signatures_cover_the_signed_bytes() {
+        let key = root_key();
+        let snapshot = SnapshotMetadataBuilder::new()
+            .insert_metadata_description(
+                MetadataPath::targets(),
+                MetadataDescription::from_slice(b"targets", 1, &[HashAlgorithm::Sha256]).unwrap(),
+            )
+            .build()
+            .unwrap();
+        let raw = SignedMetadata::<Cbor, _>::new(&snapshot, &key)
+            .unwrap()
+            .to_raw()
+            .unwrap();
+        assert!(verify_signatures(&MetadataPath::snapshot(), &raw, 1, [key.public()]).is_ok());
+
+        // Flipping any bit of the signed item breaks the signature, or the encoding.
+        let (_, signed) = Cbor::decode_signed(raw.as_bytes()).unwrap();
+        let start = 1 + 7;
+        for i in start..start + signed.len() {
+            let mut bytes = raw.as_bytes().to_vec();
+            bytes[i] ^= 0x01;
+            let tampered = RawSignedMetadata::<Cbor, SnapshotMetadata>::new(bytes);
+            assert!(
+                verify_signatures(&MetadataPath::snapshot(), &tampered, 1, [key.public()]).is_err(),
+                "byte {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn decode_signed_requires_canonical_envelope() {
+        let key = root_key();
+        let signed = SignedMetadata::<Cbor, _>::new(&root(&key), &key).unwrap();
+        let raw = signed.to_raw().unwrap().as_bytes().to_vec();
+        assert!(Cbor::decode_signed(&raw).is_ok());
+
+        let mut trailing = raw.clone();
+        trailing.push(0x00);
+        assert_matches!(Cbor::decode_signed(&trailing), Err(Error::Encoding(_)));
+
+        // The keys in the wrong order.
+        let (signatures, signed) = Cbor::decode_signed(&raw).unwrap();
+        let mut swapped = Vec::new();
+        write_header(MAJOR_MAP, 2, &mut swapped);
+        write_text("signatures", &mut swapped);
+        swapped.extend(to_vec(&signatures).unwrap());
+        write_text("signed", &mut swapped);
+        swapped.extend(&signed);
+        assert_matches!(Cbor::decode_signed(&swapped), Err(Error::Encoding(_)));
+    }
+}
@@ -0,0 +1,458 @@
+use data_encoding::BASE64;
+use serde::de::DeserializeOwned;
+use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::crypto::{KeyId, Signature, SignatureValue};
+use crate::error::Error;
+use crate::pouf::Pouf;
+use crate::Result;
+
+/// TUF metadata wrapped in a [Dead Simple Signing Envelope] (DSSE), as supported by python-tuf
+/// and go-tuf.
+///
+/// The `signed` portion of the metadata is the same JSON document as for [Pouf1](super::Pouf1),
+/// serialized with sorted keys and no whitespace. It is carried as the envelope's payload, and
+/// the signatures are computed over the pre-authentication encoding (PAE) of the payload and its
+/// type rather than over canonical JSON. Since the signed bytes travel in the envelope, metadata
+/// produced by other implementations verifies regardless of how they chose to serialize it.
+///
+/// # Schema
+///
+/// ```bash
+/// {
+///   "payload": PAYLOAD,
+///   "payloadType": "application/vnd.tuf+json",
+///   "signatures": [SIGNATURE, ...]
+/// }
+/// ```
+///
+/// `PAYLOAD` is the base64 encoded JSON of one of the `SIGNED` documents described in
+/// [Pouf1](super::Pouf1).
+///
+/// `SIGNATURE` is:
+///
+/// ```bash
+/// {
+///   "keyid": KEY_ID,
+///   "sig": SIG_VALUE
+/// }
+/// ```
+///
+/// `SIG_VALUE` is the base64 encoded signature over `PAE(payloadType, payload)`, where:
+///
+/// ```bash
+/// PAE(type, body) = "DSSEv1" + SP + LEN(type) + SP + type + SP + LEN(body) + SP + body
+/// ```
+///
+/// `LEN(s)` is the ASCII decimal byte length of `s`, and `SP` is a single space.
+///
+/// [Dead Simple Signing Envelope]: https://github.com/secure-systems-lab/dsse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dsse;
+
+impl Dsse {
+    /// The DSSE payload type of TUF metadata.
+    pub const PAYLOAD_TYPE: &'static str = "application/vnd.tuf+json";
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    payload: String,
+    #[serde(rename = "payloadType")]
+    payload_type: String,
+    signatures: Vec<EnvelopeSignature>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EnvelopeSignature {
+    keyid: KeyId,
+    sig: String,
+}
+
+impl Pouf for Dsse {
+    /// The payload of the envelope, which is exactly what was signed.
+    type RawData = Vec<u8>;
+
+    /// ```
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// assert_eq!(Dsse::extension(), "json");
+    /// ```
+    fn extension() -> &'static str {
+        "json"
+    }
+
+    /// ```
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// let pae = Dsse::canonicalize(&b"hello world".to_vec()).unwrap();
+    /// assert_eq!(pae, &b"DSSEv1 24 application/vnd.tuf+json 11 hello world"[..]);
+    /// ```
+    fn canonicalize(raw_data: &Self::RawData) -> Result<Vec<u8>> {
+        Ok(pae(Self::PAYLOAD_TYPE, raw_data))
+    }
+
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// let map: HashMap<String, String> = Dsse::deserialize(&br#"{"foo":"bar"}"#.to_vec()).unwrap();
+    /// assert_eq!(map["foo"], "bar");
+    /// ```
+    fn deserialize<T>(raw_data: &Self::RawData) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(serde_json::from_slice(raw_data)?)
+    }
+
+    /// ```
+    /// # use serde_json::json;
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// let raw = Dsse::serialize(&json!({"foo": "wat", "bar": "new\nline"})).unwrap();
+    /// assert_eq!(raw, br#"{"bar":"new\nline","foo":"wat"}"#);
+    /// ```
+    fn serialize<T>(data: &T) -> Result<Self::RawData>
+    where
+        T: Serialize,
+    {
+        // Going through a `Value` sorts the object keys, so equal metadata has equal payloads.
+        Ok(serde_json::to_vec(&serde_json::to_value(data)?)?)
+    }
+
+    /// ```
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// # use std::collections::HashMap;
+    /// let jsn: &[u8] = br#"{"foo": "bar", "baz": "quux"}"#;
+    /// let _: HashMap<String, String> = Dsse::from_slice(&jsn).unwrap();
+    /// ```
+    fn from_slice<T>(slice: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(serde_json::from_slice(slice)?)
+    }
+
+    /// ```
+    /// # use tuf::pouf::{Dsse, Pouf};
+    /// # use std::collections::HashMap;
+    /// let pae = Dsse::canonicalize(&br#"{"foo":"bar"}"#.to_vec()).unwrap();
+    /// let map: HashMap<String, String> = Dsse::from_canonical_slice(&pae).unwrap();
+    /// assert_eq!(map["foo"], "bar");
+    /// ```
+    fn from_canonical_slice<T>(slice: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let (payload_type, payload) = split_pae(slice)?;
+        check_payload_type(payload_type)?;
+        Self::from_slice(payload)
+    }
+
+    fn encode_signed(signatures: &[Signature], signed: &Self::RawData) -> Result<Vec<u8>> {
+        let envelope = Envelope {
+            payload: BASE64.encode(signed),
+            payload_type: Self::PAYLOAD_TYPE.into(),
+            signatures: signatures
+                .iter()
+                .map(|sig| EnvelopeSignature {
+                    keyid: sig.key_id().clone(),
+                    sig: BASE64.encode(sig.value().as_bytes()),
+                })
+                .collect(),
+        };
+
+        Ok(serde_json::to_vec(&envelope)?)
+    }
+
+    fn decode_signed(slice: &[u8]) -> Result<(Vec<Signature>, Self::RawData)> {
+        let envelope: Envelope = Self::from_slice(slice)?;
+        check_payload_type(envelope.payload_type.as_bytes())?;
+
+        let payload = decode_base64("payload", &envelope.payload)?;
+        let signatures = envelope
+            .signatures
+            .into_iter()
+            .map(|sig| {
+                let value = decode_base64("signature", &sig.sig)?;
+                Ok(Signature::new(sig.keyid, SignatureValue::new(value)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((signatures, payload))
+    }
+}
+
+/// Compute the DSSE pre-authentication encoding of `payload`.
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(payload_type.len() + payload.len() + 32);
+    buf.extend(b"DSSEv1 ");
+    buf.extend(itoa::Buffer::new().format(payload_type.len()).bytes());
+    buf.push(b' ');
+    buf.extend(payload_type.bytes());
+    buf.push(b' ');
+    buf.extend(itoa::Buffer::new().format(payload.len()).bytes());
+    buf.push(b' ');
+    buf.extend(payload);
+    buf
+}
+
+/// Split the output of [pae] back into the payload type and payload.
+fn split_pae(slice: &[u8]) -> Result<(&[u8], &[u8])> {
+    // Read a `LEN(s) SP s` field from the front of `rest`.
+    fn field<'a>(rest: &mut &'a [u8]) -> Option<&'a [u8]> {
+        let space = rest.iter().position(|&c| c == b' ')?;
+        let len: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let end = space.checked_add(1)?.checked_add(len)?;
+        let value = rest.get(space + 1..end)?;
+        *rest = &rest[end..];
+        Some(value)
+    }
+
+    let malformed = || Error::Encoding("Malformed DSSE pre-authentication encoding".into());
+
+    let mut rest = slice.strip_prefix(b"DSSEv1 ").ok_or_else(malformed)?;
+    let payload_type = field(&mut rest).ok_or_else(malformed)?;
+    rest = rest.strip_prefix(b" ").ok_or_else(malformed)?;
+    let payload = field(&mut rest).ok_or_else(malformed)?;
+    if !rest.is_empty() {
+        return Err(malformed());
+    }
+
+    Ok((payload_type, payload))
+}
+
+fn check_payload_type(payload_type: &[u8]) -> Result<()> {
+    if payload_type == Dsse::PAYLOAD_TYPE.as_bytes() {
+        Ok(())
+    } else {
+        Err(Error::Encoding(format!(
+            "Unexpected DSSE payload type {:?}",
+            String::from_utf8_lossy(payload_type)
+        )))
+    }
+}
+
+fn decode_base64(name: &str, value: &str) -> Result<Vec<u8>> {
+    BASE64
+        .decode(value.as_bytes())
+        .map_err(|e| Error::Encoding(format!("DSSE {}: {:?}", name, e)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::{Client, Config};
+    use crate::crypto::{Ed25519PrivateKey, PrivateKey};
+    use crate::metadata::{
+        MetadataPath, RawSignedMetadata, SignedMetadata, SnapshotMetadata, SnapshotMetadataBuilder,
+        TargetPath,
+    };
+    use crate::repo_builder::RepoBuilder;
+    use crate::repository::EphemeralRepository;
+    use crate::verify::verify_signatures;
+    use assert_matches::assert_matches;
+    use chrono::prelude::*;
+    use futures_executor::block_on;
+    use futures_util::io::{AsyncReadExt, Cursor};
+    use serde_json::json;
+
+    const ED25519_1_PK8: &[u8] = include_bytes!("../../tests/ed25519/ed25519-1.pk8.der");
+
+    fn snapshot() -> SnapshotMetadata {
+        SnapshotMetadataBuilder::new()
+            .expires(Utc.with_ymd_and_hms(2038, 1, 1, 0, 0, 0).unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn envelope(payload: &[u8], signatures: serde_json::Value) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "payload": BASE64.encode(payload),
+            "payloadType": Dsse::PAYLOAD_TYPE,
+            "signatures": signatures,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pae_matches_spec() {
+        // Test vector from the DSSE protocol specification.
+        assert_eq!(
+            pae("http://example.com/HelloWorld", b"hello world"),
+            &b"DSSEv1 29 http://example.com/HelloWorld 11 hello world"[..]
+        );
+        assert_eq!(pae("", b""), &b"DSSEv1 0  0 "[..]);
+    }
+
+    #[test]
+    fn split_pae_round_trips() {
+        let payload = b"a payload with spaces and\nnewlines";
+        let encoded = pae(Dsse::PAYLOAD_TYPE, payload);
+        assert_eq!(
+            split_pae(&encoded).unwrap(),
+            (Dsse::PAYLOAD_TYPE.as_bytes(), &payload[..])
+        );
+    }
+
+    #[test]
+    fn split_pae_rejects_malformed_encodings() {
+        for bad in [
+            &b""[..],
+            b"DSSEv2 1 a 1 b",
+            b"DSSEv1 1 a 2 b",
+            b"DSSEv1 1 a 1 bc",
+            b"DSSEv1 x a 1 b",
+            b"DSSEv1 1 a1 b",
+            b"DSSEv1 99999999999999999999999 a 1 b",
+        ] {
+            assert_matches!(split_pae(bad), Err(Error::Encoding(_)), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn signed_metadata_round_trips() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let snapshot = snapshot();
+        let signed = SignedMetadata::<Dsse, _>::new(&snapshot, &key).unwrap();
+
+        // The signature is over the PAE of the payload.
+        let payload = Dsse::serialize(&snapshot).unwrap();
+        assert_eq!(signed.payload().unwrap(), pae(Dsse::PAYLOAD_TYPE, &payload));
+
+        let raw = signed.to_raw().unwrap();
+        let jsn: serde_json::Value = serde_json::from_slice(raw.as_bytes()).unwrap();
+        assert_eq!(
+            jsn,
+            json!({
+                "payload": BASE64.encode(&payload),
+                "payloadType": "application/vnd.tuf+json",
+                "signatures": [{
+                    "keyid": key.public().key_id(),
+                    "sig": BASE64.encode(signed.signatures()[0].value().as_bytes()),
+                }],
+            })
+        );
+
+        assert_eq!(raw.parse_untrusted().unwrap(), signed);
+        assert_eq!(
+            *verify_signatures(&MetadataPath::snapshot(), &raw, 1, [key.public()]).unwrap(),
+            snapshot
+        );
+    }
+
+    #[test]
+    fn verifies_payloads_that_are_not_canonical() {
+        let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let snapshot = snapshot();
+
+        // Other implementations may serialize the payload however they like.
+        let payload = serde_json::to_vec_pretty(&snapshot).unwrap();
+        let sig = key.sign(&pae(Dsse::PAYLOAD_TYPE, &payload)).unwrap();
+        let raw = RawSignedMetadata::<Dsse, SnapshotMetadata>::new(envelope(
+            &payload,
+            json!([{
+                "keyid": sig.key_id(),
+                "sig": BASE64.encode(sig.value().as_bytes()),
+            }]),
+        ));
+
+        assert_eq!(
+            *verify_signatures(&MetadataPath::snapshot(), &raw, 1, [key.public()]).unwrap(),
+            snapshot
+        );
+
+        // Modifying the payload invalidates the signature.
+        let mut tampered = payload.clone();
+        tampered.push(b'\n');
+        let raw = RawSignedMetadata::<Dsse, SnapshotMetadata>::new(envelope(
+            &tampered,
+            json!([{
+                "keyid": sig.key_id(),
+                "sig": BASE64.encode(sig.value().as_bytes()),
+            }]),
+        ));
+        assert_matches!(
+            verify_signatures(&MetadataPath::snapshot(), &raw, 1, [key.public()]),
+            Err(Error::MetadataMissingSignatures { .. })
+        );
+    }
+
+    #[test]
+    fn rejects_unexpected_payload_type() {
+        let snapshot = snapshot();
+        let raw = serde_json::to_vec(&json!({
+            "payload": BASE64.encode(&Dsse::serialize(&snapshot).unwrap()),
+            "payloadType": "application/vnd.in-toto+json",
+            "signatures": [],
+        }))
+        .unwrap();
+
+        assert_matches!(
+            RawSignedMetadata::<Dsse, SnapshotMetadata>::new(raw).parse_untrusted(),
+            Err(Error::Encoding(_))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_base64() {
+        let raw = serde_json::to_vec(&json!({
+            "payload": "not base64!",
+            "payloadType": Dsse::PAYLOAD_TYPE,
+            "signatures": [],
+        }))
+        .unwrap();
+
+        assert_matches!(
+            RawSignedMetadata::<Dsse, SnapshotMetadata>::new(raw).parse_untrusted(),
+            Err(Error::Encoding(_))
+        );
+    }
+
+    #[test]
+    fn client_updates_from_dsse_repository() {
+        block_on(async {
+            let key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+            let target_path = TargetPath::new("foo").unwrap();
+
+            let mut remote = EphemeralRepository::<Dsse>::new();
+            let metadata = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&key])
+                .trusted_targets_keys(&[&key])
+                .trusted_snapshot_keys(&[&key])
+                .trusted_timestamp_keys(&[&key])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_target(target_path.clone(), Cursor::new(b"foo"))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            // The repository contains envelopes, not POUF-1 metadata.
+            let jsn: serde_json::Value =
+                serde_json::from_slice(metadata.timestamp().unwrap().as_bytes()).unwrap();
+            assert_eq!(jsn["payloadType"], Dsse::PAYLOAD_TYPE);
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::<Dsse>::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+            assert!(client.update().await.unwrap());
+
+            let mut buf = vec![];
+            client
+                .fetch_target(&target_path)
+                .await
+                .unwrap()
+                .read_to_end(&mut buf)
+                .await
+                .unwrap();
+            assert_eq!(buf, b"foo");
+        })
+    }
+}
@@ -1,11 +1,17 @@
 //! Structures and functions to aid in various TUF data pouf formats.
 
+pub(crate) mod cbor;
+pub(crate) mod dsse;
 pub(crate) mod pouf1;
+pub use cbor::Cbor;
+pub use dsse::Dsse;
 pub use pouf1::Pouf1;
 
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::crypto::Signature;
 use crate::Result;
 
 /// The format used for data interchange, serialization, and deserialization.
@@ -33,4 +39,40 @@ pub trait Pouf: Sync {
     fn from_slice<T>(slice: &[u8]) -> Result<T>
     where
         T: DeserializeOwned;
+
+    /// Read a struct from the output of `canonicalize`.
+    fn from_canonical_slice<T>(slice: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Self::from_slice(slice)
+    }
+
+    /// Encode the `signed` portion of metadata and the signatures over it into the bytes that
+    /// are stored in a repository.
+    ///
+    /// By default this is the canonicalized form of an object with `signatures` and `signed`
+    /// fields.
+    fn encode_signed(signatures: &[Signature], signed: &Self::RawData) -> Result<Vec<u8>> {
+        #[derive(Serialize)]
+        struct Signed<'a, R> {
+            signatures: &'a [Signature],
+            signed: &'a R,
+        }
+
+        Self::canonicalize(&Self::serialize(&Signed { signatures, signed })?)
+    }
+
+    /// Decode the output of `encode_signed` into the signatures and the `signed` portion of the
+    /// metadata. The signatures are not verified.
+    fn decode_signed(slice: &[u8]) -> Result<(Vec<Signature>, Self::RawData)> {
+        #[derive(Deserialize)]
+        struct Signed<R> {
+            signatures: Vec<Signature>,
+            signed: R,
+        }
+
+        let Signed { signatures, signed } = Self::from_slice(slice)?;
+        Ok((signatures, signed))
+    }
 }
@@ -30,11 +30,13 @@ pub(crate) mod shims;
 /// }
 /// ```
 ///
-/// `PUBLIC` is a base64url encoded `SubjectPublicKeyInfo` DER public key.
+/// `PUBLIC` is a hex encoded public key for `ed25519` keys, or a PEM encoded
+/// `SubjectPublicKeyInfo` for `rsa` and `ecdsa` keys.
 ///
-/// `KEY_TYPE` is a string (either `rsa` or `ed25519`).
+/// `KEY_TYPE` is a string (either `rsa`, `ed25519`, or `ecdsa`).
 ///
-/// `SCHEME` is a string (either `ed25519`, `rsassa-pss-sha256`, or `rsassa-pss-sha512`
+/// `SCHEME` is a string (either `ed25519`, `ecdsa-sha2-nistp256`, `ecdsa-sha2-nistp384`,
+/// `rsassa-pss-sha256`, `rsassa-pss-sha512`, or `rsa-pkcs1v15-sha256`)
 ///
 /// `HASH_VALUE` is a hex encoded hash value.
 ///
@@ -145,10 +147,13 @@ pub(crate) mod shims;
 ///   "roles": {
 ///     ROLE: DELEGATION,
 ///     ...
-///   }
+///   },
+///   "succinct_roles": SUCCINCT_ROLES
 /// }
 /// ```
 ///
+/// Exactly one of `roles` and `succinct_roles` must be present.
+///
 /// `DELEGATION` is:
 ///
 /// ```bash
@@ -157,13 +162,32 @@ pub(crate) mod shims;
 ///   "threshold": NATURAL_NUMBER,
 ///   "terminating": BOOLEAN,
 ///   "keyids": [KEY_ID, ...],
-///   "paths": [PATH, ...]
+///   "paths": [PATH_PATTERN, ...],
+///   "path_hash_prefixes": [HASH_PREFIX, ...]
 /// }
 /// ```
 ///
+/// Exactly one of `paths` and `path_hash_prefixes` must be present.
+///
 /// `ROLE` is a string,
 ///
-/// `PATH` is a string.
+/// `PATH_PATTERN` is a string containing a shell-style wildcard pattern (`*`, `?`, `[seq]` and
+/// `[!seq]`), where `*` does not match across `/`.
+///
+/// `HASH_PREFIX` is a lowercase hex prefix of the SHA-256 digest of a target path.
+///
+/// `SUCCINCT_ROLES` describes `2^BIT_LENGTH` hashed bins named `NAME_PREFIX-XX`, as in TAP 15:
+///
+/// ```bash
+/// {
+///   "keyids": [KEY_ID, ...],
+///   "threshold": NATURAL_NUMBER,
+///   "bit_length": BIT_LENGTH,
+///   "name_prefix": ROLE
+/// }
+/// ```
+///
+/// `BIT_LENGTH` is an integer in the range `[1, 32]`.
 ///
 /// ## `TimestampMetadata`
 ///
@@ -261,6 +285,39 @@ impl Pouf for Pouf1 {
     {
         Ok(serde_json::from_slice(slice)?)
     }
+
+    /// ```
+    /// # use tuf::pouf::{Pouf, Pouf1};
+    /// # use std::collections::HashMap;
+    /// let jsn: &[u8] = b"{\"foo\":\"new\nline\"}";
+    /// let map: HashMap<String, String> = Pouf1::from_canonical_slice(&jsn).unwrap();
+    /// assert_eq!(map["foo"], "new\nline");
+    /// ```
+    fn from_canonical_slice<T>(slice: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        // Canonical JSON leaves control characters in strings unescaped, which isn't valid JSON,
+        // so escape them before handing the bytes to serde_json.
+        let mut escaped = Vec::with_capacity(slice.len());
+        let mut in_string = false;
+        let mut in_escape = false;
+        for &c in slice {
+            if in_escape {
+                in_escape = false;
+            } else if c == b'\\' {
+                in_escape = in_string;
+            } else if c == b'"' {
+                in_string = !in_string;
+            } else if in_string && c < 0x20 {
+                escaped.extend(format!("\\u{:04x}", c).as_bytes());
+                continue;
+            }
+            escaped.push(c);
+        }
+
+        Ok(serde_json::from_slice(&escaped)?)
+    }
 }
 
 fn canonicalize(jsn: &serde_json::Value) -> std::result::Result<Vec<u8>, String> {
@@ -298,10 +355,7 @@ impl Value {
                 buf.extend(itoa::Buffer::new().format(n).bytes());
             }
             Value::String(ref s) => {
-                // this mess is abusing serde_json to get json escaping
-                let s = serde_json::Value::String(s.clone());
-                let s = serde_json::to_string(&s).map_err(|e| format!("{:?}", e))?;
-                buf.extend(s.as_bytes());
+                write_string(s, buf);
             }
             Value::Array(ref arr) => {
                 buf.push(b'[');
@@ -324,10 +378,7 @@ impl Value {
                     }
                     first = false;
 
-                    // this mess is abusing serde_json to get json escaping
-                    let k = serde_json::Value::String(k.clone());
-                    let k = serde_json::to_string(&k).map_err(|e| format!("{:?}", e))?;
-                    buf.extend(k.as_bytes());
+                    write_string(k, buf);
 
                     buf.push(b':');
                     v.write(buf)?;
@@ -339,6 +390,22 @@ impl Value {
     }
 }
 
+/// Write a string as an [OLPC canonical JSON](http://wiki.laptop.org/go/Canonical_JSON) string.
+///
+/// Only `"` and `\` are escaped, so unlike regular JSON, control characters such as the newlines
+/// in PEM encoded keys are written as is. This matches python-tuf and go-tuf, which is necessary
+/// to agree on key IDs and signatures.
+fn write_string(s: &str, buf: &mut Vec<u8>) {
+    buf.push(b'"');
+    for c in s.bytes() {
+        if c == b'"' || c == b'\\' {
+            buf.push(b'\\');
+        }
+        buf.push(c);
+    }
+    buf.push(b'"');
+}
+
 enum Number {
     I64(i64),
     U64(u64),
@@ -407,6 +474,26 @@ mod test {
         let jsn = Value::Object(map);
         let mut out = Vec::new();
         jsn.write(&mut out).unwrap();
-        assert_eq!(&out, &b"{\"lol\":[\"haha\",\"new\\nline\"]}");
+        assert_eq!(&out, &b"{\"lol\":[\"haha\",\"new\nline\"]}");
+    }
+
+    #[test]
+    fn canonicalize_round_trips_control_characters() {
+        let jsn = serde_json::json!({
+            "a\nb": ["\"\\", "-----BEGIN PUBLIC KEY-----\nabc\n-----END PUBLIC KEY-----\n"],
+        });
+        let canonical = Pouf1::canonicalize(&jsn).unwrap();
+        assert!(canonical.contains(&b'\n'));
+
+        let decoded: serde_json::Value = Pouf1::from_canonical_slice(&canonical).unwrap();
+        assert_eq!(decoded, jsn);
+    }
+
+    #[test]
+    fn write_str_escapes_only_quote_and_backslash() {
+        let jsn = Value::String(String::from("a\"b\\c\td\u{e9}"));
+        let mut out = Vec::new();
+        jsn.write(&mut out).unwrap();
+        assert_eq!(out, "\"a\\\"b\\\\c\td\u{e9}\"".as_bytes());
     }
 }
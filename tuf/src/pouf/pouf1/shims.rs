@@ -13,14 +13,21 @@ use {
     },
 };
 
-const SPEC_VERSION: &str = "1.0";
-
-// Ensure the given spec version matches our spec version.
+// Parse the given spec version, and ensure it has the same major version as our spec version.
 //
-// We also need to handle the literal "1.0" here, despite that fact that it is not a valid version
+// `SpecVersion` also handles the literal "1.0", despite that fact that it is not a valid version
 // according to the SemVer spec, because it is already baked into some of the old roots.
-fn valid_spec_version(other: &str) -> bool {
-    matches!(other, "1.0" | "1.0.0")
+fn parse_spec_version(spec_version: &str) -> Result<metadata::SpecVersion> {
+    let spec_version = spec_version.parse::<metadata::SpecVersion>()?;
+
+    if !spec_version.is_supported() {
+        return Err(Error::Encoding(format!(
+            "Unknown spec version {}",
+            spec_version
+        )));
+    }
+
+    Ok(spec_version)
 }
 
 fn parse_datetime(ts: &str) -> Result<DateTime<Utc>> {
@@ -52,13 +59,15 @@ pub struct RootMetadata {
     #[serde(deserialize_with = "deserialize_reject_duplicates::deserialize")]
     keys: BTreeMap<crypto::KeyId, crypto::PublicKey>,
     roles: RoleDefinitions,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl RootMetadata {
     pub fn from(meta: &metadata::RootMetadata) -> Result<Self> {
         Ok(RootMetadata {
             typ: metadata::Role::Root,
-            spec_version: SPEC_VERSION.to_string(),
+            spec_version: meta.spec_version().to_string(),
             version: meta.version(),
             expires: format_datetime(meta.expires()),
             consistent_snapshot: meta.consistent_snapshot(),
@@ -73,6 +82,11 @@ impl RootMetadata {
                 targets: meta.targets().clone(),
                 timestamp: meta.timestamp().clone(),
             },
+            additional_fields: meta
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         })
     }
 
@@ -84,12 +98,7 @@ impl RootMetadata {
             )));
         }
 
-        if !valid_spec_version(&self.spec_version) {
-            return Err(Error::Encoding(format!(
-                "Unknown spec version {}",
-                self.spec_version
-            )));
-        }
+        let spec_version = parse_spec_version(&self.spec_version)?;
 
         // Ignore all keys with incorrect key IDs. We should give an error if the key ID is not
         // correct according to TUF spec. However, due to backward compatibility, we may receive
@@ -109,7 +118,9 @@ impl RootMetadata {
             self.roles.snapshot,
             self.roles.targets,
             self.roles.timestamp,
-        )
+        )?
+        .with_spec_version(spec_version)?
+        .with_additional_fields(self.additional_fields.into_iter().collect())
     }
 }
 
@@ -126,6 +137,8 @@ pub struct RoleDefinition<M: Metadata> {
     threshold: u32,
     #[serde(rename = "keyids")]
     key_ids: Vec<crypto::KeyId>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
     #[serde(skip)]
     _metadata: PhantomData<M>,
 }
@@ -139,6 +152,11 @@ impl<M: Metadata> From<&metadata::RoleDefinition<M>> for RoleDefinition<M> {
         RoleDefinition {
             threshold: role.threshold(),
             key_ids,
+            additional_fields: role
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
             _metadata: PhantomData,
         }
     }
@@ -160,7 +178,8 @@ impl<M: Metadata> TryFrom<RoleDefinition<M>> for metadata::RoleDefinition<M> {
             }
         }
 
-        metadata::RoleDefinition::new(definition.threshold, key_ids)
+        metadata::RoleDefinition::new(definition.threshold, key_ids)?
+            .with_additional_fields(definition.additional_fields.into_iter().collect())
     }
 }
 
@@ -172,6 +191,8 @@ pub struct TimestampMetadata {
     version: u32,
     expires: String,
     meta: TimestampMeta,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -185,12 +206,17 @@ impl TimestampMetadata {
     pub fn from(metadata: &metadata::TimestampMetadata) -> Result<Self> {
         Ok(TimestampMetadata {
             typ: metadata::Role::Timestamp,
-            spec_version: SPEC_VERSION.to_string(),
+            spec_version: metadata.spec_version().to_string(),
             version: metadata.version(),
             expires: format_datetime(metadata.expires()),
             meta: TimestampMeta {
                 snapshot: metadata.snapshot().clone(),
             },
+            additional_fields: metadata
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         })
     }
 
@@ -202,18 +228,15 @@ impl TimestampMetadata {
             )));
         }
 
-        if !valid_spec_version(&self.spec_version) {
-            return Err(Error::Encoding(format!(
-                "Unknown spec version {}",
-                self.spec_version
-            )));
-        }
+        let spec_version = parse_spec_version(&self.spec_version)?;
 
         metadata::TimestampMetadata::new(
             self.version,
             parse_datetime(&self.expires)?,
             self.meta.snapshot,
-        )
+        )?
+        .with_spec_version(spec_version)?
+        .with_additional_fields(self.additional_fields.into_iter().collect())
     }
 }
 
@@ -226,13 +249,15 @@ pub struct SnapshotMetadata {
     expires: String,
     #[serde(deserialize_with = "deserialize_reject_duplicates::deserialize")]
     meta: BTreeMap<String, metadata::MetadataDescription<metadata::TargetsMetadata>>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl SnapshotMetadata {
     pub fn from(metadata: &metadata::SnapshotMetadata) -> Result<Self> {
         Ok(SnapshotMetadata {
             typ: metadata::Role::Snapshot,
-            spec_version: SPEC_VERSION.to_string(),
+            spec_version: metadata.spec_version().to_string(),
             version: metadata.version(),
             expires: format_datetime(metadata.expires()),
             meta: metadata
@@ -240,6 +265,11 @@ impl SnapshotMetadata {
                 .iter()
                 .map(|(p, d)| (format!("{}.json", p), d.clone()))
                 .collect(),
+            additional_fields: metadata
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         })
     }
 
@@ -251,12 +281,7 @@ impl SnapshotMetadata {
             )));
         }
 
-        if !valid_spec_version(&self.spec_version) {
-            return Err(Error::Encoding(format!(
-                "Unknown spec version {}",
-                self.spec_version
-            )));
-        }
+        let spec_version = parse_spec_version(&self.spec_version)?;
 
         metadata::SnapshotMetadata::new(
             self.version,
@@ -277,7 +302,9 @@ impl SnapshotMetadata {
                     Ok((p, d))
                 })
                 .collect::<Result<_>>()?,
-        )
+        )?
+        .with_spec_version(spec_version)?
+        .with_additional_fields(self.additional_fields.into_iter().collect())
     }
 }
 
@@ -291,13 +318,15 @@ pub struct TargetsMetadata {
     targets: BTreeMap<metadata::TargetPath, metadata::TargetDescription>,
     #[serde(default, skip_serializing_if = "metadata::Delegations::is_empty")]
     delegations: metadata::Delegations,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl TargetsMetadata {
     pub fn from(metadata: &metadata::TargetsMetadata) -> Result<Self> {
         Ok(TargetsMetadata {
             typ: metadata::Role::Targets,
-            spec_version: SPEC_VERSION.to_string(),
+            spec_version: metadata.spec_version().to_string(),
             version: metadata.version(),
             expires: format_datetime(metadata.expires()),
             targets: metadata
@@ -306,6 +335,11 @@ impl TargetsMetadata {
                 .map(|(p, d)| (p.clone(), d.clone()))
                 .collect(),
             delegations: metadata.delegations().clone(),
+            additional_fields: metadata
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         })
     }
 
@@ -317,19 +351,16 @@ impl TargetsMetadata {
             )));
         }
 
-        if !valid_spec_version(&self.spec_version) {
-            return Err(Error::Encoding(format!(
-                "Unknown spec version {}",
-                self.spec_version
-            )));
-        }
+        let spec_version = parse_spec_version(&self.spec_version)?;
 
         metadata::TargetsMetadata::new(
             self.version,
             parse_datetime(&self.expires)?,
             self.targets.into_iter().collect(),
             self.delegations,
-        )
+        )?
+        .with_spec_version(spec_version)?
+        .with_additional_fields(self.additional_fields.into_iter().collect())
     }
 }
 
@@ -340,6 +371,8 @@ pub struct PublicKey {
     #[serde(skip_serializing_if = "Option::is_none")]
     keyid_hash_algorithms: Option<Vec<String>>,
     keyval: PublicKeyValue,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl PublicKey {
@@ -348,12 +381,14 @@ impl PublicKey {
         scheme: crypto::SignatureScheme,
         keyid_hash_algorithms: Option<Vec<String>>,
         public_key: String,
+        additional_fields: BTreeMap<String, serde_json::Value>,
     ) -> Self {
         PublicKey {
             keytype,
             scheme,
             keyid_hash_algorithms,
             keyval: PublicKeyValue { public: public_key },
+            additional_fields,
         }
     }
 
@@ -372,6 +407,10 @@ impl PublicKey {
     pub fn keyid_hash_algorithms(&self) -> &Option<Vec<String>> {
         &self.keyid_hash_algorithms
     }
+
+    pub fn additional_fields(&self) -> &BTreeMap<String, serde_json::Value> {
+        &self.additional_fields
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -386,18 +425,16 @@ pub struct Delegation {
     threshold: u32,
     #[serde(rename = "keyids")]
     key_ids: Vec<crypto::KeyId>,
-    paths: Vec<metadata::TargetPath>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    paths: Option<Vec<metadata::PathPattern>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path_hash_prefixes: Option<Vec<metadata::PathHashPrefix>>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl From<&metadata::Delegation> for Delegation {
     fn from(delegation: &metadata::Delegation) -> Self {
-        let mut paths = delegation
-            .paths()
-            .iter()
-            .cloned()
-            .collect::<Vec<metadata::TargetPath>>();
-        paths.sort();
-
         let mut key_ids = delegation
             .key_ids()
             .iter()
@@ -405,12 +442,38 @@ impl From<&metadata::Delegation> for Delegation {
             .collect::<Vec<crypto::KeyId>>();
         key_ids.sort();
 
+        let (paths, path_hash_prefixes) = if delegation.path_hash_prefixes().is_empty() {
+            let mut paths = delegation
+                .paths()
+                .iter()
+                .cloned()
+                .collect::<Vec<metadata::PathPattern>>();
+            paths.sort();
+
+            (Some(paths), None)
+        } else {
+            let mut path_hash_prefixes = delegation
+                .path_hash_prefixes()
+                .iter()
+                .cloned()
+                .collect::<Vec<metadata::PathHashPrefix>>();
+            path_hash_prefixes.sort();
+
+            (None, Some(path_hash_prefixes))
+        };
+
         Delegation {
             name: delegation.name().clone(),
             terminating: delegation.terminating(),
             threshold: delegation.threshold(),
             key_ids,
             paths,
+            path_hash_prefixes,
+            additional_fields: delegation
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         }
     }
 }
@@ -426,20 +489,56 @@ impl TryFrom<Delegation> for metadata::Delegation {
             return Err(Error::Encoding("Non-unique delegation key IDs.".into()));
         }
 
-        let delegation_paths_len = delegation.paths.len();
-        let paths = delegation.paths.into_iter().collect::<HashSet<_>>();
+        let additional_fields = delegation.additional_fields.into_iter().collect();
 
-        if paths.len() != delegation_paths_len {
-            return Err(Error::Encoding("Non-unique delegation paths.".into()));
-        }
+        let delegation = match (delegation.paths, delegation.path_hash_prefixes) {
+            (Some(paths), None) => {
+                let delegation_paths_len = paths.len();
+                let paths = paths.into_iter().collect::<HashSet<_>>();
 
-        metadata::Delegation::new(
-            delegation.name,
-            delegation.terminating,
-            delegation.threshold,
-            key_ids,
-            paths,
-        )
+                if paths.len() != delegation_paths_len {
+                    return Err(Error::Encoding("Non-unique delegation paths.".into()));
+                }
+
+                metadata::Delegation::new(
+                    delegation.name,
+                    delegation.terminating,
+                    delegation.threshold,
+                    key_ids,
+                    paths,
+                )?
+            }
+            (None, Some(path_hash_prefixes)) => {
+                let delegation_path_hash_prefixes_len = path_hash_prefixes.len();
+                let path_hash_prefixes = path_hash_prefixes.into_iter().collect::<HashSet<_>>();
+
+                if path_hash_prefixes.len() != delegation_path_hash_prefixes_len {
+                    return Err(Error::Encoding(
+                        "Non-unique delegation path hash prefixes.".into(),
+                    ));
+                }
+
+                metadata::Delegation::new_with_path_hash_prefixes(
+                    delegation.name,
+                    delegation.terminating,
+                    delegation.threshold,
+                    key_ids,
+                    path_hash_prefixes,
+                )?
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::Encoding(
+                    "Delegation cannot have both paths and path hash prefixes.".into(),
+                ))
+            }
+            (None, None) => {
+                return Err(Error::Encoding(
+                    "Delegation must have either paths or path hash prefixes.".into(),
+                ))
+            }
+        };
+
+        delegation.with_additional_fields(additional_fields)
     }
 }
 
@@ -447,19 +546,31 @@ impl TryFrom<Delegation> for metadata::Delegation {
 pub struct Delegations {
     #[serde(deserialize_with = "deserialize_reject_duplicates::deserialize")]
     keys: BTreeMap<crypto::KeyId, crypto::PublicKey>,
-    roles: Vec<Delegation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    roles: Option<Vec<Delegation>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    succinct_roles: Option<SuccinctRoles>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl From<&metadata::Delegations> for Delegations {
     fn from(delegations: &metadata::Delegations) -> Delegations {
-        let mut roles = delegations
-            .roles()
-            .iter()
-            .map(Delegation::from)
-            .collect::<Vec<Delegation>>();
-
-        // We want our roles in a consistent order.
-        roles.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        let (roles, succinct_roles) = match delegations.succinct_roles() {
+            Some(succinct_roles) => (None, Some(SuccinctRoles::from(succinct_roles))),
+            None => {
+                let mut roles = delegations
+                    .roles()
+                    .iter()
+                    .map(Delegation::from)
+                    .collect::<Vec<Delegation>>();
+
+                // We want our roles in a consistent order.
+                roles.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+
+                (Some(roles), None)
+            }
+        };
 
         Delegations {
             keys: delegations
@@ -468,6 +579,12 @@ impl From<&metadata::Delegations> for Delegations {
                 .map(|(id, key)| (id.clone(), key.clone()))
                 .collect(),
             roles,
+            succinct_roles,
+            additional_fields: delegations
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         }
     }
 }
@@ -476,14 +593,88 @@ impl TryFrom<Delegations> for metadata::Delegations {
     type Error = Error;
 
     fn try_from(delegations: Delegations) -> Result<metadata::Delegations> {
-        metadata::Delegations::new(
-            delegations.keys.into_iter().collect(),
-            delegations
-                .roles
-                .into_iter()
-                .map(|delegation| delegation.try_into())
-                .collect::<Result<Vec<_>>>()?,
-        )
+        let keys = delegations.keys.into_iter().collect();
+        let additional_fields = delegations.additional_fields.into_iter().collect();
+
+        let delegations = match (delegations.roles, delegations.succinct_roles) {
+            (Some(roles), None) => metadata::Delegations::new(
+                keys,
+                roles
+                    .into_iter()
+                    .map(|delegation| delegation.try_into())
+                    .collect::<Result<Vec<_>>>()?,
+            )?,
+            (None, Some(succinct_roles)) => {
+                metadata::Delegations::new_with_succinct_roles(keys, succinct_roles.try_into()?)?
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::Encoding(
+                    "Delegations cannot have both roles and succinct roles.".into(),
+                ))
+            }
+            (None, None) => {
+                return Err(Error::Encoding(
+                    "Delegations must have either roles or succinct roles.".into(),
+                ))
+            }
+        };
+
+        delegations.with_additional_fields(additional_fields)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SuccinctRoles {
+    #[serde(rename = "keyids")]
+    key_ids: Vec<crypto::KeyId>,
+    threshold: u32,
+    bit_length: u8,
+    name_prefix: String,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
+}
+
+impl From<&metadata::SuccinctRoles> for SuccinctRoles {
+    fn from(succinct_roles: &metadata::SuccinctRoles) -> Self {
+        let mut key_ids = succinct_roles
+            .key_ids()
+            .iter()
+            .cloned()
+            .collect::<Vec<crypto::KeyId>>();
+        key_ids.sort();
+
+        SuccinctRoles {
+            key_ids,
+            threshold: succinct_roles.threshold(),
+            bit_length: succinct_roles.bit_length(),
+            name_prefix: succinct_roles.name_prefix().into(),
+            additional_fields: succinct_roles
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SuccinctRoles> for metadata::SuccinctRoles {
+    type Error = Error;
+
+    fn try_from(succinct_roles: SuccinctRoles) -> Result<Self> {
+        let succinct_roles_key_ids_len = succinct_roles.key_ids.len();
+        let key_ids = succinct_roles.key_ids.into_iter().collect::<HashSet<_>>();
+
+        if key_ids.len() != succinct_roles_key_ids_len {
+            return Err(Error::Encoding("Non-unique succinct roles key IDs.".into()));
+        }
+
+        metadata::SuccinctRoles::new(
+            key_ids,
+            succinct_roles.threshold,
+            succinct_roles.bit_length,
+            succinct_roles.name_prefix,
+        )?
+        .with_additional_fields(succinct_roles.additional_fields.into_iter().collect())
     }
 }
 
@@ -493,6 +684,8 @@ pub struct TargetDescription {
     hashes: BTreeMap<crypto::HashAlgorithm, crypto::HashValue>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     custom: BTreeMap<String, serde_json::Value>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
 }
 
 impl From<&metadata::TargetDescription> for TargetDescription {
@@ -509,6 +702,11 @@ impl From<&metadata::TargetDescription> for TargetDescription {
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
+            additional_fields: description
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
         }
     }
 }
@@ -521,7 +719,8 @@ impl TryFrom<TargetDescription> for metadata::TargetDescription {
             description.length,
             description.hashes.into_iter().collect(),
             description.custom.into_iter().collect(),
-        )
+        )?
+        .with_additional_fields(description.additional_fields.into_iter().collect())
     }
 }
 
@@ -532,6 +731,8 @@ pub struct MetadataDescription<M: Metadata> {
     length: Option<usize>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     hashes: BTreeMap<crypto::HashAlgorithm, crypto::HashValue>,
+    #[serde(flatten)]
+    additional_fields: BTreeMap<String, serde_json::Value>,
     #[serde(skip)]
     _metadata: PhantomData<M>,
 }
@@ -546,6 +747,11 @@ impl<M: Metadata> From<&metadata::MetadataDescription<M>> for MetadataDescriptio
                 .iter()
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
+            additional_fields: description
+                .additional_fields()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
             _metadata: PhantomData,
         }
     }
@@ -559,7 +765,8 @@ impl<M: Metadata> TryFrom<MetadataDescription<M>> for metadata::MetadataDescript
             description.version,
             description.length,
             description.hashes.into_iter().collect(),
-        )
+        )?
+        .with_additional_fields(description.additional_fields.into_iter().collect())
     }
 }
 
@@ -618,17 +825,31 @@ mod test {
 
     #[test]
     fn spec_version_validation() {
-        let valid_spec_versions = ["1.0.0", "1.0"];
+        let valid_spec_versions = ["1.0.0", "1.0", "1.0.1", "1.0.31", "1.1.0", "1.23.4"];
 
         for version in valid_spec_versions {
-            assert!(valid_spec_version(version), "{:?} should be valid", version);
+            let parsed = parse_spec_version(version)
+                .unwrap_or_else(|err| panic!("{:?} should be valid: {:?}", version, err));
+            assert_eq!(parsed.to_string(), version);
         }
 
-        let invalid_spec_versions = ["1.0.1", "1.1.0", "2.0.0", "3.0"];
+        let invalid_spec_versions = [
+            "2.0.0",
+            "3.0",
+            "0.9.0",
+            "1",
+            "1.0.0.0",
+            "v1.0.0",
+            "01.0.0",
+            "1.0.0-rc1",
+            "1.0.0+build",
+            "1..0",
+            "",
+        ];
 
         for version in invalid_spec_versions {
             assert!(
-                !valid_spec_version(version),
+                parse_spec_version(version).is_err(),
                 "{:?} should be invalid",
                 version
             );
@@ -2,24 +2,29 @@
 
 use {
     crate::{
-        crypto::{self, HashAlgorithm, PrivateKey, PublicKey},
+        crypto::{self, HashAlgorithm, KeyId, PublicKey, Signer},
         database::Database,
         error::{Error, Result},
         metadata::{
             Delegation, DelegationsBuilder, Metadata, MetadataDescription, MetadataPath,
             MetadataVersion, RawSignedMetadata, RawSignedMetadataSet, RawSignedMetadataSetBuilder,
-            RootMetadata, RootMetadataBuilder, SignedMetadataBuilder, SnapshotMetadata,
-            SnapshotMetadataBuilder, TargetDescription, TargetPath, TargetsMetadata,
-            TargetsMetadataBuilder, TimestampMetadata, TimestampMetadataBuilder,
+            Role, RootMetadata, RootMetadataBuilder, SignedMetadata, SignedMetadataBuilder,
+            SnapshotMetadata, SnapshotMetadataBuilder, SuccinctRoles, TargetDescription,
+            TargetPath, TargetsMetadata, TargetsMetadataBuilder, TimestampMetadata,
+            TimestampMetadataBuilder,
         },
         pouf::Pouf,
-        repository::RepositoryStorage,
-        verify::Verified,
+        repository::{RepositoryProvider, RepositoryStorage},
+        verify::{self, Verified},
     },
     chrono::{DateTime, Duration, Utc},
     futures_io::{AsyncRead, AsyncSeek},
-    futures_util::AsyncSeekExt as _,
-    std::{collections::HashMap, io::SeekFrom, marker::PhantomData},
+    futures_util::{AsyncReadExt as _, AsyncSeekExt as _},
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        io::SeekFrom,
+        marker::PhantomData,
+    },
 };
 
 mod private {
@@ -62,9 +67,8 @@ impl State for Root {}
 #[doc(hidden)]
 pub struct Targets<D: Pouf> {
     staged_root: Option<Staged<D, RootMetadata>>,
-    targets: HashMap<TargetPath, TargetDescription>,
-    delegation_keys: Vec<PublicKey>,
-    delegation_roles: Vec<Delegation>,
+    changes: TargetsChanges,
+    delegated_targets: BTreeMap<MetadataPath, TargetsChanges>,
     file_hash_algorithms: Vec<HashAlgorithm>,
     inherit_from_trusted_targets: bool,
 }
@@ -73,15 +77,172 @@ impl<D: Pouf> Targets<D> {
     fn new(staged_root: Option<Staged<D, RootMetadata>>) -> Self {
         Self {
             staged_root,
-            targets: HashMap::new(),
-            delegation_keys: vec![],
-            delegation_roles: vec![],
+            changes: TargetsChanges::default(),
+            delegated_targets: BTreeMap::new(),
             file_hash_algorithms: vec![HashAlgorithm::Sha256],
             inherit_from_trusted_targets: true,
         }
     }
 }
 
+/// Changes to make to a targets metadata, on top of whatever it inherits from the trusted version.
+///
+/// Changes to delegated targets roles are staged right before the snapshot, since a delegated role
+/// may be changed without staging a new top-level targets metadata.
+#[derive(Default)]
+struct TargetsChanges {
+    targets: HashMap<TargetPath, TargetDescription>,
+    removed_targets: HashSet<TargetPath>,
+    delegation_keys: Vec<PublicKey>,
+    removed_delegation_keys: HashSet<KeyId>,
+    delegation_roles: Vec<Delegation>,
+    removed_delegation_roles: HashSet<MetadataPath>,
+    delegation_succinct_roles: Option<SuccinctRoles>,
+}
+
+impl TargetsChanges {
+    fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+            && self.removed_targets.is_empty()
+            && self.delegation_keys.is_empty()
+            && self.removed_delegation_keys.is_empty()
+            && self.delegation_roles.is_empty()
+            && self.removed_delegation_roles.is_empty()
+            && self.delegation_succinct_roles.is_none()
+    }
+
+    fn add_target(&mut self, target_path: TargetPath, target_description: TargetDescription) {
+        self.removed_targets.remove(&target_path);
+        self.targets.insert(target_path, target_description);
+    }
+
+    fn remove_target(&mut self, target_path: TargetPath) {
+        self.targets.remove(&target_path);
+        self.removed_targets.insert(target_path);
+    }
+
+    fn add_delegation_key(&mut self, key: PublicKey) {
+        self.removed_delegation_keys.remove(key.key_id());
+        self.delegation_keys.push(key);
+    }
+
+    fn remove_delegation_key(&mut self, key_id: KeyId) {
+        self.delegation_keys.retain(|key| key.key_id() != &key_id);
+        self.removed_delegation_keys.insert(key_id);
+    }
+
+    fn add_delegation_role(&mut self, delegation: Delegation) {
+        self.removed_delegation_roles.remove(delegation.name());
+        self.delegation_roles.push(delegation);
+    }
+
+    fn remove_delegation_role(&mut self, role: MetadataPath) {
+        self.delegation_roles
+            .retain(|delegation| delegation.name() != &role);
+        self.removed_delegation_roles.insert(role);
+    }
+
+    /// Apply these changes to `targets_builder`, on top of the targets and delegations inherited
+    /// from `trusted_targets`.
+    fn apply(
+        &self,
+        mut targets_builder: TargetsMetadataBuilder,
+        trusted_targets: Option<&TargetsMetadata>,
+    ) -> Result<TargetsMetadataBuilder> {
+        let mut delegations_builder = DelegationsBuilder::new();
+
+        if let Some(trusted_targets) = trusted_targets {
+            targets_builder = targets_builder.spec_version(trusted_targets.spec_version());
+
+            for (name, value) in trusted_targets.additional_fields() {
+                targets_builder = targets_builder.additional_field(name.clone(), value.clone());
+            }
+
+            for (name, value) in trusted_targets.delegations().additional_fields() {
+                delegations_builder =
+                    delegations_builder.additional_field(name.clone(), value.clone());
+            }
+
+            for (target_path, target_description) in trusted_targets.targets() {
+                targets_builder = targets_builder
+                    .insert_target_description(target_path.clone(), target_description.clone());
+            }
+
+            for key in trusted_targets.delegations().keys().values() {
+                delegations_builder = delegations_builder.key(key.clone());
+            }
+
+            for role in trusted_targets.delegations().roles() {
+                delegations_builder = delegations_builder.role(role.clone());
+            }
+
+            if let Some(succinct_roles) = trusted_targets.delegations().succinct_roles() {
+                delegations_builder = delegations_builder.succinct_roles(succinct_roles.clone());
+            }
+        }
+
+        for target_path in &self.removed_targets {
+            targets_builder = targets_builder.remove_target(target_path);
+        }
+
+        for key_id in &self.removed_delegation_keys {
+            delegations_builder = delegations_builder.remove_key(key_id);
+        }
+
+        for role in &self.removed_delegation_roles {
+            delegations_builder = delegations_builder.remove_role(role);
+        }
+
+        // Overwrite any of the old targets with the new ones.
+        for (target_path, target_description) in &self.targets {
+            targets_builder = targets_builder
+                .insert_target_description(target_path.clone(), target_description.clone());
+        }
+
+        // Overwrite the old delegation keys.
+        for key in &self.delegation_keys {
+            delegations_builder = delegations_builder.key(key.clone());
+        }
+
+        // Overwrite the old delegation roles.
+        for role in &self.delegation_roles {
+            delegations_builder = delegations_builder.role(role.clone());
+        }
+
+        if let Some(succinct_roles) = &self.delegation_succinct_roles {
+            delegations_builder = delegations_builder.succinct_roles(succinct_roles.clone());
+        }
+
+        let delegations = delegations_builder.build()?;
+
+        // Make sure we didn't remove a key that a delegation still needs.
+        let delegated_key_ids = delegations
+            .roles()
+            .iter()
+            .map(|role| (role.name().to_string(), role.key_ids()))
+            .chain(delegations.succinct_roles().map(|succinct_roles| {
+                (
+                    succinct_roles.name_prefix().to_string(),
+                    succinct_roles.key_ids(),
+                )
+            }));
+
+        for (role, key_ids) in delegated_key_ids {
+            if let Some(key_id) = key_ids
+                .iter()
+                .find(|key_id| !delegations.keys().contains_key(key_id))
+            {
+                return Err(Error::IllegalArgument(format!(
+                    "delegation {} refers to key {:?}, which is not a delegation key",
+                    role, key_id
+                )));
+            }
+        }
+
+        Ok(targets_builder.delegations(delegations))
+    }
+}
+
 impl<D: Pouf> State for Targets<D> {}
 
 /// State to stage a snapshot metadata.
@@ -89,6 +250,9 @@ impl<D: Pouf> State for Targets<D> {}
 pub struct Snapshot<D: Pouf> {
     staged_root: Option<Staged<D, RootMetadata>>,
     staged_targets: Option<Staged<D, TargetsMetadata>>,
+    delegated_targets: BTreeMap<MetadataPath, TargetsChanges>,
+    removed_delegation_roles: HashSet<MetadataPath>,
+    inherit_from_trusted_targets: bool,
     include_targets_length: bool,
     targets_hash_algorithms: Vec<HashAlgorithm>,
     inherit_from_trusted_snapshot: bool,
@@ -97,13 +261,18 @@ pub struct Snapshot<D: Pouf> {
 impl<D: Pouf> State for Snapshot<D> {}
 
 impl<D: Pouf> Snapshot<D> {
-    fn new(
-        staged_root: Option<Staged<D, RootMetadata>>,
-        staged_targets: Option<Staged<D, TargetsMetadata>>,
-    ) -> Self {
+    fn new(state: Targets<D>, staged_targets: Option<Staged<D, TargetsMetadata>>) -> Self {
         Self {
-            staged_root,
+            staged_root: state.staged_root,
             staged_targets,
+            removed_delegation_roles: state
+                .delegated_targets
+                .values()
+                .chain(Some(&state.changes))
+                .flat_map(|changes| changes.removed_delegation_roles.iter().cloned())
+                .collect(),
+            delegated_targets: state.delegated_targets,
+            inherit_from_trusted_targets: state.inherit_from_trusted_targets,
             include_targets_length: false,
             targets_hash_algorithms: vec![],
             inherit_from_trusted_snapshot: true,
@@ -112,46 +281,59 @@ impl<D: Pouf> Snapshot<D> {
 
     fn targets_description(&self) -> Result<Option<MetadataDescription<TargetsMetadata>>> {
         if let Some(ref targets) = self.staged_targets {
-            let length = if self.include_targets_length {
-                Some(targets.raw.as_bytes().len())
-            } else {
-                None
-            };
-
-            let hashes = if self.targets_hash_algorithms.is_empty() {
-                HashMap::new()
-            } else {
-                crypto::calculate_hashes_from_slice(
-                    targets.raw.as_bytes(),
-                    &self.targets_hash_algorithms,
-                )?
-            };
-
-            Ok(Some(MetadataDescription::new(
-                targets.metadata.version(),
-                length,
-                hashes,
-            )?))
+            Ok(Some(self.metadata_description(targets)?))
         } else {
             Ok(None)
         }
     }
+
+    fn metadata_description(
+        &self,
+        targets: &Staged<D, TargetsMetadata>,
+    ) -> Result<MetadataDescription<TargetsMetadata>> {
+        let length = if self.include_targets_length {
+            Some(targets.raw.as_bytes().len())
+        } else {
+            None
+        };
+
+        let hashes = if self.targets_hash_algorithms.is_empty() {
+            HashMap::new()
+        } else {
+            crypto::calculate_hashes_from_slice(
+                targets.raw.as_bytes(),
+                &self.targets_hash_algorithms,
+            )?
+        };
+
+        MetadataDescription::new(targets.metadata.version(), length, hashes)
+    }
 }
 
 /// State to stage a timestamp metadata.
 pub struct Timestamp<D: Pouf> {
     staged_root: Option<Staged<D, RootMetadata>>,
     staged_targets: Option<Staged<D, TargetsMetadata>>,
+    staged_delegated_targets: BTreeMap<MetadataPath, Staged<D, TargetsMetadata>>,
+    unstaged_delegated_targets: Vec<MetadataPath>,
     staged_snapshot: Option<Staged<D, SnapshotMetadata>>,
     include_snapshot_length: bool,
     snapshot_hash_algorithms: Vec<HashAlgorithm>,
 }
 
 impl<D: Pouf> Timestamp<D> {
-    fn new(state: Snapshot<D>, staged_snapshot: Option<Staged<D, SnapshotMetadata>>) -> Self {
+    fn new(
+        state: Snapshot<D>,
+        staged_delegated_targets: BTreeMap<MetadataPath, Staged<D, TargetsMetadata>>,
+        staged_snapshot: Option<Staged<D, SnapshotMetadata>>,
+    ) -> Self {
         Self {
             staged_root: state.staged_root,
             staged_targets: state.staged_targets,
+            staged_delegated_targets,
+            // Staging a snapshot stages every delegated targets with pending changes, so any that
+            // are left were skipped along with the snapshot.
+            unstaged_delegated_targets: state.delegated_targets.into_keys().collect(),
             staged_snapshot,
             include_snapshot_length: false,
             snapshot_hash_algorithms: vec![],
@@ -192,6 +374,8 @@ impl<D: Pouf> State for Timestamp<D> {}
 pub struct Done<D: Pouf> {
     staged_root: Option<Staged<D, RootMetadata>>,
     staged_targets: Option<Staged<D, TargetsMetadata>>,
+    staged_delegated_targets: BTreeMap<MetadataPath, Staged<D, TargetsMetadata>>,
+    unstaged_delegated_targets: Vec<MetadataPath>,
     staged_snapshot: Option<Staged<D, SnapshotMetadata>>,
     staged_timestamp: Option<Staged<D, TimestampMetadata>>,
 }
@@ -211,14 +395,15 @@ where
     repo: R,
     db: Option<&'a Database<D>>,
     current_time: DateTime<Utc>,
-    signing_root_keys: Vec<&'a dyn PrivateKey>,
-    signing_targets_keys: Vec<&'a dyn PrivateKey>,
-    signing_snapshot_keys: Vec<&'a dyn PrivateKey>,
-    signing_timestamp_keys: Vec<&'a dyn PrivateKey>,
-    trusted_root_keys: Vec<&'a dyn PrivateKey>,
-    trusted_targets_keys: Vec<&'a dyn PrivateKey>,
-    trusted_snapshot_keys: Vec<&'a dyn PrivateKey>,
-    trusted_timestamp_keys: Vec<&'a dyn PrivateKey>,
+    signing_root_keys: Vec<&'a dyn Signer>,
+    signing_targets_keys: Vec<&'a dyn Signer>,
+    signing_snapshot_keys: Vec<&'a dyn Signer>,
+    signing_timestamp_keys: Vec<&'a dyn Signer>,
+    signing_delegated_targets_keys: HashMap<MetadataPath, Vec<&'a dyn Signer>>,
+    trusted_root_keys: Vec<&'a dyn Signer>,
+    trusted_targets_keys: Vec<&'a dyn Signer>,
+    trusted_snapshot_keys: Vec<&'a dyn Signer>,
+    trusted_timestamp_keys: Vec<&'a dyn Signer>,
     time_version: Option<u32>,
     root_expiration_duration: Duration,
     targets_expiration_duration: Duration,
@@ -239,7 +424,12 @@ where
         }
 
         for key in &self.trusted_root_keys {
-            if root.root().key_ids().get(key.public().key_id()).is_none() {
+            if root
+                .root()
+                .key_ids()
+                .get(key.public_key().key_id())
+                .is_none()
+            {
                 return true;
             }
         }
@@ -251,7 +441,7 @@ where
             if root
                 .targets()
                 .key_ids()
-                .get(key.public().key_id())
+                .get(key.public_key().key_id())
                 .is_none()
             {
                 return true;
@@ -266,7 +456,7 @@ where
             if root
                 .snapshot()
                 .key_ids()
-                .get(key.public().key_id())
+                .get(key.public_key().key_id())
                 .is_none()
             {
                 return true;
@@ -281,7 +471,7 @@ where
             if root
                 .timestamp()
                 .key_ids()
-                .get(key.public().key_id())
+                .get(key.public_key().key_id())
                 .is_none()
             {
                 return true;
@@ -315,11 +505,10 @@ where
     }
 
     /// The next version number for non-root metadata.
-    fn non_root_next_version(
-        &self,
-        current_version: u32,
-        path: fn() -> MetadataPath,
-    ) -> Result<u32> {
+    fn non_root_next_version<F>(&self, current_version: u32, path: F) -> Result<u32>
+    where
+        F: FnOnce() -> MetadataPath,
+    {
         if let Some(time_version) = self.time_version {
             // We can only use the time version if it's larger than our current version. If not,
             // then fall back to the next version.
@@ -334,18 +523,18 @@ where
     }
 }
 
-fn sign<'a, D, I, M>(meta: &M, keys: I) -> Result<RawSignedMetadata<D, M>>
+async fn sign<'a, D, I, M>(meta: &M, keys: I) -> Result<RawSignedMetadata<D, M>>
 where
     D: Pouf,
     M: Metadata,
-    I: IntoIterator<Item = &'a &'a dyn PrivateKey>,
+    I: IntoIterator<Item = &'a &'a dyn Signer>,
 {
     // Sign the root.
     let mut signed_builder = SignedMetadataBuilder::<D, _>::from_metadata(meta)?;
     let mut has_key = false;
     for key in keys {
         has_key = true;
-        signed_builder = signed_builder.sign(*key)?;
+        signed_builder = signed_builder.sign(*key).await?;
     }
 
     // We need at least one private key to sign the metadata.
@@ -358,6 +547,123 @@ where
     signed_builder.build().to_raw()
 }
 
+/// Store metadata that is still waiting for signatures in `storage`, so that collecting the
+/// signatures can be resumed later, possibly by a different process. It is stored at the path of
+/// its role, so `storage` should be a staging area rather than the published repository.
+///
+/// See [RepoBuilder::unsigned_root_with_builder] for the whole workflow.
+pub async fn store_pending_metadata<D, M, S>(
+    storage: &S,
+    metadata: &SignedMetadata<D, M>,
+) -> Result<()>
+where
+    D: Pouf,
+    M: Metadata,
+    S: RepositoryStorage<D> + ?Sized,
+{
+    let raw = metadata.to_raw()?;
+
+    storage
+        .store_metadata(&M::ROLE.into(), MetadataVersion::None, &mut raw.as_bytes())
+        .await
+}
+
+/// Fetch metadata that was saved with [store_pending_metadata].
+pub async fn fetch_pending_metadata<D, M, P>(provider: &P) -> Result<SignedMetadata<D, M>>
+where
+    D: Pouf,
+    M: Metadata,
+    P: RepositoryProvider<D> + ?Sized,
+{
+    let mut reader = provider
+        .fetch_metadata(&M::ROLE.into(), MetadataVersion::None)
+        .await?;
+
+    let mut buf = vec![];
+    reader.read_to_end(&mut buf).await?;
+
+    RawSignedMetadata::<D, M>::new(buf).parse_untrusted()
+}
+
+/// Whether `targets` delegates `target_path` to `role`.
+fn delegates_target(
+    targets: &TargetsMetadata,
+    role: &MetadataPath,
+    target_path: &TargetPath,
+) -> bool {
+    let delegations = targets.delegations();
+
+    delegations
+        .roles()
+        .iter()
+        .any(|d| d.name() == role && d.matches_target(target_path))
+        || delegations
+            .succinct_roles()
+            .map(|succinct_roles| succinct_roles.role_for_target(target_path) == *role)
+            .unwrap_or(false)
+}
+
+/// Walk down the delegation tree from `targets` to find which of `roles` are still delegated to,
+/// looking up each delegated targets metadata in `delegations`.
+///
+/// The second value is false if we reached a role that is missing from `delegations`, since we
+/// can't know which roles it delegates to.
+fn reachable_roles<'m>(
+    targets: &TargetsMetadata,
+    delegations: &HashMap<&MetadataPath, &TargetsMetadata>,
+    roles: impl IntoIterator<Item = &'m MetadataPath>,
+) -> (HashSet<&'m MetadataPath>, bool) {
+    let mut unreached = roles
+        .into_iter()
+        .filter(|role| **role != MetadataPath::targets())
+        .collect::<Vec<_>>();
+    let mut reached = HashSet::new();
+    let mut complete = true;
+    let mut frontier = vec![targets];
+
+    while let Some(parent) = frontier.pop() {
+        let (children, rest) = unreached
+            .into_iter()
+            .partition::<Vec<_>, _>(|role| parent.delegations().delegates_to(role));
+        unreached = rest;
+
+        for child in children {
+            if let Some(child_targets) = delegations.get(child) {
+                frontier.push(child_targets);
+            } else {
+                complete = false;
+            }
+
+            reached.insert(child);
+        }
+    }
+
+    (reached, complete)
+}
+
+/// Find the role trusted by `db` that delegates to `role`, ignoring any roles in `pending` since
+/// they have not been validated yet.
+fn find_delegating_role<D: Pouf>(
+    db: &Database<D>,
+    role: &MetadataPath,
+    pending: &[&MetadataPath],
+) -> Option<MetadataPath> {
+    if let Some(trusted_targets) = db.trusted_targets() {
+        if trusted_targets.delegations().delegates_to(role) {
+            return Some(MetadataPath::targets());
+        }
+    }
+
+    db.trusted_delegations()
+        .iter()
+        .filter(|(path, targets)| {
+            !pending.contains(path) && targets.delegations().delegates_to(role)
+        })
+        .map(|(path, _)| path)
+        .min()
+        .cloned()
+}
+
 /// This helper builder simplifies the process of creating new metadata.
 pub struct RepoBuilder<'a, D, R, S = Root>
 where
@@ -415,6 +721,7 @@ where
                 signing_targets_keys: vec![],
                 signing_snapshot_keys: vec![],
                 signing_timestamp_keys: vec![],
+                signing_delegated_targets_keys: HashMap::new(),
                 trusted_root_keys: vec![],
                 trusted_targets_keys: vec![],
                 trusted_snapshot_keys: vec![],
@@ -476,7 +783,7 @@ where
     ///     .trusted_targets_keys(&[&key])
     ///     .trusted_snapshot_keys(&[&key])
     ///     .trusted_timestamp_keys(&[&key])
-    ///     .stage_root()
+    ///     .stage_root().await
     ///     .unwrap()
     ///     .commit()
     ///     .await
@@ -487,12 +794,34 @@ where
         let builder = {
             let trusted_root = db.trusted_root();
 
-            RootMetadataBuilder::new()
+            let mut builder = RootMetadataBuilder::new()
+                .spec_version(trusted_root.spec_version())
                 .consistent_snapshot(trusted_root.consistent_snapshot())
                 .root_threshold(trusted_root.root().threshold())
                 .targets_threshold(trusted_root.targets().threshold())
                 .snapshot_threshold(trusted_root.snapshot().threshold())
-                .timestamp_threshold(trusted_root.timestamp().threshold())
+                .timestamp_threshold(trusted_root.timestamp().threshold());
+
+            // Keep any fields we don't understand, so re-signing the root doesn't discard them.
+            for (name, value) in trusted_root.additional_fields() {
+                builder = builder.additional_field(name.clone(), value.clone());
+            }
+
+            for (role, additional_fields) in [
+                (Role::Root, trusted_root.root().additional_fields()),
+                (Role::Targets, trusted_root.targets().additional_fields()),
+                (Role::Snapshot, trusted_root.snapshot().additional_fields()),
+                (
+                    Role::Timestamp,
+                    trusted_root.timestamp().additional_fields(),
+                ),
+            ] {
+                for (name, value) in additional_fields {
+                    builder = builder.role_additional_field(role, name.clone(), value.clone());
+                }
+            }
+
+            builder
         };
 
         Self {
@@ -504,6 +833,7 @@ where
                 signing_targets_keys: vec![],
                 signing_snapshot_keys: vec![],
                 signing_timestamp_keys: vec![],
+                signing_delegated_targets_keys: HashMap::new(),
                 trusted_root_keys: vec![],
                 trusted_targets_keys: vec![],
                 trusted_snapshot_keys: vec![],
@@ -596,7 +926,7 @@ where
 
     /// Sign the root metadata with `keys`, but do not include the keys as trusted root keys in the
     /// root metadata. This is typically used to support root key rotation.
-    pub fn signing_root_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn signing_root_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.signing_root_keys.push(*key);
         }
@@ -605,7 +935,7 @@ where
 
     /// Sign the targets metadata with `keys`, but do not include the keys as trusted targets keys
     /// in the root metadata. This is typically used to support targets key rotation.
-    pub fn signing_targets_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn signing_targets_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.signing_targets_keys.push(*key);
         }
@@ -614,7 +944,7 @@ where
 
     /// Sign the snapshot metadata with `keys`, but do not include the keys as trusted snapshot keys
     /// in the root metadata. This is typically used to support snapshot key rotation.
-    pub fn signing_snapshot_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn signing_snapshot_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.signing_snapshot_keys.push(*key);
         }
@@ -623,7 +953,7 @@ where
 
     /// Sign the timestamp metadata with `keys`, but do not include the keys as trusted timestamp
     /// keys in the root metadata. This is typically used to support timestamp key rotation.
-    pub fn signing_timestamp_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn signing_timestamp_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.signing_timestamp_keys.push(*key);
         }
@@ -632,40 +962,40 @@ where
 
     /// Sign the root metadata with `keys`, and include the keys as trusted root keys in the root
     /// metadata.
-    pub fn trusted_root_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn trusted_root_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.trusted_root_keys.push(*key);
-            self.state.builder = self.state.builder.root_key(key.public().clone());
+            self.state.builder = self.state.builder.root_key(key.public_key().clone());
         }
         self
     }
 
     /// Sign the targets metadata with `keys`, and include the keys as trusted targets keys in the
     /// targets metadata.
-    pub fn trusted_targets_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn trusted_targets_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.trusted_targets_keys.push(*key);
-            self.state.builder = self.state.builder.targets_key(key.public().clone());
+            self.state.builder = self.state.builder.targets_key(key.public_key().clone());
         }
         self
     }
 
     /// Sign the snapshot metadata with `keys`, and include the keys as trusted snapshot keys in the
     /// root metadata.
-    pub fn trusted_snapshot_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn trusted_snapshot_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.trusted_snapshot_keys.push(*key);
-            self.state.builder = self.state.builder.snapshot_key(key.public().clone());
+            self.state.builder = self.state.builder.snapshot_key(key.public_key().clone());
         }
         self
     }
 
     /// Sign the timestamp metadata with `keys`, and include the keys as
     /// trusted timestamp keys in the root metadata.
-    pub fn trusted_timestamp_keys(mut self, keys: &[&'a dyn PrivateKey]) -> Self {
+    pub fn trusted_timestamp_keys(mut self, keys: &[&'a dyn Signer]) -> Self {
         for key in keys {
             self.ctx.trusted_timestamp_keys.push(*key);
-            self.state.builder = self.state.builder.timestamp_key(key.public().clone());
+            self.state.builder = self.state.builder.timestamp_key(key.public_key().clone());
         }
         self
     }
@@ -691,8 +1021,8 @@ where
     /// * targets_threshold: match the trusted root's targets threshold
     /// * snapshot_threshold: match the trusted root's snapshot threshold
     /// * timestamp_threshold: match the trusted root's timestamp threshold
-    pub fn stage_root(self) -> Result<RepoBuilder<'a, D, R, Targets<D>>> {
-        self.stage_root_with_builder(|builder| builder)
+    pub async fn stage_root(self) -> Result<RepoBuilder<'a, D, R, Targets<D>>> {
+        self.stage_root_with_builder(|builder| builder).await
     }
 
     /// Stage a new root using the default settings if:
@@ -700,9 +1030,9 @@ where
     /// * There is no trusted root metadata.
     /// * The trusted keys are different from the keys that are in the trusted root.
     /// * The trusted root metadata has expired.
-    pub fn stage_root_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Targets<D>>> {
+    pub async fn stage_root_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Targets<D>>> {
         if self.need_new_root() {
-            self.stage_root()
+            self.stage_root().await
         } else {
             Ok(self.skip_root())
         }
@@ -740,24 +1070,11 @@ where
     /// * targets_threshold: match the trusted root's targets threshold
     /// * snapshot_threshold: match the trusted root's snapshot threshold
     /// * timestamp_threshold: match the trusted root's timestamp threshold
-    pub fn stage_root_with_builder<F>(self, f: F) -> Result<RepoBuilder<'a, D, R, Targets<D>>>
+    pub async fn stage_root_with_builder<F>(self, f: F) -> Result<RepoBuilder<'a, D, R, Targets<D>>>
     where
         F: FnOnce(RootMetadataBuilder) -> RootMetadataBuilder,
     {
-        let next_version = if let Some(db) = self.ctx.db {
-            db.trusted_root().version().checked_add(1).ok_or_else(|| {
-                Error::MetadataVersionMustBeSmallerThanMaxU32(MetadataPath::root())
-            })?
-        } else {
-            1
-        };
-
-        let root_builder = self
-            .state
-            .builder
-            .version(next_version)
-            .expires(self.ctx.current_time + self.ctx.root_expiration_duration);
-        let root = f(root_builder).build()?;
+        let root = self.build_root(f)?;
 
         let raw_root = sign(
             &root,
@@ -765,6 +1082,77 @@ where
                 .signing_root_keys
                 .iter()
                 .chain(&self.ctx.trusted_root_keys),
+        )
+        .await?;
+
+        Ok(RepoBuilder {
+            ctx: self.ctx,
+            state: Targets::new(Some(Staged {
+                metadata: root,
+                raw: raw_root,
+            })),
+        })
+    }
+
+    /// Build a root metadata with the default settings, without signing it, so it can be signed by
+    /// keyholders that are not online.
+    ///
+    /// See [RepoBuilder::unsigned_root_with_builder] for more details.
+    pub fn unsigned_root(&self) -> Result<SignedMetadata<D, RootMetadata>> {
+        self.unsigned_root_with_builder(|builder| builder)
+    }
+
+    /// Initialize a [RootMetadataBuilder] and pass it to the closure for further configuration,
+    /// like [RepoBuilder::stage_root_with_builder]. The root metadata is returned without any
+    /// signatures, rather than being staged.
+    ///
+    /// This is the first step for signing a root with a threshold of keys held by different
+    /// parties:
+    ///
+    /// 1. Build the unsigned root, and save it with [store_pending_metadata].
+    /// 2. Hand the [payload](SignedMetadata::payload) to each keyholder, who signs it offline.
+    /// 3. Collect the signatures with [SignedMetadata::add_detached_signature], saving the
+    ///    partially signed root between each one.
+    /// 4. Once enough signatures are collected, stage it with [RepoBuilder::stage_signed_root]
+    ///    and commit as usual.
+    pub fn unsigned_root_with_builder<F>(&self, f: F) -> Result<SignedMetadata<D, RootMetadata>>
+    where
+        F: FnOnce(RootMetadataBuilder) -> RootMetadataBuilder,
+    {
+        let root = self.build_root(f)?;
+
+        Ok(SignedMetadataBuilder::from_metadata(&root)?.build())
+    }
+
+    /// Stage a root metadata that was signed outside of this builder, such as one produced by
+    /// [RepoBuilder::unsigned_root_with_builder].
+    ///
+    /// This will fail unless the root is signed by a threshold of its own root keys, and, if there
+    /// is a trusted root, by a threshold of the trusted root's keys.
+    pub fn stage_signed_root(
+        self,
+        root: SignedMetadata<D, RootMetadata>,
+    ) -> Result<RepoBuilder<'a, D, R, Targets<D>>> {
+        let raw_root = root.to_raw()?;
+        let root = root.assume_valid()?;
+
+        // TUF-1.0.5 §5.2.3 requires a new root to be signed by both the trusted and new root keys.
+        if let Some(db) = self.ctx.db {
+            let trusted_root = db.trusted_root();
+
+            verify::verify_signatures(
+                &MetadataPath::root(),
+                &raw_root,
+                trusted_root.root().threshold(),
+                trusted_root.root_keys(),
+            )?;
+        }
+
+        verify::verify_signatures(
+            &MetadataPath::root(),
+            &raw_root,
+            root.root().threshold(),
+            root.root_keys(),
         )?;
 
         Ok(RepoBuilder {
@@ -790,7 +1178,8 @@ where
     where
         Rd: AsyncRead + AsyncSeek + Unpin + Send,
     {
-        self.stage_root_if_necessary()?
+        self.stage_root_if_necessary()
+            .await?
             .add_target(target_path, reader)
             .await
     }
@@ -801,7 +1190,29 @@ where
     ///
     /// See [RepoBuilder::commit] for more details.
     pub async fn commit(self) -> Result<RawSignedMetadataSet<D>> {
-        self.stage_root_if_necessary()?.commit().await
+        self.stage_root_if_necessary().await?.commit().await
+    }
+
+    fn build_root<F>(&self, f: F) -> Result<RootMetadata>
+    where
+        F: FnOnce(RootMetadataBuilder) -> RootMetadataBuilder,
+    {
+        let next_version = if let Some(db) = self.ctx.db {
+            db.trusted_root().version().checked_add(1).ok_or_else(|| {
+                Error::MetadataVersionMustBeSmallerThanMaxU32(MetadataPath::root())
+            })?
+        } else {
+            1
+        };
+
+        let root_builder = self
+            .state
+            .builder
+            .clone()
+            .version(next_version)
+            .expires(self.ctx.current_time + self.ctx.root_expiration_duration);
+
+        f(root_builder).build()
     }
 
     /// Check if we need a new root database.
@@ -855,17 +1266,17 @@ where
     }
 
     /// Stage a targets metadata using the default settings.
-    pub fn stage_targets(self) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>> {
-        self.stage_targets_with_builder(|builder| builder)
+    pub async fn stage_targets(self) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>> {
+        self.stage_targets_with_builder(|builder| builder).await
     }
 
     /// Stage a new targets using the default settings if:
     ///
     /// * There is no trusted targets metadata.
     /// * The trusted targets metadata has expired.
-    pub fn stage_targets_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>> {
+    pub async fn stage_targets_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>> {
         if self.need_new_targets() {
-            self.stage_targets_with_builder(|builder| builder)
+            self.stage_targets_with_builder(|builder| builder).await
         } else {
             Ok(self.skip_targets())
         }
@@ -875,7 +1286,7 @@ where
     pub fn skip_targets(self) -> RepoBuilder<'a, D, R, Snapshot<D>> {
         RepoBuilder {
             ctx: self.ctx,
-            state: Snapshot::new(self.state.staged_root, None),
+            state: Snapshot::new(self.state, None),
         }
     }
 
@@ -902,131 +1313,254 @@ where
     pub async fn add_target_with_custom<Rd>(
         mut self,
         target_path: TargetPath,
-        mut reader: Rd,
+        reader: Rd,
         custom: HashMap<String, serde_json::Value>,
     ) -> Result<RepoBuilder<'a, D, R, Targets<D>>>
     where
         Rd: AsyncRead + AsyncSeek + Unpin + Send,
     {
-        let consistent_snapshot = if let Some(ref staged_root) = self.state.staged_root {
-            staged_root.metadata.consistent_snapshot()
-        } else if let Some(db) = self.ctx.db {
-            db.trusted_root().consistent_snapshot()
-        } else {
-            return Err(Error::MetadataNotFound {
-                path: MetadataPath::root(),
-                version: MetadataVersion::None,
-            });
-        };
-
-        let target_description = TargetDescription::from_reader_with_custom(
-            &mut reader,
-            &self.state.file_hash_algorithms,
-            custom,
-        )
-        .await?;
-
-        // According to TUF section 5.5.2, when consistent snapshot is enabled, target files should be
-        // stored at `$HASH.FILENAME.EXT`. Otherwise it is stored at `FILENAME.EXT`.
-        if consistent_snapshot {
-            for digest in target_description.hashes().values() {
-                reader.seek(SeekFrom::Start(0)).await?;
+        let target_description = self.store_target(&target_path, reader, custom).await?;
+        self.state
+            .changes
+            .add_target(target_path, target_description);
 
-                let hash_prefixed_path = target_path.with_hash_prefix(digest)?;
-
-                self.ctx
-                    .repo
-                    .store_target(&hash_prefixed_path, &mut reader)
-                    .await?;
-            }
-        } else {
-            reader.seek(SeekFrom::Start(0)).await?;
+        Ok(self)
+    }
 
-            self.ctx
-                .repo
-                .store_target(&target_path, &mut reader)
-                .await?;
-        }
+    /// Add a target that's loaded in from the reader to the delegated targets metadata `role`.
+    /// This will store the target in the repository.
+    ///
+    /// The role must be delegated to by the top-level targets or by another delegated targets
+    /// role, either in the trusted metadata or in the metadata staged by this builder, otherwise
+    /// [commit](#method.commit-4) will fail. It will be signed with the keys passed to
+    /// [RepoBuilder::signing_delegated_targets_keys].
+    ///
+    /// This will hash the file with the hash specified in [RepoBuilder::target_hash_algorithms]. If
+    /// none was specified, the file will be hashed with [HashAlgorithm::Sha256].
+    pub async fn add_delegated_target<Rd>(
+        self,
+        role: MetadataPath,
+        target_path: TargetPath,
+        reader: Rd,
+    ) -> Result<RepoBuilder<'a, D, R, Targets<D>>>
+    where
+        Rd: AsyncRead + AsyncSeek + Unpin + Send,
+    {
+        self.add_delegated_target_with_custom(role, target_path, reader, HashMap::new())
+            .await
+    }
 
-        self.state.targets.insert(target_path, target_description);
+    /// Add a target that's loaded in from the reader to the delegated targets metadata `role`.
+    /// This will store the target in the repository.
+    ///
+    /// See [RepoBuilder::add_delegated_target] for more details.
+    pub async fn add_delegated_target_with_custom<Rd>(
+        mut self,
+        role: MetadataPath,
+        target_path: TargetPath,
+        reader: Rd,
+        custom: HashMap<String, serde_json::Value>,
+    ) -> Result<RepoBuilder<'a, D, R, Targets<D>>>
+    where
+        Rd: AsyncRead + AsyncSeek + Unpin + Send,
+    {
+        let target_description = self.store_target(&target_path, reader, custom).await?;
+        self.delegated_targets(role)
+            .add_target(target_path, target_description);
 
         Ok(self)
     }
 
+    async fn store_target<Rd>(
+        &mut self,
+        target_path: &TargetPath,
+        mut reader: Rd,
+        custom: HashMap<String, serde_json::Value>,
+    ) -> Result<TargetDescription>
+    where
+        Rd: AsyncRead + AsyncSeek + Unpin + Send,
+    {
+        let consistent_snapshot = if let Some(ref staged_root) = self.state.staged_root {
+            staged_root.metadata.consistent_snapshot()
+        } else if let Some(db) = self.ctx.db {
+            db.trusted_root().consistent_snapshot()
+        } else {
+            return Err(Error::MetadataNotFound {
+                path: MetadataPath::root(),
+                version: MetadataVersion::None,
+            });
+        };
+
+        let target_description = TargetDescription::from_reader_with_custom(
+            &mut reader,
+            &self.state.file_hash_algorithms,
+            custom,
+        )
+        .await?;
+
+        // According to TUF section 5.5.2, when consistent snapshot is enabled, target files should be
+        // stored at `$HASH.FILENAME.EXT`. Otherwise it is stored at `FILENAME.EXT`.
+        if consistent_snapshot {
+            for digest in target_description.hashes().values() {
+                reader.seek(SeekFrom::Start(0)).await?;
+
+                let hash_prefixed_path = target_path.with_hash_prefix(digest)?;
+
+                self.ctx
+                    .repo
+                    .store_target(&hash_prefixed_path, &mut reader)
+                    .await?;
+            }
+        } else {
+            reader.seek(SeekFrom::Start(0)).await?;
+
+            self.ctx.repo.store_target(target_path, &mut reader).await?;
+        }
+
+        Ok(target_description)
+    }
+
+    /// Remove a target from the targets metadata, such as one inherited from the trusted targets.
+    ///
+    /// This only removes the target from the metadata, so clients will no longer trust it. The
+    /// target file itself is left in the repository.
+    pub fn remove_target(mut self, target_path: TargetPath) -> Self {
+        self.state.changes.remove_target(target_path);
+        self
+    }
+
     /// Add a target delegation key.
     pub fn add_delegation_key(mut self, key: PublicKey) -> Self {
-        self.state.delegation_keys.push(key);
+        self.state.changes.add_delegation_key(key);
         self
     }
 
-    /// Add a target delegation role.
+    /// Remove a target delegation key. [commit](#method.commit-4) will fail if a delegation role
+    /// still refers to it.
+    pub fn remove_delegation_key(mut self, key_id: KeyId) -> Self {
+        self.state.changes.remove_delegation_key(key_id);
+        self
+    }
+
+    /// Add a target delegation role. This replaces any delegation role with the same name.
     pub fn add_delegation_role(mut self, delegation: Delegation) -> Self {
-        self.state.delegation_roles.push(delegation);
+        self.state.changes.add_delegation_role(delegation);
         self
     }
 
-    /// Initialize a [TargetsMetadataBuilder] and pass it to the closure for further configuration.
-    /// This builder will then be used to generate and stage a new [TargetsMetadata] for eventual
-    /// commitment to the repository.
-    ///
-    /// This builder will be initialized with:
+    /// Remove a target delegation role.
     ///
-    /// * version: 1 if a new repository, otherwise 1 past the trusted targets's version.
-    /// * expires: 90 days from the current day.
-    pub fn stage_targets_with_builder<F>(self, f: F) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>>
-    where
-        F: FnOnce(TargetsMetadataBuilder) -> TargetsMetadataBuilder,
-    {
-        let mut targets_builder = TargetsMetadataBuilder::new()
-            .expires(self.ctx.current_time + self.ctx.targets_expiration_duration);
-
-        let mut delegations_builder = DelegationsBuilder::new();
+    /// The new snapshot will no longer describe the role, nor any roles that were only delegated
+    /// to through it.
+    pub fn remove_delegation_role(mut self, role: MetadataPath) -> Self {
+        self.state.changes.remove_delegation_role(role);
+        self
+    }
 
-        if let Some(trusted_targets) = self.ctx.db.and_then(|db| db.trusted_targets()) {
-            let next_version = self
-                .ctx
-                .non_root_next_version(trusted_targets.version(), MetadataPath::targets)?;
+    /// Delegate targets to the succinct hashed bin roles described by `succinct_roles`, rather
+    /// than to individually listed roles.
+    pub fn delegation_succinct_roles(mut self, succinct_roles: SuccinctRoles) -> Self {
+        self.state.changes.delegation_succinct_roles = Some(succinct_roles);
+        self
+    }
 
-            targets_builder = targets_builder.version(next_version);
+    /// Sign the delegated targets metadata `role` with `keys`. The keys must be listed in the
+    /// delegation to `role` in order for clients to trust the metadata.
+    ///
+    /// This will stage a new version of `role`, even if no targets or delegations were added to
+    /// it, which can be used to refresh expiring delegated metadata.
+    pub fn signing_delegated_targets_keys(
+        mut self,
+        role: MetadataPath,
+        keys: &[&'a dyn Signer],
+    ) -> Self {
+        let _ = self.delegated_targets(role.clone());
+        self.ctx
+            .signing_delegated_targets_keys
+            .entry(role)
+            .or_default()
+            .extend(keys);
+        self
+    }
 
-            // Insert all the metadata from the trusted snapshot.
-            if self.state.inherit_from_trusted_targets {
-                for (target_path, target_description) in trusted_targets.targets() {
-                    targets_builder = targets_builder
-                        .insert_target_description(target_path.clone(), target_description.clone());
-                }
+    /// Remove a target from the delegated targets metadata `role`.
+    ///
+    /// See [RepoBuilder::remove_target] for more details.
+    pub fn remove_delegated_target(mut self, role: MetadataPath, target_path: TargetPath) -> Self {
+        self.delegated_targets(role).remove_target(target_path);
+        self
+    }
 
-                for key in trusted_targets.delegations().keys().values() {
-                    delegations_builder = delegations_builder.key(key.clone());
-                }
+    /// Add a target delegation key to the delegated targets metadata `role`.
+    pub fn add_delegated_delegation_key(mut self, role: MetadataPath, key: PublicKey) -> Self {
+        self.delegated_targets(role).add_delegation_key(key);
+        self
+    }
 
-                for role in trusted_targets.delegations().roles() {
-                    delegations_builder = delegations_builder.role(role.clone());
-                }
-            }
-        } else {
-            targets_builder = targets_builder.version(self.ctx.non_root_initial_version());
-        }
+    /// Remove a target delegation key from the delegated targets metadata `role`.
+    ///
+    /// See [RepoBuilder::remove_delegation_key] for more details.
+    pub fn remove_delegated_delegation_key(mut self, role: MetadataPath, key_id: KeyId) -> Self {
+        self.delegated_targets(role).remove_delegation_key(key_id);
+        self
+    }
 
-        // Overwrite any of the old targets with the new ones.
-        for (target_path, target_description) in self.state.targets {
-            targets_builder = targets_builder
-                .insert_target_description(target_path.clone(), target_description.clone());
-        }
+    /// Add a target delegation role to the delegated targets metadata `role`. This is how
+    /// delegations are nested.
+    pub fn add_delegated_delegation_role(
+        mut self,
+        role: MetadataPath,
+        delegation: Delegation,
+    ) -> Self {
+        self.delegated_targets(role).add_delegation_role(delegation);
+        self
+    }
 
-        // Overwrite the old delegation keys.
-        for key in self.state.delegation_keys {
-            delegations_builder = delegations_builder.key(key);
-        }
+    /// Remove a target delegation role from the delegated targets metadata `role`.
+    ///
+    /// See [RepoBuilder::remove_delegation_role] for more details.
+    pub fn remove_delegated_delegation_role(
+        mut self,
+        role: MetadataPath,
+        delegation_role: MetadataPath,
+    ) -> Self {
+        self.delegated_targets(role)
+            .remove_delegation_role(delegation_role);
+        self
+    }
 
-        // Overwrite the old delegation roles.
-        for role in self.state.delegation_roles {
-            delegations_builder = delegations_builder.role(role);
-        }
+    /// Delegate targets from the delegated targets metadata `role` to the succinct hashed bin
+    /// roles described by `succinct_roles`.
+    pub fn delegated_delegation_succinct_roles(
+        mut self,
+        role: MetadataPath,
+        succinct_roles: SuccinctRoles,
+    ) -> Self {
+        self.delegated_targets(role).delegation_succinct_roles = Some(succinct_roles);
+        self
+    }
 
-        targets_builder = targets_builder.delegations(delegations_builder.build()?);
+    /// The pending changes to the delegated targets metadata `role`.
+    fn delegated_targets(&mut self, role: MetadataPath) -> &mut TargetsChanges {
+        self.state.delegated_targets.entry(role).or_default()
+    }
 
-        let targets = f(targets_builder).build()?;
+    /// Initialize a [TargetsMetadataBuilder] and pass it to the closure for further configuration.
+    /// This builder will then be used to generate and stage a new [TargetsMetadata] for eventual
+    /// commitment to the repository.
+    ///
+    /// This builder will be initialized with:
+    ///
+    /// * version: 1 if a new repository, otherwise 1 past the trusted targets's version.
+    /// * expires: 90 days from the current day.
+    pub async fn stage_targets_with_builder<F>(
+        self,
+        f: F,
+    ) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>>
+    where
+        F: FnOnce(TargetsMetadataBuilder) -> TargetsMetadataBuilder,
+    {
+        let targets = self.build_targets(f)?;
 
         // Sign the targets metadata.
         let raw_targets = sign(
@@ -1035,12 +1569,13 @@ where
                 .signing_targets_keys
                 .iter()
                 .chain(&self.ctx.trusted_targets_keys),
-        )?;
+        )
+        .await?;
 
         Ok(RepoBuilder {
             ctx: self.ctx,
             state: Snapshot::new(
-                self.state.staged_root,
+                self.state,
                 Some(Staged {
                     metadata: targets,
                     raw: raw_targets,
@@ -1049,18 +1584,112 @@ where
         })
     }
 
+    /// Build a targets metadata with the default settings, without signing it, so it can be signed
+    /// by keyholders that are not online.
+    ///
+    /// See [RepoBuilder::unsigned_root_with_builder] for how to collect the signatures.
+    pub fn unsigned_targets(&self) -> Result<SignedMetadata<D, TargetsMetadata>> {
+        self.unsigned_targets_with_builder(|builder| builder)
+    }
+
+    /// Initialize a [TargetsMetadataBuilder] and pass it to the closure for further configuration,
+    /// like [RepoBuilder::stage_targets_with_builder]. The targets metadata is returned without any
+    /// signatures, rather than being staged.
+    pub fn unsigned_targets_with_builder<F>(
+        &self,
+        f: F,
+    ) -> Result<SignedMetadata<D, TargetsMetadata>>
+    where
+        F: FnOnce(TargetsMetadataBuilder) -> TargetsMetadataBuilder,
+    {
+        let targets = self.build_targets(f)?;
+
+        Ok(SignedMetadataBuilder::from_metadata(&targets)?.build())
+    }
+
+    /// Stage a targets metadata that was signed outside of this builder, such as one produced by
+    /// [RepoBuilder::unsigned_targets_with_builder].
+    ///
+    /// This will fail unless the targets is signed by a threshold of the targets keys in the staged
+    /// root, or the trusted root if no root was staged.
+    pub fn stage_signed_targets(
+        self,
+        targets: SignedMetadata<D, TargetsMetadata>,
+    ) -> Result<RepoBuilder<'a, D, R, Snapshot<D>>> {
+        let raw_targets = targets.to_raw()?;
+
+        let root = if let Some(ref staged_root) = self.state.staged_root {
+            &staged_root.metadata
+        } else if let Some(db) = self.ctx.db {
+            db.trusted_root()
+        } else {
+            return Err(Error::MetadataNotFound {
+                path: MetadataPath::root(),
+                version: MetadataVersion::None,
+            });
+        };
+
+        let targets = verify::verify_signatures(
+            &MetadataPath::targets(),
+            &raw_targets,
+            root.targets().threshold(),
+            root.targets_keys(),
+        )?;
+
+        Ok(RepoBuilder {
+            ctx: self.ctx,
+            state: Snapshot::new(
+                self.state,
+                Some(Staged {
+                    metadata: (*targets).clone(),
+                    raw: raw_targets,
+                }),
+            ),
+        })
+    }
+
     /// Validate and write the metadata to the repository.
     ///
     /// This may stage a targets, snapshot, and timestamp metadata if necessary.
     ///
     /// See [RepoBuilder::commit](#method.commit-4) for more details.
     pub async fn commit(self) -> Result<RawSignedMetadataSet<D>> {
-        self.stage_targets_if_necessary()?.commit().await
+        self.stage_targets_if_necessary().await?.commit().await
+    }
+
+    fn build_targets<F>(&self, f: F) -> Result<TargetsMetadata>
+    where
+        F: FnOnce(TargetsMetadataBuilder) -> TargetsMetadataBuilder,
+    {
+        let mut targets_builder = TargetsMetadataBuilder::new()
+            .expires(self.ctx.current_time + self.ctx.targets_expiration_duration);
+
+        let trusted_targets = self.ctx.db.and_then(|db| db.trusted_targets());
+
+        if let Some(trusted_targets) = trusted_targets {
+            let next_version = self
+                .ctx
+                .non_root_next_version(trusted_targets.version(), MetadataPath::targets)?;
+
+            targets_builder = targets_builder.version(next_version);
+        } else {
+            targets_builder = targets_builder.version(self.ctx.non_root_initial_version());
+        }
+
+        // Insert all the metadata from the trusted targets, then apply our changes.
+        let targets_builder = self.state.changes.apply(
+            targets_builder,
+            trusted_targets
+                .filter(|_| self.state.inherit_from_trusted_targets)
+                .map(|targets| &**targets),
+        )?;
+
+        f(targets_builder).build()
     }
 
     fn need_new_targets(&self) -> bool {
-        // We need a new targets metadata if we added any targets.
-        if !self.state.targets.is_empty() {
+        // We need a new targets metadata if we changed any targets or delegations.
+        if !self.state.changes.is_empty() {
             return true;
         }
 
@@ -1125,27 +1754,30 @@ where
     }
 
     /// Stage a snapshot metadata using the default settings.
-    pub fn stage_snapshot(self) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>> {
-        self.stage_snapshot_with_builder(|builder| builder)
+    pub async fn stage_snapshot(self) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>> {
+        self.stage_snapshot_with_builder(|builder| builder).await
     }
 
     /// Stage a new snapshot using the default settings if:
     ///
     /// * There is no trusted snapshot metadata.
     /// * The trusted snapshot metadata has expired.
-    pub fn stage_snapshot_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>> {
+    pub async fn stage_snapshot_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>> {
         if self.need_new_snapshot() {
-            self.stage_snapshot()
+            self.stage_snapshot().await
         } else {
             Ok(self.skip_snapshot())
         }
     }
 
     /// Skip creating the snapshot metadata.
+    ///
+    /// Delegated targets metadata is only staged along with a new snapshot metadata, so if any
+    /// delegated targets have pending changes, committing will fail.
     pub fn skip_snapshot(self) -> RepoBuilder<'a, D, R, Timestamp<D>> {
         RepoBuilder {
             ctx: self.ctx,
-            state: Timestamp::new(self.state, None),
+            state: Timestamp::new(self.state, BTreeMap::new(), None),
         }
     }
 
@@ -1157,10 +1789,18 @@ where
     ///
     /// * version: 1 if a new repository, otherwise 1 past the trusted snapshot's version.
     /// * expires: 7 days from the current day.
-    pub fn stage_snapshot_with_builder<F>(self, f: F) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>>
+    ///
+    /// Any delegated targets metadata with pending changes will be signed and staged first, and
+    /// described in the new snapshot.
+    pub async fn stage_snapshot_with_builder<F>(
+        mut self,
+        f: F,
+    ) -> Result<RepoBuilder<'a, D, R, Timestamp<D>>>
     where
         F: FnOnce(SnapshotMetadataBuilder) -> SnapshotMetadataBuilder,
     {
+        let staged_delegated_targets = self.stage_delegated_targets().await?;
+
         let mut snapshot_builder = SnapshotMetadataBuilder::new()
             .expires(self.ctx.current_time + self.ctx.snapshot_expiration_duration);
 
@@ -1171,11 +1811,24 @@ where
 
             snapshot_builder = snapshot_builder.version(next_version);
 
-            // Insert all the metadata from the trusted snapshot.
+            // Insert all the metadata from the trusted snapshot, except for roles that are no
+            // longer delegated to.
             if self.state.inherit_from_trusted_snapshot {
+                let removed_roles =
+                    self.removed_snapshot_roles(trusted_snapshot, &staged_delegated_targets);
+
+                snapshot_builder = snapshot_builder.spec_version(trusted_snapshot.spec_version());
+
+                for (name, value) in trusted_snapshot.additional_fields() {
+                    snapshot_builder =
+                        snapshot_builder.additional_field(name.clone(), value.clone());
+                }
+
                 for (path, description) in trusted_snapshot.meta() {
-                    snapshot_builder = snapshot_builder
-                        .insert_metadata_description(path.clone(), description.clone());
+                    if !removed_roles.contains(path) {
+                        snapshot_builder = snapshot_builder
+                            .insert_metadata_description(path.clone(), description.clone());
+                    }
                 }
             }
         } else {
@@ -1188,6 +1841,14 @@ where
                 .insert_metadata_description(MetadataPath::targets(), targets_description);
         };
 
+        // Overwrite the entries of any delegated targets we staged.
+        for (role, delegated_targets) in &staged_delegated_targets {
+            snapshot_builder = snapshot_builder.insert_metadata_description(
+                role.clone(),
+                self.state.metadata_description(delegated_targets)?,
+            );
+        }
+
         let snapshot = f(snapshot_builder).build()?;
         let raw_snapshot = sign(
             &snapshot,
@@ -1195,12 +1856,14 @@ where
                 .signing_snapshot_keys
                 .iter()
                 .chain(&self.ctx.trusted_snapshot_keys),
-        )?;
+        )
+        .await?;
 
         Ok(RepoBuilder {
             ctx: self.ctx,
             state: Timestamp::new(
                 self.state,
+                staged_delegated_targets,
                 Some(Staged {
                     metadata: snapshot,
                     raw: raw_snapshot,
@@ -1215,35 +1878,196 @@ where
     ///
     /// See [RepoBuilder::commit](#method.commit-4) for more details.
     pub async fn commit(self) -> Result<RawSignedMetadataSet<D>> {
-        self.stage_snapshot_if_necessary()?.commit().await
+        self.stage_snapshot_if_necessary().await?.commit().await
     }
 
-    fn need_new_snapshot(&self) -> bool {
-        // We need a new snapshot metadata if we staged a new root.
-        if self.state.staged_root.is_some() {
-            return true;
-        }
+    /// Build and sign each delegated targets metadata that has pending changes.
+    ///
+    /// Each one will be initialized with:
+    ///
+    /// * version: 1 if the role has not been published yet, otherwise 1 past the trusted version.
+    /// * expires: the same duration from the current day as the targets metadata.
+    async fn stage_delegated_targets(
+        &mut self,
+    ) -> Result<BTreeMap<MetadataPath, Staged<D, TargetsMetadata>>> {
+        let mut staged_delegated_targets = BTreeMap::new();
+
+        for (role, changes) in std::mem::take(&mut self.state.delegated_targets) {
+            if role == MetadataPath::root()
+                || role == MetadataPath::targets()
+                || role == MetadataPath::snapshot()
+                || role == MetadataPath::timestamp()
+            {
+                return Err(Error::IllegalArgument(format!(
+                    "{} is not a delegated targets role",
+                    role
+                )));
+            }
 
-        // We need a new snapshot metadata if we staged a new targets.
-        if self.state.staged_targets.is_some() {
-            return true;
-        }
+            let mut targets_builder = TargetsMetadataBuilder::new()
+                .expires(self.ctx.current_time + self.ctx.targets_expiration_duration);
 
-        // We need a new snapshot metadata if we don't have a database yet.
-        let db = if let Some(ref db) = self.ctx.db {
-            db
-        } else {
-            return true;
-        };
+            let trusted_delegated_targets = self
+                .ctx
+                .db
+                .and_then(|db| db.trusted_delegations().get(&role));
 
-        // We need a new snapshot metadata if the database doesn't have a snapshot.
-        let trusted_snapshot = if let Some(trusted_snapshot) = db.trusted_snapshot() {
-            trusted_snapshot
-        } else {
-            return true;
-        };
+            let trusted_description = self
+                .ctx
+                .db
+                .and_then(|db| db.trusted_snapshot())
+                .and_then(|snapshot| snapshot.meta().get(&role));
+
+            if let Some(trusted_delegated_targets) = trusted_delegated_targets {
+                let next_version = self
+                    .ctx
+                    .non_root_next_version(trusted_delegated_targets.version(), || role.clone())?;
+
+                targets_builder = targets_builder.version(next_version);
+            } else if let Some(trusted_description) = trusted_description {
+                // The role has been published, but the database does not have a copy of it. We
+                // can't inherit from metadata we don't have, so rather than silently dropping its
+                // targets, require the caller to load it into the database first.
+                if self.state.inherit_from_trusted_targets {
+                    return Err(Error::MetadataNotFound {
+                        path: role,
+                        version: MetadataVersion::Number(trusted_description.version()),
+                    });
+                }
 
-        // We need a new snapshot metadata if the metadata expired.
+                let next_version = self
+                    .ctx
+                    .non_root_next_version(trusted_description.version(), || role.clone())?;
+
+                targets_builder = targets_builder.version(next_version);
+            } else {
+                targets_builder = targets_builder.version(self.ctx.non_root_initial_version());
+            }
+
+            // Insert all the metadata from the trusted delegated targets, then apply our changes.
+            let targets = changes
+                .apply(
+                    targets_builder,
+                    trusted_delegated_targets
+                        .filter(|_| self.state.inherit_from_trusted_targets)
+                        .map(|targets| &**targets),
+                )?
+                .build()?;
+
+            // `sign` would report the missing keys as belonging to the top-level targets.
+            let keys = match self.ctx.signing_delegated_targets_keys.get(&role) {
+                Some(keys) if !keys.is_empty() => keys,
+                _ => return Err(Error::MissingPrivateKey { role }),
+            };
+
+            let raw_targets = sign(&targets, keys).await?;
+
+            staged_delegated_targets.insert(
+                role,
+                Staged {
+                    metadata: targets,
+                    raw: raw_targets,
+                },
+            );
+        }
+
+        Ok(staged_delegated_targets)
+    }
+
+    /// Find the roles in `trusted_snapshot` that should be dropped from the new snapshot. These are
+    /// the delegation roles that were removed, along with any roles that were only delegated to
+    /// through them.
+    fn removed_snapshot_roles<'m>(
+        &self,
+        trusted_snapshot: &'m SnapshotMetadata,
+        staged_delegated_targets: &'m BTreeMap<MetadataPath, Staged<D, TargetsMetadata>>,
+    ) -> HashSet<&'m MetadataPath> {
+        if self.state.removed_delegation_roles.is_empty() {
+            return HashSet::new();
+        }
+
+        let targets = if let Some(ref targets) = self.state.staged_targets {
+            &targets.metadata
+        } else if let Some(targets) = self.ctx.db.and_then(|db| db.trusted_targets()) {
+            targets
+        } else {
+            return HashSet::new();
+        };
+
+        let mut delegations = HashMap::new();
+        if let Some(db) = self.ctx.db {
+            for (role, targets) in db.trusted_delegations() {
+                delegations.insert(role, &**targets);
+            }
+        }
+        for (role, targets) in staged_delegated_targets {
+            delegations.insert(role, &targets.metadata);
+        }
+
+        let (reached, _) = reachable_roles(targets, &delegations, trusted_snapshot.meta().keys());
+
+        let mut removed = trusted_snapshot
+            .meta()
+            .keys()
+            .filter(|role| {
+                self.state.removed_delegation_roles.contains(*role) && !reached.contains(role)
+            })
+            .collect::<HashSet<_>>();
+
+        // Also drop any roles that are only delegated to through a removed role.
+        let mut frontier = removed.iter().copied().collect::<Vec<_>>();
+        while let Some(parent) = frontier.pop() {
+            let parent = if let Some(parent) = delegations.get(parent) {
+                parent
+            } else {
+                continue;
+            };
+
+            for role in trusted_snapshot.meta().keys() {
+                if *role != MetadataPath::targets()
+                    && !reached.contains(role)
+                    && parent.delegations().delegates_to(role)
+                    && removed.insert(role)
+                {
+                    frontier.push(role);
+                }
+            }
+        }
+
+        removed
+    }
+
+    fn need_new_snapshot(&self) -> bool {
+        // We need a new snapshot metadata if we staged a new root.
+        if self.state.staged_root.is_some() {
+            return true;
+        }
+
+        // We need a new snapshot metadata if we staged a new targets.
+        if self.state.staged_targets.is_some() {
+            return true;
+        }
+
+        // We need a new snapshot metadata if we have changes to any delegated targets.
+        if !self.state.delegated_targets.is_empty() {
+            return true;
+        }
+
+        // We need a new snapshot metadata if we don't have a database yet.
+        let db = if let Some(ref db) = self.ctx.db {
+            db
+        } else {
+            return true;
+        };
+
+        // We need a new snapshot metadata if the database doesn't have a snapshot.
+        let trusted_snapshot = if let Some(trusted_snapshot) = db.trusted_snapshot() {
+            trusted_snapshot
+        } else {
+            return true;
+        };
+
+        // We need a new snapshot metadata if the metadata expired.
         if trusted_snapshot.expires() <= &self.ctx.current_time {
             return true;
         }
@@ -1278,17 +2102,17 @@ where
     /// * stage a root metadata with the default settings if necessary.
     /// * stage a targets metadata if necessary.
     /// * stage a snapshot metadata if necessary.
-    pub fn stage_timestamp(self) -> Result<RepoBuilder<'a, D, R, Done<D>>> {
-        self.stage_timestamp_with_builder(|builder| builder)
+    pub async fn stage_timestamp(self) -> Result<RepoBuilder<'a, D, R, Done<D>>> {
+        self.stage_timestamp_with_builder(|builder| builder).await
     }
 
     /// Stage a new timestamp using the default settings if:
     ///
     /// * There is no trusted timestamp metadata.
     /// * The trusted timestamp metadata has expired.
-    pub fn stage_timestamp_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Done<D>>> {
+    pub async fn stage_timestamp_if_necessary(self) -> Result<RepoBuilder<'a, D, R, Done<D>>> {
         if self.need_new_timestamp() {
-            self.stage_timestamp()
+            self.stage_timestamp().await
         } else {
             Ok(self.skip_timestamp())
         }
@@ -1301,6 +2125,8 @@ where
             state: Done {
                 staged_root: self.state.staged_root,
                 staged_targets: self.state.staged_targets,
+                staged_delegated_targets: self.state.staged_delegated_targets,
+                unstaged_delegated_targets: self.state.unstaged_delegated_targets,
                 staged_snapshot: self.state.staged_snapshot,
                 staged_timestamp: None,
             },
@@ -1315,7 +2141,10 @@ where
     ///
     /// * version: 1 if a new repository, otherwise 1 past the trusted snapshot's version.
     /// * expires: 1 day from the current day.
-    pub fn stage_timestamp_with_builder<F>(self, f: F) -> Result<RepoBuilder<'a, D, R, Done<D>>>
+    pub async fn stage_timestamp_with_builder<F>(
+        self,
+        f: F,
+    ) -> Result<RepoBuilder<'a, D, R, Done<D>>>
     where
         F: FnOnce(TimestampMetadataBuilder) -> TimestampMetadataBuilder,
     {
@@ -1343,9 +2172,18 @@ where
                 })?
         };
 
-        let timestamp_builder = TimestampMetadataBuilder::from_metadata_description(description)
-            .version(next_version)
-            .expires(self.ctx.current_time + self.ctx.timestamp_expiration_duration);
+        let mut timestamp_builder =
+            TimestampMetadataBuilder::from_metadata_description(description)
+                .version(next_version)
+                .expires(self.ctx.current_time + self.ctx.timestamp_expiration_duration);
+
+        if let Some(trusted_timestamp) = self.ctx.db.and_then(|db| db.trusted_timestamp()) {
+            timestamp_builder = timestamp_builder.spec_version(trusted_timestamp.spec_version());
+
+            for (name, value) in trusted_timestamp.additional_fields() {
+                timestamp_builder = timestamp_builder.additional_field(name.clone(), value.clone());
+            }
+        }
 
         let timestamp = f(timestamp_builder).build()?;
         let raw_timestamp = sign(
@@ -1354,13 +2192,16 @@ where
                 .signing_timestamp_keys
                 .iter()
                 .chain(&self.ctx.trusted_timestamp_keys),
-        )?;
+        )
+        .await?;
 
         Ok(RepoBuilder {
             ctx: self.ctx,
             state: Done {
                 staged_root: self.state.staged_root,
                 staged_targets: self.state.staged_targets,
+                staged_delegated_targets: self.state.staged_delegated_targets,
+                unstaged_delegated_targets: self.state.unstaged_delegated_targets,
                 staged_snapshot: self.state.staged_snapshot,
                 staged_timestamp: Some(Staged {
                     metadata: timestamp,
@@ -1372,7 +2213,7 @@ where
 
     /// See [RepoBuilder::commit](#method.commit-4) for more details.
     pub async fn commit(self) -> Result<RawSignedMetadataSet<D>> {
-        self.stage_timestamp_if_necessary()?.commit().await
+        self.stage_timestamp_if_necessary().await?.commit().await
     }
 
     fn need_new_timestamp(&self) -> bool {
@@ -1432,6 +2273,10 @@ where
             builder = builder.targets(targets.raw);
         }
 
+        for (role, delegated_targets) in self.state.staged_delegated_targets {
+            builder = builder.delegated_targets(role, delegated_targets.raw);
+        }
+
         if let Some(snapshot) = self.state.staged_snapshot {
             builder = builder.snapshot(snapshot.raw);
         }
@@ -1446,6 +2291,15 @@ where
     /// Before we commit any metadata, make sure that we can update from our
     /// current TUF database to the latest version.
     fn validate_built_metadata(&self) -> Result<()> {
+        // The changes to these roles were never signed, even though their targets may already
+        // have been stored.
+        if !self.state.unstaged_delegated_targets.is_empty() {
+            return Err(Error::IllegalArgument(format!(
+                "delegated targets {:?} have pending changes, which need a new snapshot",
+                self.state.unstaged_delegated_targets
+            )));
+        }
+
         // Use a TUF database to make sure we can update to the metadata we just
         // produced. If we were constructed with a database, create a copy of it
         // and make sure we can install the update.
@@ -1478,6 +2332,86 @@ where
             db.update_targets(&self.ctx.current_time, &targets.raw)?;
         }
 
+        // Walk down the delegation tree, so that each delegated targets is checked against the
+        // latest version of the role that delegates to it. That role may either be one we just
+        // staged, or one that's already trusted by the database.
+        let mut pending = self
+            .state
+            .staged_delegated_targets
+            .keys()
+            .collect::<Vec<_>>();
+
+        while !pending.is_empty() {
+            let ready = pending.iter().enumerate().find_map(|(i, role)| {
+                find_delegating_role(&db, role, &pending).map(|parent| (i, parent))
+            });
+
+            let (i, parent) = if let Some(ready) = ready {
+                ready
+            } else {
+                return Err(Error::UnauthorizedDelegation {
+                    parent_role: MetadataPath::targets(),
+                    child_role: pending[0].clone(),
+                });
+            };
+
+            let role = pending.remove(i);
+            let staged = &self.state.staged_delegated_targets[role];
+
+            // Clients ignore targets outside of the paths a role was delegated, so refuse to
+            // publish them.
+            let parent_targets = if parent == MetadataPath::targets() {
+                db.trusted_targets().map(|targets| &**targets)
+            } else {
+                db.trusted_delegations()
+                    .get(&parent)
+                    .map(|targets| &**targets)
+            };
+            if let Some(parent_targets) = parent_targets {
+                if let Some(target_path) = staged
+                    .metadata
+                    .targets()
+                    .keys()
+                    .find(|target_path| !delegates_target(parent_targets, role, target_path))
+                {
+                    return Err(Error::IllegalArgument(format!(
+                        "delegated targets {} lists target {}, which is outside the paths \
+                         delegated to it by {}",
+                        role, target_path, parent
+                    )));
+                }
+            }
+
+            db.update_delegated_targets(&self.ctx.current_time, &parent, role, &staged.raw)?;
+        }
+
+        // Make sure the new snapshot doesn't describe any roles that are no longer delegated to. We
+        // can only tell if we know every delegated targets metadata along the way.
+        if let (Some(snapshot), Some(targets)) = (db.trusted_snapshot(), db.trusted_targets()) {
+            let delegations = db
+                .trusted_delegations()
+                .iter()
+                .map(|(role, targets)| (role, &**targets))
+                .collect::<HashMap<_, _>>();
+
+            let (reached, complete) =
+                reachable_roles(targets, &delegations, snapshot.meta().keys());
+
+            if complete && self.state.staged_snapshot.is_some() {
+                if let Some(role) = snapshot
+                    .meta()
+                    .keys()
+                    .filter(|role| **role != MetadataPath::targets() && !reached.contains(role))
+                    .min()
+                {
+                    return Err(Error::UnauthorizedDelegation {
+                        parent_role: MetadataPath::targets(),
+                        child_role: role.clone(),
+                    });
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -1534,6 +2468,28 @@ where
             }
         }
 
+        for (path, delegated_targets) in &self.state.staged_delegated_targets {
+            self.ctx
+                .repo
+                .store_metadata(
+                    path,
+                    MetadataVersion::None,
+                    &mut delegated_targets.raw.as_bytes(),
+                )
+                .await?;
+
+            if consistent_snapshot {
+                self.ctx
+                    .repo
+                    .store_metadata(
+                        path,
+                        MetadataVersion::Number(delegated_targets.metadata.version()),
+                        &mut delegated_targets.raw.as_bytes(),
+                    )
+                    .await?;
+            }
+        }
+
         if let Some(ref snapshot) = self.state.staged_snapshot {
             let path = MetadataPath::snapshot();
             self.ctx
@@ -1574,8 +2530,8 @@ mod tests {
         super::*,
         crate::{
             client::{Client, Config},
-            crypto::Ed25519PrivateKey,
-            metadata::SignedMetadata,
+            crypto::{Ed25519PrivateKey, PrivateKey},
+            metadata::{PathPattern, SignedMetadata},
             pouf::Pouf1,
             repository::{EphemeralRepository, RepositoryProvider},
         },
@@ -1585,11 +2541,21 @@ mod tests {
             DateTime,
         },
         futures_executor::block_on,
+        futures_util::future::{BoxFuture, FutureExt as _},
         futures_util::io::{AsyncReadExt, Cursor},
         lazy_static::lazy_static,
         maplit::hashmap,
         pretty_assertions::assert_eq,
-        std::collections::BTreeMap,
+        serde_json::json,
+        std::{
+            collections::BTreeMap,
+            io::{Read as _, Write as _},
+            net::{SocketAddr, TcpListener, TcpStream},
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc,
+            },
+        },
     };
 
     lazy_static! {
@@ -1608,7 +2574,7 @@ mod tests {
         };
     }
 
-    fn create_root(
+    async fn create_root(
         version: u32,
         consistent_snapshot: bool,
         expires: DateTime<Utc>,
@@ -1636,18 +2602,21 @@ mod tests {
             .build()
             .unwrap();
 
-        SignedMetadataBuilder::from_metadata(&root)
-            .unwrap()
-            .sign(&KEYS[0])
-            .unwrap()
-            .sign(&KEYS[1])
-            .unwrap()
-            .sign(&KEYS[2])
-            .unwrap()
-            .build()
+        sign_metadata(&root, &[&KEYS[0], &KEYS[1], &KEYS[2]]).await
+    }
+
+    async fn sign_metadata<M: Metadata>(
+        metadata: &M,
+        keys: &[&dyn Signer],
+    ) -> SignedMetadata<Pouf1, M> {
+        let mut builder = SignedMetadataBuilder::from_metadata(metadata).unwrap();
+        for key in keys {
+            builder = builder.sign(*key).await.unwrap();
+        }
+        builder.build()
     }
 
-    fn create_targets(
+    async fn create_targets(
         version: u32,
         expires: DateTime<Utc>,
     ) -> SignedMetadata<Pouf1, TargetsMetadata> {
@@ -1656,18 +2625,10 @@ mod tests {
             .expires(expires)
             .build()
             .unwrap();
-        SignedMetadataBuilder::<Pouf1, _>::from_metadata(&targets)
-            .unwrap()
-            .sign(&KEYS[1])
-            .unwrap()
-            .sign(&KEYS[2])
-            .unwrap()
-            .sign(&KEYS[3])
-            .unwrap()
-            .build()
+        sign_metadata(&targets, &[&KEYS[1], &KEYS[2], &KEYS[3]]).await
     }
 
-    fn create_snapshot(
+    async fn create_snapshot(
         version: u32,
         expires: DateTime<Utc>,
         targets: &SignedMetadata<Pouf1, TargetsMetadata>,
@@ -1692,18 +2653,10 @@ mod tests {
             .expires(expires)
             .build()
             .unwrap();
-        SignedMetadataBuilder::<Pouf1, _>::from_metadata(&snapshot)
-            .unwrap()
-            .sign(&KEYS[2])
-            .unwrap()
-            .sign(&KEYS[3])
-            .unwrap()
-            .sign(&KEYS[4])
-            .unwrap()
-            .build()
+        sign_metadata(&snapshot, &[&KEYS[2], &KEYS[3], &KEYS[4]]).await
     }
 
-    fn create_timestamp(
+    async fn create_timestamp(
         version: u32,
         expires: DateTime<Utc>,
         snapshot: &SignedMetadata<Pouf1, SnapshotMetadata>,
@@ -1727,15 +2680,7 @@ mod tests {
             .expires(expires)
             .build()
             .unwrap();
-        SignedMetadataBuilder::<Pouf1, _>::from_metadata(&timestamp)
-            .unwrap()
-            .sign(&KEYS[3])
-            .unwrap()
-            .sign(&KEYS[4])
-            .unwrap()
-            .sign(&KEYS[5])
-            .unwrap()
-            .build()
+        sign_metadata(&timestamp, &[&KEYS[3], &KEYS[4], &KEYS[5]]).await
     }
 
     fn assert_metadata(
@@ -1815,16 +2760,20 @@ mod tests {
                     .snapshot_threshold(2)
                     .timestamp_threshold(2)
             })
+            .await
             .unwrap()
             .stage_targets_with_builder(|builder| builder.expires(expires1))
+            .await
             .unwrap()
             .snapshot_includes_length(true)
             .snapshot_includes_hashes(&[HashAlgorithm::Sha256])
             .stage_snapshot_with_builder(|builder| builder.expires(expires1))
+            .await
             .unwrap()
             .timestamp_includes_length(true)
             .timestamp_includes_hashes(&[HashAlgorithm::Sha256])
             .stage_timestamp_with_builder(|builder| builder.expires(expires1))
+            .await
             .unwrap()
             .commit()
             .await
@@ -1832,10 +2781,10 @@ mod tests {
 
         // Generate the expected metadata by hand, and make sure we produced
         // what we expected.
-        let signed_root1 = create_root(1, consistent_snapshot, expires1);
-        let signed_targets1 = create_targets(1, expires1);
-        let signed_snapshot1 = create_snapshot(1, expires1, &signed_targets1, true);
-        let signed_timestamp1 = create_timestamp(1, expires1, &signed_snapshot1, true);
+        let signed_root1 = create_root(1, consistent_snapshot, expires1).await;
+        let signed_targets1 = create_targets(1, expires1).await;
+        let signed_snapshot1 = create_snapshot(1, expires1, &signed_targets1, true).await;
+        let signed_timestamp1 = create_timestamp(1, expires1, &signed_snapshot1, true).await;
 
         let raw_root1 = signed_root1.to_raw().unwrap();
         let raw_targets1 = signed_targets1.to_raw().unwrap();
@@ -1926,26 +2875,30 @@ mod tests {
             .trusted_snapshot_keys(&[&KEYS[2], &KEYS[3], &KEYS[4]])
             .trusted_timestamp_keys(&[&KEYS[3], &KEYS[4], &KEYS[5]])
             .stage_root_with_builder(|builder| builder.expires(expires2))
+            .await
             .unwrap()
             .stage_targets_with_builder(|builder| builder.expires(expires2))
+            .await
             .unwrap()
             .snapshot_includes_length(false)
             .snapshot_includes_hashes(&[])
             .stage_snapshot_with_builder(|builder| builder.expires(expires2))
+            .await
             .unwrap()
             .timestamp_includes_length(false)
             .timestamp_includes_hashes(&[])
             .stage_timestamp_with_builder(|builder| builder.expires(expires2))
+            .await
             .unwrap()
             .commit()
             .await
             .unwrap();
 
         // Make sure the new metadata was generated as expected.
-        let signed_root2 = create_root(2, consistent_snapshot, expires2);
-        let signed_targets2 = create_targets(2, expires2);
-        let signed_snapshot2 = create_snapshot(2, expires2, &signed_targets2, false);
-        let signed_timestamp2 = create_timestamp(2, expires2, &signed_snapshot2, false);
+        let signed_root2 = create_root(2, consistent_snapshot, expires2).await;
+        let signed_targets2 = create_targets(2, expires2).await;
+        let signed_snapshot2 = create_snapshot(2, expires2, &signed_targets2, false).await;
+        let signed_timestamp2 = create_timestamp(2, expires2, &signed_snapshot2, false).await;
 
         let raw_root2 = signed_root2.to_raw().unwrap();
         let raw_targets2 = signed_targets2.to_raw().unwrap();
@@ -2035,6 +2988,7 @@ mod tests {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root_with_builder(|builder| builder.consistent_snapshot(consistent_snapshot))
+            .await
             .unwrap()
             .commit()
             .await
@@ -2092,6 +3046,7 @@ mod tests {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root_with_builder(|builder| builder.consistent_snapshot(consistent_snapshot))
+            .await
             .unwrap()
             .commit()
             .await
@@ -2164,6 +3119,7 @@ mod tests {
             .trusted_snapshot_keys(&[&KEYS[0]])
             .trusted_timestamp_keys(&[&KEYS[0]])
             .stage_root()
+            .await
             .unwrap()
             .commit()
             .await
@@ -2207,7 +3163,7 @@ mod tests {
                     .trusted_targets_keys(&[&KEYS[0]])
                     .trusted_snapshot_keys(&[&KEYS[0]])
                     .trusted_timestamp_keys(&[&KEYS[0]])
-                    .stage_root_with_builder(|builder| builder.version(3))
+                    .stage_root_with_builder(|builder| builder.version(3)).await
                     .unwrap()
                     .commit()
                     .await,
@@ -2337,6 +3293,7 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root_with_builder(|builder| builder.consistent_snapshot(true))
+                .await
                 .unwrap()
                 .target_hash_algorithms(hash_algs)
                 .add_target(target_path1.clone(), Cursor::new(target_file1))
@@ -2427,12 +3384,16 @@ mod tests {
                 .stage_root_with_builder(|builder| {
                     builder.consistent_snapshot(true).expires(expires1)
                 })
+                .await
                 .unwrap()
                 .stage_targets_with_builder(|builder| builder.expires(expires1))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|builder| builder.expires(expires1))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|builder| builder.expires(expires1))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2489,10 +3450,13 @@ mod tests {
                 .trusted_timestamp_keys(&[&KEYS[3]])
                 .skip_root()
                 .stage_targets_with_builder(|builder| builder.expires(expires2))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|builder| builder.expires(expires2))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|builder| builder.expires(expires2))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2505,31 +3469,28 @@ mod tests {
             assert!(metadata2.snapshot().is_some());
             assert!(metadata2.timestamp().is_some());
 
-            expected_metadata.extend(
-                vec![
-                    (
-                        (MetadataPath::targets(), MetadataVersion::Number(2)),
-                        metadata2.targets().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::targets(), MetadataVersion::None),
-                        metadata2.targets().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::snapshot(), MetadataVersion::Number(2)),
-                        metadata2.snapshot().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::snapshot(), MetadataVersion::None),
-                        metadata2.snapshot().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::timestamp(), MetadataVersion::None),
-                        metadata2.timestamp().unwrap().as_bytes(),
-                    ),
-                ]
-                .into_iter(),
-            );
+            expected_metadata.extend(vec![
+                (
+                    (MetadataPath::targets(), MetadataVersion::Number(2)),
+                    metadata2.targets().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::targets(), MetadataVersion::None),
+                    metadata2.targets().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::snapshot(), MetadataVersion::Number(2)),
+                    metadata2.snapshot().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::snapshot(), MetadataVersion::None),
+                    metadata2.snapshot().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::timestamp(), MetadataVersion::None),
+                    metadata2.timestamp().unwrap().as_bytes(),
+                ),
+            ]);
 
             assert_repo(&remote, &expected_metadata);
 
@@ -2541,8 +3502,10 @@ mod tests {
                 .skip_root()
                 .skip_targets()
                 .stage_snapshot_with_builder(|builder| builder.expires(expires3))
+                .await
                 .unwrap()
                 .stage_timestamp_with_builder(|builder| builder.expires(expires3))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2556,23 +3519,20 @@ mod tests {
             assert!(metadata3.snapshot().is_some());
             assert!(metadata3.timestamp().is_some());
 
-            expected_metadata.extend(
-                vec![
-                    (
-                        (MetadataPath::snapshot(), MetadataVersion::Number(3)),
-                        metadata3.snapshot().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::snapshot(), MetadataVersion::None),
-                        metadata3.snapshot().unwrap().as_bytes(),
-                    ),
-                    (
-                        (MetadataPath::timestamp(), MetadataVersion::None),
-                        metadata3.timestamp().unwrap().as_bytes(),
-                    ),
-                ]
-                .into_iter(),
-            );
+            expected_metadata.extend(vec![
+                (
+                    (MetadataPath::snapshot(), MetadataVersion::Number(3)),
+                    metadata3.snapshot().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::snapshot(), MetadataVersion::None),
+                    metadata3.snapshot().unwrap().as_bytes(),
+                ),
+                (
+                    (MetadataPath::timestamp(), MetadataVersion::None),
+                    metadata3.timestamp().unwrap().as_bytes(),
+                ),
+            ]);
 
             assert_repo(&remote, &expected_metadata);
 
@@ -2584,6 +3544,7 @@ mod tests {
                 .skip_targets()
                 .skip_snapshot()
                 .stage_timestamp_with_builder(|builder| builder.expires(expires4))
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2597,13 +3558,10 @@ mod tests {
             assert!(metadata4.snapshot().is_none());
             assert!(metadata4.timestamp().is_some());
 
-            expected_metadata.extend(
-                vec![(
-                    (MetadataPath::timestamp(), MetadataVersion::None),
-                    metadata4.timestamp().unwrap().as_bytes(),
-                )]
-                .into_iter(),
-            );
+            expected_metadata.extend(vec![(
+                (MetadataPath::timestamp(), MetadataVersion::None),
+                metadata4.timestamp().unwrap().as_bytes(),
+            )]);
 
             assert_repo(&remote, &expected_metadata);
         })
@@ -2627,7 +3585,7 @@ mod tests {
 
             let delegation1 = Delegation::builder(delegation_path.clone())
                 .key(delegation_key.public())
-                .delegate_path(TargetPath::new("delegations/").unwrap())
+                .delegate_path(PathPattern::new("delegations/").unwrap())
                 .build()
                 .unwrap();
 
@@ -2648,6 +3606,7 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .target_hash_algorithms(hash_algs)
                 .add_target(target_path1.clone(), Cursor::new(target_file1))
@@ -2656,6 +3615,7 @@ mod tests {
                 .add_delegation_key(delegation_key.public().clone())
                 .add_delegation_role(delegation1.clone())
                 .stage_targets()
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|builder| {
                     builder.insert_metadata_description(
@@ -2668,6 +3628,7 @@ mod tests {
                         .unwrap(),
                     )
                 })
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2685,7 +3646,7 @@ mod tests {
 
             let delegation2 = Delegation::builder(delegation_path.clone())
                 .key(delegation_key.public())
-                .delegate_path(TargetPath::new("delegations/").unwrap())
+                .delegate_path(PathPattern::new("delegations/").unwrap())
                 .build()
                 .unwrap();
 
@@ -2706,6 +3667,7 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .target_hash_algorithms(hash_algs)
                 .add_target(target_path2.clone(), Cursor::new(target_file2))
@@ -2713,6 +3675,7 @@ mod tests {
                 .unwrap()
                 .add_delegation_role(delegation2.clone())
                 .stage_targets_with_builder(|b| b.expires(expires))
+                .await
                 .unwrap()
                 .stage_snapshot_with_builder(|builder| {
                     builder.insert_metadata_description(
@@ -2725,6 +3688,7 @@ mod tests {
                         .unwrap(),
                     )
                 })
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -2756,7 +3720,7 @@ mod tests {
     }
 
     #[test]
-    fn test_builder_rotating_keys_refreshes_metadata() {
+    fn test_builder_preserves_additional_fields() {
         block_on(async move {
             let mut repo = EphemeralRepository::<Pouf1>::new();
 
@@ -2765,38 +3729,128 @@ mod tests {
                 .trusted_targets_keys(&[&KEYS[0]])
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root_with_builder(|builder| {
+                    builder
+                        .additional_field("x-root", json!("root"))
+                        .role_additional_field(Role::Targets, "x-role", json!("targets role"))
+                })
+                .await
+                .unwrap()
+                .stage_targets_with_builder(|builder| {
+                    builder.additional_field("x-targets", json!("targets"))
+                })
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|builder| {
+                    builder.additional_field("x-snapshot", json!("snapshot"))
+                })
+                .await
+                .unwrap()
+                .stage_timestamp_with_builder(|builder| {
+                    builder.additional_field("x-timestamp", json!("timestamp"))
+                })
+                .await
+                .unwrap()
                 .commit()
                 .await
                 .unwrap();
 
-            let mut db = Database::from_trusted_metadata(&metadata1).unwrap();
+            let mut database = Database::from_trusted_metadata(&metadata1).unwrap();
 
-            // Because of [update-root], rotating any root keys should make a new timestamp and
-            // snapshot.
-            //
-            // FIXME(#297): This also purges targets, even though that's not conforming to the spec.
-            //
-            // [update-root]: https://theupdateframework.github.io/specification/v1.0.30/#update-root
-            let metadata2 = RepoBuilder::from_database(&mut repo, &db)
+            // Re-sign every role. None of them should lose the fields we didn't set this time.
+            let metadata2 = RepoBuilder::from_database(&mut repo, &database)
                 .trusted_root_keys(&[&KEYS[0]])
                 .trusted_targets_keys(&[&KEYS[0]])
                 .trusted_snapshot_keys(&[&KEYS[0]])
-                .trusted_timestamp_keys(&[&KEYS[1]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_target(TargetPath::new("foo").unwrap(), Cursor::new(b"foo"))
+                .await
+                .unwrap()
                 .commit()
                 .await
                 .unwrap();
 
-            assert!(metadata2.root().is_some());
-            assert!(metadata2.targets().is_some());
-            assert!(metadata2.snapshot().is_some());
-            assert!(metadata2.timestamp().is_some());
+            database.update_metadata(&metadata2).unwrap();
 
-            db.update_metadata(&metadata2).unwrap();
+            let root = database.trusted_root();
+            assert_eq!(root.version(), 2);
+            assert_eq!(
+                root.additional_fields(),
+                &hashmap! { "x-root".into() => json!("root") }
+            );
+            assert_eq!(
+                root.targets().additional_fields(),
+                &hashmap! { "x-role".into() => json!("targets role") }
+            );
 
-            assert_eq!(db.trusted_root().version(), 2);
-            assert_eq!(db.trusted_targets().unwrap().version(), 2);
-            assert_eq!(db.trusted_snapshot().unwrap().version(), 2);
-            assert_eq!(db.trusted_timestamp().unwrap().version(), 2);
+            let targets = database.trusted_targets().unwrap();
+            assert_eq!(targets.version(), 2);
+            assert_eq!(
+                targets.additional_fields(),
+                &hashmap! { "x-targets".into() => json!("targets") }
+            );
+
+            let snapshot = database.trusted_snapshot().unwrap();
+            assert_eq!(snapshot.version(), 2);
+            assert_eq!(
+                snapshot.additional_fields(),
+                &hashmap! { "x-snapshot".into() => json!("snapshot") }
+            );
+
+            let timestamp = database.trusted_timestamp().unwrap();
+            assert_eq!(timestamp.version(), 2);
+            assert_eq!(
+                timestamp.additional_fields(),
+                &hashmap! { "x-timestamp".into() => json!("timestamp") }
+            );
+        })
+    }
+
+    #[test]
+    fn test_builder_rotating_keys_refreshes_metadata() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+
+            let metadata1 = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .commit()
+                .await
+                .unwrap();
+
+            let mut db = Database::from_trusted_metadata(&metadata1).unwrap();
+
+            // Because of [update-root], rotating any root keys should make a new timestamp and
+            // snapshot.
+            //
+            // FIXME(#297): This also purges targets, even though that's not conforming to the spec.
+            //
+            // [update-root]: https://theupdateframework.github.io/specification/v1.0.30/#update-root
+            let metadata2 = RepoBuilder::from_database(&mut repo, &db)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[1]])
+                .commit()
+                .await
+                .unwrap();
+
+            assert!(metadata2.root().is_some());
+            assert!(metadata2.targets().is_some());
+            assert!(metadata2.snapshot().is_some());
+            assert!(metadata2.timestamp().is_some());
+
+            db.update_metadata(&metadata2).unwrap();
+
+            assert_eq!(db.trusted_root().version(), 2);
+            assert_eq!(db.trusted_targets().unwrap().version(), 2);
+            assert_eq!(db.trusted_snapshot().unwrap().version(), 2);
+            assert_eq!(db.trusted_timestamp().unwrap().version(), 2);
 
             // Note that rotating the timestamp keys purges all the metadata, so add it back in.
 
@@ -3085,8 +4139,10 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .stage_targets()
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -3110,8 +4166,10 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .stage_targets()
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -3163,8 +4221,10 @@ mod tests {
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .trusted_timestamp_keys(&[&KEYS[0]])
                 .stage_root()
+                .await
                 .unwrap()
                 .stage_targets()
+                .await
                 .unwrap()
                 .commit()
                 .await
@@ -3196,7 +4256,7 @@ mod tests {
 
             let db = Database::from_trusted_metadata(&metadata).unwrap();
 
-            match RepoBuilder::from_database(&repo, &db).stage_root() {
+            match RepoBuilder::from_database(&repo, &db).stage_root().await {
                 Err(Error::MetadataRoleDoesNotHaveEnoughKeyIds {
                     role,
                     key_ids: 0,
@@ -3209,8 +4269,10 @@ mod tests {
             match RepoBuilder::from_database(&repo, &db)
                 .trusted_root_keys(&[&KEYS[0]])
                 .stage_root_if_necessary()
+                .await
                 .unwrap()
                 .stage_targets()
+                .await
             {
                 Err(Error::MissingPrivateKey { role }) if role == MetadataPath::targets() => {}
                 Err(err) => panic!("unexpected error: {}", err),
@@ -3221,10 +4283,13 @@ mod tests {
                 .trusted_root_keys(&[&KEYS[0]])
                 .trusted_targets_keys(&[&KEYS[0]])
                 .stage_root_if_necessary()
+                .await
                 .unwrap()
                 .stage_targets_if_necessary()
+                .await
                 .unwrap()
                 .stage_snapshot()
+                .await
             {
                 Err(Error::MissingPrivateKey { role }) if role == MetadataPath::snapshot() => {}
                 Err(err) => panic!("unexpected error: {}", err),
@@ -3236,12 +4301,16 @@ mod tests {
                 .trusted_targets_keys(&[&KEYS[0]])
                 .trusted_snapshot_keys(&[&KEYS[0]])
                 .stage_root_if_necessary()
+                .await
                 .unwrap()
                 .stage_targets_if_necessary()
+                .await
                 .unwrap()
                 .stage_snapshot_if_necessary()
+                .await
                 .unwrap()
                 .stage_timestamp()
+                .await
             {
                 Err(Error::MissingPrivateKey { role }) if role == MetadataPath::timestamp() => {}
                 Err(err) => panic!("unexpected error: {}", err),
@@ -3249,4 +4318,854 @@ mod tests {
             }
         })
     }
+
+    #[test]
+    fn test_skip_snapshot_rejects_pending_delegated_targets() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let role = MetadataPath::new("a").unwrap();
+
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .add_delegation_role(
+                    Delegation::builder(role.clone())
+                        .key(KEYS[1].public())
+                        .delegate_path(PathPattern::new("foo/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .commit()
+                .await
+                .unwrap();
+            let db = Database::from_trusted_metadata(&metadata).unwrap();
+
+            // The delegated targets can only be staged with a new snapshot.
+            assert_matches!(
+                RepoBuilder::from_database(&mut repo, &db)
+                    .trusted_root_keys(&[&KEYS[0]])
+                    .trusted_targets_keys(&[&KEYS[0]])
+                    .trusted_snapshot_keys(&[&KEYS[0]])
+                    .trusted_timestamp_keys(&[&KEYS[0]])
+                    .skip_root()
+                    .signing_delegated_targets_keys(role.clone(), &[&KEYS[1]])
+                    .add_delegated_target(
+                        role.clone(),
+                        TargetPath::new("foo/bar").unwrap(),
+                        Cursor::new(b"bar"),
+                    )
+                    .await
+                    .unwrap()
+                    .skip_targets()
+                    .skip_snapshot()
+                    .commit()
+                    .await,
+                Err(Error::IllegalArgument(msg)) if msg.contains("pending changes")
+            );
+            assert_matches!(
+                repo.fetch_metadata(&role, MetadataVersion::None)
+                    .await
+                    .map(|_| ()),
+                Err(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
+    #[test]
+    fn test_stage_delegated_targets_rejects_targets_outside_delegated_paths() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+            let role = MetadataPath::new("a").unwrap();
+
+            assert_matches!(
+                RepoBuilder::create(&mut repo)
+                    .trusted_root_keys(&[&KEYS[0]])
+                    .trusted_targets_keys(&[&KEYS[0]])
+                    .trusted_snapshot_keys(&[&KEYS[0]])
+                    .trusted_timestamp_keys(&[&KEYS[0]])
+                    .stage_root()
+                    .await
+                    .unwrap()
+                    .add_delegation_key(KEYS[1].public().clone())
+                    .add_delegation_role(
+                        Delegation::builder(role.clone())
+                            .key(KEYS[1].public())
+                            .delegate_path(PathPattern::new("foo/*").unwrap())
+                            .build()
+                            .unwrap(),
+                    )
+                    .signing_delegated_targets_keys(role.clone(), &[&KEYS[1]])
+                    .add_delegated_target(
+                        role.clone(),
+                        TargetPath::new("bar/baz").unwrap(),
+                        Cursor::new(b"baz"),
+                    )
+                    .await
+                    .unwrap()
+                    .commit()
+                    .await,
+                Err(Error::IllegalArgument(msg)) if msg.contains("outside the paths")
+            );
+            assert_matches!(
+                repo.fetch_metadata(&role, MetadataVersion::None)
+                    .await
+                    .map(|_| ()),
+                Err(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
+    #[test]
+    fn test_stage_nested_delegated_targets() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+
+            let role_a = MetadataPath::new("a").unwrap();
+            let role_b = MetadataPath::new("b").unwrap();
+
+            let target_path1 = TargetPath::new("foo/bar").unwrap();
+            let target_file1: &[u8] = b"things fade, alternatives exclude";
+            let target_path2 = TargetPath::new("foo/baz").unwrap();
+            let target_file2: &[u8] = b"hello world";
+
+            // targets delegates `foo/*` to `a`, which delegates it to `b`.
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .add_delegation_role(
+                    Delegation::builder(role_a.clone())
+                        .key(KEYS[1].public())
+                        .delegate_path(PathPattern::new("foo/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role_a.clone(), &[&KEYS[1]])
+                .add_delegated_delegation_key(role_a.clone(), KEYS[2].public().clone())
+                .add_delegated_delegation_role(
+                    role_a.clone(),
+                    Delegation::builder(role_b.clone())
+                        .key(KEYS[2].public())
+                        .delegate_path(PathPattern::new("foo/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role_b.clone(), &[&KEYS[2]])
+                .add_delegated_target(
+                    role_b.clone(),
+                    target_path1.clone(),
+                    Cursor::new(target_file1),
+                )
+                .await
+                .unwrap()
+                .stage_targets()
+                .await
+                .unwrap()
+                .snapshot_includes_length(true)
+                .snapshot_includes_hashes(&[HashAlgorithm::Sha256])
+                .commit()
+                .await
+                .unwrap();
+
+            // The snapshot should describe the delegated metadata we wrote to the repository.
+            let snapshot = metadata
+                .snapshot()
+                .unwrap()
+                .parse_untrusted()
+                .unwrap()
+                .assume_valid()
+                .unwrap();
+
+            for role in [&role_a, &role_b] {
+                let mut rdr = repo
+                    .fetch_metadata(role, MetadataVersion::Number(1))
+                    .await
+                    .unwrap();
+                let mut buf = vec![];
+                rdr.read_to_end(&mut buf).await.unwrap();
+
+                assert_eq!(
+                    snapshot.meta().get(role),
+                    Some(
+                        &MetadataDescription::from_slice(&buf, 1, &[HashAlgorithm::Sha256])
+                            .unwrap()
+                    ),
+                );
+                assert_eq!(metadata.delegated_targets()[role].as_bytes(), &buf[..]);
+            }
+            assert_eq!(metadata.delegated_targets().len(), 2);
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                repo,
+            )
+            .await
+            .unwrap();
+
+            client.update().await.unwrap();
+
+            assert_eq!(
+                client
+                    .fetch_target_description(&target_path1)
+                    .await
+                    .unwrap(),
+                TargetDescription::from_slice(target_file1, &[HashAlgorithm::Sha256]).unwrap(),
+            );
+
+            // Only change `b`. The new version should keep the old target, and the snapshot should
+            // keep describing `a`.
+            let mut parts = client.into_parts();
+            let metadata = RepoBuilder::from_database(&mut parts.remote, &parts.database)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .skip_root()
+                .signing_delegated_targets_keys(role_b.clone(), &[&KEYS[2]])
+                .add_delegated_target(
+                    role_b.clone(),
+                    target_path2.clone(),
+                    Cursor::new(target_file2),
+                )
+                .await
+                .unwrap()
+                .skip_targets()
+                .commit()
+                .await
+                .unwrap();
+
+            assert!(metadata.targets().is_none());
+
+            let snapshot = metadata
+                .snapshot()
+                .unwrap()
+                .parse_untrusted()
+                .unwrap()
+                .assume_valid()
+                .unwrap();
+            assert_eq!(snapshot.meta().get(&role_a).map(|m| m.version()), Some(1));
+            assert_eq!(snapshot.meta().get(&role_b).map(|m| m.version()), Some(2));
+
+            let mut client = Client::from_parts(parts);
+            client.update().await.unwrap();
+
+            for (target_path, target_file) in
+                [(&target_path1, target_file1), (&target_path2, target_file2)]
+            {
+                assert_eq!(
+                    client.fetch_target_description(target_path).await.unwrap(),
+                    TargetDescription::from_slice(target_file, &[HashAlgorithm::Sha256]).unwrap(),
+                );
+            }
+        })
+    }
+
+    #[test]
+    fn test_delegated_targets_must_be_delegated() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+
+            let role = MetadataPath::new("a").unwrap();
+
+            let res = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .signing_delegated_targets_keys(role.clone(), &[&KEYS[1]])
+                .commit()
+                .await;
+
+            assert_matches!(
+                res,
+                Err(Error::UnauthorizedDelegation { parent_role, child_role })
+                if parent_role == MetadataPath::targets() && child_role == role
+            );
+
+            // Nothing should have been written to the repository.
+            assert_matches!(
+                repo.fetch_metadata(&MetadataPath::root(), MetadataVersion::None)
+                    .await
+                    .err(),
+                Some(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
+    #[test]
+    fn test_delegated_targets_must_be_signed_by_delegated_keys() {
+        block_on(async move {
+            let role = MetadataPath::new("a").unwrap();
+
+            let builder = || {
+                RepoBuilder::create(EphemeralRepository::<Pouf1>::new())
+                    .trusted_root_keys(&[&KEYS[0]])
+                    .trusted_targets_keys(&[&KEYS[0]])
+                    .trusted_snapshot_keys(&[&KEYS[0]])
+                    .trusted_timestamp_keys(&[&KEYS[0]])
+                    .skip_root()
+                    .add_delegation_key(KEYS[1].public().clone())
+                    .add_delegation_role(
+                        Delegation::builder(role.clone())
+                            .key(KEYS[1].public())
+                            .delegate_path(PathPattern::new("a/*").unwrap())
+                            .build()
+                            .unwrap(),
+                    )
+            };
+
+            // Changes without any keys can't be signed.
+            let res = builder()
+                .add_delegated_delegation_key(role.clone(), KEYS[2].public().clone())
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot()
+                .await;
+            assert_matches!(res.err(), Some(Error::MissingPrivateKey { role: r }) if r == role);
+
+            // Signing with a key that isn't part of the delegation fails validation.
+            let res = RepoBuilder::create(EphemeralRepository::<Pouf1>::new())
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .add_delegation_role(
+                    Delegation::builder(role.clone())
+                        .key(KEYS[1].public())
+                        .delegate_path(PathPattern::new("a/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role.clone(), &[&KEYS[2]])
+                .commit()
+                .await;
+            assert_matches!(res, Err(Error::MetadataMissingSignatures { .. }));
+
+            // The top-level roles are not delegated targets.
+            let res = builder()
+                .signing_delegated_targets_keys(MetadataPath::snapshot(), &[&KEYS[1]])
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot()
+                .await;
+            assert_matches!(res.err(), Some(Error::IllegalArgument(_)));
+        })
+    }
+
+    #[test]
+    fn test_remove_targets_and_delegations() {
+        block_on(async move {
+            let mut repo = EphemeralRepository::<Pouf1>::new();
+
+            let role_a = MetadataPath::new("a").unwrap();
+            let role_b = MetadataPath::new("b").unwrap();
+
+            let target_path1 = TargetPath::new("foo/bar").unwrap();
+            let target_file1: &[u8] = b"things fade, alternatives exclude";
+            let target_path2 = TargetPath::new("foo/baz").unwrap();
+            let target_file2: &[u8] = b"hello world";
+            let target_path3 = TargetPath::new("a/qux").unwrap();
+            let target_file3: &[u8] = b"goodbye world";
+
+            // targets delegates `a/*` to `a`, which delegates it to `b`.
+            let metadata = RepoBuilder::create(&mut repo)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_target(target_path1.clone(), Cursor::new(target_file1))
+                .await
+                .unwrap()
+                .add_target(target_path2.clone(), Cursor::new(target_file2))
+                .await
+                .unwrap()
+                .add_delegation_key(KEYS[1].public().clone())
+                .add_delegation_role(
+                    Delegation::builder(role_a.clone())
+                        .key(KEYS[1].public())
+                        .delegate_path(PathPattern::new("a/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role_a.clone(), &[&KEYS[1]])
+                .add_delegated_delegation_key(role_a.clone(), KEYS[2].public().clone())
+                .add_delegated_delegation_role(
+                    role_a.clone(),
+                    Delegation::builder(role_b.clone())
+                        .key(KEYS[2].public())
+                        .delegate_path(PathPattern::new("a/*").unwrap())
+                        .build()
+                        .unwrap(),
+                )
+                .signing_delegated_targets_keys(role_b.clone(), &[&KEYS[2]])
+                .add_delegated_target(
+                    role_b.clone(),
+                    target_path3.clone(),
+                    Cursor::new(target_file3),
+                )
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::new(),
+                repo,
+            )
+            .await
+            .unwrap();
+            client.update().await.unwrap();
+            assert!(client.fetch_target_description(&target_path3).await.is_ok());
+
+            let mut parts = client.into_parts();
+
+            // Removing a delegation key that's still used by a delegation is an error.
+            let res = RepoBuilder::from_database(&mut parts.remote, &parts.database)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .skip_root()
+                .remove_delegation_key(KEYS[1].public().key_id().clone())
+                .stage_targets()
+                .await;
+            assert_matches!(res.err(), Some(Error::IllegalArgument(_)));
+
+            // Remove a target and the delegation to `a`.
+            let metadata = RepoBuilder::from_database(&mut parts.remote, &parts.database)
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .skip_root()
+                .remove_target(target_path1.clone())
+                .remove_delegation_role(role_a.clone())
+                .remove_delegation_key(KEYS[1].public().key_id().clone())
+                .commit()
+                .await
+                .unwrap();
+
+            let targets = metadata
+                .targets()
+                .unwrap()
+                .parse_untrusted()
+                .unwrap()
+                .assume_valid()
+                .unwrap();
+            assert_eq!(
+                targets.targets().keys().collect::<Vec<_>>(),
+                vec![&target_path2]
+            );
+            assert!(targets.delegations().roles().is_empty());
+            assert!(targets.delegations().keys().is_empty());
+
+            // The snapshot should no longer describe `a`, nor `b` which was delegated through it.
+            let snapshot = metadata
+                .snapshot()
+                .unwrap()
+                .parse_untrusted()
+                .unwrap()
+                .assume_valid()
+                .unwrap();
+            assert_eq!(
+                snapshot.meta().keys().collect::<Vec<_>>(),
+                vec![&MetadataPath::targets()]
+            );
+
+            let mut client = Client::from_parts(parts);
+            client.update().await.unwrap();
+
+            assert!(client
+                .fetch_target_description(&target_path1)
+                .await
+                .is_err());
+            assert!(client
+                .fetch_target_description(&target_path3)
+                .await
+                .is_err());
+            assert_eq!(
+                client
+                    .fetch_target_description(&target_path2)
+                    .await
+                    .unwrap(),
+                TargetDescription::from_slice(target_file2, &[HashAlgorithm::Sha256]).unwrap(),
+            );
+        })
+    }
+
+    #[test]
+    fn test_snapshot_must_not_describe_undelegated_roles() {
+        block_on(async move {
+            let role = MetadataPath::new("a").unwrap();
+
+            let res = RepoBuilder::create(EphemeralRepository::<Pouf1>::new())
+                .trusted_root_keys(&[&KEYS[0]])
+                .trusted_targets_keys(&[&KEYS[0]])
+                .trusted_snapshot_keys(&[&KEYS[0]])
+                .trusted_timestamp_keys(&[&KEYS[0]])
+                .stage_root()
+                .await
+                .unwrap()
+                .stage_targets()
+                .await
+                .unwrap()
+                .stage_snapshot_with_builder(|builder| {
+                    builder.insert_metadata_description(
+                        role.clone(),
+                        MetadataDescription::new(1, None, HashMap::new()).unwrap(),
+                    )
+                })
+                .await
+                .unwrap()
+                .commit()
+                .await;
+
+            assert_matches!(
+                res,
+                Err(Error::UnauthorizedDelegation { parent_role, child_role })
+                if parent_role == MetadataPath::targets() && child_role == role
+            );
+        })
+    }
+
+    #[test]
+    fn test_threshold_signing_with_detached_signatures() {
+        block_on(async move {
+            let repo = EphemeralRepository::<Pouf1>::new();
+            let staging = EphemeralRepository::<Pouf1>::new();
+
+            // The root keys are held by five different people, three of which need to sign.
+            let root_keyholders = &KEYS[..5];
+
+            let unsigned_root = RepoBuilder::create(&repo)
+                .trusted_targets_keys(&[&KEYS[5]])
+                .trusted_snapshot_keys(&[&KEYS[5]])
+                .trusted_timestamp_keys(&[&KEYS[5]])
+                .unsigned_root_with_builder(|builder| {
+                    root_keyholders
+                        .iter()
+                        .fold(builder, |builder, key| {
+                            builder.root_key(key.public().clone())
+                        })
+                        .root_threshold(3)
+                })
+                .unwrap();
+            assert!(unsigned_root.signatures().is_empty());
+
+            store_pending_metadata(&staging, &unsigned_root)
+                .await
+                .unwrap();
+            let payload = unsigned_root.payload().unwrap();
+
+            // Each keyholder signs the payload offline and sends back a detached signature.
+            let detached_signatures = root_keyholders
+                .iter()
+                .map(|key| serde_json::to_vec(&key.sign(&payload).unwrap()).unwrap())
+                .collect::<Vec<_>>();
+
+            // A signature from a key that isn't in the root does not count.
+            let mut root: SignedMetadata<Pouf1, RootMetadata> =
+                fetch_pending_metadata(&staging).await.unwrap();
+            root.add_detached_signature(KEYS[5].sign(&payload).unwrap());
+
+            for (i, signature) in detached_signatures.iter().take(2).enumerate() {
+                let mut root: SignedMetadata<Pouf1, RootMetadata> =
+                    fetch_pending_metadata(&staging).await.unwrap();
+                root.add_detached_signature(serde_json::from_slice(signature).unwrap());
+                assert_eq!(root.signatures().len(), i + 1);
+                store_pending_metadata(&staging, &root).await.unwrap();
+            }
+
+            // Two signatures are not enough.
+            let mut root: SignedMetadata<Pouf1, RootMetadata> =
+                fetch_pending_metadata(&staging).await.unwrap();
+            root.add_detached_signature(KEYS[5].sign(&payload).unwrap());
+            assert_matches!(
+                RepoBuilder::create(&repo)
+                    .stage_signed_root(root.clone())
+                    .err(),
+                Some(Error::MetadataMissingSignatures {
+                    number_of_valid_signatures: 2,
+                    threshold: 3,
+                    ..
+                })
+            );
+
+            // But three are.
+            root.add_detached_signature(serde_json::from_slice(&detached_signatures[4]).unwrap());
+            let metadata = RepoBuilder::create(&repo)
+                .trusted_targets_keys(&[&KEYS[5]])
+                .trusted_snapshot_keys(&[&KEYS[5]])
+                .trusted_timestamp_keys(&[&KEYS[5]])
+                .stage_signed_root(root)
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            let db = Database::from_trusted_metadata(&metadata).unwrap();
+            assert_eq!(db.trusted_root().root().threshold(), 3);
+            assert_eq!(db.trusted_timestamp().map(|m| m.version()), Some(1));
+
+            // Rotating the root also needs a threshold of the trusted root's keys.
+            let unsigned_root = RepoBuilder::from_database(&repo, &db)
+                .trusted_targets_keys(&[&KEYS[5]])
+                .trusted_snapshot_keys(&[&KEYS[5]])
+                .trusted_timestamp_keys(&[&KEYS[5]])
+                .unsigned_root_with_builder(|builder| {
+                    builder.root_key(KEYS[5].public().clone()).root_threshold(1)
+                })
+                .unwrap();
+            let payload = unsigned_root.payload().unwrap();
+
+            let mut root = unsigned_root.clone();
+            root.add_detached_signature(KEYS[5].sign(&payload).unwrap());
+            for key in &root_keyholders[..2] {
+                root.add_detached_signature(key.sign(&payload).unwrap());
+            }
+            assert_matches!(
+                RepoBuilder::from_database(&repo, &db)
+                    .stage_signed_root(root.clone())
+                    .err(),
+                Some(Error::MetadataMissingSignatures {
+                    number_of_valid_signatures: 2,
+                    threshold: 3,
+                    ..
+                })
+            );
+
+            root.add_detached_signature(root_keyholders[2].sign(&payload).unwrap());
+            let metadata = RepoBuilder::from_database(&repo, &db)
+                .stage_signed_root(root)
+                .unwrap()
+                .skip_targets()
+                .skip_snapshot()
+                .skip_timestamp()
+                .commit()
+                .await
+                .unwrap();
+
+            let mut db = db;
+            db.update_metadata(&metadata).unwrap();
+            assert_eq!(db.trusted_root().version(), 2);
+        })
+    }
+
+    #[test]
+    fn test_stage_signed_targets() {
+        block_on(async move {
+            let target_path = TargetPath::new("foo").unwrap();
+            let target_file: &[u8] = b"things fade, alternatives exclude";
+
+            for keyholders in [&KEYS[1..2], &KEYS[1..3]] {
+                let repo = EphemeralRepository::<Pouf1>::new();
+
+                let builder = RepoBuilder::create(&repo)
+                    .trusted_root_keys(&[&KEYS[0]])
+                    .trusted_snapshot_keys(&[&KEYS[0]])
+                    .trusted_timestamp_keys(&[&KEYS[0]])
+                    .stage_root_with_builder(|builder| {
+                        builder
+                            .targets_key(KEYS[1].public().clone())
+                            .targets_key(KEYS[2].public().clone())
+                            .targets_threshold(2)
+                    })
+                    .await
+                    .unwrap()
+                    .add_target(target_path.clone(), Cursor::new(target_file))
+                    .await
+                    .unwrap();
+
+                let mut targets = builder.unsigned_targets().unwrap();
+                let payload = targets.payload().unwrap();
+                for key in keyholders {
+                    targets.add_detached_signature(key.sign(&payload).unwrap());
+                }
+
+                if keyholders.len() < 2 {
+                    assert_matches!(
+                        builder.stage_signed_targets(targets).err(),
+                        Some(Error::MetadataMissingSignatures {
+                            number_of_valid_signatures: 1,
+                            threshold: 2,
+                            ..
+                        })
+                    );
+                    continue;
+                }
+
+                let metadata = builder
+                    .stage_signed_targets(targets)
+                    .unwrap()
+                    .commit()
+                    .await
+                    .unwrap();
+
+                let db = Database::from_trusted_metadata(&metadata).unwrap();
+                assert_eq!(
+                    db.trusted_targets().unwrap().targets().get(&target_path),
+                    Some(
+                        &TargetDescription::from_slice(target_file, &[HashAlgorithm::Sha256])
+                            .unwrap()
+                    ),
+                );
+            }
+        })
+    }
+
+    /// A [Signer] that asks a signing server listening on a socket for its signatures, the way a
+    /// remote signing service or an HSM daemon would.
+    struct SocketSigner {
+        public: PublicKey,
+        addr: SocketAddr,
+    }
+
+    impl SocketSigner {
+        /// Start a signing server on a background thread that signs with `key`.
+        fn spawn(key: &'static Ed25519PrivateKey, requests: Arc<AtomicUsize>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let addr = listener.local_addr().unwrap();
+
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let mut stream = stream.unwrap();
+                    let mut len = [0; 4];
+                    stream.read_exact(&mut len).unwrap();
+                    let mut msg = vec![0; u32::from_be_bytes(len) as usize];
+                    stream.read_exact(&mut msg).unwrap();
+
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    let sig = key.sign(&msg).unwrap();
+                    stream.write_all(sig.value().as_bytes()).unwrap();
+                }
+            });
+
+            SocketSigner {
+                public: key.public().clone(),
+                addr,
+            }
+        }
+    }
+
+    impl Signer for SocketSigner {
+        fn public_key(&self) -> &PublicKey {
+            &self.public
+        }
+
+        fn sign_async<'a>(&'a self, msg: &'a [u8]) -> BoxFuture<'a, Result<crypto::Signature>> {
+            async move {
+                let mut stream = TcpStream::connect(self.addr)?;
+                stream.write_all(&(msg.len() as u32).to_be_bytes())?;
+                stream.write_all(msg)?;
+
+                let mut sig = vec![];
+                stream.read_to_end(&mut sig)?;
+
+                Ok(crypto::Signature::new(
+                    self.public.key_id().clone(),
+                    crypto::SignatureValue::new(sig),
+                ))
+            }
+            .boxed()
+        }
+    }
+
+    #[test]
+    fn test_builder_signs_with_async_signers() {
+        block_on(async move {
+            let requests = Arc::new(AtomicUsize::new(0));
+            let root_signer = SocketSigner::spawn(&KEYS[0], Arc::clone(&requests));
+            let targets_signer = SocketSigner::spawn(&KEYS[1], Arc::clone(&requests));
+            let snapshot_signer = SocketSigner::spawn(&KEYS[2], Arc::clone(&requests));
+            let timestamp_signer = SocketSigner::spawn(&KEYS[3], Arc::clone(&requests));
+
+            let repo = EphemeralRepository::<Pouf1>::new();
+            let metadata = RepoBuilder::create(&repo)
+                .trusted_root_keys(&[&root_signer])
+                .trusted_targets_keys(&[&targets_signer])
+                .trusted_snapshot_keys(&[&snapshot_signer])
+                .trusted_timestamp_keys(&[&timestamp_signer])
+                .commit()
+                .await
+                .unwrap();
+            assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+            // The signatures must be the same as if the keys were held locally, and verify
+            // against the keys in the new root.
+            let db = Database::from_trusted_metadata(&metadata).unwrap();
+            assert_eq!(
+                db.trusted_root().root_keys().collect::<Vec<_>>(),
+                vec![KEYS[0].public()]
+            );
+            assert_eq!(db.trusted_timestamp().map(|m| m.version()), Some(1));
+
+            // Signers and in-process keys can be mixed, such as when rotating from a local key to
+            // one held by a signing service.
+            let mut signed =
+                SignedMetadataBuilder::<Pouf1, _>::from_metadata(&**db.trusted_targets().unwrap())
+                    .unwrap()
+                    .sign(&KEYS[4])
+                    .await
+                    .unwrap()
+                    .build();
+            signed.add_signature(&targets_signer).await.unwrap();
+            assert_eq!(signed.signatures().len(), 2);
+            assert_eq!(requests.load(Ordering::SeqCst), 5);
+        })
+    }
+
+    #[test]
+    fn test_builder_fails_if_signer_fails() {
+        block_on(async move {
+            let requests = Arc::new(AtomicUsize::new(0));
+            let signer = SocketSigner::spawn(&KEYS[0], Arc::clone(&requests));
+
+            // Point the targets signer at a socket nothing is listening on.
+            let unreachable = SocketSigner {
+                public: KEYS[1].public().clone(),
+                addr: TcpListener::bind("127.0.0.1:0")
+                    .unwrap()
+                    .local_addr()
+                    .unwrap(),
+            };
+
+            let repo = EphemeralRepository::<Pouf1>::new();
+            let res = RepoBuilder::create(&repo)
+                .trusted_root_keys(&[&signer])
+                .trusted_targets_keys(&[&unreachable])
+                .trusted_snapshot_keys(&[&signer])
+                .trusted_timestamp_keys(&[&signer])
+                .commit()
+                .await;
+            assert_matches!(res, Err(Error::Io(_)));
+        })
+    }
 }
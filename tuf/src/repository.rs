@@ -1,18 +1,25 @@
 //! Interfaces for interacting with different types of TUF repositories.
 
+use crate::client::Config;
 use crate::crypto::{self, HashAlgorithm, HashValue};
 use crate::metadata::{
     Metadata, MetadataPath, MetadataVersion, RawSignedMetadata, TargetDescription, TargetPath,
 };
 use crate::pouf::Pouf;
-use crate::util::SafeAsyncRead;
+use crate::timer::SharedTimer;
+use crate::util::{Deadline, SafeAsyncRead};
 use crate::{Error, Result};
 
 use futures_io::AsyncRead;
-use futures_util::future::BoxFuture;
+use futures_util::future::{BoxFuture, FutureExt as _, TryFutureExt as _};
 use futures_util::io::AsyncReadExt;
+use std::fmt;
+use std::io;
 use std::marker::PhantomData;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 mod file_system;
 pub use self::file_system::{
@@ -28,6 +35,15 @@ pub use self::http::{HttpRepository, HttpRepositoryBuilder};
 mod ephemeral;
 pub use self::ephemeral::{EphemeralBatchUpdate, EphemeralRepository};
 
+mod mirrors;
+pub use self::mirrors::MirrorsRepository;
+
+mod retry;
+pub use self::retry::{RetryPolicy, RetryPolicyBuilder, RetryRepository};
+
+mod spec_version;
+pub use self::spec_version::SpecVersionRepository;
+
 #[cfg(test)]
 mod error_repo;
 #[cfg(test)]
@@ -46,13 +62,7 @@ where
     /// Fetch signed metadata identified by `meta_path`, `version`, and
     /// [`D::extension()`][extension].
     ///
-    /// Implementations may ignore `max_length` and `hash_data` as [`Client`][Client] will verify
-    /// these constraints itself. However, it may be more efficient for an implementation to detect
-    /// invalid metadata and fail the fetch operation before streaming all of the bytes of the
-    /// metadata.
-    ///
     /// [extension]: crate::pouf::Pouf::extension
-    /// [Client]: crate::client::Client
     fn fetch_metadata<'a>(
         &'a self,
         meta_path: &MetadataPath,
@@ -60,17 +70,192 @@ where
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>>;
 
     /// Fetch the given target.
+    fn fetch_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>>;
+
+    /// Fetch signed metadata like [`fetch_metadata`](Self::fetch_metadata), which the client
+    /// expects to be at most `max_length` bytes, and to match `hashes`.
     ///
-    /// Implementations may ignore the `length` and `hashes` fields in `target_description` as
-    /// [`Client`][Client] will verify these constraints itself. However, it may be more efficient
-    /// for an implementation to detect invalid targets and fail the fetch operation before
-    /// streaming all of the bytes.
+    /// Implementations may ignore `max_length` and `hashes` as [`Client`][Client] will verify
+    /// these constraints itself. However, it may be more efficient for an implementation to detect
+    /// invalid metadata and fail the fetch operation before streaming all of the bytes of the
+    /// metadata, such as by returning [`Error::ExceededMaxLength`] when the length is known up
+    /// front. The default implementation calls `fetch_metadata`.
     ///
     /// [Client]: crate::client::Client
-    fn fetch_target<'a>(
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let _ = (max_length, hashes);
+        self.fetch_metadata(meta_path, version)
+    }
+
+    /// Fetch the given target like [`fetch_target`](Self::fetch_target), which the client
+    /// expects to be `length` bytes, and to match `hashes`.
+    ///
+    /// Implementations may ignore `length` and `hashes` as [`Client`][Client] will verify these
+    /// constraints itself. However, it may be more efficient for an implementation to detect
+    /// invalid targets and fail the fetch operation before streaming all of the bytes. The
+    /// default implementation calls `fetch_target`.
+    ///
+    /// [Client]: crate::client::Client
+    fn fetch_target_with_constraints<'a>(
         &'a self,
         target_path: &TargetPath,
-    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>>;
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let _ = (length, hashes);
+        self.fetch_target(target_path)
+    }
+
+    /// Fetch signed metadata like
+    /// [`fetch_metadata_with_constraints`](Self::fetch_metadata_with_constraints), returning a
+    /// reader that also identifies the source that served the metadata.
+    ///
+    /// This is what [`Client`][Client] uses. If the metadata can't be read or fails verification,
+    /// the client calls [`SourcedReader::reject`], so that repositories which fetch from several
+    /// sources, such as [`MirrorsRepository`], can fetch it again from another source. The default
+    /// implementation calls `fetch_metadata_with_constraints`, and reports no source.
+    ///
+    /// [Client]: crate::client::Client
+    fn fetch_metadata_with_source<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        self.fetch_metadata_with_constraints(meta_path, version, max_length, hashes)
+            .map_ok(SourcedReader::new)
+            .boxed()
+    }
+
+    /// Fetch the given target like
+    /// [`fetch_target_with_constraints`](Self::fetch_target_with_constraints), returning a reader
+    /// that also identifies the source that served the target.
+    ///
+    /// See [`fetch_metadata_with_source`](Self::fetch_metadata_with_source) for how
+    /// [`Client`][Client] uses the source. The default implementation calls
+    /// `fetch_target_with_constraints`, and reports no source.
+    ///
+    /// [Client]: crate::client::Client
+    fn fetch_target_with_source<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        self.fetch_target_with_constraints(target_path, length, hashes)
+            .map_ok(SourcedReader::new)
+            .boxed()
+    }
+}
+
+/// The source that served a [`SourcedReader`], for repositories that fetch from several sources.
+///
+/// Every fetch gets its own `FetchSource`, so it keeps track of what was fetched, and which
+/// sources have already been tried for it, without sharing any state with other fetches.
+pub trait FetchSource<'a>: Send {
+    /// A name for the source, such as the name of a mirror.
+    fn name(&self) -> Option<&str>;
+
+    /// Reject the data this source served, which couldn't be read or failed verification with
+    /// `error`. Returns the result of fetching the same data again from another source, or `None`
+    /// if there is no other source to try.
+    fn reject(self: Box<Self>, error: &Error) -> BoxFuture<'a, Option<Result<SourcedReader<'a>>>>;
+}
+
+/// Reads metadata or a target fetched by
+/// [`RepositoryProvider::fetch_metadata_with_source`] or
+/// [`RepositoryProvider::fetch_target_with_source`], and identifies the source that served it.
+pub struct SourcedReader<'a> {
+    read: Box<dyn AsyncRead + Send + Unpin + 'a>,
+    source: Option<Box<dyn FetchSource<'a> + 'a>>,
+}
+
+impl<'a> SourcedReader<'a> {
+    /// Create a `SourcedReader` for data that wasn't served by any particular source.
+    pub fn new(read: Box<dyn AsyncRead + Send + Unpin + 'a>) -> Self {
+        Self { read, source: None }
+    }
+
+    /// Create a `SourcedReader` for data that was served by `source`.
+    pub fn with_source(
+        read: Box<dyn AsyncRead + Send + Unpin + 'a>,
+        source: Box<dyn FetchSource<'a> + 'a>,
+    ) -> Self {
+        Self {
+            read,
+            source: Some(source),
+        }
+    }
+
+    /// A name for the source that served the data, if the repository reports one.
+    pub fn source_name(&self) -> Option<&str> {
+        self.source.as_ref().and_then(|source| source.name())
+    }
+
+    /// Reject the data, which couldn't be read or failed verification with `error`. Returns the
+    /// result of fetching it again from another source, or `None` if there is no other source to
+    /// try.
+    pub fn reject(self, error: &Error) -> BoxFuture<'a, Option<Result<SourcedReader<'a>>>> {
+        match self.source {
+            Some(source) => source.reject(error),
+            None => async { None }.boxed(),
+        }
+    }
+
+    /// Split the reader from its source.
+    pub fn into_parts(
+        self,
+    ) -> (
+        Box<dyn AsyncRead + Send + Unpin + 'a>,
+        Option<Box<dyn FetchSource<'a> + 'a>>,
+    ) {
+        (self.read, self.source)
+    }
+}
+
+impl AsyncRead for SourcedReader<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.read).poll_read(cx, buf)
+    }
+}
+
+impl fmt::Debug for SourcedReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SourcedReader")
+            .field("source", &self.source_name())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Used by providers that know the length of metadata or a target before serving it, to fail with
+/// [`Error::ExceededMaxLength`] if the `length` of `location` is more than `max_length`.
+pub(crate) fn check_max_length(
+    location: impl ToString,
+    length: u64,
+    max_length: Option<u64>,
+) -> Result<()> {
+    match max_length {
+        Some(max_length) if length > max_length => Err(Error::ExceededMaxLength {
+            location: location.to_string(),
+            length,
+            max_length,
+        }),
+        _ => Ok(()),
+    }
 }
 
 /// Test helper to help read a metadata file from a repository into a string.
@@ -130,6 +315,25 @@ where
         target_path: &TargetPath,
         target: &'a mut (dyn AsyncRead + Send + Unpin),
     ) -> BoxFuture<'a, Result<()>>;
+
+    /// Remove the metadata identified by `meta_path`, `version`, and
+    /// [`D::extension()`][extension]. Removing metadata that does not exist is not an error.
+    ///
+    /// [`Client`][Client] uses this to delete its persisted copies of metadata that was signed
+    /// with keys that a new root metadata no longer trusts. The default implementation does
+    /// nothing, and returns `Ok(())`. Such copies then fail verification when they are next
+    /// loaded, and are replaced by the client.
+    ///
+    /// [extension]: crate::pouf::Pouf::extension
+    /// [Client]: crate::client::Client
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let _ = (meta_path, version);
+        async { Ok(()) }.boxed()
+    }
 }
 
 /// A subtrait of both RepositoryStorage and RepositoryProvider. This is useful to create
@@ -166,6 +370,44 @@ macro_rules! impl_provider {
             ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
                 (**self).fetch_target(target_path)
             }
+
+            fn fetch_metadata_with_constraints<'a>(
+                &'a self,
+                meta_path: &MetadataPath,
+                version: MetadataVersion,
+                max_length: Option<usize>,
+                hashes: &[(&'static HashAlgorithm, HashValue)],
+            ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+                (**self).fetch_metadata_with_constraints(meta_path, version, max_length, hashes)
+            }
+
+            fn fetch_target_with_constraints<'a>(
+                &'a self,
+                target_path: &TargetPath,
+                length: u64,
+                hashes: &[(&'static HashAlgorithm, HashValue)],
+            ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+                (**self).fetch_target_with_constraints(target_path, length, hashes)
+            }
+
+            fn fetch_metadata_with_source<'a>(
+                &'a self,
+                meta_path: &MetadataPath,
+                version: MetadataVersion,
+                max_length: Option<usize>,
+                hashes: &[(&'static HashAlgorithm, HashValue)],
+            ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+                (**self).fetch_metadata_with_source(meta_path, version, max_length, hashes)
+            }
+
+            fn fetch_target_with_source<'a>(
+                &'a self,
+                target_path: &TargetPath,
+                length: u64,
+                hashes: &[(&'static HashAlgorithm, HashValue)],
+            ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+                (**self).fetch_target_with_source(target_path, length, hashes)
+            }
         }
     };
 }
@@ -196,6 +438,14 @@ macro_rules! impl_storage {
             ) -> BoxFuture<'a, Result<()>> {
                 (**self).store_target(target_path, target)
             }
+
+            fn remove_metadata<'a>(
+                &'a self,
+                meta_path: &MetadataPath,
+                version: MetadataVersion,
+            ) -> BoxFuture<'a, Result<()>> {
+                (**self).remove_metadata(meta_path, version)
+            }
         }
     };
 }
@@ -210,6 +460,9 @@ impl_storage!(<D: Pouf, T: RepositoryStorage<D> + ?Sized> RepositoryStorage<D> f
 #[derive(Debug, Clone)]
 pub(crate) struct Repository<R, D> {
     repository: R,
+    metadata_timeout: Option<Duration>,
+    target_timeout: Option<Duration>,
+    timer: SharedTimer,
     _pouf: PhantomData<D>,
 }
 
@@ -218,10 +471,21 @@ impl<R, D> Repository<R, D> {
     pub(crate) fn new(repository: R) -> Self {
         Self {
             repository,
+            metadata_timeout: None,
+            target_timeout: None,
+            timer: SharedTimer::default(),
             _pouf: PhantomData,
         }
     }
 
+    /// Fail fetches that take longer than the timeouts in `config`.
+    pub(crate) fn with_timeouts(mut self, config: &Config) -> Self {
+        self.metadata_timeout = config.metadata_timeout();
+        self.target_timeout = config.target_timeout();
+        self.timer = config.timer.clone();
+        self
+    }
+
     /// Perform a sanity check that `M`, `Role`, and `MetadataPath` all describe the same entity.
     fn check<M>(meta_path: &MetadataPath) -> Result<()>
     where
@@ -273,16 +537,9 @@ where
     where
         M: Metadata,
     {
-        Self::check::<M>(meta_path)?;
-
-        // Fetch the metadata, verifying max_length and hashes (if provided), as
-        // the repository implementation should only be trusted to use those as
-        // hints to fail early.
         let mut reader = self
-            .repository
-            .fetch_metadata(meta_path, version)
-            .await?
-            .check_length_and_hash(max_length.unwrap_or(::std::usize::MAX) as u64, hashes)?;
+            .fetch_metadata_with_source::<M>(meta_path, version, max_length, hashes)
+            .await?;
 
         let mut buf = Vec::new();
         reader.read_to_end(&mut buf).await?;
@@ -290,6 +547,43 @@ where
         Ok(RawSignedMetadata::new(buf))
     }
 
+    /// Fetch metadata like [`Repository::fetch_metadata`], but through the returned
+    /// [`SourcedReader`], so that the caller can reject the metadata if it fails verification.
+    /// The reader fails if the metadata exceeds `max_length` bytes or doesn't match `hashes`, and
+    /// so does the reader for any metadata fetched again after a rejection.
+    pub(crate) async fn fetch_metadata_with_source<'a, M>(
+        &'a self,
+        meta_path: &'a MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: Vec<(&'static HashAlgorithm, HashValue)>,
+    ) -> Result<SourcedReader<'a>>
+    where
+        M: Metadata,
+    {
+        Self::check::<M>(meta_path)?;
+
+        let mut deadline = Deadline::new(self.timer.get(), self.metadata_timeout);
+        let reader = deadline
+            .run(
+                self.repository
+                    .fetch_metadata_with_source(meta_path, version, max_length, &hashes),
+            )
+            .await??;
+
+        // Verify max_length and hashes (if provided), as the repository implementation should
+        // only be trusted to use those as hints to fail early. The deadline also covers reading
+        // the metadata.
+        CheckedSource::check(
+            reader,
+            max_length.unwrap_or(::std::usize::MAX) as u64,
+            hashes,
+            deadline,
+            self.timer.clone(),
+            self.metadata_timeout,
+        )
+    }
+
     /// Fetch the target identified by `target_path` through the returned `AsyncRead`, verifying
     /// that the target matches the preferred hash specified in `target_description` and that it is
     /// the expected length. Such verification errors will be provided by a read failure on the
@@ -297,12 +591,15 @@ where
     ///
     /// It is **critical** that none of the bytes from the returned `AsyncRead` are used until it
     /// has been fully consumed as the data is untrusted.
+    ///
+    /// If the returned reader fails, the caller may reject the target with
+    /// [`SourcedReader::reject`] to fetch it again from another source, if the repository has one.
     pub(crate) async fn fetch_target(
         &self,
         consistent_snapshot: bool,
         target_path: &TargetPath,
         target_description: TargetDescription,
-    ) -> Result<impl AsyncRead + Send + Unpin + '_> {
+    ) -> Result<SourcedReader<'_>> {
         // https://theupdateframework.github.io/specification/v1.0.26/#fetch-target 5.7.3:
         //
         // [...] download the target (up to the number of bytes specified in the targets metadata),
@@ -318,25 +615,116 @@ where
         // [...] If consistent snapshots are not used (see § 6.2 Consistent snapshots), then the
         // filename used to download the target file is of the fixed form FILENAME.EXT (e.g.,
         // foobar.tar.gz). Otherwise, the filename is of the form HASH.FILENAME.EXT [...]
-        let target = if consistent_snapshot {
-            let mut hashes = hashes.iter();
-            loop {
-                if let Some((_, hash)) = hashes.next() {
+        let mut deadline = Deadline::new(self.timer.get(), self.target_timeout);
+        let target = deadline
+            .run(async {
+                if !consistent_snapshot {
+                    return self
+                        .repository
+                        .fetch_target_with_source(target_path, length, &hashes)
+                        .await;
+                }
+
+                for (_, hash) in &hashes {
                     let target_path = target_path.with_hash_prefix(hash)?;
-                    match self.repository.fetch_target(&target_path).await {
-                        Ok(target) => break target,
+                    match self
+                        .repository
+                        .fetch_target_with_source(&target_path, length, &hashes)
+                        .await
+                    {
                         Err(Error::TargetNotFound(_)) => {}
-                        Err(err) => return Err(err),
+                        result => return result,
                     }
-                } else {
-                    return Err(Error::TargetNotFound(target_path.clone()));
                 }
-            }
-        } else {
-            self.repository.fetch_target(target_path).await?
-        };
 
-        target.check_length_and_hash(length, hashes)
+                Err(Error::TargetNotFound(target_path.clone()))
+            })
+            .await??;
+
+        // The deadline also covers reading the target.
+        CheckedSource::check(
+            target,
+            length,
+            hashes,
+            deadline,
+            self.timer.clone(),
+            self.target_timeout,
+        )
+    }
+}
+
+/// Wraps the [`FetchSource`] of data fetched through a [`Repository`], so that the data it fetches
+/// again after a rejection is checked the same way as the data it first served.
+struct CheckedSource<'a> {
+    source: Box<dyn FetchSource<'a> + 'a>,
+    length: u64,
+    hashes: Vec<(&'static HashAlgorithm, HashValue)>,
+    timer: SharedTimer,
+    timeout: Option<Duration>,
+}
+
+impl<'a> CheckedSource<'a> {
+    /// Check that `reader` is at most `length` bytes, matches `hashes`, and is read before
+    /// `deadline`.
+    fn check(
+        reader: SourcedReader<'a>,
+        length: u64,
+        hashes: Vec<(&'static HashAlgorithm, HashValue)>,
+        deadline: Deadline,
+        timer: SharedTimer,
+        timeout: Option<Duration>,
+    ) -> Result<SourcedReader<'a>> {
+        let (read, source) = reader.into_parts();
+        let read = Box::new(
+            read.check_length_and_hash(length, hashes.clone())?
+                .enforce_deadline(deadline),
+        );
+
+        Ok(match source {
+            Some(source) => SourcedReader::with_source(
+                read,
+                Box::new(CheckedSource {
+                    source,
+                    length,
+                    hashes,
+                    timer,
+                    timeout,
+                }),
+            ),
+            None => SourcedReader::new(read),
+        })
+    }
+}
+
+impl<'a> FetchSource<'a> for CheckedSource<'a> {
+    fn name(&self) -> Option<&str> {
+        self.source.name()
+    }
+
+    fn reject(self: Box<Self>, error: &Error) -> BoxFuture<'a, Option<Result<SourcedReader<'a>>>> {
+        let CheckedSource {
+            source,
+            length,
+            hashes,
+            timer,
+            timeout,
+        } = *self;
+        let refetch = source.reject(error);
+
+        async move {
+            let mut deadline = Deadline::new(timer.get(), timeout);
+            let reader = match deadline.run(refetch).await {
+                Ok(Some(Ok(reader))) => reader,
+                Ok(Some(Err(err))) => return Some(Err(err)),
+                Ok(None) => return None,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            Some(Self::check(
+                reader, length, hashes, deadline, timer, timeout,
+            ))
+        }
+        .boxed()
     }
 }
 
@@ -373,6 +761,20 @@ where
     ) -> Result<()> {
         self.repository.store_target(target_path, target).await
     }
+
+    /// Remove the metadata identified by `meta_path` and `version`, if it exists.
+    pub async fn remove_metadata<M>(
+        &mut self,
+        path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> Result<()>
+    where
+        M: Metadata,
+    {
+        Self::check::<M>(path)?;
+
+        self.repository.remove_metadata(path, version).await
+    }
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@ use {
         io::{AsyncReadExt, Cursor},
     },
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         marker::PhantomData,
         sync::{Arc, RwLock},
     },
@@ -29,6 +29,7 @@ pub struct EphemeralRepository<D> {
 
 type MetadataMap = HashMap<(MetadataPath, MetadataVersion), Arc<[u8]>>;
 type TargetsMap = HashMap<TargetPath, Arc<[u8]>>;
+type MetadataSet = HashSet<(MetadataPath, MetadataVersion)>;
 
 #[derive(Debug, Default)]
 struct Inner {
@@ -64,6 +65,7 @@ where
                 metadata: MetadataMap::new(),
                 targets: TargetsMap::new(),
             }),
+            removed_metadata: RwLock::new(MetadataSet::new()),
             _pouf: self._pouf,
         }
     }
@@ -131,6 +133,25 @@ where
     ) -> BoxFuture<'a, Result<()>> {
         store_target(&self.inner, target_path, read)
     }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut inner = self.inner.write().unwrap();
+
+        if inner
+            .metadata
+            .remove(&(meta_path.clone(), version))
+            .is_some()
+        {
+            // Increment the version since we changed.
+            inner.version += 1;
+        }
+
+        async { Ok(()) }.boxed()
+    }
 }
 
 /// [EphemeralBatchUpdate] is a special repository that is designed to write the metadata and
@@ -143,6 +164,7 @@ pub struct EphemeralBatchUpdate<'a, D> {
     initial_parent_version: u64,
     parent_repo: &'a RwLock<Inner>,
     staging_repo: RwLock<Inner>,
+    removed_metadata: RwLock<MetadataSet>,
     _pouf: PhantomData<D>,
 }
 
@@ -170,6 +192,9 @@ where
 
         // Since parent hasn't changed, merged everything we wrote into its tables.
         let staging_repo = self.staging_repo.into_inner().unwrap();
+        for key in self.removed_metadata.into_inner().unwrap() {
+            parent_repo.metadata.remove(&key);
+        }
         parent_repo.metadata.extend(staging_repo.metadata);
         parent_repo.targets.extend(staging_repo.targets);
 
@@ -192,6 +217,11 @@ where
         let key = (meta_path.clone(), version);
         let bytes = if let Some(bytes) = self.staging_repo.read().unwrap().metadata.get(&key) {
             Ok(Arc::clone(bytes))
+        } else if self.removed_metadata.read().unwrap().contains(&key) {
+            Err(Error::MetadataNotFound {
+                path: meta_path.clone(),
+                version,
+            })
         } else {
             self.parent_repo
                 .read()
@@ -237,6 +267,11 @@ where
         version: MetadataVersion,
         metadata: &'a mut (dyn AsyncRead + Send + Unpin),
     ) -> BoxFuture<'a, Result<()>> {
+        self.removed_metadata
+            .write()
+            .unwrap()
+            .remove(&(meta_path.clone(), version));
+
         store_metadata(&self.staging_repo, meta_path, version, metadata)
     }
 
@@ -247,6 +282,18 @@ where
     ) -> BoxFuture<'a, Result<()>> {
         store_target(&self.staging_repo, target_path, read)
     }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let key = (meta_path.clone(), version);
+        self.staging_repo.write().unwrap().metadata.remove(&key);
+        self.removed_metadata.write().unwrap().insert(key);
+
+        async { Ok(()) }.boxed()
+    }
 }
 
 fn store_metadata<'a>(
@@ -446,6 +493,60 @@ mod test {
         })
     }
 
+    #[test]
+    fn ephemeral_repo_remove_metadata() {
+        block_on(async {
+            let repo = EphemeralRepository::<Pouf1>::new();
+
+            let meta_path = MetadataPath::new("meta").unwrap();
+            let meta_version = MetadataVersion::None;
+
+            // Removing missing metadata is fine.
+            repo.remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+
+            repo.store_metadata(&meta_path, meta_version, &mut "meta".as_bytes())
+                .await
+                .unwrap();
+            repo.remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &meta_path, meta_version).await,
+                Err(Error::MetadataNotFound { .. })
+            );
+
+            // Removals in a batch_update are only visible to the repository after a commit.
+            repo.store_metadata(&meta_path, meta_version, &mut "meta".as_bytes())
+                .await
+                .unwrap();
+
+            let batch = repo.batch_update();
+            batch
+                .remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+            assert_matches!(
+                fetch_metadata_to_string(&batch, &meta_path, meta_version).await,
+                Err(Error::MetadataNotFound { .. })
+            );
+            assert_eq!(
+                fetch_metadata_to_string(&repo, &meta_path, meta_version)
+                    .await
+                    .unwrap(),
+                "meta",
+            );
+
+            batch.commit().await.unwrap();
+
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &meta_path, meta_version).await,
+                Err(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
     #[test]
     fn ephemeral_repo_batch_commit_fails_with_metadata_conflicts() {
         block_on(async {
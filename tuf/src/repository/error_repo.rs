@@ -6,16 +6,29 @@ use {
         Error, Result,
     },
     futures_io::AsyncRead,
-    futures_util::future::{BoxFuture, FutureExt},
-    std::sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+    futures_util::{
+        future::{BoxFuture, FutureExt},
+        io::AsyncReadExt as _,
+    },
+    std::{
+        collections::VecDeque,
+        io,
+        pin::Pin,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        task::{Context, Poll},
     },
 };
 
 pub(crate) struct ErrorRepository<R> {
     repo: R,
     fail_metadata_stores: Arc<AtomicBool>,
+    fail_target_stores: AtomicBool,
+    fetch_errors: Mutex<VecDeque<Error>>,
+    read_errors: Mutex<VecDeque<(u64, io::ErrorKind)>>,
+    fetches: AtomicUsize,
 }
 
 impl<R> ErrorRepository<R> {
@@ -23,6 +36,10 @@ impl<R> ErrorRepository<R> {
         Self {
             repo,
             fail_metadata_stores: Arc::new(AtomicBool::new(false)),
+            fail_target_stores: AtomicBool::new(false),
+            fetch_errors: Mutex::new(VecDeque::new()),
+            read_errors: Mutex::new(VecDeque::new()),
+            fetches: AtomicUsize::new(0),
         }
     }
 
@@ -30,6 +47,65 @@ impl<R> ErrorRepository<R> {
         self.fail_metadata_stores
             .store(fail_metadata_stores, Ordering::SeqCst);
     }
+
+    /// Fail target stores after reading the whole target, like a disk that fills up.
+    pub(crate) fn fail_target_stores(&self, fail_target_stores: bool) {
+        self.fail_target_stores
+            .store(fail_target_stores, Ordering::SeqCst);
+    }
+
+    /// Fail the next fetches, of metadata or targets, with `errors`, one per fetch.
+    pub(crate) fn fail_next_fetches(&self, errors: impl IntoIterator<Item = Error>) {
+        self.fetch_errors.lock().unwrap().extend(errors);
+    }
+
+    /// Fail reading the next successful fetches, one per fetch, after reading the given number of
+    /// bytes, with an error of the given kind.
+    pub(crate) fn fail_next_reads(&self, errors: impl IntoIterator<Item = (u64, io::ErrorKind)>) {
+        self.read_errors.lock().unwrap().extend(errors);
+    }
+
+    /// The number of fetches attempted, including those that failed.
+    pub(crate) fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+
+    fn next_fetch_error(&self) -> Option<Error> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        self.fetch_errors.lock().unwrap().pop_front()
+    }
+
+    fn with_read_error<'a>(
+        &'a self,
+        fetch: BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>>,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>>
+    where
+        R: Sync,
+    {
+        async move {
+            let read = fetch.await?;
+            let read_error = self.read_errors.lock().unwrap().pop_front();
+            match read_error {
+                Some((len, kind)) => Ok(Box::new(read.take(len).chain(FailingRead(kind)))
+                    as Box<dyn AsyncRead + Send + Unpin>),
+                None => Ok(read),
+            }
+        }
+        .boxed()
+    }
+}
+
+/// A reader that always fails.
+struct FailingRead(io::ErrorKind);
+
+impl AsyncRead for FailingRead {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Err(io::Error::from(self.0)))
+    }
 }
 
 impl<D, R> RepositoryProvider<D> for ErrorRepository<R>
@@ -42,14 +118,20 @@ where
         meta_path: &MetadataPath,
         version: MetadataVersion,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
-        self.repo.fetch_metadata(meta_path, version)
+        match self.next_fetch_error() {
+            Some(err) => async { Err(err) }.boxed(),
+            None => self.with_read_error(self.repo.fetch_metadata(meta_path, version)),
+        }
     }
 
     fn fetch_target<'a>(
         &'a self,
         target_path: &TargetPath,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
-        self.repo.fetch_target(target_path)
+        match self.next_fetch_error() {
+            Some(err) => async { Err(err) }.boxed(),
+            None => self.with_read_error(self.repo.fetch_target(target_path)),
+        }
     }
 }
 
@@ -76,6 +158,26 @@ where
         target_path: &TargetPath,
         target: &'a mut (dyn AsyncRead + Send + Unpin),
     ) -> BoxFuture<'a, Result<()>> {
-        self.repo.store_target(target_path, target)
+        if self.fail_target_stores.load(Ordering::SeqCst) {
+            async move {
+                target.read_to_end(&mut Vec::new()).await?;
+                Err(io::Error::new(io::ErrorKind::Other, "disk full").into())
+            }
+            .boxed()
+        } else {
+            self.repo.store_target(target_path, target)
+        }
+    }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        if self.fail_metadata_stores.load(Ordering::SeqCst) {
+            async { Err(Error::Encoding("failed".into())) }.boxed()
+        } else {
+            self.repo.remove_metadata(meta_path, version)
+        }
     }
 }
@@ -2,17 +2,18 @@
 
 use {
     crate::{
+        crypto::{HashAlgorithm, HashValue},
         error::{Error, Result},
         metadata::{MetadataPath, MetadataVersion, TargetPath},
         pouf::Pouf,
-        repository::{RepositoryProvider, RepositoryStorage},
+        repository::{check_max_length, RepositoryProvider, RepositoryStorage},
     },
     futures_io::AsyncRead,
     futures_util::future::{BoxFuture, FutureExt},
     futures_util::io::{copy, AllowStdIo},
     log::debug,
     std::{
-        collections::HashMap,
+        collections::{HashMap, HashSet},
         fs::{DirBuilder, File},
         io,
         marker::PhantomData,
@@ -131,6 +132,7 @@ where
             initial_parent_version: *self.version.read().unwrap(),
             parent_repo: self,
             metadata: RwLock::new(HashMap::new()),
+            removed_metadata: RwLock::new(HashSet::new()),
             targets: RwLock::new(HashMap::new()),
         }
     }
@@ -152,19 +154,16 @@ where
         meta_path: &MetadataPath,
         version: MetadataVersion,
         path: &Path,
+        max_length: Option<u64>,
     ) -> BoxFuture<'_, Result<Box<dyn AsyncRead + Send + Unpin + '_>>> {
-        let reader = File::open(&path).map_err(|err| {
-            if err.kind() == io::ErrorKind::NotFound {
+        let reader = open_with_max_length(path, max_length).map_err(|err| match err {
+            Error::IoPath { err, .. } if err.kind() == io::ErrorKind::NotFound => {
                 Error::MetadataNotFound {
                     path: meta_path.clone(),
                     version,
                 }
-            } else {
-                Error::IoPath {
-                    path: path.to_path_buf(),
-                    err,
-                }
             }
+            err => err,
         });
 
         async move {
@@ -179,16 +178,13 @@ where
         &self,
         target_path: &TargetPath,
         path: &Path,
+        max_length: Option<u64>,
     ) -> BoxFuture<'_, Result<Box<dyn AsyncRead + Send + Unpin + '_>>> {
-        let reader = File::open(&path).map_err(|err| {
-            if err.kind() == io::ErrorKind::NotFound {
+        let reader = open_with_max_length(path, max_length).map_err(|err| match err {
+            Error::IoPath { err, .. } if err.kind() == io::ErrorKind::NotFound => {
                 Error::TargetNotFound(target_path.clone())
-            } else {
-                Error::IoPath {
-                    path: path.to_path_buf(),
-                    err,
-                }
             }
+            err => err,
         });
 
         async move {
@@ -200,6 +196,22 @@ where
     }
 }
 
+/// Open the file at `path`, failing without reading it if it's longer than `max_length`.
+fn open_with_max_length(path: &Path, max_length: Option<u64>) -> Result<File> {
+    let io_path_err = |err| Error::IoPath {
+        path: path.to_path_buf(),
+        err,
+    };
+
+    let file = File::open(path).map_err(io_path_err)?;
+    if max_length.is_some() {
+        let length = file.metadata().map_err(io_path_err)?.len();
+        check_max_length(path.display(), length, max_length)?;
+    }
+
+    Ok(file)
+}
+
 impl<D> RepositoryProvider<D> for FileSystemRepository<D>
 where
     D: Pouf,
@@ -210,7 +222,7 @@ where
         version: MetadataVersion,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
         let path = self.metadata_path(meta_path, version);
-        self.fetch_metadata_from_path(meta_path, version, &path)
+        self.fetch_metadata_from_path(meta_path, version, &path, None)
     }
 
     fn fetch_target<'a>(
@@ -218,7 +230,28 @@ where
         target_path: &TargetPath,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
         let path = self.target_path(target_path);
-        self.fetch_target_from_path(target_path, &path)
+        self.fetch_target_from_path(target_path, &path, None)
+    }
+
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        _hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let path = self.metadata_path(meta_path, version);
+        self.fetch_metadata_from_path(meta_path, version, &path, max_length.map(|len| len as u64))
+    }
+
+    fn fetch_target_with_constraints<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        _hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let path = self.target_path(target_path);
+        self.fetch_target_from_path(target_path, &path, Some(length))
     }
 }
 
@@ -298,6 +331,30 @@ where
         }
         .boxed()
     }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let path = self.metadata_path(meta_path, version);
+
+        async move {
+            let mut version = self.version.write().unwrap();
+
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(err) => return Err(Error::IoPath { path, err }),
+            }
+
+            // Increment our version since the repository changed.
+            *version += 1;
+
+            Ok(())
+        }
+        .boxed()
+    }
 }
 
 /// [FileSystemBatchUpdate] is a special repository that is designed to write the metadata and
@@ -310,6 +367,7 @@ pub struct FileSystemBatchUpdate<'a, D: Pouf> {
     initial_parent_version: u64,
     parent_repo: &'a FileSystemRepository<D>,
     metadata: RwLock<HashMap<PathBuf, TempPath>>,
+    removed_metadata: RwLock<HashSet<PathBuf>>,
     targets: RwLock<HashMap<PathBuf, TempPath>>,
 }
 
@@ -360,6 +418,14 @@ where
             })?;
         }
 
+        for path in self.removed_metadata.into_inner().unwrap() {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(CommitError::IoPath { path, err }),
+            }
+        }
+
         for (path, tmp_path) in self.metadata.into_inner().unwrap() {
             if path.exists() {
                 debug!("Metadata path exists. Overwriting: {:?}", path);
@@ -389,10 +455,16 @@ where
         let path = self.parent_repo.metadata_path(meta_path, version);
         if let Some(temp_path) = self.metadata.read().unwrap().get(&path) {
             self.parent_repo
-                .fetch_metadata_from_path(meta_path, version, temp_path)
+                .fetch_metadata_from_path(meta_path, version, temp_path, None)
+        } else if self.removed_metadata.read().unwrap().contains(&path) {
+            let err = Error::MetadataNotFound {
+                path: meta_path.clone(),
+                version,
+            };
+            async move { Err(err) }.boxed()
         } else {
             self.parent_repo
-                .fetch_metadata_from_path(meta_path, version, &path)
+                .fetch_metadata_from_path(meta_path, version, &path, None)
         }
     }
 
@@ -403,9 +475,10 @@ where
         let path = self.parent_repo.target_path(target_path);
         if let Some(temp_path) = self.targets.read().unwrap().get(&path) {
             self.parent_repo
-                .fetch_target_from_path(target_path, temp_path)
+                .fetch_target_from_path(target_path, temp_path, None)
         } else {
-            self.parent_repo.fetch_target_from_path(target_path, &path)
+            self.parent_repo
+                .fetch_target_from_path(target_path, &path, None)
         }
     }
 }
@@ -427,6 +500,7 @@ where
             if let Err(err) = copy(read, &mut temp_file).await {
                 return Err(Error::IoPath { path, err });
             }
+            self.removed_metadata.write().unwrap().remove(&path);
             self.metadata
                 .write()
                 .unwrap()
@@ -458,6 +532,18 @@ where
         }
         .boxed()
     }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let path = self.parent_repo.metadata_path(meta_path, version);
+        self.metadata.write().unwrap().remove(&path);
+        self.removed_metadata.write().unwrap().insert(path);
+
+        async { Ok(()) }.boxed()
+    }
 }
 
 fn create_temp_file(path: &Path) -> Result<NamedTempFile> {
@@ -569,6 +655,66 @@ mod test {
         })
     }
 
+    #[test]
+    fn file_system_repo_checks_file_size_before_reading() {
+        block_on(async {
+            let temp_dir = tempfile::Builder::new()
+                .prefix("rust-tuf")
+                .tempdir()
+                .unwrap();
+            let repo = FileSystemRepositoryBuilder::<Pouf1>::new(temp_dir.path()).build();
+
+            let data: &[u8] = b"like tears in the rain";
+            let path = TargetPath::new("foo").unwrap();
+            repo.store_target(&path, &mut &*data).await.unwrap();
+            repo.store_metadata(&MetadataPath::root(), MetadataVersion::None, &mut &*data)
+                .await
+                .unwrap();
+
+            assert_matches!(
+                repo.fetch_target_with_constraints(&path, 4, &[])
+                    .await
+                    .map(|_| ()),
+                Err(Error::ExceededMaxLength {
+                    length: 22,
+                    max_length: 4,
+                    ..
+                })
+            );
+            assert_matches!(
+                repo.fetch_metadata_with_constraints(
+                    &MetadataPath::root(),
+                    MetadataVersion::None,
+                    Some(4),
+                    &[],
+                )
+                .await
+                .map(|_| ()),
+                Err(Error::ExceededMaxLength {
+                    length: 22,
+                    max_length: 4,
+                    ..
+                })
+            );
+
+            // Files that fit, and missing files, are handled as before.
+            let mut buf = Vec::new();
+            let mut read = repo
+                .fetch_target_with_constraints(&path, data.len() as u64, &[])
+                .await
+                .unwrap();
+            read.read_to_end(&mut buf).await.unwrap();
+            assert_eq!(buf.as_slice(), data);
+
+            assert_matches!(
+                repo.fetch_target_with_constraints(&TargetPath::new("bar").unwrap(), 4, &[])
+                    .await
+                    .map(|_| ()),
+                Err(Error::TargetNotFound(_))
+            );
+        })
+    }
+
     #[test]
     fn file_system_repo_batch_update() {
         block_on(async {
@@ -677,6 +823,64 @@ mod test {
         })
     }
 
+    #[test]
+    fn file_system_repo_remove_metadata() {
+        block_on(async {
+            let temp_dir = tempfile::Builder::new()
+                .prefix("rust-tuf")
+                .tempdir()
+                .unwrap();
+            let repo = FileSystemRepositoryBuilder::<Pouf1>::new(temp_dir.path().to_path_buf())
+                .metadata_prefix("meta")
+                .targets_prefix("targs")
+                .build();
+
+            let meta_path = MetadataPath::new("meta").unwrap();
+            let meta_version = MetadataVersion::None;
+
+            // Removing missing metadata is fine.
+            repo.remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+
+            repo.store_metadata(&meta_path, meta_version, &mut "meta".as_bytes())
+                .await
+                .unwrap();
+            repo.remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+            assert!(!temp_dir.path().join("meta").join("meta.json").exists());
+
+            // Removals in a batch_update are only visible to the repository after a commit.
+            repo.store_metadata(&meta_path, meta_version, &mut "meta".as_bytes())
+                .await
+                .unwrap();
+
+            let batch = repo.batch_update();
+            batch
+                .remove_metadata(&meta_path, meta_version)
+                .await
+                .unwrap();
+            assert_matches!(
+                fetch_metadata_to_string(&batch, &meta_path, meta_version).await,
+                Err(Error::MetadataNotFound { .. })
+            );
+            assert_eq!(
+                fetch_metadata_to_string(&repo, &meta_path, meta_version)
+                    .await
+                    .unwrap(),
+                "meta",
+            );
+
+            batch.commit().await.unwrap();
+
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &meta_path, meta_version).await,
+                Err(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
     #[test]
     fn file_system_repo_batch_commit_fails_with_metadata_conflicts() {
         block_on(async {
@@ -3,22 +3,29 @@
 use futures_io::AsyncRead;
 use futures_util::future::{BoxFuture, FutureExt as _, TryFutureExt as _};
 use futures_util::stream::TryStreamExt;
+use http::header::{self, HeaderMap, HeaderValue};
 use http::{Response, StatusCode, Uri};
 use hyper::body::Body;
 use hyper::client::connect::Connect;
 use hyper::Client;
 use hyper::Request;
+use log::warn;
 use percent_encoding::utf8_percent_encode;
 use std::future::Future;
 use std::io;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use url::Url;
 
+use crate::crypto::{HashAlgorithm, HashValue};
 use crate::error::Error;
 use crate::metadata::{MetadataPath, MetadataVersion, TargetPath};
 use crate::pouf::Pouf;
-use crate::repository::RepositoryProvider;
-use crate::util::SafeAsyncRead;
+use crate::repository::{check_max_length, RepositoryProvider};
+use crate::timer::{SharedTimer, Timer};
+use crate::util::{Deadline, SafeAsyncRead};
 use crate::Result;
 
 /// A builder to create a repository accessible over HTTP.
@@ -33,6 +40,11 @@ where
     metadata_prefix: Option<Vec<String>>,
     targets_prefix: Option<Vec<String>>,
     min_bytes_per_second: u32,
+    max_resumes: u32,
+    stall_timeout: Option<Duration>,
+    metadata_timeout: Option<Duration>,
+    target_timeout: Option<Duration>,
+    timer: SharedTimer,
     _pouf: PhantomData<D>,
 }
 
@@ -50,6 +62,11 @@ where
             metadata_prefix: None,
             targets_prefix: None,
             min_bytes_per_second: 4096,
+            max_resumes: 3,
+            stall_timeout: Some(Duration::from_secs(30)),
+            metadata_timeout: None,
+            target_timeout: None,
+            timer: SharedTimer::default(),
             _pouf: PhantomData,
         }
     }
@@ -63,6 +80,11 @@ where
             metadata_prefix: None,
             targets_prefix: None,
             min_bytes_per_second: 4096,
+            max_resumes: 3,
+            stall_timeout: Some(Duration::from_secs(30)),
+            metadata_timeout: None,
+            target_timeout: None,
+            timer: SharedTimer::default(),
             _pouf: PhantomData,
         }
     }
@@ -103,6 +125,49 @@ where
         self
     }
 
+    /// Set how many times the download of a single target may be resumed after the connection
+    /// drops. Defaults to 3, and 0 disables resuming.
+    ///
+    /// Downloads are resumed with a `Range` request for the rest of the target, but only if the
+    /// server advertised `Accept-Ranges: bytes` when the download started. The `ETag` or
+    /// `Last-Modified` header of the original response is sent as `If-Range`, so the download
+    /// fails instead of mixing two versions of the target if it changed in between.
+    pub fn max_resumes(mut self, max: u32) -> Self {
+        self.max_resumes = max;
+        self
+    }
+
+    /// Set how long a download may go without receiving any bytes before it fails with
+    /// [`io::ErrorKind::TimedOut`]. Defaults to 30 seconds, and `None` disables it.
+    ///
+    /// Unlike [`min_bytes_per_second`](Self::min_bytes_per_second), which is only checked as bytes
+    /// arrive, this catches a server that stops sending altogether.
+    pub fn stall_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.stall_timeout = timeout;
+        self
+    }
+
+    /// Set how long fetching a single metadata file may take, from sending the request to reading
+    /// the last byte. Defaults to `None`, for no limit.
+    pub fn metadata_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.metadata_timeout = timeout;
+        self
+    }
+
+    /// Set how long fetching a single target may take, from sending the request to reading the
+    /// last byte, including any resumed requests. Defaults to `None`, for no limit.
+    pub fn target_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.target_timeout = timeout;
+        self
+    }
+
+    /// Set the [`Timer`] that measures the timeouts. Defaults to a
+    /// [`ThreadTimer`](crate::timer::ThreadTimer).
+    pub fn timer<T: Timer + 'static>(mut self, timer: T) -> Self {
+        self.timer = SharedTimer::new(timer);
+        self
+    }
+
     /// Build a `HttpRepository`.
     pub fn build(self) -> HttpRepository<C, D> {
         let user_agent = match self.user_agent {
@@ -117,6 +182,11 @@ where
             metadata_prefix: self.metadata_prefix,
             targets_prefix: self.targets_prefix,
             min_bytes_per_second: self.min_bytes_per_second,
+            max_resumes: self.max_resumes,
+            stall_timeout: self.stall_timeout,
+            metadata_timeout: self.metadata_timeout,
+            target_timeout: self.target_timeout,
+            timer: self.timer,
             _pouf: PhantomData,
         }
     }
@@ -135,6 +205,11 @@ where
     metadata_prefix: Option<Vec<String>>,
     targets_prefix: Option<Vec<String>>,
     min_bytes_per_second: u32,
+    max_resumes: u32,
+    stall_timeout: Option<Duration>,
+    metadata_timeout: Option<Duration>,
+    target_timeout: Option<Duration>,
+    timer: SharedTimer,
     _pouf: PhantomData<D>,
 }
 
@@ -210,50 +285,220 @@ where
     C: Connect + Clone + Send + Sync + 'static,
     D: Pouf,
 {
-    fn get<'a>(&self, uri: &'a Uri) -> Result<impl Future<Output = Result<Response<Body>>> + 'a> {
-        let req = Request::builder()
-            .uri(uri)
-            .header("User-Agent", &*self.user_agent)
-            .body(Body::default())
-            .map_err(|err| Error::Http {
-                uri: uri.to_string(),
-                err,
-            })?;
-
-        Ok(self.client.request(req).map_err(|err| Error::Hyper {
-            uri: uri.to_string(),
-            err,
-        }))
+    fn get(&self, uri: &Uri) -> Result<impl Future<Output = Result<Response<Body>>>> {
+        get(&self.client, &self.user_agent, uri, HeaderMap::new())
     }
 }
 
-impl<C, D> RepositoryProvider<D> for HttpRepository<C, D>
+fn get<C>(
+    client: &Client<C>,
+    user_agent: &str,
+    uri: &Uri,
+    headers: HeaderMap,
+) -> Result<impl Future<Output = Result<Response<Body>>> + Send + 'static>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    let mut builder = Request::builder().uri(uri).header("User-Agent", user_agent);
+    if let Some(req_headers) = builder.headers_mut() {
+        req_headers.extend(headers);
+    }
+
+    let req = builder.body(Body::default()).map_err(|err| Error::Http {
+        uri: uri.to_string(),
+        err,
+    })?;
+
+    let uri = uri.to_string();
+    Ok(client.request(req).map_err(|err| Error::Hyper { uri, err }))
+}
+
+fn body_reader(resp: Response<Body>) -> Box<dyn AsyncRead + Send + Unpin> {
+    Box::new(
+        resp.into_body()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+            .into_async_read(),
+    )
+}
+
+/// The body of a target response, which resumes the download with a `Range` request if the
+/// connection drops partway through.
+///
+/// Resuming happens below the [`SafeReader`](crate::util::SafeReader) that the client wraps
+/// around the target, so the length and hashes of the target are still checked across the whole
+/// download.
+struct ResumableBody {
+    uri: Uri,
+    resume: Box<dyn Fn(HeaderMap) -> Result<ResponseFuture> + Send + Sync>,
+    if_range: Option<HeaderValue>,
+    offset: u64,
+    resumes_left: u32,
+    state: ResumableBodyState,
+}
+
+type ResponseFuture = BoxFuture<'static, Result<Response<Body>>>;
+
+enum ResumableBodyState {
+    Reading(Box<dyn AsyncRead + Send + Unpin>),
+    Resuming(ResponseFuture),
+}
+
+impl ResumableBody {
+    /// Start resuming the download from `self.offset`.
+    fn resume(&mut self) -> Result<ResponseFuture> {
+        let mut headers = HeaderMap::new();
+        let range = format!("bytes={}-", self.offset);
+        headers.insert(
+            header::RANGE,
+            HeaderValue::from_str(&range).expect("a range is a valid header value"),
+        );
+        if let Some(if_range) = &self.if_range {
+            headers.insert(header::IF_RANGE, if_range.clone());
+        }
+
+        (self.resume)(headers)
+    }
+
+    /// Check that `resp` continues the download from `self.offset`.
+    fn resumed_body(&self, resp: Response<Body>) -> io::Result<Box<dyn AsyncRead + Send + Unpin>> {
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "failed to resume {}: request failed with status code {}",
+                    self.uri,
+                    resp.status()
+                ),
+            ));
+        }
+
+        let start = resp
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_start);
+        if start != Some(self.offset) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "failed to resume {}: response does not start at byte {}",
+                    self.uri, self.offset
+                ),
+            ));
+        }
+
+        Ok(body_reader(resp))
+    }
+}
+
+impl AsyncRead for ResumableBody {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+
+        loop {
+            match &mut this.state {
+                ResumableBodyState::Reading(body) => match Pin::new(body).poll_read(cx, buf) {
+                    Poll::Ready(Ok(n)) => {
+                        this.offset += n as u64;
+                        return Poll::Ready(Ok(n));
+                    }
+                    Poll::Ready(Err(err)) if this.resumes_left > 0 => {
+                        warn!(
+                            "Download of {} was interrupted after {} bytes, resuming: {}",
+                            this.uri, this.offset, err
+                        );
+                        this.resumes_left -= 1;
+
+                        let resume = this
+                            .resume()
+                            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+                        this.state = ResumableBodyState::Resuming(resume);
+                    }
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                },
+                ResumableBodyState::Resuming(resp) => match resp.poll_unpin(cx) {
+                    Poll::Ready(Ok(resp)) => {
+                        this.state = ResumableBodyState::Reading(this.resumed_body(resp)?);
+                    }
+                    Poll::Ready(Err(err)) => {
+                        return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, err)));
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+            }
+        }
+    }
+}
+
+/// Whether the server will serve parts of the file in this response.
+fn accepts_byte_ranges(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT_RANGES)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|unit| unit.trim().eq_ignore_ascii_case("bytes"))
+}
+
+/// The validator to send as `If-Range` when resuming, which must be a strong `ETag` or a
+/// `Last-Modified` date.
+fn if_range_validator(headers: &HeaderMap) -> Option<HeaderValue> {
+    match headers.get(header::ETAG) {
+        Some(etag) if !etag.as_bytes().starts_with(b"W/") => Some(etag.clone()),
+        _ => headers.get(header::LAST_MODIFIED).cloned(),
+    }
+}
+
+/// Parse the first byte position out of a `Content-Range` header like `bytes 100-199/200`.
+fn content_range_start(content_range: &str) -> Option<u64> {
+    let range = content_range.strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// The length of the body from the `Content-Length` header, if the server sent one.
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+impl<C, D> HttpRepository<C, D>
 where
     C: Connect + Clone + Send + Sync + 'static,
     D: Pouf,
 {
-    fn fetch_metadata<'a>(
+    fn fetch_metadata_with_max_length<'a>(
         &'a self,
         meta_path: &MetadataPath,
         version: MetadataVersion,
+        max_length: Option<u64>,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
         let meta_path = meta_path.clone();
         let components = meta_path.components::<D>(version);
         let uri = extend_uri(&self.uri, &self.metadata_prefix, &components);
 
         async move {
-            // TODO(#278) check content length if known and fail early if the payload is too large.
-
             let uri = uri?;
-            let resp = self.get(&uri)?.await?;
+            let mut deadline = Deadline::new(self.timer.get(), self.metadata_timeout);
+            let resp = deadline.run(self.get(&uri)?).await??;
 
             let status = resp.status();
             if status == StatusCode::OK {
-                let reader = resp
-                    .into_body()
-                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-                    .into_async_read()
-                    .enforce_minimum_bitrate(self.min_bytes_per_second);
+                if let Some(length) = content_length(resp.headers()) {
+                    check_max_length(&uri, length, max_length)?;
+                }
+
+                let reader = body_reader(resp)
+                    .enforce_minimum_bitrate(self.min_bytes_per_second)
+                    .with_stall_timeout(&self.timer, self.stall_timeout)
+                    .enforce_deadline(deadline);
 
                 let reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(reader);
                 Ok(reader)
@@ -272,29 +517,51 @@ where
         .boxed()
     }
 
-    fn fetch_target<'a>(
+    fn fetch_target_with_max_length<'a>(
         &'a self,
         target_path: &TargetPath,
+        max_length: Option<u64>,
     ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
         let target_path = target_path.clone();
         let components = target_path.components();
         let uri = extend_uri(&self.uri, &self.targets_prefix, &components);
 
         async move {
-            // TODO(#278) check content length if known and fail early if the payload is too large.
-
             let uri = uri?;
-            let resp = self.get(&uri)?.await?;
+            let mut deadline = Deadline::new(self.timer.get(), self.target_timeout);
+            let resp = deadline.run(self.get(&uri)?).await??;
 
             let status = resp.status();
             if status == StatusCode::OK {
-                let reader = resp
-                    .into_body()
-                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-                    .into_async_read()
-                    .enforce_minimum_bitrate(self.min_bytes_per_second);
+                if let Some(length) = content_length(resp.headers()) {
+                    check_max_length(&uri, length, max_length)?;
+                }
 
-                let reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(reader);
+                let reader = if self.max_resumes > 0 && accepts_byte_ranges(resp.headers()) {
+                    let client = self.client.clone();
+                    let user_agent = self.user_agent.clone();
+                    let resume_uri = uri.clone();
+
+                    Box::new(ResumableBody {
+                        if_range: if_range_validator(resp.headers()),
+                        resume: Box::new(move |headers| {
+                            Ok(get(&client, &user_agent, &resume_uri, headers)?.boxed())
+                        }),
+                        uri,
+                        offset: 0,
+                        resumes_left: self.max_resumes,
+                        state: ResumableBodyState::Reading(body_reader(resp)),
+                    })
+                } else {
+                    body_reader(resp)
+                };
+
+                let reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(
+                    reader
+                        .enforce_minimum_bitrate(self.min_bytes_per_second)
+                        .with_stall_timeout(&self.timer, self.stall_timeout)
+                        .enforce_deadline(deadline),
+                );
                 Ok(reader)
             } else if status == StatusCode::NOT_FOUND {
                 Err(Error::TargetNotFound(target_path))
@@ -309,9 +576,335 @@ where
     }
 }
 
+impl<C, D> RepositoryProvider<D> for HttpRepository<C, D>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+    D: Pouf,
+{
+    fn fetch_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_metadata_with_max_length(meta_path, version, None)
+    }
+
+    fn fetch_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_target_with_max_length(target_path, None)
+    }
+
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        _hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_metadata_with_max_length(meta_path, version, max_length.map(|len| len as u64))
+    }
+
+    fn fetch_target_with_constraints<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        _hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_target_with_max_length(target_path, Some(length))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::metadata::{RootMetadata, TargetDescription};
+    use crate::pouf::Pouf1;
+    use crate::repository::Repository;
+    use assert_matches::assert_matches;
+    use futures_util::io::AsyncReadExt as _;
+    use futures_util::stream::StreamExt as _;
+    use hyper::client::HttpConnector;
+    use hyper::service::{make_service_fn, service_fn};
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A server for a single target, which drops the connection after sending `drop_after` bytes
+    /// of each response, or stops sending if `stall` is set.
+    #[derive(Clone)]
+    struct FlakyServer {
+        data: Arc<Vec<u8>>,
+        resumed_data: Arc<Vec<u8>>,
+        accept_ranges: bool,
+        drop_after: usize,
+        stall: bool,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl FlakyServer {
+        fn new(data: Vec<u8>, drop_after: usize) -> Self {
+            let data = Arc::new(data);
+            Self {
+                resumed_data: data.clone(),
+                data,
+                accept_ranges: true,
+                drop_after,
+                stall: false,
+                requests: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+
+        fn respond(&self, req: Request<Body>) -> Response<Body> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+
+            let start = req.headers().get(header::RANGE).map(|range| {
+                let range = range.to_str().unwrap();
+                let start = range.strip_prefix("bytes=").unwrap().strip_suffix('-');
+                start.unwrap().parse::<usize>().unwrap()
+            });
+
+            let mut resp = Response::builder().header(header::ETAG, "\"v1\"");
+            if self.accept_ranges {
+                resp = resp.header(header::ACCEPT_RANGES, "bytes");
+            }
+
+            let data = match start {
+                Some(start) => {
+                    let data = &self.resumed_data[start..];
+                    resp = resp.status(StatusCode::PARTIAL_CONTENT).header(
+                        header::CONTENT_RANGE,
+                        format!(
+                            "bytes {}-{}/{}",
+                            start,
+                            self.resumed_data.len() - 1,
+                            self.resumed_data.len()
+                        ),
+                    );
+                    data
+                }
+                None => &self.data[..],
+            };
+
+            let sent = data[..data.len().min(self.drop_after)].to_vec();
+            let dropped = data.len() > self.drop_after;
+            let stall = self.stall;
+
+            // Give the server a moment to flush what it sent before it drops the connection.
+            let body = futures_util::stream::once(async { Ok(sent) }).chain(
+                futures_util::stream::once(async move {
+                    if stall {
+                        futures_util::future::pending::<()>().await;
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    Err(io::Error::new(io::ErrorKind::Other, "dropped"))
+                })
+                .filter(move |_| futures_util::future::ready(dropped)),
+            );
+
+            resp.header(header::CONTENT_LENGTH, data.len())
+                .body(Body::wrap_stream(body))
+                .unwrap()
+        }
+
+        /// Serve on a loopback port, which must be done within a tokio runtime.
+        fn serve(&self) -> Uri {
+            let server = self.clone();
+            let make_service = make_service_fn(move |_| {
+                let server = server.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let resp = server.respond(req);
+                        async move { Ok::<_, Infallible>(resp) }
+                    }))
+                }
+            });
+
+            let http_server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+            let uri = format!("http://{}/", http_server.local_addr())
+                .parse()
+                .unwrap();
+            tokio::spawn(http_server);
+
+            uri
+        }
+    }
+
+    fn target_data() -> Vec<u8> {
+        (0..64 * 1024).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Fetch the target described by `data` from `server`, checking its length and hashes.
+    fn fetch_target(server: &FlakyServer, data: &[u8], max_resumes: u32) -> Result<Vec<u8>> {
+        fetch_target_with(server, data, |builder| builder.max_resumes(max_resumes))
+    }
+
+    /// Like `fetch_target`, but with a repository configured by `configure`.
+    fn fetch_target_with<F>(server: &FlakyServer, data: &[u8], configure: F) -> Result<Vec<u8>>
+    where
+        F: FnOnce(
+            HttpRepositoryBuilder<HttpConnector, Pouf1>,
+        ) -> HttpRepositoryBuilder<HttpConnector, Pouf1>,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            let repo = configure(HttpRepositoryBuilder::new_with_uri(
+                server.serve(),
+                hyper::Client::new(),
+            ))
+            .build();
+            let repo = Repository::<_, Pouf1>::new(repo);
+
+            let target_path = TargetPath::new("target").unwrap();
+            let description = TargetDescription::from_slice(data, &[HashAlgorithm::Sha256])?;
+
+            let mut buf = vec![];
+            repo.fetch_target(false, &target_path, description)
+                .await?
+                .read_to_end(&mut buf)
+                .await?;
+            Ok(buf)
+        })
+    }
+
+    #[test]
+    fn http_repository_resumes_interrupted_target() {
+        let data = target_data();
+        let server = FlakyServer::new(data.clone(), 20 * 1024);
+
+        assert_eq!(fetch_target(&server, &data, 3).unwrap(), data);
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn http_repository_gives_up_after_max_resumes() {
+        let data = target_data();
+        let server = FlakyServer::new(data.clone(), 20 * 1024);
+
+        assert_matches!(fetch_target(&server, &data, 2), Err(Error::Io(_)));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn http_repository_only_resumes_if_server_accepts_ranges() {
+        let data = target_data();
+        let mut server = FlakyServer::new(data.clone(), 20 * 1024);
+        server.accept_ranges = false;
+
+        assert_matches!(fetch_target(&server, &data, 3), Err(Error::Io(_)));
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn http_repository_verifies_target_across_resumes() {
+        let data = target_data();
+
+        // The rest of the target changed while we were downloading it.
+        let mut server = FlakyServer::new(data.clone(), 20 * 1024);
+        let mut tampered = data.clone();
+        tampered[40 * 1024] ^= 1;
+        server.resumed_data = Arc::new(tampered);
+
+        assert_matches!(
+            fetch_target(&server, &data, 3),
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::InvalidData
+        );
+        assert_eq!(server.requests.load(Ordering::SeqCst), 4);
+
+        // The resumed download is longer than the target.
+        let mut server = FlakyServer::new(data.clone(), 20 * 1024);
+        let mut extended = data.clone();
+        extended.extend_from_slice(b"extra");
+        server.resumed_data = Arc::new(extended);
+
+        assert_matches!(
+            fetch_target(&server, &data, 3),
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn http_repository_fails_stalled_target() {
+        let data = target_data();
+        let mut server = FlakyServer::new(data.clone(), 20 * 1024);
+        server.stall = true;
+
+        assert_matches!(
+            fetch_target_with(&server, &data, |builder| {
+                builder.stall_timeout(Some(Duration::from_millis(100)))
+            }),
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::TimedOut
+        );
+    }
+
+    #[test]
+    fn http_repository_fails_target_after_deadline() {
+        let data = target_data();
+        let mut server = FlakyServer::new(data.clone(), 20 * 1024);
+        server.stall = true;
+
+        assert_matches!(
+            fetch_target_with(&server, &data, |builder| {
+                builder
+                    .stall_timeout(None)
+                    .target_timeout(Some(Duration::from_millis(100)))
+            }),
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::TimedOut
+        );
+    }
+
+    #[test]
+    fn http_repository_rejects_long_content_length() {
+        let data = target_data();
+        let server = FlakyServer::new(data.clone(), data.len());
+
+        assert_matches!(
+            fetch_target(&server, &data[..1024], 3),
+            Err(Error::ExceededMaxLength { length, max_length: 1024, .. })
+                if length == data.len() as u64
+        );
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let result = runtime.block_on(async {
+            let repo = HttpRepositoryBuilder::<_, Pouf1>::new_with_uri(
+                server.serve(),
+                hyper::Client::new(),
+            )
+            .build();
+            let repo = Repository::<_, Pouf1>::new(repo);
+
+            repo.fetch_metadata::<RootMetadata>(
+                &MetadataPath::root(),
+                MetadataVersion::None,
+                Some(1024),
+                vec![],
+            )
+            .await
+        });
+        assert_matches!(
+            result,
+            Err(Error::ExceededMaxLength {
+                max_length: 1024,
+                ..
+            })
+        );
+    }
+
+    #[test]
+    fn content_range_start_parses_byte_ranges() {
+        assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+        assert_eq!(content_range_start("bytes 0-0/*"), Some(0));
+        assert_eq!(content_range_start("bytes */200"), None);
+        assert_eq!(content_range_start("items 100-199/200"), None);
+    }
 
     // Old behavior of the `HttpRepository::get` extension
     // functionality
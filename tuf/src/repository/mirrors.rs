@@ -0,0 +1,654 @@
+use {
+    crate::{
+        crypto::{HashAlgorithm, HashValue},
+        metadata::{MetadataPath, MetadataVersion, TargetPath},
+        pouf::Pouf,
+        repository::{FetchSource, RepositoryProvider, SourcedReader},
+        Error, Result,
+    },
+    futures_io::AsyncRead,
+    futures_util::future::{BoxFuture, FutureExt, TryFutureExt},
+    log::{debug, warn},
+    std::{
+        collections::HashSet,
+        sync::{Arc, Mutex, MutexGuard},
+    },
+};
+
+/// A repository that fetches from several mirrors of the same repository, failing over from one
+/// to the next.
+///
+/// Mirrors are tried in the order they were given in, except that mirrors that have served data
+/// which was rejected are penalized, and are only tried after the mirrors that have served less
+/// rejected data. A fetch that fails for any reason, such as a network error, an unexpected HTTP
+/// status, or the mirror not having the file, is retried with the next mirror. If every mirror
+/// fails, the fetch fails with a not found error if any mirror reported one, or otherwise with the
+/// error from the first mirror that was tried.
+///
+/// The repository can't verify what it fetches itself. Instead, the readers returned by
+/// [`RepositoryProvider::fetch_metadata_with_source`] and
+/// [`RepositoryProvider::fetch_target_with_source`] name the mirror that served them, and the
+/// [`Client`][Client] calls [`SourcedReader::reject`] when the data fails verification. This
+/// penalizes the mirror that served it, and fetches it again from a mirror that hasn't yet served
+/// rejected data for the same fetch. Since each reader carries its own source, concurrent fetches
+/// don't interfere with each other. The client also records the name of the mirror that supplied
+/// each metadata and target, see [`Client::metadata_mirror`][metadata_mirror] and
+/// [`Client::target_mirror`][target_mirror].
+///
+/// ```
+/// # use futures_executor::block_on;
+/// # use futures_util::io::AsyncReadExt as _;
+/// # use tuf::{
+/// #     metadata::{MetadataPath, MetadataVersion},
+/// #     pouf::Pouf1,
+/// #     repository::{EphemeralRepository, MirrorsRepository, RepositoryProvider,
+/// #                  RepositoryStorage},
+/// # };
+/// # block_on(async {
+/// let primary = EphemeralRepository::<Pouf1>::new();
+/// let backup = EphemeralRepository::<Pouf1>::new();
+/// backup.store_metadata(
+///     &MetadataPath::timestamp(),
+///     MetadataVersion::None,
+///     &mut &b"timestamp"[..],
+/// ).await.unwrap();
+///
+/// let repo = MirrorsRepository::new([("primary", primary), ("backup", backup)]).unwrap();
+/// let mut reader = RepositoryProvider::<Pouf1>::fetch_metadata_with_source(
+///     &repo,
+///     &MetadataPath::timestamp(),
+///     MetadataVersion::None,
+///     None,
+///     &[],
+/// )
+/// .await
+/// .unwrap();
+/// let mut buf = vec![];
+/// reader.read_to_end(&mut buf).await.unwrap();
+/// assert_eq!(buf, b"timestamp");
+/// assert_eq!(reader.source_name(), Some("backup"));
+/// # })
+/// ```
+///
+/// [Client]: crate::client::Client
+/// [metadata_mirror]: crate::client::Client::metadata_mirror
+/// [target_mirror]: crate::client::Client::target_mirror
+#[derive(Debug)]
+pub struct MirrorsRepository<R> {
+    mirrors: Vec<(String, R)>,
+
+    /// The number of times each mirror served data that was rejected.
+    penalties: Mutex<Vec<u32>>,
+}
+
+/// Fetches the data for a request from one mirror.
+type FetchFn<'a, R> =
+    Arc<dyn Fn(&'a R) -> BoxFuture<'a, Result<SourcedReader<'a>>> + Send + Sync + 'a>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Request {
+    Metadata(MetadataPath, MetadataVersion),
+    Target(TargetPath),
+}
+
+impl Request {
+    fn not_found(self) -> Error {
+        match self {
+            Request::Metadata(path, version) => Error::MetadataNotFound { path, version },
+            Request::Target(path) => Error::TargetNotFound(path),
+        }
+    }
+}
+
+impl<R> MirrorsRepository<R> {
+    /// Create a new `MirrorsRepository` from `mirrors`, in the order they should be tried in. Each
+    /// mirror has a name, which is used to report where data was fetched from.
+    ///
+    /// Returns an error if there are no mirrors, or if two mirrors have the same name.
+    pub fn new<I, N>(mirrors: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (N, R)>,
+        N: Into<String>,
+    {
+        let mirrors = mirrors
+            .into_iter()
+            .map(|(name, repository)| (name.into(), repository))
+            .collect::<Vec<_>>();
+
+        if mirrors.is_empty() {
+            return Err(Error::IllegalArgument("no mirrors were given".into()));
+        }
+
+        let mut names = HashSet::new();
+        for (name, _) in &mirrors {
+            if !names.insert(name) {
+                return Err(Error::IllegalArgument(format!(
+                    "mirror {:?} was given more than once",
+                    name
+                )));
+            }
+        }
+
+        Ok(Self {
+            penalties: Mutex::new(vec![0; mirrors.len()]),
+            mirrors,
+        })
+    }
+
+    /// The names of the mirrors, in the order they were given in.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.mirrors.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// An immutable reference to the mirror named `name`.
+    pub fn get(&self, name: &str) -> Option<&R> {
+        self.mirrors
+            .iter()
+            .find(|(mirror_name, _)| mirror_name == name)
+            .map(|(_, repository)| repository)
+    }
+
+    /// The number of times the mirror named `name` served data that was rejected.
+    pub fn penalty(&self, name: &str) -> Option<u32> {
+        let index = self.mirrors.iter().position(|(n, _)| n == name)?;
+        Some(self.penalties()[index])
+    }
+
+    fn penalties(&self) -> MutexGuard<'_, Vec<u32>> {
+        // The penalties are always left consistent, so it's fine to keep using them after a panic.
+        self.penalties.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// The mirrors to try, in order, leaving out the mirrors in `rejected`.
+    fn order(&self, rejected: &HashSet<usize>) -> Vec<usize> {
+        let penalties = self.penalties();
+
+        let mut order = (0..self.mirrors.len())
+            .filter(|index| !rejected.contains(index))
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&index| penalties[index]);
+
+        order
+    }
+
+    /// Fetch `request` with `fetch` from the first mirror that serves it, skipping the mirrors in
+    /// `rejected`.
+    async fn fetch<'a>(
+        &'a self,
+        request: Request,
+        fetch: FetchFn<'a, R>,
+        rejected: HashSet<usize>,
+    ) -> Result<SourcedReader<'a>>
+    where
+        R: Sync,
+    {
+        let mut not_found = None;
+        let mut first_err = None;
+
+        for index in self.order(&rejected) {
+            let (name, repository) = &self.mirrors[index];
+
+            match fetch(repository).await {
+                Ok(reader) => {
+                    debug!("Fetching {:?} from mirror {}", request, name);
+                    let (read, inner) = reader.into_parts();
+                    return Ok(SourcedReader::with_source(
+                        read,
+                        Box::new(MirrorSource {
+                            mirrors: self,
+                            request,
+                            fetch,
+                            index,
+                            rejected,
+                            inner,
+                        }),
+                    ));
+                }
+                Err(err @ (Error::MetadataNotFound { .. } | Error::TargetNotFound(_))) => {
+                    debug!("Mirror {} does not have {:?}", name, request);
+                    not_found.get_or_insert(err);
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to fetch {:?} from mirror {}: {}",
+                        request, name, err
+                    );
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        Err(not_found
+            .or(first_err)
+            .unwrap_or_else(|| request.not_found()))
+    }
+}
+
+/// The mirror that served a fetch from a [`MirrorsRepository`].
+struct MirrorSource<'a, R> {
+    mirrors: &'a MirrorsRepository<R>,
+    request: Request,
+    fetch: FetchFn<'a, R>,
+
+    /// The mirror that served the data.
+    index: usize,
+
+    /// The mirrors that already served data for `request` which was rejected.
+    rejected: HashSet<usize>,
+
+    /// The source within the mirror, if it reports one.
+    inner: Option<Box<dyn FetchSource<'a> + 'a>>,
+}
+
+impl<'a, R> FetchSource<'a> for MirrorSource<'a, R>
+where
+    R: Sync,
+{
+    fn name(&self) -> Option<&str> {
+        Some(&self.mirrors.mirrors[self.index].0)
+    }
+
+    fn reject(self: Box<Self>, error: &Error) -> BoxFuture<'a, Option<Result<SourcedReader<'a>>>> {
+        let MirrorSource {
+            mirrors,
+            request,
+            fetch,
+            index,
+            mut rejected,
+            inner,
+        } = *self;
+
+        // Let the mirror serve the data again itself, such as when it retries failed reads,
+        // before failing over to another mirror.
+        let refetch = inner.map(|inner| inner.reject(error));
+        let error = error.to_string();
+
+        async move {
+            if let Some(refetch) = refetch {
+                if let Some(result) = refetch.await {
+                    return Some(result.map(|reader| {
+                        let (read, inner) = reader.into_parts();
+                        SourcedReader::with_source(
+                            read,
+                            Box::new(MirrorSource {
+                                mirrors,
+                                request,
+                                fetch,
+                                index,
+                                rejected,
+                                inner,
+                            }),
+                        )
+                    }));
+                }
+            }
+
+            warn!(
+                "Mirror {} served {:?}, which was rejected: {}",
+                mirrors.mirrors[index].0, request, error
+            );
+            mirrors.penalties()[index] += 1;
+            rejected.insert(index);
+
+            if rejected.len() == mirrors.mirrors.len() {
+                return None;
+            }
+
+            Some(mirrors.fetch(request, fetch, rejected).await)
+        }
+        .boxed()
+    }
+}
+
+impl<D, R> RepositoryProvider<D> for MirrorsRepository<R>
+where
+    R: RepositoryProvider<D> + Send + Sync,
+    D: Pouf,
+{
+    fn fetch_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let request = Request::Metadata(meta_path.clone(), version);
+        let meta_path = meta_path.clone();
+        let fetch: FetchFn<'a, R> = Arc::new(move |repository: &'a R| {
+            repository
+                .fetch_metadata(&meta_path, version)
+                .map_ok(SourcedReader::new)
+                .boxed()
+        });
+
+        self.fetch(request, fetch, HashSet::new())
+            .map_ok(|reader| reader.into_parts().0)
+            .boxed()
+    }
+
+    fn fetch_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let request = Request::Target(target_path.clone());
+        let target_path = target_path.clone();
+        let fetch: FetchFn<'a, R> = Arc::new(move |repository: &'a R| {
+            repository
+                .fetch_target(&target_path)
+                .map_ok(SourcedReader::new)
+                .boxed()
+        });
+
+        self.fetch(request, fetch, HashSet::new())
+            .map_ok(|reader| reader.into_parts().0)
+            .boxed()
+    }
+
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_metadata_with_source(meta_path, version, max_length, hashes)
+            .map_ok(|reader| reader.into_parts().0)
+            .boxed()
+    }
+
+    fn fetch_target_with_constraints<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_target_with_source(target_path, length, hashes)
+            .map_ok(|reader| reader.into_parts().0)
+            .boxed()
+    }
+
+    fn fetch_metadata_with_source<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        let request = Request::Metadata(meta_path.clone(), version);
+        let meta_path = meta_path.clone();
+        let hashes = hashes.to_vec();
+        let fetch: FetchFn<'a, R> = Arc::new(move |repository: &'a R| {
+            repository.fetch_metadata_with_source(&meta_path, version, max_length, &hashes)
+        });
+
+        self.fetch(request, fetch, HashSet::new()).boxed()
+    }
+
+    fn fetch_target_with_source<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        let request = Request::Target(target_path.clone());
+        let target_path = target_path.clone();
+        let hashes = hashes.to_vec();
+        let fetch: FetchFn<'a, R> = Arc::new(move |repository: &'a R| {
+            repository.fetch_target_with_source(&target_path, length, &hashes)
+        });
+
+        self.fetch(request, fetch, HashSet::new()).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            pouf::Pouf1,
+            repository::RepositoryStorage,
+            repository::{fetch_metadata_to_string, fetch_target_to_string, EphemeralRepository},
+        },
+        assert_matches::assert_matches,
+        futures_executor::block_on,
+        futures_util::io::AsyncReadExt as _,
+    };
+
+    /// A mirror that is always down.
+    struct Offline;
+
+    impl<D: Pouf> RepositoryProvider<D> for Offline {
+        fn fetch_metadata<'a>(
+            &'a self,
+            meta_path: &MetadataPath,
+            _version: MetadataVersion,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            let uri = format!("http://offline/{}", meta_path);
+            async move {
+                Err(Error::BadHttpStatus {
+                    uri,
+                    code: http::StatusCode::SERVICE_UNAVAILABLE,
+                })
+            }
+            .boxed()
+        }
+
+        fn fetch_target<'a>(
+            &'a self,
+            target_path: &TargetPath,
+        ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+            let uri = format!("http://offline/{}", target_path);
+            async move {
+                Err(Error::BadHttpStatus {
+                    uri,
+                    code: http::StatusCode::SERVICE_UNAVAILABLE,
+                })
+            }
+            .boxed()
+        }
+    }
+
+    async fn mirror(timestamp: &str) -> Box<dyn RepositoryProvider<Pouf1> + Send + Sync> {
+        let repo = EphemeralRepository::<Pouf1>::new();
+        repo.store_metadata(
+            &MetadataPath::timestamp(),
+            MetadataVersion::None,
+            &mut timestamp.as_bytes(),
+        )
+        .await
+        .unwrap();
+        repo.store_target(&TargetPath::new("foo").unwrap(), &mut timestamp.as_bytes())
+            .await
+            .unwrap();
+        Box::new(repo)
+    }
+
+    async fn fetch_timestamp<R: RepositoryProvider<Pouf1>>(repo: &R) -> Result<String> {
+        fetch_metadata_to_string(repo, &MetadataPath::timestamp(), MetadataVersion::None).await
+    }
+
+    async fn fetch_timestamp_with_source<'a, R: RepositoryProvider<Pouf1>>(
+        repo: &'a R,
+    ) -> Result<SourcedReader<'a>> {
+        repo.fetch_metadata_with_source(
+            &MetadataPath::timestamp(),
+            MetadataVersion::None,
+            None,
+            &[],
+        )
+        .await
+    }
+
+    async fn read_to_string(reader: &mut SourcedReader<'_>) -> String {
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).await.unwrap();
+        buf
+    }
+
+    #[test]
+    fn mirrors_repository_rejects_bad_mirror_lists() {
+        assert_matches!(
+            MirrorsRepository::<EphemeralRepository<Pouf1>>::new(Vec::<(String, _)>::new()),
+            Err(Error::IllegalArgument(_))
+        );
+        assert_matches!(
+            MirrorsRepository::new([
+                ("a", EphemeralRepository::<Pouf1>::new()),
+                ("a", EphemeralRepository::<Pouf1>::new()),
+            ]),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+
+    #[test]
+    fn mirrors_repository_fails_over_in_order() {
+        block_on(async {
+            let repo = MirrorsRepository::new([
+                (
+                    "offline",
+                    Box::new(Offline) as Box<dyn RepositoryProvider<Pouf1> + Send + Sync>,
+                ),
+                ("empty", Box::new(EphemeralRepository::<Pouf1>::new())),
+                ("first", mirror("first").await),
+                ("second", mirror("second").await),
+            ])
+            .unwrap();
+
+            assert_eq!(fetch_timestamp(&repo).await.unwrap(), "first");
+            assert_eq!(
+                fetch_target_to_string(&repo, &TargetPath::new("foo").unwrap())
+                    .await
+                    .unwrap(),
+                "first"
+            );
+
+            let mut reader = fetch_timestamp_with_source(&repo).await.unwrap();
+            assert_eq!(read_to_string(&mut reader).await, "first");
+            assert_eq!(reader.source_name(), Some("first"));
+
+            let mut reader = RepositoryProvider::<Pouf1>::fetch_target_with_source(
+                &repo,
+                &TargetPath::new("foo").unwrap(),
+                5,
+                &[],
+            )
+            .await
+            .unwrap();
+            assert_eq!(read_to_string(&mut reader).await, "first");
+            assert_eq!(reader.source_name(), Some("first"));
+        })
+    }
+
+    #[test]
+    fn mirrors_repository_reports_not_found_over_other_errors() {
+        block_on(async {
+            let repo = MirrorsRepository::new([
+                (
+                    "offline",
+                    Box::new(Offline) as Box<dyn RepositoryProvider<Pouf1> + Send + Sync>,
+                ),
+                ("empty", Box::new(EphemeralRepository::<Pouf1>::new())),
+            ])
+            .unwrap();
+
+            assert_matches!(
+                fetch_timestamp(&repo).await,
+                Err(Error::MetadataNotFound { path, .. }) if path == MetadataPath::timestamp()
+            );
+
+            let repo = MirrorsRepository::new([("offline", Offline)]).unwrap();
+            assert_matches!(
+                RepositoryProvider::<Pouf1>::fetch_target(&repo, &TargetPath::new("foo").unwrap())
+                    .await
+                    .map(|_| ()),
+                Err(Error::BadHttpStatus { .. })
+            );
+        })
+    }
+
+    #[test]
+    fn mirrors_repository_penalizes_rejected_mirrors() {
+        block_on(async {
+            let repo = MirrorsRepository::new([
+                ("bad", mirror("bad").await),
+                ("good", mirror("good").await),
+            ])
+            .unwrap();
+
+            let mut reader = fetch_timestamp_with_source(&repo).await.unwrap();
+            assert_eq!(read_to_string(&mut reader).await, "bad");
+            assert_eq!(reader.source_name(), Some("bad"));
+
+            let mut reader = reader
+                .reject(&Error::Encoding("bad".into()))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(repo.penalty("bad"), Some(1));
+            assert_eq!(read_to_string(&mut reader).await, "good");
+            assert_eq!(reader.source_name(), Some("good"));
+
+            // The bad mirror is now tried last, even for other files.
+            assert_eq!(
+                fetch_target_to_string(&repo, &TargetPath::new("foo").unwrap())
+                    .await
+                    .unwrap(),
+                "good"
+            );
+
+            // Once every mirror has been rejected for a fetch, there's nothing left to try.
+            assert_matches!(reader.reject(&Error::Encoding("good".into())).await, None);
+            assert_eq!(repo.penalty("bad"), Some(1));
+            assert_eq!(repo.penalty("good"), Some(1));
+        })
+    }
+
+    #[test]
+    fn mirrors_repository_rejects_concurrent_fetches_independently() {
+        block_on(async {
+            let repo = MirrorsRepository::new([
+                ("first", mirror("first").await),
+                ("second", mirror("second").await),
+                ("third", mirror("third").await),
+            ])
+            .unwrap();
+
+            let first = fetch_timestamp_with_source(&repo).await.unwrap();
+            let second = fetch_timestamp_with_source(&repo).await.unwrap();
+            assert_eq!(first.source_name(), Some("first"));
+            assert_eq!(second.source_name(), Some("first"));
+
+            // Rejecting one fetch fails it over without affecting the other, which still names
+            // the mirror that actually served it.
+            let mut first = first
+                .reject(&Error::Encoding("first".into()))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(read_to_string(&mut first).await, "second");
+            assert_eq!(first.source_name(), Some("second"));
+            assert_eq!(second.source_name(), Some("first"));
+
+            // Each fetch only skips the mirrors that served it rejected data, although the
+            // penalties are shared.
+            let mut first = first
+                .reject(&Error::Encoding("second".into()))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(first.source_name(), Some("third"));
+            assert_eq!(read_to_string(&mut first).await, "third");
+
+            let mut second = second
+                .reject(&Error::Encoding("first".into()))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(second.source_name(), Some("third"));
+            assert_eq!(read_to_string(&mut second).await, "third");
+
+            assert_eq!(repo.penalty("first"), Some(2));
+            assert_eq!(repo.penalty("second"), Some(1));
+            assert_eq!(repo.penalty("third"), Some(0));
+        })
+    }
+}
@@ -0,0 +1,827 @@
+use {
+    crate::{
+        crypto::{HashAlgorithm, HashValue},
+        metadata::{MetadataPath, MetadataVersion, TargetPath},
+        pouf::Pouf,
+        repository::{FetchSource, RepositoryProvider, SourcedReader},
+        timer::{SharedTimer, Timer},
+        Error, Result,
+    },
+    futures_io::AsyncRead,
+    futures_util::future::{BoxFuture, FutureExt},
+    log::warn,
+    rand_core::{OsRng, RngCore},
+    std::{fmt, io, sync::Arc, time::Duration},
+};
+
+/// How a [`RetryRepository`] retries fetches that fail.
+///
+/// A failed fetch is retried if the policy considers its error retryable, up to a maximum number
+/// of attempts. Before each retry the repository waits for a backoff, which starts at
+/// `initial_backoff` and is multiplied by `multiplier` after every retry, up to `max_backoff`. To
+/// keep many clients from retrying in lockstep, each backoff is then shortened by a random amount
+/// of up to `jitter` times the backoff.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use tuf::repository::RetryPolicy;
+/// let policy = RetryPolicy::default();
+/// assert_eq!(policy.max_attempts(), 3);
+/// assert_eq!(policy.initial_backoff(), Duration::from_millis(250));
+/// assert_eq!(policy.max_backoff(), Duration::from_secs(10));
+/// assert_eq!(policy.multiplier(), 2.0);
+/// assert_eq!(policy.jitter(), 0.5);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    retryable: Arc<dyn Fn(&Error) -> bool + Send + Sync>,
+    timer: SharedTimer,
+}
+
+impl RetryPolicy {
+    /// Initialize a `RetryPolicyBuilder` with the default values.
+    pub fn build() -> RetryPolicyBuilder {
+        RetryPolicyBuilder::default()
+    }
+
+    /// The maximum number of times a fetch is attempted, including the first attempt.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The backoff before the first retry.
+    pub fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// The longest backoff between two attempts.
+    pub fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// The factor the backoff grows by after every retry.
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// The largest fraction of each backoff that is randomly skipped.
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// Whether a fetch that failed with `err` should be retried.
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        (self.retryable)(err)
+    }
+
+    /// The backoff before retry number `retry`, counting from 1, before any jitter is applied.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+
+        // This also catches backoffs that overflowed to infinity.
+        if (0.0..self.max_backoff.as_secs_f64()).contains(&backoff) {
+            Duration::from_secs_f64(backoff)
+        } else {
+            self.max_backoff
+        }
+    }
+
+    /// Whether `err` is likely to be transient, so that the fetch may succeed if it is retried.
+    /// This is what the policy retries by default.
+    ///
+    /// Network errors, timeouts, and HTTP statuses that ask the client to come back later, such as
+    /// `503 Service Unavailable` or `429 Too Many Requests`, are transient. Metadata or targets
+    /// that are missing or don't verify are not.
+    pub fn is_transient(err: &Error) -> bool {
+        match err {
+            #[cfg(feature = "hyper")]
+            Error::Hyper { .. } => true,
+            Error::BadHttpStatus { code, .. } => {
+                code.is_server_error()
+                    || *code == http::StatusCode::REQUEST_TIMEOUT
+                    || *code == http::StatusCode::TOO_MANY_REQUESTS
+            }
+            Error::Io(err) => matches!(
+                err.kind(),
+                io::ErrorKind::ConnectionRefused
+                    | io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::TimedOut
+                    | io::ErrorKind::Interrupted
+                    | io::ErrorKind::UnexpectedEof
+            ),
+            _ => false,
+        }
+    }
+
+    /// The backoff before retry number `retry`, with a random amount of jitter.
+    fn backoff_with_jitter(&self, retry: u32) -> Duration {
+        let backoff = self.backoff(retry);
+        if self.jitter == 0.0 {
+            return backoff;
+        }
+
+        let random = f64::from(OsRng.next_u32()) / (f64::from(u32::MAX) + 1.0);
+        backoff.mul_f64(1.0 - self.jitter * random)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.5,
+            retryable: Arc::new(Self::is_transient),
+            timer: SharedTimer::default(),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .field("timer", &self.timer)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Helper for building and validating a [`RetryPolicy`].
+#[derive(Debug, Default)]
+pub struct RetryPolicyBuilder {
+    policy: RetryPolicy,
+}
+
+impl RetryPolicyBuilder {
+    /// Validate this builder and return a `RetryPolicy` if validation succeeds.
+    pub fn finish(self) -> Result<RetryPolicy> {
+        let policy = self.policy;
+
+        if policy.max_attempts == 0 {
+            return Err(Error::IllegalArgument(
+                "max_attempts must be at least 1".into(),
+            ));
+        }
+
+        if !(policy.multiplier >= 1.0 && policy.multiplier.is_finite()) {
+            return Err(Error::IllegalArgument(format!(
+                "multiplier must be at least 1, not {}",
+                policy.multiplier
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&policy.jitter) {
+            return Err(Error::IllegalArgument(format!(
+                "jitter must be between 0 and 1, not {}",
+                policy.jitter
+            )));
+        }
+
+        if policy.initial_backoff > policy.max_backoff {
+            return Err(Error::IllegalArgument(format!(
+                "initial_backoff {:?} is longer than max_backoff {:?}",
+                policy.initial_backoff, policy.max_backoff
+            )));
+        }
+
+        Ok(policy)
+    }
+
+    /// Set the maximum number of times a fetch is attempted, including the first attempt. `1`
+    /// disables retrying.
+    pub fn max_attempts(mut self, max: u32) -> Self {
+        self.policy.max_attempts = max;
+        self
+    }
+
+    /// Set the backoff before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.policy.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the longest backoff between two attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.policy.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the backoff grows by after every retry. It must be at least 1.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.policy.multiplier = multiplier;
+        self
+    }
+
+    /// Set the largest fraction of each backoff that is randomly skipped, between 0 for no jitter
+    /// and 1.
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.policy.jitter = jitter;
+        self
+    }
+
+    /// Set which errors are retried. Defaults to [`RetryPolicy::is_transient`].
+    pub fn retryable<F>(mut self, retryable: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.policy.retryable = Arc::new(retryable);
+        self
+    }
+
+    /// Set the [`Timer`] that waits for the backoffs. Defaults to a
+    /// [`ThreadTimer`](crate::timer::ThreadTimer).
+    pub fn timer<T: Timer + 'static>(mut self, timer: T) -> Self {
+        self.policy.timer = SharedTimer::new(timer);
+        self
+    }
+}
+
+/// A repository that retries fetches from another repository when they fail, according to a
+/// [`RetryPolicy`].
+///
+/// Every attempt fetches a new stream from the wrapped repository, so nothing from a failed
+/// attempt is reused, and the [`Client`][Client] verifies the stream from the attempt that
+/// succeeded as usual. Errors while reading the stream are returned to the caller, but when the
+/// client rejects a stream fetched through
+/// [`RepositoryProvider::fetch_metadata_with_source`] or
+/// [`RepositoryProvider::fetch_target_with_source`] because reading it failed, such as when the
+/// connection was reset partway through, it is fetched again if the policy retries the error and
+/// attempts remain. The attempts are counted across both kinds of failure.
+///
+/// To fail over to another mirror only after retrying the current one, wrap each mirror of a
+/// [`MirrorsRepository`](crate::repository::MirrorsRepository) in a `RetryRepository`.
+///
+/// ```
+/// # use futures_executor::block_on;
+/// # use tuf::{
+/// #     metadata::{MetadataPath, MetadataVersion},
+/// #     pouf::Pouf1,
+/// #     repository::{EphemeralRepository, RepositoryProvider, RetryPolicy, RetryRepository},
+/// #     Error,
+/// # };
+/// # block_on(async {
+/// let policy = RetryPolicy::build().max_attempts(5).finish()?;
+/// let repo = RetryRepository::new(EphemeralRepository::<Pouf1>::new(), policy);
+///
+/// // Missing metadata isn't retried.
+/// assert!(matches!(
+///     repo.fetch_metadata(&MetadataPath::root(), MetadataVersion::Number(1)).await,
+///     Err(Error::MetadataNotFound { .. })
+/// ));
+/// # Ok::<(), Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [Client]: crate::client::Client
+#[derive(Debug)]
+pub struct RetryRepository<R> {
+    repository: R,
+    policy: RetryPolicy,
+}
+
+impl<R> RetryRepository<R> {
+    /// Create a new `RetryRepository` that retries fetches from `repository` according to
+    /// `policy`.
+    pub fn new(repository: R, policy: RetryPolicy) -> Self {
+        Self { repository, policy }
+    }
+
+    /// The policy fetches are retried with.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// An immutable reference to the wrapped repository.
+    pub fn as_inner(&self) -> &R {
+        &self.repository
+    }
+
+    /// Return the wrapped repository.
+    pub fn into_inner(self) -> R {
+        self.repository
+    }
+
+    async fn fetch<'a, F, T>(&'a self, what: &(dyn fmt::Debug + Sync), fetch: F) -> Result<T>
+    where
+        F: FnMut() -> BoxFuture<'a, Result<T>>,
+    {
+        self.fetch_from_attempt(what, 1, fetch)
+            .await
+            .map(|(_, value)| value)
+    }
+
+    /// Fetch with `fetch`, starting at attempt number `attempt`, and return the number of the
+    /// attempt that succeeded along with its result.
+    async fn fetch_from_attempt<'a, F, T>(
+        &'a self,
+        what: &(dyn fmt::Debug + Sync),
+        mut attempt: u32,
+        mut fetch: F,
+    ) -> Result<(u32, T)>
+    where
+        F: FnMut() -> BoxFuture<'a, Result<T>>,
+    {
+        loop {
+            match fetch().await {
+                Err(err)
+                    if attempt < self.policy.max_attempts && self.policy.is_retryable(&err) =>
+                {
+                    let backoff = self.policy.backoff_with_jitter(attempt);
+                    warn!(
+                        "Attempt {} to fetch {:?} failed, retrying in {:?}: {}",
+                        attempt, what, backoff, err
+                    );
+
+                    self.policy.timer.get().sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result.map(|value| (attempt, value)),
+            }
+        }
+    }
+
+    /// Fetch with `fetch`, returning a reader that is fetched again if it is rejected because
+    /// reading it failed with a retryable error.
+    async fn fetch_with_source<'a, W>(
+        &'a self,
+        what: W,
+        fetch: RefetchFn<'a>,
+    ) -> Result<SourcedReader<'a>>
+    where
+        W: fmt::Debug + Send + Sync + 'a,
+        R: Sync,
+    {
+        let (attempt, reader) = self.fetch_from_attempt(&what, 1, || fetch()).await?;
+
+        Ok(RetrySource::wrap(
+            self,
+            Box::new(what),
+            fetch,
+            attempt,
+            reader,
+        ))
+    }
+}
+
+/// Fetches the data for a request from the wrapped repository.
+type RefetchFn<'a> = Arc<dyn Fn() -> BoxFuture<'a, Result<SourcedReader<'a>>> + Send + Sync + 'a>;
+
+/// The source of data fetched through a [`RetryRepository`], which fetches the data again when it
+/// is rejected with a retryable error.
+struct RetrySource<'a, R> {
+    repo: &'a RetryRepository<R>,
+    what: Box<dyn fmt::Debug + Send + Sync + 'a>,
+    fetch: RefetchFn<'a>,
+
+    /// The number of the attempt that served the data.
+    attempt: u32,
+
+    /// The source within the wrapped repository, if it reports one.
+    inner: Option<Box<dyn FetchSource<'a> + 'a>>,
+}
+
+impl<'a, R> RetrySource<'a, R>
+where
+    R: Sync,
+{
+    fn wrap(
+        repo: &'a RetryRepository<R>,
+        what: Box<dyn fmt::Debug + Send + Sync + 'a>,
+        fetch: RefetchFn<'a>,
+        attempt: u32,
+        reader: SourcedReader<'a>,
+    ) -> SourcedReader<'a> {
+        let (read, inner) = reader.into_parts();
+        SourcedReader::with_source(
+            read,
+            Box::new(RetrySource {
+                repo,
+                what,
+                fetch,
+                attempt,
+                inner,
+            }),
+        )
+    }
+}
+
+impl<'a, R> FetchSource<'a> for RetrySource<'a, R>
+where
+    R: Sync,
+{
+    fn name(&self) -> Option<&str> {
+        self.inner.as_ref().and_then(|inner| inner.name())
+    }
+
+    fn reject(self: Box<Self>, error: &Error) -> BoxFuture<'a, Option<Result<SourcedReader<'a>>>> {
+        let RetrySource {
+            repo,
+            what,
+            fetch,
+            attempt,
+            inner,
+        } = *self;
+
+        // Let the wrapped repository serve the data from another source first, if it has one.
+        let refetch = inner.map(|inner| inner.reject(error));
+        let retry = attempt < repo.policy.max_attempts && repo.policy.is_retryable(error);
+        let error = error.to_string();
+
+        async move {
+            if let Some(refetch) = refetch {
+                if let Some(result) = refetch.await {
+                    return Some(
+                        result.map(|reader| RetrySource::wrap(repo, what, fetch, attempt, reader)),
+                    );
+                }
+            }
+
+            if !retry {
+                return None;
+            }
+
+            let backoff = repo.policy.backoff_with_jitter(attempt);
+            warn!(
+                "Attempt {} to read {:?} failed, retrying in {:?}: {}",
+                attempt, what, backoff, error
+            );
+            repo.policy.timer.get().sleep(backoff).await;
+
+            Some(
+                repo.fetch_from_attempt(&*what, attempt + 1, || fetch())
+                    .await
+                    .map(|(attempt, reader)| RetrySource::wrap(repo, what, fetch, attempt, reader)),
+            )
+        }
+        .boxed()
+    }
+}
+
+impl<D, R> RepositoryProvider<D> for RetryRepository<R>
+where
+    R: RepositoryProvider<D> + Sync,
+    D: Pouf,
+{
+    fn fetch_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let meta_path = meta_path.clone();
+
+        async move {
+            self.fetch(&meta_path, || {
+                self.repository.fetch_metadata(&meta_path, version)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn fetch_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let target_path = target_path.clone();
+
+        async move {
+            self.fetch(&target_path, || self.repository.fetch_target(&target_path))
+                .await
+        }
+        .boxed()
+    }
+
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let meta_path = meta_path.clone();
+        let hashes = hashes.to_vec();
+
+        async move {
+            self.fetch(&meta_path, || {
+                self.repository
+                    .fetch_metadata_with_constraints(&meta_path, version, max_length, &hashes)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn fetch_target_with_constraints<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let target_path = target_path.clone();
+        let hashes = hashes.to_vec();
+
+        async move {
+            self.fetch(&target_path, || {
+                self.repository
+                    .fetch_target_with_constraints(&target_path, length, &hashes)
+            })
+            .await
+        }
+        .boxed()
+    }
+
+    fn fetch_metadata_with_source<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        let what = meta_path.clone();
+        let meta_path = meta_path.clone();
+        let hashes = hashes.to_vec();
+        let fetch: RefetchFn<'a> = Arc::new(move || {
+            self.repository
+                .fetch_metadata_with_source(&meta_path, version, max_length, &hashes)
+        });
+
+        self.fetch_with_source(what, fetch).boxed()
+    }
+
+    fn fetch_target_with_source<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        let what = target_path.clone();
+        let target_path = target_path.clone();
+        let hashes = hashes.to_vec();
+        let fetch: RefetchFn<'a> = Arc::new(move || {
+            self.repository
+                .fetch_target_with_source(&target_path, length, &hashes)
+        });
+
+        self.fetch_with_source(what, fetch).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            pouf::Pouf1,
+            repository::{
+                fetch_metadata_to_string, EphemeralRepository, ErrorRepository, RepositoryStorage,
+            },
+        },
+        assert_matches::assert_matches,
+        futures_executor::block_on,
+        futures_util::io::AsyncReadExt as _,
+        http::StatusCode,
+        std::sync::Mutex,
+    };
+
+    /// A timer that doesn't wait, and remembers every sleep.
+    #[derive(Clone, Debug, Default)]
+    struct RecordingTimer(Arc<Mutex<Vec<Duration>>>);
+
+    impl RecordingTimer {
+        fn sleeps(&self) -> Vec<Duration> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl Timer for RecordingTimer {
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            self.0.lock().unwrap().push(duration);
+            async {}.boxed()
+        }
+    }
+
+    fn unavailable() -> Error {
+        Error::BadHttpStatus {
+            uri: "http://example.com/root.json".into(),
+            code: StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn reset() -> Error {
+        Error::Io(io::Error::from(io::ErrorKind::ConnectionReset))
+    }
+
+    async fn repo_with_root() -> ErrorRepository<EphemeralRepository<Pouf1>> {
+        let repo = EphemeralRepository::<Pouf1>::new();
+        repo.store_metadata(
+            &MetadataPath::root(),
+            MetadataVersion::None,
+            &mut &b"root"[..],
+        )
+        .await
+        .unwrap();
+        ErrorRepository::new(repo)
+    }
+
+    fn policy(timer: &RecordingTimer) -> RetryPolicyBuilder {
+        RetryPolicy::build()
+            .initial_backoff(Duration::from_millis(100))
+            .jitter(0.0)
+            .timer(timer.clone())
+    }
+
+    #[test]
+    fn retry_repository_retries_transient_errors() {
+        block_on(async {
+            let timer = RecordingTimer::default();
+            let repo = repo_with_root().await;
+            repo.fail_next_fetches([unavailable(), reset()]);
+            let repo = RetryRepository::new(repo, policy(&timer).finish().unwrap());
+
+            assert_eq!(
+                fetch_metadata_to_string(&repo, &MetadataPath::root(), MetadataVersion::None)
+                    .await
+                    .unwrap(),
+                "root"
+            );
+            assert_eq!(repo.as_inner().fetches(), 3);
+            assert_eq!(
+                timer.sleeps(),
+                vec![Duration::from_millis(100), Duration::from_millis(200)]
+            );
+        })
+    }
+
+    #[test]
+    fn retry_repository_gives_up_after_max_attempts() {
+        block_on(async {
+            let timer = RecordingTimer::default();
+            let repo = repo_with_root().await;
+            repo.fail_next_fetches([unavailable(), unavailable(), reset()]);
+            let repo = RetryRepository::new(repo, policy(&timer).finish().unwrap());
+
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::root(), MetadataVersion::None)
+                    .await,
+                Err(Error::Io(err)) if err.kind() == io::ErrorKind::ConnectionReset
+            );
+            assert_eq!(repo.as_inner().fetches(), 3);
+            assert_eq!(timer.sleeps().len(), 2);
+        })
+    }
+
+    #[test]
+    fn retry_repository_does_not_retry_permanent_errors() {
+        block_on(async {
+            let timer = RecordingTimer::default();
+            let repo = repo_with_root().await;
+            repo.fail_next_fetches([Error::BadHttpStatus {
+                uri: "http://example.com/root.json".into(),
+                code: StatusCode::FORBIDDEN,
+            }]);
+            let repo = RetryRepository::new(repo, policy(&timer).finish().unwrap());
+
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::root(), MetadataVersion::None).await,
+                Err(Error::BadHttpStatus {
+                    code: StatusCode::FORBIDDEN,
+                    ..
+                })
+            );
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::timestamp(), MetadataVersion::None)
+                    .await,
+                Err(Error::MetadataNotFound { .. })
+            );
+            assert_eq!(repo.as_inner().fetches(), 2);
+            assert_eq!(timer.sleeps(), vec![]);
+
+            // Custom policies can retry anything.
+            let repo = RetryRepository::new(
+                repo.into_inner(),
+                policy(&timer)
+                    .retryable(|err| matches!(err, Error::MetadataNotFound { .. }))
+                    .finish()
+                    .unwrap(),
+            );
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::timestamp(), MetadataVersion::None)
+                    .await,
+                Err(Error::MetadataNotFound { .. })
+            );
+            assert_eq!(repo.as_inner().fetches(), 5);
+        })
+    }
+
+    async fn fetch_root_with_source<R: RepositoryProvider<Pouf1>>(
+        repo: &R,
+    ) -> Result<SourcedReader<'_>> {
+        repo.fetch_metadata_with_source(&MetadataPath::root(), MetadataVersion::None, None, &[])
+            .await
+    }
+
+    async fn read_to_string(reader: &mut SourcedReader<'_>) -> Result<String> {
+        let mut buf = String::new();
+        reader.read_to_string(&mut buf).await?;
+        Ok(buf)
+    }
+
+    #[test]
+    fn retry_repository_retries_rejected_reads() {
+        block_on(async {
+            let timer = RecordingTimer::default();
+            let repo = repo_with_root().await;
+            repo.fail_next_fetches([unavailable()]);
+            repo.fail_next_reads([(2, io::ErrorKind::ConnectionReset)]);
+            let repo = RetryRepository::new(repo, policy(&timer).finish().unwrap());
+
+            // The stream fails after a few bytes, and rejecting it fetches it again.
+            let mut reader = fetch_root_with_source(&repo).await.unwrap();
+            let err = read_to_string(&mut reader).await.unwrap_err();
+            assert_matches!(&err, Error::Io(err) if err.kind() == io::ErrorKind::ConnectionReset);
+
+            let mut reader = reader.reject(&err).await.unwrap().unwrap();
+            assert_eq!(read_to_string(&mut reader).await.unwrap(), "root");
+            assert_eq!(repo.as_inner().fetches(), 3);
+            assert_eq!(
+                timer.sleeps(),
+                vec![Duration::from_millis(100), Duration::from_millis(200)]
+            );
+
+            // The stream is only fetched again while attempts remain.
+            assert_matches!(reader.reject(&err).await, None);
+            assert_eq!(repo.as_inner().fetches(), 3);
+        })
+    }
+
+    #[test]
+    fn retry_repository_does_not_retry_permanently_rejected_reads() {
+        block_on(async {
+            let timer = RecordingTimer::default();
+            let repo =
+                RetryRepository::new(repo_with_root().await, policy(&timer).finish().unwrap());
+
+            let mut reader = fetch_root_with_source(&repo).await.unwrap();
+            assert_eq!(read_to_string(&mut reader).await.unwrap(), "root");
+            assert_matches!(reader.reject(&Error::Encoding("bad".into())).await, None);
+            assert_eq!(repo.as_inner().fetches(), 1);
+            assert_eq!(timer.sleeps(), vec![]);
+        })
+    }
+
+    #[test]
+    fn retry_policy_backoff_grows_up_to_max_backoff() {
+        let policy = RetryPolicy::build()
+            .initial_backoff(Duration::from_secs(1))
+            .max_backoff(Duration::from_secs(5))
+            .multiplier(3.0)
+            .finish()
+            .unwrap();
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(3));
+        assert_eq!(policy.backoff(3), Duration::from_secs(5));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(5));
+
+        for _ in 0..100 {
+            let backoff = policy.backoff_with_jitter(2);
+            assert!(backoff > Duration::from_millis(1500) && backoff <= Duration::from_secs(3));
+        }
+    }
+
+    #[test]
+    fn retry_policy_builder_rejects_invalid_policies() {
+        assert_matches!(
+            RetryPolicy::build().max_attempts(0).finish(),
+            Err(Error::IllegalArgument(_))
+        );
+        assert_matches!(
+            RetryPolicy::build().multiplier(0.5).finish(),
+            Err(Error::IllegalArgument(_))
+        );
+        assert_matches!(
+            RetryPolicy::build().jitter(1.5).finish(),
+            Err(Error::IllegalArgument(_))
+        );
+        assert_matches!(
+            RetryPolicy::build()
+                .initial_backoff(Duration::from_secs(60))
+                .finish(),
+            Err(Error::IllegalArgument(_))
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use {
+    crate::{
+        crypto::{HashAlgorithm, HashValue},
+        metadata::{MetadataPath, MetadataVersion, SpecVersion, TargetPath},
+        pouf::Pouf,
+        repository::{RepositoryProvider, RepositoryStorage, SourcedReader},
+        Error, Result,
+    },
+    futures_io::AsyncRead,
+    futures_util::future::{BoxFuture, FutureExt},
+    std::sync::Mutex,
+};
+
+/// A repository that keeps the metadata for each major version of the TUF specification in its
+/// own directory, as described in [TAP 14].
+///
+/// Metadata is stored in, and fetched from, the directory named after the major version this
+/// library supports, so the root metadata version 2 is addressed as `1/2.root.json`. Targets
+/// are shared by every major version, so they are passed through unchanged.
+///
+/// When fetching, repositories that predate TAP 14 are supported by falling back to the
+/// unversioned location if the versioned directory doesn't contain the metadata. Once metadata
+/// has been found in one location, all other metadata is only fetched from that location, so a
+/// repository can't mix metadata written for different versions of the specification.
+///
+/// ```
+/// # use futures_executor::block_on;
+/// # use futures_util::io::AsyncReadExt as _;
+/// # use tuf::{
+/// #     metadata::{MetadataPath, MetadataVersion},
+/// #     pouf::Pouf1,
+/// #     repository::{EphemeralRepository, RepositoryProvider, RepositoryStorage,
+/// #                  SpecVersionRepository},
+/// # };
+/// # block_on(async {
+/// let repo = EphemeralRepository::<Pouf1>::new();
+/// repo.store_metadata(
+///     &MetadataPath::new("1/timestamp").unwrap(),
+///     MetadataVersion::None,
+///     &mut &b"timestamp"[..],
+/// ).await.unwrap();
+///
+/// let repo = SpecVersionRepository::new(repo);
+/// let mut buf = vec![];
+/// repo.fetch_metadata(&MetadataPath::timestamp(), MetadataVersion::None)
+///     .await
+///     .unwrap()
+///     .read_to_end(&mut buf)
+///     .await
+///     .unwrap();
+/// assert_eq!(buf, b"timestamp");
+/// # })
+/// ```
+///
+/// [TAP 14]: https://github.com/theupdateframework/taps/blob/master/tap14.md
+#[derive(Debug)]
+pub struct SpecVersionRepository<R> {
+    repository: R,
+    directory: MetadataPath,
+    versioned: Mutex<Option<bool>>,
+}
+
+impl<R> SpecVersionRepository<R> {
+    /// Create a new `SpecVersionRepository` that stores its metadata in `repository`, under the
+    /// directory for [SpecVersion::SUPPORTED_MAJOR].
+    pub fn new(repository: R) -> Self {
+        Self {
+            repository,
+            directory: MetadataPath::new(SpecVersion::SUPPORTED_MAJOR.to_string())
+                .expect("a version number is a valid path"),
+            versioned: Mutex::new(None),
+        }
+    }
+
+    /// Whether the metadata was found in the versioned directory, or `None` if no metadata has
+    /// been fetched yet.
+    pub fn is_versioned(&self) -> Option<bool> {
+        *self.versioned.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Record where the metadata was found, unless that has already been recorded.
+    fn set_versioned(&self, versioned: bool) {
+        let mut guard = self.versioned.lock().unwrap_or_else(|err| err.into_inner());
+        if guard.is_none() {
+            *guard = Some(versioned);
+        }
+    }
+
+    /// An immutable reference to the wrapped repository.
+    pub fn as_inner(&self) -> &R {
+        &self.repository
+    }
+
+    /// Return the wrapped repository.
+    pub fn into_inner(self) -> R {
+        self.repository
+    }
+
+    fn versioned_path(&self, meta_path: &MetadataPath) -> Result<MetadataPath> {
+        MetadataPath::new(format!("{}/{}", self.directory, meta_path))
+    }
+
+    /// Fetch `meta_path` with `fetch`, from the versioned directory if possible.
+    async fn fetch_versioned_metadata<'a, F, T>(
+        &'a self,
+        meta_path: MetadataPath,
+        version: MetadataVersion,
+        fetch: F,
+    ) -> Result<T>
+    where
+        F: Fn(&MetadataPath) -> BoxFuture<'a, Result<T>>,
+    {
+        if self.is_versioned() != Some(false) {
+            let versioned_path = self.versioned_path(&meta_path)?;
+
+            match fetch(&versioned_path).await {
+                Ok(reader) => {
+                    self.set_versioned(true);
+                    return Ok(reader);
+                }
+                // Report the path the caller asked for, not our internal one.
+                Err(Error::MetadataNotFound { .. }) if self.is_versioned() == Some(true) => {
+                    return Err(Error::MetadataNotFound {
+                        path: meta_path,
+                        version,
+                    });
+                }
+                Err(Error::MetadataNotFound { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let reader = fetch(&meta_path).await?;
+        self.set_versioned(false);
+
+        Ok(reader)
+    }
+}
+
+impl<D, R> RepositoryProvider<D> for SpecVersionRepository<R>
+where
+    R: RepositoryProvider<D> + Sync,
+    D: Pouf,
+{
+    fn fetch_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.fetch_versioned_metadata(meta_path.clone(), version, move |path| {
+            self.repository.fetch_metadata(path, version)
+        })
+        .boxed()
+    }
+
+    fn fetch_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.repository.fetch_target(target_path)
+    }
+
+    fn fetch_metadata_with_constraints<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        let hashes = hashes.to_vec();
+
+        self.fetch_versioned_metadata(meta_path.clone(), version, move |path| {
+            self.repository
+                .fetch_metadata_with_constraints(path, version, max_length, &hashes)
+        })
+        .boxed()
+    }
+
+    fn fetch_target_with_constraints<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<Box<dyn AsyncRead + Send + Unpin + 'a>>> {
+        self.repository
+            .fetch_target_with_constraints(target_path, length, hashes)
+    }
+
+    fn fetch_metadata_with_source<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        max_length: Option<usize>,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        let hashes = hashes.to_vec();
+
+        self.fetch_versioned_metadata(meta_path.clone(), version, move |path| {
+            self.repository
+                .fetch_metadata_with_source(path, version, max_length, &hashes)
+        })
+        .boxed()
+    }
+
+    fn fetch_target_with_source<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        length: u64,
+        hashes: &[(&'static HashAlgorithm, HashValue)],
+    ) -> BoxFuture<'a, Result<SourcedReader<'a>>> {
+        self.repository
+            .fetch_target_with_source(target_path, length, hashes)
+    }
+}
+
+impl<D, R> RepositoryStorage<D> for SpecVersionRepository<R>
+where
+    R: RepositoryStorage<D> + Sync,
+    D: Pouf,
+{
+    fn store_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+        metadata: &'a mut (dyn AsyncRead + Send + Unpin),
+    ) -> BoxFuture<'a, Result<()>> {
+        match self.versioned_path(meta_path) {
+            Ok(versioned_path) => {
+                self.repository
+                    .store_metadata(&versioned_path, version, metadata)
+            }
+            Err(err) => async { Err(err) }.boxed(),
+        }
+    }
+
+    fn store_target<'a>(
+        &'a self,
+        target_path: &TargetPath,
+        target: &'a mut (dyn AsyncRead + Send + Unpin),
+    ) -> BoxFuture<'a, Result<()>> {
+        self.repository.store_target(target_path, target)
+    }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        match self.versioned_path(meta_path) {
+            Ok(versioned_path) => self.repository.remove_metadata(&versioned_path, version),
+            Err(err) => async { Err(err) }.boxed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            client::{Client, Config},
+            crypto::Ed25519PrivateKey,
+            pouf::Pouf1,
+            repo_builder::RepoBuilder,
+            repository::{fetch_metadata_to_string, EphemeralRepository},
+        },
+        assert_matches::assert_matches,
+        futures_executor::block_on,
+        futures_util::io::Cursor,
+    };
+
+    async fn store(repo: &EphemeralRepository<Pouf1>, path: &str, contents: &str) {
+        repo.store_metadata(
+            &MetadataPath::new(path.to_string()).unwrap(),
+            MetadataVersion::None,
+            &mut contents.as_bytes(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn spec_version_repository_prefers_versioned_directory() {
+        block_on(async {
+            let inner = EphemeralRepository::<Pouf1>::new();
+            store(&inner, "timestamp", "legacy timestamp").await;
+            store(&inner, "snapshot", "legacy snapshot").await;
+            store(&inner, "1/timestamp", "timestamp").await;
+
+            let repo = SpecVersionRepository::new(inner);
+            assert_eq!(repo.is_versioned(), None);
+
+            assert_eq!(
+                fetch_metadata_to_string(&repo, &MetadataPath::timestamp(), MetadataVersion::None)
+                    .await
+                    .unwrap(),
+                "timestamp"
+            );
+            assert_eq!(repo.is_versioned(), Some(true));
+
+            // Now that we know the repository is versioned, don't fall back to the legacy files.
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::snapshot(), MetadataVersion::None)
+                    .await,
+                Err(Error::MetadataNotFound { path, .. }) if path == MetadataPath::snapshot()
+            );
+        })
+    }
+
+    #[test]
+    fn spec_version_repository_falls_back_to_unversioned_metadata() {
+        block_on(async {
+            let inner = EphemeralRepository::<Pouf1>::new();
+            store(&inner, "timestamp", "legacy timestamp").await;
+
+            let repo = SpecVersionRepository::new(inner);
+            assert_eq!(
+                fetch_metadata_to_string(&repo, &MetadataPath::timestamp(), MetadataVersion::None)
+                    .await
+                    .unwrap(),
+                "legacy timestamp"
+            );
+            assert_eq!(repo.is_versioned(), Some(false));
+
+            // Metadata stored afterwards in the versioned directory is ignored.
+            store(repo.as_inner(), "1/snapshot", "snapshot").await;
+            assert_matches!(
+                fetch_metadata_to_string(&repo, &MetadataPath::snapshot(), MetadataVersion::None)
+                    .await,
+                Err(Error::MetadataNotFound { .. })
+            );
+        })
+    }
+
+    #[test]
+    fn spec_version_repository_client_update() {
+        block_on(async {
+            let key = Ed25519PrivateKey::from_pkcs8(&Ed25519PrivateKey::pkcs8().unwrap()).unwrap();
+            let target_path = TargetPath::new("foo").unwrap();
+
+            let mut remote = SpecVersionRepository::new(EphemeralRepository::<Pouf1>::new());
+            let metadata = RepoBuilder::create(&mut remote)
+                .trusted_root_keys(&[&key])
+                .trusted_targets_keys(&[&key])
+                .trusted_snapshot_keys(&[&key])
+                .trusted_timestamp_keys(&[&key])
+                .stage_root()
+                .await
+                .unwrap()
+                .add_target(target_path.clone(), Cursor::new(b"foo"))
+                .await
+                .unwrap()
+                .commit()
+                .await
+                .unwrap();
+
+            // The metadata was written to the versioned directory, and the targets were not.
+            let inner = remote.as_inner();
+            assert!(fetch_metadata_to_string(
+                inner,
+                &MetadataPath::new("1/timestamp").unwrap(),
+                MetadataVersion::None
+            )
+            .await
+            .is_ok());
+            assert!(fetch_metadata_to_string(
+                inner,
+                &MetadataPath::timestamp(),
+                MetadataVersion::None
+            )
+            .await
+            .is_err());
+
+            let mut client = Client::with_trusted_root(
+                Config::default(),
+                metadata.root().unwrap(),
+                EphemeralRepository::<Pouf1>::new(),
+                remote,
+            )
+            .await
+            .unwrap();
+
+            client.update().await.unwrap();
+            assert_eq!(
+                client.fetch_target_description(&target_path).await.unwrap(),
+                client
+                    .database()
+                    .trusted_targets()
+                    .unwrap()
+                    .targets()
+                    .get(&target_path)
+                    .unwrap()
+                    .clone()
+            );
+            assert_eq!(client.remote_repo().is_versioned(), Some(true));
+        })
+    }
+}
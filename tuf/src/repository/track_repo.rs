@@ -26,6 +26,7 @@ pub(crate) enum Track {
         metadata: String,
     },
     FetchErr(MetadataPath, MetadataVersion),
+    Remove(MetadataPath, MetadataVersion),
 }
 
 impl Track {
@@ -139,6 +140,25 @@ where
     ) -> BoxFuture<'a, Result<()>> {
         self.repo.store_target(target_path, target)
     }
+
+    fn remove_metadata<'a>(
+        &'a self,
+        meta_path: &MetadataPath,
+        version: MetadataVersion,
+    ) -> BoxFuture<'a, Result<()>> {
+        let meta_path = meta_path.clone();
+        async move {
+            let () = self.repo.remove_metadata(&meta_path, version).await?;
+
+            self.tracks
+                .lock()
+                .unwrap()
+                .push(Track::Remove(meta_path, version));
+
+            Ok(())
+        }
+        .boxed()
+    }
 }
 
 impl<D, R> RepositoryProvider<D> for TrackRepository<R>
@@ -0,0 +1,247 @@
+//! Timers used to enforce timeouts without tying this crate to a particular async runtime.
+//!
+//! Timeouts, such as those set with
+//! [`HttpRepositoryBuilder::stall_timeout`](crate::repository::HttpRepositoryBuilder::stall_timeout)
+//! and [`ConfigBuilder::metadata_timeout`](crate::client::ConfigBuilder::metadata_timeout), wait
+//! on a [`Timer`]. By default this is a [`ThreadTimer`], which works with any runtime. Programs
+//! that already run an async runtime can use its timer instead:
+//!
+//! ```
+//! # use futures_util::future::{BoxFuture, FutureExt as _};
+//! # use std::time::Duration;
+//! # use tuf::timer::Timer;
+//! #[derive(Debug)]
+//! struct TokioTimer;
+//!
+//! impl Timer for TokioTimer {
+//!     fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+//!         tokio::time::sleep(duration).boxed()
+//!     }
+//! }
+//! ```
+
+use {
+    futures_util::future::BoxFuture,
+    lazy_static::lazy_static,
+    std::{
+        cmp::Ordering,
+        collections::BinaryHeap,
+        fmt,
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Condvar, Mutex, Weak},
+        task::{Context, Poll, Waker},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// A source of sleeps.
+pub trait Timer: fmt::Debug + Send + Sync {
+    /// Return a future that completes once `duration` has passed.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// A [`Timer`] whose sleeps are woken by a background thread, so it doesn't depend on any async
+/// runtime.
+///
+/// Every `ThreadTimer` shares a single thread, which is started the first time a sleep is
+/// needed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadTimer;
+
+impl Timer for ThreadTimer {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        let state = Arc::new(Mutex::new(SleepState {
+            done: false,
+            waker: None,
+        }));
+
+        // A sleep too long to represent never finishes.
+        if let Some(deadline) = Instant::now().checked_add(duration) {
+            TimerThread::get().schedule(deadline, &state);
+        }
+
+        Box::pin(Sleep { state })
+    }
+}
+
+struct SleepState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+struct Sleep {
+    state: Arc<Mutex<SleepState>>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// A sleep waiting on the timer thread. Sleeps that are dropped before their deadline are skipped.
+struct Entry {
+    deadline: Instant,
+    state: Weak<Mutex<SleepState>>,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // Reversed, so the `BinaryHeap` pops the earliest deadline first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+struct TimerThread {
+    queue: Mutex<BinaryHeap<Entry>>,
+    changed: Condvar,
+}
+
+impl TimerThread {
+    fn get() -> &'static TimerThread {
+        lazy_static! {
+            static ref TIMER_THREAD: &'static TimerThread = {
+                let timer_thread: &'static TimerThread = Box::leak(Box::new(TimerThread {
+                    queue: Mutex::new(BinaryHeap::new()),
+                    changed: Condvar::new(),
+                }));
+
+                thread::Builder::new()
+                    .name("tuf-timer".into())
+                    .spawn(move || timer_thread.run())
+                    .expect("failed to start the timer thread");
+
+                timer_thread
+            };
+        }
+
+        &TIMER_THREAD
+    }
+
+    fn schedule(&self, deadline: Instant, state: &Arc<Mutex<SleepState>>) {
+        let mut queue = self.queue.lock().unwrap_or_else(|err| err.into_inner());
+        queue.push(Entry {
+            deadline,
+            state: Arc::downgrade(state),
+        });
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut queue = self.queue.lock().unwrap_or_else(|err| err.into_inner());
+
+        loop {
+            let now = Instant::now();
+            while queue.peek().map_or(false, |entry| entry.deadline <= now) {
+                let entry = queue.pop().expect("the queue is not empty");
+                if let Some(state) = entry.state.upgrade() {
+                    let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+                    state.done = true;
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                }
+            }
+
+            queue = match queue.peek() {
+                Some(entry) => {
+                    let timeout = entry.deadline.saturating_duration_since(now);
+                    self.changed
+                        .wait_timeout(queue, timeout)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+}
+
+/// The [`Timer`] chosen by the user, or a [`ThreadTimer`].
+#[derive(Clone, Default)]
+pub(crate) struct SharedTimer(Option<Arc<dyn Timer>>);
+
+impl SharedTimer {
+    pub(crate) fn new<T: Timer + 'static>(timer: T) -> Self {
+        Self(Some(Arc::new(timer)))
+    }
+
+    pub(crate) fn get(&self) -> &dyn Timer {
+        match &self.0 {
+            Some(timer) => &**timer,
+            None => &ThreadTimer,
+        }
+    }
+}
+
+impl fmt::Debug for SharedTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl PartialEq for SharedTimer {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SharedTimer {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures_executor::block_on;
+    use futures_util::future::{select, Either};
+
+    #[test]
+    fn thread_timer_sleeps() {
+        let start = Instant::now();
+        block_on(ThreadTimer.sleep(Duration::from_millis(50)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn thread_timer_wakes_earliest_sleep_first() {
+        block_on(async {
+            let long = ThreadTimer.sleep(Duration::from_secs(60));
+            let short = ThreadTimer.sleep(Duration::from_millis(10));
+
+            match select(long, short).await {
+                Either::Right(((), _)) => {}
+                Either::Left(_) => panic!("the longer sleep finished first"),
+            }
+        })
+    }
+}
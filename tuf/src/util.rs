@@ -1,6 +1,8 @@
 use futures_io::AsyncRead;
+use futures_util::future::{self, BoxFuture, Either, FutureExt as _};
 use futures_util::ready;
 use ring::digest;
+use std::future::Future;
 use std::io::{self, ErrorKind};
 use std::marker::Unpin;
 use std::pin::Pin;
@@ -8,6 +10,7 @@ use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use crate::crypto::{HashAlgorithm, HashValue};
+use crate::timer::{SharedTimer, Timer};
 use crate::Result;
 
 pub(crate) trait SafeAsyncRead: AsyncRead + Sized + Unpin {
@@ -17,6 +20,14 @@ pub(crate) trait SafeAsyncRead: AsyncRead + Sized + Unpin {
         EnforceMinimumBitrate::new(self, min_bytes_per_second)
     }
 
+    /// Creates an `AsyncRead` adapter which fails reads once `deadline` has passed.
+    fn enforce_deadline(self, deadline: Deadline) -> EnforceDeadline<Self> {
+        EnforceDeadline {
+            inner: self,
+            deadline,
+        }
+    }
+
     /// Creates an `AsyncRead` adapter that ensures the consumer can't read more than `max_length`
     /// bytes. Also, when the underlying `AsyncRead` is fully consumed, the hash of the data is
     /// optionally calculated and checked against `hash_data`. Consumers should purge and untrust
@@ -41,6 +52,15 @@ pub(crate) struct EnforceMinimumBitrate<R> {
     min_bytes_per_second: u32,
     start_time: Option<Instant>,
     bytes_read: u64,
+    stall: Option<Stall>,
+}
+
+/// Tracks how long it has been since a transfer made progress.
+struct Stall {
+    timer: SharedTimer,
+    timeout: Duration,
+    last_progress: Option<Instant>,
+    sleep: Option<BoxFuture<'static, ()>>,
 }
 
 impl<R: AsyncRead> EnforceMinimumBitrate<R> {
@@ -51,6 +71,48 @@ impl<R: AsyncRead> EnforceMinimumBitrate<R> {
             min_bytes_per_second,
             start_time: None,
             bytes_read: 0,
+            stall: None,
+        }
+    }
+
+    /// Also fail the transfer if no bytes arrive for `timeout`, as measured by `timer`. The bitrate
+    /// alone can only be checked when bytes arrive, so it can't catch a transfer that has stopped.
+    pub(crate) fn with_stall_timeout(
+        mut self,
+        timer: &SharedTimer,
+        timeout: Option<Duration>,
+    ) -> Self {
+        self.stall = timeout.map(|timeout| Stall {
+            timer: timer.clone(),
+            timeout,
+            last_progress: None,
+            sleep: None,
+        });
+        self
+    }
+}
+
+impl Stall {
+    /// Returns `true` once there has been no progress for the timeout.
+    fn poll_stalled(&mut self, cx: &mut Context<'_>) -> bool {
+        let last_progress = *self.last_progress.get_or_insert_with(Instant::now);
+
+        loop {
+            let elapsed = last_progress.elapsed();
+            if elapsed >= self.timeout {
+                return true;
+            }
+
+            // The sleep isn't restarted for every read, so once it finishes there may have been
+            // progress since, in which case we sleep again for the rest of the timeout.
+            let timer = self.timer.get();
+            let sleep = self
+                .sleep
+                .get_or_insert_with(|| timer.sleep(self.timeout - elapsed));
+            if sleep.poll_unpin(cx).is_pending() {
+                return false;
+            }
+            self.sleep = None;
         }
     }
 }
@@ -66,8 +128,24 @@ impl<R: AsyncRead + Unpin> AsyncRead for EnforceMinimumBitrate<R> {
         cx: &mut Context,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        // FIXME(#272) transfers that stall out completely won't enforce the minimum bit rate.
-        let read_bytes = ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+        let read_bytes = match Pin::new(&mut self.inner).poll_read(cx, buf) {
+            Poll::Ready(result) => result?,
+            Poll::Pending => {
+                if let Some(stall) = &mut self.stall {
+                    if stall.poll_stalled(cx) {
+                        return Poll::Ready(Err(io::Error::new(
+                            ErrorKind::TimedOut,
+                            "Read aborted. Transfer stalled.",
+                        )));
+                    }
+                }
+                return Poll::Pending;
+            }
+        };
+
+        if let Some(stall) = &mut self.stall {
+            stall.last_progress = Some(Instant::now());
+        }
 
         let start_time = *self.start_time.get_or_insert_with(Instant::now);
 
@@ -93,6 +171,65 @@ impl<R: AsyncRead + Unpin> AsyncRead for EnforceMinimumBitrate<R> {
     }
 }
 
+/// A deadline for a request and reading its response, which never passes if there is no timeout.
+pub(crate) struct Deadline {
+    sleep: Option<BoxFuture<'static, ()>>,
+}
+
+impl Deadline {
+    /// Create a deadline `timeout` from now, as measured by `timer`.
+    pub(crate) fn new(timer: &dyn Timer, timeout: Option<Duration>) -> Self {
+        Self {
+            sleep: timeout.map(|timeout| timer.sleep(timeout)),
+        }
+    }
+
+    /// Run `fut`, failing with [`ErrorKind::TimedOut`] if it doesn't finish before the deadline.
+    pub(crate) async fn run<F: Future>(&mut self, fut: F) -> io::Result<F::Output> {
+        let sleep = match &mut self.sleep {
+            Some(sleep) => sleep,
+            None => return Ok(fut.await),
+        };
+
+        futures_util::pin_mut!(fut);
+        match future::select(fut, sleep).await {
+            Either::Left((output, _)) => Ok(output),
+            Either::Right(((), _)) => Err(timed_out()),
+        }
+    }
+
+    fn poll_passed(&mut self, cx: &mut Context<'_>) -> bool {
+        match &mut self.sleep {
+            Some(sleep) => sleep.poll_unpin(cx).is_ready(),
+            None => false,
+        }
+    }
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(ErrorKind::TimedOut, "Read aborted. Deadline exceeded.")
+}
+
+/// Wraps an `AsyncRead` to fail reads once a [`Deadline`] has passed.
+pub(crate) struct EnforceDeadline<R> {
+    inner: R,
+    deadline: Deadline,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for EnforceDeadline<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.deadline.poll_passed(cx) {
+            return Poll::Ready(Err(timed_out()));
+        }
+
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
 /// Wrapper to verify a byte stream as it is read.
 ///
 /// Wraps an `AsyncRead` to ensure that the consumer can't read more than a capped maximum number of
@@ -342,4 +479,52 @@ mod test {
             assert!(reader.read_to_end(&mut buf).await.is_err());
         })
     }
+
+    /// A transfer that never sends another byte.
+    struct Stalled;
+
+    impl AsyncRead for Stalled {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context,
+            _buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn enforce_minimum_bitrate_fails_when_transfer_stalls() {
+        block_on(async {
+            let bytes: &[u8] = &[0x42; 4];
+
+            let mut reader = EnforceMinimumBitrate::new(bytes.chain(Stalled), 100)
+                .with_stall_timeout(&SharedTimer::default(), Some(Duration::from_millis(50)));
+
+            let mut buf = vec![0; 4];
+            assert!(reader.read_exact(&mut buf).await.is_ok());
+
+            let err = reader.read_to_end(&mut buf).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::TimedOut);
+        })
+    }
+
+    #[test]
+    fn deadline_fails_unfinished_work() {
+        block_on(async {
+            let timer = SharedTimer::default();
+
+            let mut deadline = Deadline::new(timer.get(), Some(Duration::from_millis(50)));
+            let err = deadline.run(future::pending::<()>()).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+            let deadline = Deadline::new(timer.get(), Some(Duration::from_millis(50)));
+            let mut reader = Stalled.enforce_deadline(deadline);
+            let err = reader.read_to_end(&mut vec![]).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+            let mut deadline = Deadline::new(timer.get(), None);
+            assert_eq!(deadline.run(async { 42 }).await.unwrap(), 42);
+        })
+    }
 }
@@ -1,7 +1,6 @@
 //! The `verify` module performs signature verification.
 
 use log::{debug, warn};
-use serde_derive::Deserialize;
 use std::collections::HashMap;
 
 use crate::crypto::{KeyId, PublicKey, Signature};
@@ -104,16 +103,10 @@ where
 
     // Extract the signatures and canonicalize the bytes.
     let (signatures, canonical_bytes) = {
-        #[derive(Deserialize)]
-        pub struct SignedMetadata<D: Pouf> {
-            signatures: Vec<Signature>,
-            signed: D::RawData,
-        }
-
-        let unverified: SignedMetadata<D> = D::from_slice(raw_metadata.as_bytes())?;
+        let (signatures, signed) = D::decode_signed(raw_metadata.as_bytes())?;
 
-        let canonical_bytes = D::canonicalize(&unverified.signed)?;
-        (unverified.signatures, canonical_bytes)
+        let canonical_bytes = D::canonicalize(&signed)?;
+        (signatures, canonical_bytes)
     };
 
     let mut signatures_needed = threshold;
@@ -161,7 +154,7 @@ where
     // information about the data. Therefore, while we've confirmed the canonical bytes are signed,
     // we shouldn't interpret this as if the raw bytes were signed. So we deserialize from the
     // `canonical_bytes`, rather than from `raw_meta.as_bytes()`.
-    let verified_metadata = D::from_slice(&canonical_bytes)?;
+    let verified_metadata = D::from_canonical_slice(&canonical_bytes)?;
 
     Ok(Verified::new(verified_metadata))
 }
@@ -0,0 +1,47 @@
+#!/bin/bash
+set -eux
+
+cd "$(dirname "$0")"
+
+for curve in P-256 P-384; do
+    key="ecdsa-$(echo "$curve" | tr -d '-' | tr 'A-Z' 'a-z')"
+    pk8="$key.pk8.der"
+    spki="$key.spki.der"
+    pem="$key.pub"
+    sig="$key.sig"
+
+    if [ ! -f "$pk8" ]; then
+        openssl genpkey -algorithm EC \
+                        -pkeyopt "ec_paramgen_curve:$curve" \
+                        -pkeyopt ec_param_enc:named_curve \
+                        -outform der | \
+            openssl pkcs8 -topk8 \
+                          -inform der \
+                          -outform der \
+                          -out "$pk8" \
+                          -nocrypt
+    fi
+
+    openssl pkey -in "$pk8" \
+                 -inform der \
+                 -pubout \
+                 -outform der \
+                 -out "$spki"
+
+    openssl pkey -in "$pk8" \
+                 -inform der \
+                 -pubout \
+                 -outform pem \
+                 -out "$pem"
+
+    if [ "$curve" = "P-256" ]; then
+        digest=-sha256
+    else
+        digest=-sha384
+    fi
+
+    printf 'hello world' | openssl dgst "$digest" \
+                                       -keyform der \
+                                       -sign "$pk8" \
+                                       -out "$sig"
+done
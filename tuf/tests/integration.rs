@@ -3,7 +3,8 @@ use chrono::offset::Utc;
 use futures_executor::block_on;
 use tuf::crypto::{Ed25519PrivateKey, HashAlgorithm, PrivateKey};
 use tuf::metadata::{
-    Delegation, Delegations, MetadataDescription, MetadataPath, TargetPath, TargetsMetadataBuilder,
+    Delegation, DelegationBuilder, Delegations, MetadataDescription, MetadataPath, PathPattern,
+    SuccinctRoles, TargetPath, TargetsMetadataBuilder,
 };
 use tuf::pouf::Pouf1;
 use tuf::repo_builder::RepoBuilder;
@@ -36,16 +37,18 @@ fn simple_delegation() {
             .trusted_targets_keys(&[&targets_key])
             .trusted_timestamp_keys(&[&timestamp_key])
             .stage_root()
+            .await
             .unwrap()
             .add_delegation_key(delegation_key.public().clone())
             .add_delegation_role(
                 Delegation::builder(MetadataPath::new("delegation").unwrap())
                     .key(delegation_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
             .stage_targets()
+            .await
             .unwrap()
             .stage_snapshot_with_builder(|builder| {
                 builder.insert_metadata_description(
@@ -53,6 +56,7 @@ fn simple_delegation() {
                     MetadataDescription::from_slice(&[0u8], 1, &[HashAlgorithm::Sha256]).unwrap(),
                 )
             })
+            .await
             .unwrap()
             .commit()
             .await
@@ -88,6 +92,350 @@ fn simple_delegation() {
     })
 }
 
+#[test]
+fn non_matching_terminating_delegation_does_not_end_search() {
+    block_on(async {
+        let now = Utc::now();
+
+        let root_key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let snapshot_key = Ed25519PrivateKey::from_pkcs8(ED25519_2_PK8).unwrap();
+        let targets_key = Ed25519PrivateKey::from_pkcs8(ED25519_3_PK8).unwrap();
+        let timestamp_key = Ed25519PrivateKey::from_pkcs8(ED25519_4_PK8).unwrap();
+        let delegation_key = Ed25519PrivateKey::from_pkcs8(ED25519_5_PK8).unwrap();
+
+        // The terminating delegation is listed first, but isn't trusted with `foo`, so it
+        // shouldn't stop the search from reaching the delegation that is.
+        let mut repo = EphemeralRepository::new();
+        let metadata = RepoBuilder::create(&mut repo)
+            .trusted_root_keys(&[&root_key])
+            .trusted_snapshot_keys(&[&snapshot_key])
+            .trusted_targets_keys(&[&targets_key])
+            .trusted_timestamp_keys(&[&timestamp_key])
+            .stage_root()
+            .await
+            .unwrap()
+            .add_delegation_key(delegation_key.public().clone())
+            .add_delegation_role(
+                Delegation::new(
+                    MetadataPath::new("baz").unwrap(),
+                    true,
+                    1,
+                    vec![delegation_key.public().key_id().clone()]
+                        .into_iter()
+                        .collect(),
+                    vec![PathPattern::new("baz/*").unwrap()]
+                        .into_iter()
+                        .collect(),
+                )
+                .unwrap(),
+            )
+            .add_delegation_role(
+                Delegation::builder(MetadataPath::new("delegation").unwrap())
+                    .key(delegation_key.public())
+                    .delegate_path(PathPattern::new("foo").unwrap())
+                    .build()
+                    .unwrap(),
+            )
+            .stage_targets()
+            .await
+            .unwrap()
+            .stage_snapshot_with_builder(|builder| {
+                builder.insert_metadata_description(
+                    MetadataPath::new("delegation").unwrap(),
+                    MetadataDescription::from_slice(&[0u8], 1, &[HashAlgorithm::Sha256]).unwrap(),
+                )
+            })
+            .await
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+
+        let mut tuf = Database::<Pouf1>::from_trusted_metadata(&metadata).unwrap();
+
+        let delegation = TargetsMetadataBuilder::new()
+            .insert_target_from_slice(
+                TargetPath::new("foo").unwrap(),
+                b"bar",
+                &[HashAlgorithm::Sha256],
+            )
+            .unwrap()
+            .signed::<Pouf1>(&delegation_key)
+            .unwrap();
+
+        tuf.update_delegated_targets(
+            &now,
+            &MetadataPath::targets(),
+            &MetadataPath::new("delegation").unwrap(),
+            &delegation.to_raw().unwrap(),
+        )
+        .unwrap();
+
+        assert!(tuf
+            .target_description(&TargetPath::new("foo").unwrap())
+            .is_ok());
+    })
+}
+
+#[test]
+fn hashed_bin_delegation() {
+    block_on(async {
+        let now = Utc::now();
+
+        let root_key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let snapshot_key = Ed25519PrivateKey::from_pkcs8(ED25519_2_PK8).unwrap();
+        let targets_key = Ed25519PrivateKey::from_pkcs8(ED25519_3_PK8).unwrap();
+        let timestamp_key = Ed25519PrivateKey::from_pkcs8(ED25519_4_PK8).unwrap();
+        let delegation_key = Ed25519PrivateKey::from_pkcs8(ED25519_5_PK8).unwrap();
+
+        // sha256("foo") starts with "2", and sha256("bar") starts with "f", so they land in
+        // different bins.
+        let foo = TargetPath::new("foo").unwrap();
+        let bar = TargetPath::new("bar").unwrap();
+
+        let low_bin = MetadataPath::new("bins/0-7").unwrap();
+        let high_bin = MetadataPath::new("bins/8-f").unwrap();
+
+        //// build the delegations ////
+
+        let target_file: &[u8] = b"bar";
+
+        let low_delegation = TargetsMetadataBuilder::new()
+            .insert_target_from_slice(foo.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .signed::<Pouf1>(&delegation_key)
+            .unwrap();
+        let raw_low_delegation = low_delegation.to_raw().unwrap();
+
+        // The high bin lists `foo`, but it isn't trusted with it.
+        let high_delegation = TargetsMetadataBuilder::new()
+            .insert_target_from_slice(foo.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .insert_target_from_slice(bar.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .signed::<Pouf1>(&delegation_key)
+            .unwrap();
+        let raw_high_delegation = high_delegation.to_raw().unwrap();
+
+        //// construct the database ////
+
+        let mut repo = EphemeralRepository::new();
+        let mut builder = RepoBuilder::create(&mut repo)
+            .trusted_root_keys(&[&root_key])
+            .trusted_snapshot_keys(&[&snapshot_key])
+            .trusted_targets_keys(&[&targets_key])
+            .trusted_timestamp_keys(&[&timestamp_key])
+            .stage_root()
+            .await
+            .unwrap()
+            .add_delegation_key(delegation_key.public().clone());
+
+        for bin in DelegationBuilder::hashed_bins("bins/", 2).unwrap() {
+            builder =
+                builder.add_delegation_role(bin.key(delegation_key.public()).build().unwrap());
+        }
+
+        let metadata = builder
+            .stage_targets()
+            .await
+            .unwrap()
+            .stage_snapshot_with_builder(|builder| {
+                builder
+                    .insert_metadata_description(
+                        low_bin.clone(),
+                        MetadataDescription::from_slice(
+                            raw_low_delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                    .insert_metadata_description(
+                        high_bin.clone(),
+                        MetadataDescription::from_slice(
+                            raw_high_delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+            })
+            .await
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+
+        let mut tuf = Database::<Pouf1>::from_trusted_metadata(&metadata).unwrap();
+
+        tuf.update_delegated_targets(
+            &now,
+            &MetadataPath::targets(),
+            &high_bin,
+            &raw_high_delegation,
+        )
+        .unwrap();
+
+        // The high bin can't vouch for `foo`.
+        assert_matches!(
+            tuf.target_description(&foo),
+            Err(Error::TargetNotFound(path)) if path == foo
+        );
+        assert!(tuf.target_description(&bar).is_ok());
+
+        tuf.update_delegated_targets(
+            &now,
+            &MetadataPath::targets(),
+            &low_bin,
+            &raw_low_delegation,
+        )
+        .unwrap();
+
+        assert!(tuf.target_description(&foo).is_ok());
+    })
+}
+
+#[test]
+fn succinct_roles_delegation() {
+    block_on(async {
+        let now = Utc::now();
+
+        let root_key = Ed25519PrivateKey::from_pkcs8(ED25519_1_PK8).unwrap();
+        let snapshot_key = Ed25519PrivateKey::from_pkcs8(ED25519_2_PK8).unwrap();
+        let targets_key = Ed25519PrivateKey::from_pkcs8(ED25519_3_PK8).unwrap();
+        let timestamp_key = Ed25519PrivateKey::from_pkcs8(ED25519_4_PK8).unwrap();
+        let delegation_key = Ed25519PrivateKey::from_pkcs8(ED25519_5_PK8).unwrap();
+
+        // sha256("foo") starts with 0b0010, and sha256("bar") starts with 0b1111.
+        let foo = TargetPath::new("foo").unwrap();
+        let bar = TargetPath::new("bar").unwrap();
+
+        let foo_bin = MetadataPath::new("bins/bin-2").unwrap();
+        let bar_bin = MetadataPath::new("bins/bin-f").unwrap();
+
+        //// build the delegations ////
+
+        let target_file: &[u8] = b"bar";
+
+        let foo_delegation = TargetsMetadataBuilder::new()
+            .insert_target_from_slice(foo.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .signed::<Pouf1>(&delegation_key)
+            .unwrap();
+        let raw_foo_delegation = foo_delegation.to_raw().unwrap();
+
+        // The wrong bin lists `foo`, but it isn't trusted with it.
+        let bar_delegation = TargetsMetadataBuilder::new()
+            .insert_target_from_slice(foo.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .insert_target_from_slice(bar.clone(), target_file, &[HashAlgorithm::Sha256])
+            .unwrap()
+            .signed::<Pouf1>(&delegation_key)
+            .unwrap();
+        let raw_bar_delegation = bar_delegation.to_raw().unwrap();
+
+        //// construct the database ////
+
+        let succinct_roles = SuccinctRoles::new(
+            vec![delegation_key.public().key_id().clone()]
+                .into_iter()
+                .collect(),
+            1,
+            4,
+            "bins/bin".into(),
+        )
+        .unwrap();
+
+        let mut repo = EphemeralRepository::new();
+        let metadata = RepoBuilder::create(&mut repo)
+            .trusted_root_keys(&[&root_key])
+            .trusted_snapshot_keys(&[&snapshot_key])
+            .trusted_targets_keys(&[&targets_key])
+            .trusted_timestamp_keys(&[&timestamp_key])
+            .stage_root()
+            .await
+            .unwrap()
+            .add_delegation_key(delegation_key.public().clone())
+            .delegation_succinct_roles(succinct_roles)
+            .stage_targets()
+            .await
+            .unwrap()
+            .stage_snapshot_with_builder(|builder| {
+                builder
+                    .insert_metadata_description(
+                        foo_bin.clone(),
+                        MetadataDescription::from_slice(
+                            raw_foo_delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                    .insert_metadata_description(
+                        bar_bin.clone(),
+                        MetadataDescription::from_slice(
+                            raw_bar_delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+                    .insert_metadata_description(
+                        MetadataPath::new("bins/bin-10").unwrap(),
+                        MetadataDescription::from_slice(
+                            raw_bar_delegation.as_bytes(),
+                            1,
+                            &[HashAlgorithm::Sha256],
+                        )
+                        .unwrap(),
+                    )
+            })
+            .await
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+
+        let mut tuf = Database::<Pouf1>::from_trusted_metadata(&metadata).unwrap();
+
+        // There are only 16 bins.
+        assert_matches!(
+            tuf.update_delegated_targets(
+                &now,
+                &MetadataPath::targets(),
+                &MetadataPath::new("bins/bin-10").unwrap(),
+                &raw_bar_delegation,
+            ),
+            Err(Error::UnauthorizedDelegation { .. })
+        );
+
+        tuf.update_delegated_targets(
+            &now,
+            &MetadataPath::targets(),
+            &bar_bin,
+            &raw_bar_delegation,
+        )
+        .unwrap();
+
+        // The bin for `bar` can't vouch for `foo`.
+        assert_matches!(
+            tuf.target_description(&foo),
+            Err(Error::TargetNotFound(path)) if path == foo
+        );
+        assert!(tuf.target_description(&bar).is_ok());
+
+        tuf.update_delegated_targets(
+            &now,
+            &MetadataPath::targets(),
+            &foo_bin,
+            &raw_foo_delegation,
+        )
+        .unwrap();
+
+        assert!(tuf.target_description(&foo).is_ok());
+    })
+}
+
 #[test]
 fn nested_delegation() {
     block_on(async {
@@ -107,16 +455,18 @@ fn nested_delegation() {
             .trusted_targets_keys(&[&targets_key])
             .trusted_timestamp_keys(&[&timestamp_key])
             .stage_root()
+            .await
             .unwrap()
             .add_delegation_key(delegation_a_key.public().clone())
             .add_delegation_role(
                 Delegation::builder(MetadataPath::new("delegation-a").unwrap())
                     .key(delegation_a_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
             .stage_targets()
+            .await
             .unwrap()
             .stage_snapshot_with_builder(|builder| {
                 builder
@@ -131,6 +481,7 @@ fn nested_delegation() {
                             .unwrap(),
                     )
             })
+            .await
             .unwrap()
             .commit()
             .await
@@ -145,7 +496,7 @@ fn nested_delegation() {
             .role(
                 Delegation::builder(MetadataPath::new("delegation-b").unwrap())
                     .key(delegation_b_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
@@ -214,16 +565,18 @@ fn rejects_bad_delegation_signatures() {
             .trusted_targets_keys(&[&targets_key])
             .trusted_timestamp_keys(&[&timestamp_key])
             .stage_root()
+            .await
             .unwrap()
             .add_delegation_key(delegation_key.public().clone())
             .add_delegation_role(
                 Delegation::builder(MetadataPath::new("delegation").unwrap())
                     .key(delegation_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
             .stage_targets()
+            .await
             .unwrap()
             .stage_snapshot_with_builder(|builder| {
                 builder.insert_metadata_description(
@@ -231,6 +584,7 @@ fn rejects_bad_delegation_signatures() {
                     MetadataDescription::from_slice(&[0u8], 1, &[HashAlgorithm::Sha256]).unwrap(),
                 )
             })
+            .await
             .unwrap()
             .commit()
             .await
@@ -308,7 +662,7 @@ fn diamond_delegation() {
             .role(
                 Delegation::builder(MetadataPath::new("delegation-c").unwrap())
                     .key(delegation_c_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
@@ -329,7 +683,7 @@ fn diamond_delegation() {
                 Delegation::builder(MetadataPath::new("delegation-c").unwrap())
                     // oops, wrong key.
                     .key(delegation_b_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
@@ -373,24 +727,26 @@ fn diamond_delegation() {
             .trusted_targets_keys(&[&targets_key])
             .trusted_timestamp_keys(&[&etc_key])
             .stage_root()
+            .await
             .unwrap()
             .add_delegation_key(delegation_a_key.public().clone())
             .add_delegation_key(delegation_b_key.public().clone())
             .add_delegation_role(
                 Delegation::builder(MetadataPath::new("delegation-a").unwrap())
                     .key(delegation_a_key.public())
-                    .delegate_path(TargetPath::new("foo").unwrap())
+                    .delegate_path(PathPattern::new("foo").unwrap())
                     .build()
                     .unwrap(),
             )
             .add_delegation_role(
                 Delegation::builder(MetadataPath::new("delegation-b").unwrap())
                     .key(delegation_b_key.public())
-                    .delegate_path(TargetPath::new("bar").unwrap())
+                    .delegate_path(PathPattern::new("bar").unwrap())
                     .build()
                     .unwrap(),
             )
             .stage_targets()
+            .await
             .unwrap()
             .stage_snapshot_with_builder(|builder| {
                 builder
@@ -422,6 +778,7 @@ fn diamond_delegation() {
                         .unwrap(),
                     )
             })
+            .await
             .unwrap()
             .commit()
             .await
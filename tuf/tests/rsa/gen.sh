@@ -7,6 +7,7 @@ for key_size in 2048 4096; do
     key="rsa-$key_size"
     pk8="$key.pk8.der"
     spki="$key.spki.der"
+    pem="$key.spki.pem"
     pkcs1="$key.pkcs1.der"
     key="$key.der"
 
@@ -30,6 +31,12 @@ for key_size in 2048 4096; do
                 -outform der \
                 -out "$spki"
 
+    openssl rsa -in "$key" \
+                -inform der \
+                -pubout \
+                -outform pem \
+                -out "$pem"
+
     openssl pkcs8 -topk8 \
                   -inform der \
                   -in "$key" \
@@ -37,3 +44,5 @@ for key_size in 2048 4096; do
                   -out "$pk8" \
                   -nocrypt
 done
+
+python3 gen_root.py
@@ -0,0 +1,103 @@
+#!/usr/bin/env python3
+"""Generate RSA root metadata the way python-tuf and go-tuf write it.
+
+This uses `cryptography` and the OLPC canonical JSON rules from
+securesystemslib directly, so the fixtures do not depend on rust-tuf's own
+encoding of keys, key IDs, or signatures.
+"""
+
+import hashlib
+import json
+
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import padding
+
+
+def encode_canonical(obj):
+    if isinstance(obj, bool):
+        return "true" if obj else "false"
+    if obj is None:
+        return "null"
+    if isinstance(obj, int):
+        return str(obj)
+    if isinstance(obj, str):
+        return '"' + obj.replace("\\", "\\\\").replace('"', '\\"') + '"'
+    if isinstance(obj, list):
+        return "[" + ",".join(encode_canonical(o) for o in obj) + "]"
+    if isinstance(obj, dict):
+        return "{" + ",".join(
+            encode_canonical(k) + ":" + encode_canonical(obj[k]) for k in sorted(obj)
+        ) + "}"
+    raise TypeError(obj)
+
+
+def sign(private_key, scheme, data):
+    if scheme == "rsassa-pss-sha256":
+        pad = padding.PSS(
+            mgf=padding.MGF1(hashes.SHA256()),
+            salt_length=padding.PSS.DIGEST_LENGTH,
+        )
+    elif scheme == "rsa-pkcs1v15-sha256":
+        pad = padding.PKCS1v15()
+    else:
+        raise ValueError(scheme)
+    return private_key.sign(data, pad, hashes.SHA256())
+
+
+def generate(pk8, scheme, keyid_hash_algorithms, out):
+    with open(pk8, "rb") as f:
+        private_key = serialization.load_der_private_key(f.read(), password=None)
+
+    public = private_key.public_key().public_bytes(
+        serialization.Encoding.PEM,
+        serialization.PublicFormat.SubjectPublicKeyInfo,
+    ).decode()
+
+    key = {"keytype": "rsa", "scheme": scheme, "keyval": {"public": public}}
+    if keyid_hash_algorithms:
+        key["keyid_hash_algorithms"] = keyid_hash_algorithms
+    keyid = hashlib.sha256(encode_canonical(key).encode()).hexdigest()
+
+    role = {"keyids": [keyid], "threshold": 1}
+    signed = {
+        "_type": "root",
+        "spec_version": "1.0.0",
+        "version": 1,
+        "expires": "2038-01-01T00:00:00Z",
+        "consistent_snapshot": True,
+        "keys": {keyid: key},
+        "roles": {
+            "root": role,
+            "snapshot": role,
+            "targets": role,
+            "timestamp": role,
+        },
+    }
+    sig = sign(private_key, scheme, encode_canonical(signed).encode())
+
+    with open(out, "w") as f:
+        json.dump(
+            {"signatures": [{"keyid": keyid, "sig": sig.hex()}], "signed": signed},
+            f,
+            indent=1,
+            sort_keys=True,
+        )
+        f.write("\n")
+
+
+# go-tuf only supports rsassa-pss-sha256, and lists `keyid_hash_algorithms`.
+generate(
+    "rsa-2048.pk8.der",
+    "rsassa-pss-sha256",
+    ["sha256", "sha512"],
+    "root-rsassa-pss-sha256.json",
+)
+
+# Modern python-tuf omits `keyid_hash_algorithms`, and still supports legacy
+# PKCS#1 v1.5 signatures.
+generate(
+    "rsa-4096.pk8.der",
+    "rsa-pkcs1v15-sha256",
+    None,
+    "root-rsa-pkcs1v15-sha256.json",
+)
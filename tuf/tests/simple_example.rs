@@ -61,7 +61,8 @@ async fn init_client(
     .await?;
     let _ = client.update().await?;
     let target_path = TargetPath::new("foo-bar")?;
-    client.fetch_target_to_local(&target_path).await
+    let _ = client.fetch_target_to_local(&target_path).await?;
+    Ok(())
 }
 
 async fn init_server(
@@ -83,6 +84,7 @@ async fn init_server(
         .trusted_targets_keys(&[&targets_key])
         .trusted_timestamp_keys(&[&timestamp_key])
         .stage_root_with_builder(|builder| builder.consistent_snapshot(consistent_snapshot))
+        .await
         .unwrap()
         .add_target(target_path.clone(), Cursor::new(target_file))
         .await